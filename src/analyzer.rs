@@ -0,0 +1,123 @@
+//! The tools a scan can read files with. ffprobe is the default; MediaInfo and
+//! GStreamer's discoverer disagree with it often enough on edge-case files that
+//! reading a file with a second one is worth a key press. Their output is translated
+//! into ffprobe's JSON shape so every backend goes through the same normalization.
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{io, process::Command};
+
+use crate::{
+    cancel,
+    config::Config,
+    gstreamer, mediainfo,
+    model::MediaInfo,
+    probe::{self, is_stream, ProbeOptions},
+    ssh,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Ffprobe,
+    /// The MediaInfo CLI (`mediainfo --Output=JSON`)
+    Mediainfo,
+    /// `gst-discoverer-1.0`
+    Gstreamer,
+}
+
+impl Backend {
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::Ffprobe => "ffprobe",
+            Backend::Mediainfo => "MediaInfo",
+            Backend::Gstreamer => "GStreamer",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Backend::Ffprobe => Backend::Mediainfo,
+            Backend::Mediainfo => Backend::Gstreamer,
+            Backend::Gstreamer => Backend::Ffprobe,
+        }
+    }
+}
+
+pub trait Analyzer: Send + Sync {
+    fn backend(&self) -> Backend;
+
+    /// Reads `path`, which may be a URL, into a [`MediaInfo`].
+    fn analyze(&self, path: &str) -> Result<MediaInfo>;
+}
+
+/// The analyzer for `backend`, with the executables and probe settings from `config`.
+pub fn analyzer(backend: Backend, config: &Config) -> Box<dyn Analyzer> {
+    let options = ProbeOptions::from_config(config);
+    match backend {
+        Backend::Ffprobe => Box::new(Ffprobe { options }),
+        Backend::Mediainfo => Box::new(External { backend, program: config.mediainfo.clone(), options }),
+        Backend::Gstreamer => Box::new(External { backend, program: config.gst_discoverer.clone(), options }),
+    }
+}
+
+pub struct Ffprobe {
+    options: ProbeOptions,
+}
+
+impl Analyzer for Ffprobe {
+    fn backend(&self) -> Backend {
+        Backend::Ffprobe
+    }
+
+    fn analyze(&self, path: &str) -> Result<MediaInfo> {
+        probe::probe_file(path, &self.options)
+    }
+}
+
+/// MediaInfo or the discoverer: run locally on the path (or a presigned URL), with
+/// the output translated for [`probe::normalize`].
+struct External {
+    backend: Backend,
+    program: String,
+    options: ProbeOptions,
+}
+
+impl Analyzer for External {
+    fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn analyze(&self, path: &str) -> Result<MediaInfo> {
+        // Neither tool has a way to stop reading a live feed, or to run over SSH
+        if is_stream(path) || ssh::is_ssh(path) {
+            return Err(anyhow!("{} only reads files and http(s) URLs", self.backend.label()));
+        }
+        let input = self.options.input(path).map_err(|e| anyhow!("could not presign {}: {}", path, e))?;
+        let mut command = Command::new(&self.program);
+        match self.backend {
+            Backend::Mediainfo => command.args(["--Output=JSON", &input]),
+            _ => command.args(["-v", &input]),
+        };
+        let timeout = self.options.probe_timeout;
+        let output = cancel::output_within(&mut command, Some(timeout)).map_err(|e| match e.kind() {
+            io::ErrorKind::TimedOut => anyhow!("timed out after {}s", timeout.as_secs_f64()),
+            _ => anyhow!("{} could not be started ('{}': {})", self.backend.label(), self.program, e),
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.trim().lines().last().unwrap_or("failed").to_string();
+            return Err(anyhow!("{}: {}", self.program, message));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let translated = match self.backend {
+            Backend::Mediainfo => mediainfo::translate(&stdout)?,
+            _ => gstreamer::translate(&stdout)?,
+        };
+        let mut info = probe::normalize(path, &input, &translated.to_string(), &self.options);
+        info.analyzer = self.backend;
+        Ok(info)
+    }
+}
@@ -0,0 +1,104 @@
+//! How fast this machine decodes a file's video: the whole stream through
+//! ffmpeg's decoder as fast as it can go, nothing encoded or shown, to see whether
+//! an 8K clip keeps up with its frame rate before it has to play live. Playback
+//! only adds work on top, so a file just above 1x realtime is still a risk.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Benchmark {
+    pub frames: u64,
+    /// Wall-clock time of the decode
+    pub seconds: f64,
+    /// Frames decoded per second
+    pub fps: f64,
+    /// How many times faster than it plays, `None` without a known frame rate
+    pub realtime: Option<f64>,
+}
+
+impl Benchmark {
+    /// `212 fps, 8.5x realtime`
+    pub fn summary(&self) -> String {
+        match self.realtime {
+            Some(realtime) => format!("{:.0} fps, {:.1}x realtime", self.fps, realtime),
+            None => format!("{:.0} fps", self.fps),
+        }
+    }
+}
+
+pub struct Job {
+    pub path: String,
+    /// The file's frame rate, which the decode speed is compared to
+    pub frame_rate: Option<f64>,
+}
+
+/// Decodes one file at a time; running two at once would halve both results.
+pub struct Benchmarker {
+    job_tx: Sender<Job>,
+    results: Receiver<(String, Result<Benchmark, String>)>,
+}
+
+impl Benchmarker {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for job in job_rx {
+                let result = run(&ffmpeg, &job);
+                if result_tx.send((job.path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, job: Job) {
+        // The worker only stops when the benchmarker is dropped
+        let _ = self.job_tx.send(job);
+    }
+
+    /// Files benchmarked since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Benchmark, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn run(ffmpeg: &str, job: &Job) -> Result<Benchmark, String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-benchmark"])
+        .args(sequence::input_args(&job.path))
+        .args(["-i", &job.path, "-map", "0:v:0", "-an", "-sn", "-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    // The last progress line has the final count, `bench:` the real time taken:
+    // "frame= 1250 fps=212 ..." and "bench: utime=20.1s stime=0.9s rtime=5.9s"
+    let value = |key: &str| -> Option<&str> {
+        let rest = &stderr[stderr.rfind(key)? + key.len()..];
+        rest.split_whitespace().next()
+    };
+    let frames: u64 = value("frame=").and_then(|n| n.parse().ok()).ok_or("no frame count in ffmpeg output")?;
+    let seconds: f64 = value("rtime=")
+        .and_then(|t| t.trim_end_matches('s').parse().ok())
+        .ok_or("no benchmark time in ffmpeg output")?;
+    if frames == 0 {
+        return Err("no frames decoded".to_string());
+    }
+    let fps = frames as f64 / seconds.max(0.001);
+    let realtime = job.frame_rate.filter(|rate| *rate > 0.0).map(|rate| fps / rate);
+    Ok(Benchmark { frames, seconds, fps, realtime })
+}
@@ -0,0 +1,170 @@
+//! Black and silent stretches found by ffmpeg's blackdetect and silencedetect
+//! filters, both run in a single decode pass. The ones at the head or tail of a
+//! clip are what trip up playout, so those are called out.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+/// Shortest black or silent stretch reported, in seconds.
+const MIN_DURATION: f64 = 0.5;
+/// Level under which audio counts as silent.
+const SILENCE_THRESHOLD: &str = "-50dB";
+/// Luma (as a fraction of full range) under which a pixel counts as black.
+const BLACK_PIXEL: &str = "0.10";
+/// How close to the start or end a stretch must be to count as leading or trailing.
+const EDGE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Segment {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Blanks {
+    pub black: Vec<Segment>,
+    pub silence: Vec<Segment>,
+}
+
+/// Where a stretch sits in the clip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Head,
+    Tail,
+    /// Covers the whole clip
+    Throughout,
+    Middle,
+}
+
+impl Position {
+    pub fn of(segment: &Segment, duration: Option<f64>) -> Self {
+        let head = segment.start <= EDGE;
+        let tail = duration.is_some_and(|d| segment.end >= d - EDGE);
+        match (head, tail) {
+            (true, true) => Position::Throughout,
+            (true, false) => Position::Head,
+            (false, true) => Position::Tail,
+            (false, false) => Position::Middle,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Position::Head => "head",
+            Position::Tail => "tail",
+            Position::Throughout => "whole clip",
+            Position::Middle => "middle",
+        }
+    }
+}
+
+pub struct Job {
+    pub path: String,
+    pub duration: Option<f64>,
+    pub video: bool,
+    pub audio: bool,
+}
+
+/// Scans one file at a time on a background thread; each needs a full decode.
+pub struct BlankDetector {
+    job_tx: Sender<Job>,
+    results: Receiver<(String, Result<Blanks, String>)>,
+}
+
+impl BlankDetector {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for job in job_rx {
+                let result = detect(&ffmpeg, &job);
+                if result_tx.send((job.path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, job: Job) {
+        // The worker only stops when the detector is dropped
+        let _ = self.job_tx.send(job);
+    }
+
+    /// Files scanned since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Blanks, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn detect(ffmpeg: &str, job: &Job) -> Result<Blanks, String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin"])
+        .args(sequence::input_args(&job.path))
+        .args(["-i", &job.path]);
+    if job.video {
+        let filter = format!("blackdetect=d={}:pix_th={}", MIN_DURATION, BLACK_PIXEL);
+        command.args(["-map", "0:v:0", "-vf", &filter]);
+    }
+    if job.audio {
+        let filter = format!("silencedetect=n={}:d={}", SILENCE_THRESHOLD, MIN_DURATION);
+        command.args(["-map", "0:a:0", "-af", &filter]);
+    }
+    command.args(["-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    let mut blanks = Blanks::default();
+    let mut silence_start = None;
+    for line in stderr.lines() {
+        if line.contains("blackdetect") {
+            // `black_start:0 black_end:5.005 black_duration:5.005`
+            if let (Some(start), Some(end)) = (number_after(line, "black_start:"), number_after(line, "black_end:")) {
+                blanks.black.push(Segment { start, end });
+            }
+        } else if line.contains("silencedetect") {
+            // `silence_start: 12.5`, then later `silence_end: 14.1 | silence_duration: 1.6`
+            if let Some(start) = number_after(line, "silence_start:") {
+                silence_start = Some(start);
+            } else if let Some(end) = number_after(line, "silence_end:") {
+                blanks.silence.push(Segment { start: silence_start.take().unwrap_or(0.0), end });
+            }
+        }
+    }
+    // Silence running into the end of the file may never be closed
+    if let (Some(start), Some(end)) = (silence_start, job.duration) {
+        blanks.silence.push(Segment { start, end });
+    }
+    Ok(blanks)
+}
+
+fn number_after(line: &str, key: &str) -> Option<f64> {
+    let rest = line[line.find(key)? + key.len()..].trim_start();
+    let end = rest.find(|c: char| c.is_whitespace() || c == '|').unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// `00:01:02.35`, precise enough to find the frame in an editor.
+pub fn format_timestamp(seconds: f64) -> String {
+    let hundredths = (seconds.max(0.0) * 100.0).round() as u64;
+    let (secs, hundredths) = (hundredths / 100, hundredths % 100);
+    format!("{:02}:{:02}:{:02}.{:02}", secs / 3600, secs % 3600 / 60, secs % 60, hundredths)
+}
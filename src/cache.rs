@@ -0,0 +1,104 @@
+//! Analysis results kept on disk between runs, so re-opening a large archive only
+//! probes the files that changed. An entry is reused while the file's size and
+//! modification time match what they were when it was analyzed; URLs, streams and
+//! image sequences have neither and are always probed.
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, time::UNIX_EPOCH};
+use xxhash_rust::xxh64::xxh64;
+
+use crate::{
+    analyzer::Backend,
+    config::{self, Config},
+    model::MediaInfo,
+    probe::is_url,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Results from another release may have been normalized differently
+    version: String,
+    path: String,
+    size: u64,
+    /// Seconds and nanoseconds since the epoch
+    modified: (u64, u32),
+    info: MediaInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    /// `None` when caching is off or there is nowhere to keep it
+    dir: Option<PathBuf>,
+    /// The ffprobe arguments change what a probe finds, so they're part of the key
+    ffprobe_args: Vec<String>,
+}
+
+impl AnalysisCache {
+    pub fn from_config(config: &Config) -> Self {
+        let dir = config.cache.then(config::cache_dir).flatten().map(|dir| dir.join("analysis"));
+        Self { dir, ffprobe_args: config.ffprobe_args.clone() }
+    }
+
+    /// Where the entry for `path` read by `backend` lives. Relative paths are resolved
+    /// first, so a file added from another directory still finds its entry.
+    fn entry_file(&self, path: &str, backend: Backend) -> Option<(PathBuf, String)> {
+        let dir = self.dir.as_ref()?;
+        let path = fs::canonicalize(path).ok()?.to_string_lossy().to_string();
+        let key = format!("{}\0{}\0{}", backend.label(), path, self.ffprobe_args.join("\0"));
+        Some((dir.join(format!("{:016x}.json", xxh64(key.as_bytes(), 0))), path))
+    }
+
+    /// The size and modification time of a local file, `None` for anything else.
+    fn stamp(path: &str) -> Option<(u64, (u64, u32))> {
+        if is_url(path) {
+            return None;
+        }
+        let metadata = fs::metadata(path).ok().filter(|metadata| metadata.is_file())?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some((metadata.len(), (modified.as_secs(), modified.subsec_nanos())))
+    }
+
+    /// The cached reading of `path` by `backend`, if the file hasn't changed since.
+    pub fn get(&self, path: &str, backend: Backend) -> Option<MediaInfo> {
+        let (size, modified) = Self::stamp(path)?;
+        let (file, absolute) = self.entry_file(path, backend)?;
+        let entry: Entry = serde_json::from_slice(&fs::read(file).ok()?).ok()?;
+        let current = entry.version == env!("CARGO_PKG_VERSION")
+            && entry.path == absolute
+            && entry.size == size
+            && entry.modified == modified;
+        // As added this time, which the rest of the UI looks the row up by
+        let mut info = entry.info;
+        info.path = path.to_string();
+        current.then_some(info)
+    }
+
+    /// Records `info` for its path. Failures only cost a probe next time, so they
+    /// are ignored.
+    pub fn put(&self, info: &MediaInfo) {
+        let Some((size, modified)) = Self::stamp(&info.path) else {
+            return;
+        };
+        let Some((file, absolute)) = self.entry_file(&info.path, info.analyzer) else {
+            return;
+        };
+        let entry = Entry {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            path: absolute,
+            size,
+            modified,
+            info: info.clone(),
+        };
+        let Ok(json) = serde_json::to_vec(&entry) else {
+            return;
+        };
+        // Written aside and renamed, so a reader never sees half an entry
+        let partial = file.with_extension(format!("{}.tmp", std::process::id()));
+        let written = file.parent().is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+            && fs::write(&partial, json).is_ok()
+            && fs::rename(&partial, &file).is_ok();
+        if !written {
+            let _ = fs::remove_file(&partial);
+        }
+    }
+}
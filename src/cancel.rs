@@ -0,0 +1,93 @@
+//! Cancelling the background passes. Every cancel moves a shared count on; a
+//! worker thread pins the count its work was queued at, the ffmpeg and ffprobe
+//! processes it runs are killed once the count moves past it, and anything it
+//! still had queued fails straight away instead of starting.
+
+use std::{
+    cell::Cell,
+    io::{self, Read},
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // `None` on threads nothing ever cancels, such as the one of a headless run
+    static PINNED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Cancels the work of every thread pinned so far.
+pub fn cancel_all() {
+    EPOCH.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The current count, for a worker to pin once it starts.
+pub fn epoch() -> u64 {
+    EPOCH.load(Ordering::SeqCst)
+}
+
+/// Ties the work of the current thread to `epoch`, so a later cancel stops it.
+pub fn pin(epoch: u64) {
+    PINNED.set(Some(epoch));
+}
+
+/// Whether the work of the current thread has been cancelled.
+pub fn cancelled() -> bool {
+    PINNED.get().is_some_and(|pinned| pinned != epoch())
+}
+
+pub fn interrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
+}
+
+/// Runs `command` to completion like `Command::output`, with stdin closed, but
+/// kills it once the thread's work is cancelled.
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    output_within(command, None)
+}
+
+/// `output`, also killing the process after `timeout`, with an error of kind
+/// `TimedOut`.
+pub fn output_within(command: &mut Command, timeout: Option<Duration>) -> io::Result<Output> {
+    if cancelled() {
+        return Err(interrupted());
+    }
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    // Drained on threads so a chatty process can't block on a full pipe
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let started = Instant::now();
+    // Short waits at first, so quick probes of a large batch don't add up to much
+    let mut wait = Duration::from_millis(1);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let timed_out = timeout.is_some_and(|timeout| started.elapsed() >= timeout);
+        if timed_out || cancelled() {
+            let _ = child.kill();
+            // Reaped off this thread: a process stuck on a dead network mount can take
+            // its time to die
+            thread::spawn(move || child.wait());
+            return Err(match timed_out {
+                true => io::Error::new(io::ErrorKind::TimedOut, "timed out"),
+                false => interrupted(),
+            });
+        }
+        thread::sleep(wait);
+        wait = (wait * 2).min(Duration::from_millis(50));
+    };
+    Ok(Output { status, stdout: stdout.join().unwrap_or_default(), stderr: stderr.join().unwrap_or_default() })
+}
@@ -0,0 +1,109 @@
+//! Whole-file checksums for delivery manifests, computed in the background since
+//! large masters take a while to read.
+
+use clap::ValueEnum;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    fs::File,
+    io::{self, Read},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::cancel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    Md5,
+    #[default]
+    Sha256,
+    /// xxHash64, the fast checksum offload tools such as Silverstack and ShotPut write
+    #[serde(alias = "xxhash")]
+    Xxh64,
+}
+
+impl Algorithm {
+    pub fn label(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Xxh64 => "xxHash64",
+        }
+    }
+}
+
+/// Lowercase hex digest of the whole file.
+pub fn compute(algorithm: Algorithm, path: &str) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    Ok(match algorithm {
+        Algorithm::Md5 => {
+            let mut hasher = Md5::new();
+            read_chunks(&mut file, |data| hasher.update(data))?;
+            hex(&hasher.finalize())
+        }
+        Algorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            read_chunks(&mut file, |data| hasher.update(data))?;
+            hex(&hasher.finalize())
+        }
+        Algorithm::Xxh64 => {
+            let mut hasher = Xxh64::new(0);
+            read_chunks(&mut file, |data| hasher.update(data))?;
+            format!("{:016x}", hasher.digest())
+        }
+    })
+}
+
+fn read_chunks(file: &mut File, mut update: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        if cancel::cancelled() {
+            return Err(cancel::interrupted());
+        }
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        update(&buffer[..read]);
+    }
+}
+
+/// Checksums one file at a time; reading several at once would only make the
+/// disk seek between them.
+pub struct Checksummer {
+    path_tx: Sender<String>,
+    results: Receiver<(String, Result<String, String>)>,
+}
+
+impl Checksummer {
+    pub fn new(algorithm: Algorithm) -> Self {
+        let (path_tx, path_rx) = mpsc::channel::<String>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for path in path_rx {
+                let result = compute(algorithm, &path).map_err(|e| e.to_string());
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { path_tx, results }
+    }
+
+    pub fn queue(&self, path: String) {
+        // The worker only stops when the checksummer is dropped
+        let _ = self.path_tx.send(path);
+    }
+
+    /// Files finished since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<String, String>)> {
+        self.results.try_iter().collect()
+    }
+}
@@ -0,0 +1,248 @@
+//! Columns of the Files table, which ones show and in what order, from the
+//! `columns` config key or the column picker (`H`).
+
+use anyhow::{bail, Result};
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Name,
+    Qc,
+    Decode,
+    Dup,
+    Checksum,
+    Lufs,
+    Quality,
+    HwDecode,
+    Scan,
+    Picture,
+    Timecode,
+    Languages,
+    Created,
+    Audio,
+    Mxf,
+    Tags,
+    Note,
+    Rating,
+    Flag,
+    Container,
+    Codec,
+    Resolution,
+    Aspect,
+    Fps,
+    Bitrate,
+    Duration,
+    Size,
+    PixFmt,
+    Depth,
+    Hdr,
+    Alpha,
+    Faststart,
+}
+
+impl Column {
+    /// Every column, in the default order.
+    pub const ALL: [Column; 32] = [
+        Column::Name,
+        Column::Qc,
+        Column::Decode,
+        Column::Dup,
+        Column::Checksum,
+        Column::Lufs,
+        Column::Quality,
+        Column::HwDecode,
+        Column::Scan,
+        Column::Picture,
+        Column::Timecode,
+        Column::Languages,
+        Column::Created,
+        Column::Audio,
+        Column::Mxf,
+        Column::Tags,
+        Column::Note,
+        Column::Rating,
+        Column::Flag,
+        Column::Container,
+        Column::Codec,
+        Column::Resolution,
+        Column::Aspect,
+        Column::Fps,
+        Column::Bitrate,
+        Column::Duration,
+        Column::Size,
+        Column::PixFmt,
+        Column::Depth,
+        Column::Hdr,
+        Column::Alpha,
+        Column::Faststart,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Qc => "QC",
+            Column::Decode => "Decode",
+            Column::Dup => "Dup",
+            Column::Checksum => "Checksum",
+            Column::Lufs => "LUFS",
+            Column::Quality => "Quality",
+            Column::HwDecode => "HW decode",
+            Column::Scan => "Scan",
+            Column::Picture => "Picture",
+            Column::Timecode => "Timecode",
+            Column::Languages => "Languages",
+            Column::Created => "Created",
+            Column::Audio => "Audio",
+            Column::Mxf => "MXF",
+            Column::Tags => "Tags",
+            Column::Note => "Note",
+            Column::Rating => "Rating",
+            Column::Flag => "Flag",
+            Column::Container => "Container",
+            Column::Codec => "Codec",
+            Column::Resolution => "Resolution",
+            Column::Aspect => "Aspect",
+            Column::Fps => "FPS",
+            Column::Bitrate => "Bitrate(Mbps)",
+            Column::Duration => "Duration",
+            Column::Size => "Size",
+            Column::PixFmt => "Pix Fmt",
+            Column::Depth => "Depth",
+            Column::Hdr => "HDR",
+            Column::Alpha => "Alpha",
+            Column::Faststart => "Faststart",
+        }
+    }
+
+    /// Columns for a pass or a kind of file, shown only once some file has their data.
+    pub fn on_demand(self) -> bool {
+        matches!(
+            self,
+            Column::Qc
+                | Column::Decode
+                | Column::Dup
+                | Column::Checksum
+                | Column::Lufs
+                | Column::Quality
+                | Column::HwDecode
+                | Column::Scan
+                | Column::Picture
+                | Column::Timecode
+                | Column::Languages
+                | Column::Created
+                | Column::Audio
+                | Column::Mxf
+                | Column::Tags
+                | Column::Note
+                | Column::Rating
+                | Column::Flag
+        )
+    }
+
+    /// The column's width when the config doesn't set one. The columns that only
+    /// appear once some file has their data get a fixed width; the rest share
+    /// what's left of the table.
+    pub fn default_width(self) -> Constraint {
+        match self {
+            Column::Name => Constraint::Percentage(15),
+            Column::Qc => Constraint::Length(4),
+            Column::Decode => Constraint::Length(6),
+            Column::Dup => Constraint::Length(3),
+            Column::Checksum => Constraint::Length(12),
+            Column::Lufs => Constraint::Length(6),
+            Column::Quality => Constraint::Length(13),
+            Column::HwDecode => Constraint::Length(12),
+            Column::Scan => Constraint::Length(4),
+            Column::Picture => Constraint::Length(9),
+            Column::Timecode => Constraint::Length(11),
+            Column::Languages => Constraint::Length(14),
+            Column::Created => Constraint::Length(10),
+            Column::Audio => Constraint::Length(22),
+            Column::Mxf => Constraint::Length(18),
+            Column::Tags => Constraint::Length(18),
+            Column::Note => Constraint::Length(24),
+            Column::Rating => Constraint::Length(6),
+            Column::Flag => Constraint::Length(8),
+            Column::Container => Constraint::Percentage(7),
+            Column::Codec => Constraint::Percentage(8),
+            Column::Resolution => Constraint::Percentage(10),
+            Column::Aspect => Constraint::Percentage(6),
+            Column::Fps => Constraint::Percentage(5),
+            Column::Bitrate => Constraint::Percentage(8),
+            Column::Duration => Constraint::Percentage(8),
+            Column::Size => Constraint::Percentage(8),
+            Column::PixFmt => Constraint::Percentage(8),
+            Column::Depth => Constraint::Percentage(6),
+            Column::Hdr => Constraint::Percentage(7),
+            Column::Alpha => Constraint::Percentage(5),
+            Column::Faststart => Constraint::Percentage(5),
+        }
+    }
+}
+
+/// A column in the config: its name, or a table with a width in cells.
+/// `["name", { name = "codec", width = 12 }, "duration"]`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnSpec {
+    Plain(Column),
+    Sized { name: Column, width: u16 },
+}
+
+impl ColumnSpec {
+    pub fn column(self) -> Column {
+        match self {
+            ColumnSpec::Plain(column) | ColumnSpec::Sized { name: column, .. } => column,
+        }
+    }
+
+    pub fn width(self) -> Option<u16> {
+        match self {
+            ColumnSpec::Plain(_) => None,
+            ColumnSpec::Sized { width, .. } => Some(width),
+        }
+    }
+
+    pub fn with_width(self, width: Option<u16>) -> Self {
+        match width {
+            Some(width) => ColumnSpec::Sized { name: self.column(), width },
+            None => ColumnSpec::Plain(self.column()),
+        }
+    }
+
+    /// Wider or narrower by `delta` cells, starting from the default width, or
+    /// the header's for a column that shares the table's width.
+    pub fn resized(self, delta: i16) -> Self {
+        let column = self.column();
+        let width = self.width().unwrap_or(match column.default_width() {
+            Constraint::Length(width) => width,
+            _ => column.header().chars().count() as u16,
+        });
+        self.with_width(Some(width.saturating_add_signed(delta).max(1)))
+    }
+
+    pub fn constraint(self) -> Constraint {
+        self.width().map_or(self.column().default_width(), Constraint::Length)
+    }
+}
+
+/// Every column in the default order, for a config without `columns`.
+pub fn default_columns() -> Vec<ColumnSpec> {
+    Column::ALL.into_iter().map(ColumnSpec::Plain).collect()
+}
+
+/// Rejects a column listed twice or given no width, at startup.
+pub fn validate(columns: &[ColumnSpec]) -> Result<()> {
+    for (i, spec) in columns.iter().enumerate() {
+        let column = spec.column();
+        if columns[..i].iter().any(|c| c.column() == column) {
+            bail!("columns: {} is listed twice", column.header());
+        }
+        if spec.width() == Some(0) {
+            bail!("columns: {} needs a width above 0", column.header());
+        }
+    }
+    Ok(())
+}
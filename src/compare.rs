@@ -0,0 +1,61 @@
+//! The compare view: the metadata of two or more files side by side, the fields
+//! they disagree on highlighted, e.g. to see what changed between two versions
+//! of a render.
+
+use ratatui::widgets::TableState;
+
+use crate::{export, MediaInfo};
+
+/// One field of every compared file.
+pub struct FieldRow {
+    pub label: &'static str,
+    pub values: Vec<String>,
+    pub differs: bool,
+}
+
+pub struct Comparison {
+    /// The compared files, in table order
+    pub paths: Vec<String>,
+    /// Hides the fields every file agrees on
+    pub only_differences: bool,
+    /// First file shown, when they don't all fit
+    pub offset: usize,
+    pub table_state: TableState,
+}
+
+impl Comparison {
+    pub fn new(paths: Vec<String>) -> Self {
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+        Self { paths, only_differences: false, offset: 0, table_state }
+    }
+
+    pub fn files<'a>(&self, media_files: &'a [MediaInfo]) -> Vec<&'a MediaInfo> {
+        self.paths.iter().filter_map(|path| media_files.iter().find(|f| f.path == *path)).collect()
+    }
+
+    /// The exported fields but the name, which heads the columns. Paths always
+    /// differ, so theirs doesn't count as a difference.
+    pub fn rows(&self, media_files: &[MediaInfo]) -> Vec<FieldRow> {
+        let fields: Vec<Vec<(&'static str, String)>> =
+            self.files(media_files).into_iter().map(export::fields).collect();
+        let Some(first) = fields.first() else {
+            return Vec::new();
+        };
+        (0..first.len())
+            .filter(|&n| first[n].0 != "Name")
+            .map(|n| {
+                let label = first[n].0;
+                let values: Vec<String> = fields.iter().map(|file| file[n].1.clone()).collect();
+                let differs = label != "Path" && values.iter().any(|value| *value != values[0]);
+                FieldRow { label, values, differs }
+            })
+            .filter(|row| row.differs || !self.only_differences)
+            .collect()
+    }
+
+    pub fn toggle_only_differences(&mut self) {
+        self.only_differences = !self.only_differences;
+        self.table_state.select(Some(0));
+    }
+}
@@ -0,0 +1,151 @@
+//! Whether a clip plays natively on the common media servers. The rules follow
+//! each server's published codec recommendations and are deliberately coarse:
+//! they answer "will this need converting before the show", not "will it play at 60 fps".
+
+use ratatui::style::Color;
+
+use crate::{model::MediaInfo, theme::Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaServer {
+    Resolume,
+    TouchDesigner,
+    Millumin,
+}
+
+impl MediaServer {
+    pub const ALL: [MediaServer; 3] = [
+        MediaServer::Resolume,
+        MediaServer::TouchDesigner,
+        MediaServer::Millumin,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaServer::Resolume => "Resolume",
+            MediaServer::TouchDesigner => "TouchDesigner",
+            MediaServer::Millumin => "Millumin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Support {
+    Native,
+    Transcode,
+    Unsupported,
+}
+
+impl Support {
+    pub fn label(self) -> &'static str {
+        match self {
+            Support::Native => "plays natively",
+            Support::Transcode => "will transcode on import",
+            Support::Unsupported => "unsupported",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            Support::Native => theme.good,
+            Support::Transcode => theme.warning,
+            Support::Unsupported => theme.bad,
+        }
+    }
+}
+
+pub struct Compatibility {
+    pub support: Support,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Family {
+    /// Hap and DXV: GPU texture codecs
+    Hap,
+    Dxv,
+    NotchLc,
+    ProRes,
+    Dnx,
+    H264,
+    H265,
+    Still,
+    /// No video stream: sound files
+    Audio,
+    /// Formats ffmpeg decodes that no media server treats as a playback codec
+    Other,
+    Unknown,
+}
+
+fn family(codec: &str) -> Family {
+    match codec {
+        c if c.starts_with("Hap") => Family::Hap,
+        "DXV3" => Family::Dxv,
+        "NotchLC" => Family::NotchLc,
+        c if c.starts_with("ProRes") => Family::ProRes,
+        c if c.starts_with("DNx") => Family::Dnx,
+        "H.264" => Family::H264,
+        "H.265" => Family::H265,
+        "JPEG" | "PNG" | "TIFF" | "BMP" | "GIF" | "WebP" | "EXR" | "DPX" | "JPEG 2000" => Family::Still,
+        "None" => Family::Audio,
+        "Unknown" => Family::Unknown,
+        _ => Family::Other,
+    }
+}
+
+pub fn check(server: MediaServer, file: &MediaInfo) -> Compatibility {
+    let codec = file.codec.as_str();
+    let (support, reason) = match (server, family(codec)) {
+        (_, Family::Unknown) => (Support::Unsupported, "no video stream recognised".to_string()),
+        (_, Family::Still) => (Support::Native, "still image".to_string()),
+        (_, Family::Audio) => (Support::Native, format!("{} audio, no video to decode", file.audio_codec)),
+
+        (MediaServer::Resolume, Family::Hap | Family::Dxv | Family::NotchLc) => {
+            (Support::Native, format!("{} is GPU decoded", codec))
+        }
+        (MediaServer::Resolume, _) => (
+            Support::Transcode,
+            format!("{} is CPU decoded, Resolume converts it to DXV", codec),
+        ),
+
+        (MediaServer::TouchDesigner, Family::Dxv) => (
+            Support::Unsupported,
+            "DXV3 only decodes in Resolume".to_string(),
+        ),
+        (MediaServer::TouchDesigner, Family::Other) => (
+            Support::Native,
+            format!("{} plays through the CPU decoder, Hap or NotchLC scale better", codec),
+        ),
+        (MediaServer::TouchDesigner, _) => (Support::Native, format!("{} is supported", codec)),
+
+        (MediaServer::Millumin, Family::Dxv) => (
+            Support::Unsupported,
+            "DXV3 only decodes in Resolume".to_string(),
+        ),
+        (
+            MediaServer::Millumin,
+            Family::Hap | Family::ProRes | Family::H264 | Family::H265 | Family::NotchLc,
+        ) => (Support::Native, format!("{} is supported", codec)),
+        (MediaServer::Millumin, _) => (
+            Support::Transcode,
+            format!("{} isn't a Millumin codec, it offers to convert to ProRes or Hap", codec),
+        ),
+    };
+
+    let mut reason = reason;
+    if matches!(family(codec), Family::Hap | Family::Dxv) && !texture_aligned(&file.resolution) {
+        reason.push_str("; dimensions aren't multiples of 4, expect a padded edge");
+    }
+    if file.variable_frame_rate && support == Support::Native {
+        reason.push_str("; variable frame rate may stutter");
+    }
+    Compatibility { support, reason }
+}
+
+/// DXT-compressed frames are stored in 4x4 blocks.
+fn texture_aligned(resolution: &str) -> bool {
+    resolution
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+        .is_none_or(|(w, h)| w % 4 == 0 && h % 4 == 0)
+}
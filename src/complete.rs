@@ -0,0 +1,96 @@
+//! Tab completion for the Add File dialog. The candidate list doubles as a small
+//! file browser: directories can be entered from it without typing their names.
+
+use ratatui::widgets::ListState;
+use std::{fs, path};
+
+use crate::{is_media_file, paste};
+
+pub struct Candidate {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+pub struct PathCompletion {
+    /// Directory part of the input as typed, e.g. `~/Movies/`
+    base: String,
+    pub candidates: Vec<Candidate>,
+    pub list_state: ListState,
+}
+
+impl PathCompletion {
+    /// Entries of the input's directory whose names start with its last component,
+    /// directories first. Only media files are listed, as nothing else can be added.
+    pub fn new(input: &str) -> Self {
+        let split = input.rfind(path::is_separator).map_or(0, |i| i + 1);
+        let (base, prefix) = input.split_at(split);
+        let dir = if base.is_empty() { ".".into() } else { paste::expand_home(base) };
+        let prefix = prefix.to_lowercase();
+
+        let mut candidates: Vec<Candidate> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Hidden entries only when asked for
+                if name.starts_with('.') && !prefix.starts_with('.') {
+                    return None;
+                }
+                if !name.to_lowercase().starts_with(&prefix) {
+                    return None;
+                }
+                let is_dir = entry.path().is_dir();
+                (is_dir || is_media_file(&entry.path())).then_some(Candidate { name, is_dir })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+        let mut list_state = ListState::default();
+        if !candidates.is_empty() {
+            list_state.select(Some(0));
+        }
+        Self { base: base.to_string(), candidates, list_state }
+    }
+
+    /// The input with candidate `i` filled in; directories end in a separator.
+    pub fn completed(&self, i: usize) -> String {
+        let candidate = &self.candidates[i];
+        let separator = if candidate.is_dir { path::MAIN_SEPARATOR_STR } else { "" };
+        format!("{}{}{}", self.base, candidate.name, separator)
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.list_state.selected().filter(|&i| i < self.candidates.len())
+    }
+
+    /// The input extended as far as every candidate agrees.
+    pub fn common_prefix(&self) -> String {
+        let mut names = self.candidates.iter().map(|c| c.name.as_str());
+        let first = names.next().unwrap_or_default();
+        let len = names.fold(first.len(), |len, name| {
+            first[..len]
+                .char_indices()
+                .zip(name.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(len.min(name.len()), |((i, _), _)| i)
+        });
+        format!("{}{}", self.base, &first[..len])
+    }
+
+    pub fn next(&mut self) {
+        let len = self.candidates.len();
+        if len > 0 {
+            let i = self.list_state.selected().map_or(0, |i| (i + 1) % len);
+            self.list_state.select(Some(i));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.candidates.len();
+        if len > 0 {
+            let i = self.list_state.selected().map_or(0, |i| (i + len - 1) % len);
+            self.list_state.select(Some(i));
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{
+    analyzer::Backend,
+    checksum,
+    columns::{self, ColumnSpec},
+    filter::FilterOptions,
+    highlight::HighlightRule,
+    ignore,
+    keymap::{Action, KeyList},
+    organize,
+    probe::Symlinks,
+    query,
+    rename,
+    report::{self, Brand, ReportFormat},
+    ssh::RemoteHost,
+    template::Template,
+    theme::Palette,
+    thumbnail::Graphics,
+    validate::ValidationProfile,
+};
+
+/// User settings loaded from `config.toml`. Every field is optional in the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// ffprobe executable, either a bare name looked up on PATH or a full path
+    pub ffprobe: String,
+    /// Tool files are analyzed with: ffprobe, mediainfo or gstreamer. `A` switches it
+    /// for the next scans.
+    pub analyzer: Backend,
+    /// MediaInfo CLI executable, for the mediainfo analyzer
+    pub mediainfo: String,
+    /// Discoverer executable, for the gstreamer analyzer
+    pub gst_discoverer: String,
+    /// ffmpeg executable used for suggested and queued re-encodes
+    pub ffmpeg: String,
+    /// Command used by `o` to play a file, e.g. `["mpv", "--loop"]`; the path is
+    /// appended. Empty uses the platform's default application.
+    pub player: Vec<String>,
+    /// Checksum computed with `K` (and by `--checksum` without the TUI): md5, sha256 or xxh64
+    pub checksum: checksum::Algorithm,
+    /// Format of the reports `w` writes: html, markdown or pdf
+    pub report: ReportFormat,
+    /// Letterhead of PDF reports: a name, a JPEG logo and an accent color
+    pub brand: Brand,
+    /// File name pattern Ctrl+R starts from, e.g. `{name}_{resolution}_{codec}.{container}`
+    pub rename: String,
+    /// Folder pattern Ctrl+O starts from, e.g. `~/Library/{codec}/{height}p`
+    pub organize: String,
+    /// How `t` draws frame previews: auto, kitty, iterm, sixel or blocks
+    pub graphics: Graphics,
+    /// Mbps above which the bitrate graph (`B`) flags a peak, twice the file's
+    /// average when unset
+    pub peak_bitrate: Option<f64>,
+    /// Extra arguments added to every ffprobe invocation, e.g. `["-probesize", "100M"]`
+    pub ffprobe_args: Vec<String>,
+    /// Number of files analyzed concurrently
+    pub parallelism: usize,
+    /// Keep analysis results in the cache directory and reuse them while a file's size
+    /// and modification time stay the same
+    pub cache: bool,
+    /// Collapse numbered stills in scanned directories (`frame_0001.png` …) into one
+    /// image sequence entry. Turn off to list a camera's `IMG_0001.JPG` … one by one.
+    pub image_sequences: bool,
+    /// Symlinks met while scanning a directory: follow (to what they point to,
+    /// once however many lead there) or skip
+    pub symlinks: Symlinks,
+    /// `.gitignore`-style rules of what directory scans leave out, e.g. `["*_proxy.mov",
+    /// "Cache/"]`; a `.scoutignore` file adds rules for its own directory
+    pub ignore: Vec<String>,
+    /// Levels of folders a scan goes down, 1 for the files of the folder itself;
+    /// every level when unset
+    pub max_depth: Option<usize>,
+    /// File extensions scans pick up, e.g. `["mov", "mp4"]`, in place of the usual
+    /// media extensions
+    pub extensions: Vec<String>,
+    /// Sizes outside which scans leave files out, e.g. `"100MB"` or `"4 GiB"`
+    pub min_size: Option<String>,
+    pub max_size: Option<String>,
+    /// Frame rate image sequences are timed at, for their duration and bitrate. The
+    /// ffmpeg passes read them at ffmpeg's default of 25 whatever this says.
+    pub sequence_fps: f64,
+    /// Seconds a probe of a file may take before it's killed and the file reported
+    /// as timed out, so a dead network mount can't stall a scan
+    pub probe_timeout: f64,
+    /// Seconds to wait on a live stream (`rtsp://`, `srt://`, HLS ...) before giving up
+    pub stream_timeout: f64,
+    /// Seconds of a live stream read to measure its bitrate
+    pub stream_sample: f64,
+    /// AWS CLI executable used to list and presign `s3://` objects
+    pub aws: String,
+    /// Endpoint of an S3-compatible store, e.g. `https://minio.example.com`; AWS when unset
+    pub s3_endpoint: Option<String>,
+    /// Machines `ssh://<name>/...` paths are probed on
+    pub hosts: Vec<RemoteHost>,
+    /// Options listed on the Filters tab
+    pub filters: FilterOptions,
+    /// Saved filter queries, listed on the Filters tab and bound to 1-9 in order
+    pub presets: Vec<FilterPreset>,
+    /// QC profiles, shown in the QC column one at a time
+    pub profiles: Vec<ValidationProfile>,
+    /// Row colors, first matching rule wins
+    pub highlights: Vec<HighlightRule>,
+    /// Color theme: dark, light, high-contrast or a palette from `themes`
+    pub theme: String,
+    /// Palettes by name, each overriding some colors of a built-in theme
+    pub themes: BTreeMap<String, Palette>,
+    /// Output templates by name, for `--template` and the palette's "Copy as"
+    pub templates: BTreeMap<String, String>,
+    /// Keys for the main view's actions in place of the defaults, e.g. `quit = "Q"`
+    pub keys: BTreeMap<Action, KeyList>,
+    /// Files table columns in order, each a name or `{ name, width }`; unlisted ones are hidden
+    pub columns: Vec<ColumnSpec>,
+}
+
+/// A named filter query, e.g. `codec~Hap AND resolution<=3840x2160 AND fps<=60`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub query: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            ffprobe: "ffprobe".to_string(),
+            analyzer: Backend::default(),
+            mediainfo: "mediainfo".to_string(),
+            gst_discoverer: "gst-discoverer-1.0".to_string(),
+            ffmpeg: "ffmpeg".to_string(),
+            player: Vec::new(),
+            checksum: checksum::Algorithm::default(),
+            report: ReportFormat::default(),
+            brand: Brand::default(),
+            rename: rename::DEFAULT_PATTERN.to_string(),
+            organize: organize::DEFAULT_PATTERN.to_string(),
+            graphics: Graphics::default(),
+            peak_bitrate: None,
+            ffprobe_args: Vec::new(),
+            parallelism: thread::available_parallelism().map_or(1, |n| n.get()),
+            cache: true,
+            image_sequences: true,
+            symlinks: Symlinks::default(),
+            ignore: ignore::DEFAULT_RULES.map(String::from).to_vec(),
+            max_depth: None,
+            extensions: Vec::new(),
+            min_size: None,
+            max_size: None,
+            sequence_fps: 25.0,
+            probe_timeout: 60.0,
+            stream_timeout: 15.0,
+            stream_sample: 5.0,
+            aws: "aws".to_string(),
+            s3_endpoint: None,
+            hosts: Vec::new(),
+            filters: FilterOptions::default(),
+            presets: Vec::new(),
+            profiles: Vec::new(),
+            highlights: Vec::new(),
+            theme: "dark".to_string(),
+            themes: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            keys: BTreeMap::new(),
+            columns: columns::default_columns(),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/media-scout`, falling back to `~/.config/media-scout`
+/// (or `%APPDATA%\media-scout` on Windows).
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(base.join("media-scout"))
+}
+
+/// `$XDG_CACHE_HOME/media-scout`, falling back to `~/.cache/media-scout`
+/// (or `%LOCALAPPDATA%\media-scout` on Windows).
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+    Some(base.join("media-scout"))
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    /// Loads the config from `path`, or the default location when `None`.
+    /// A missing file yields the defaults; a malformed one is an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(default_config_path) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        let mut config: Config = toml::from_str(&contents)
+            .with_context(|| format!("parsing config {}", path.display()))?;
+        config.parallelism = config.parallelism.max(1);
+        if config.sequence_fps.is_nan() || config.sequence_fps <= 0.0 {
+            anyhow::bail!("sequence_fps in {} must be above 0", path.display());
+        }
+        let durations = [
+            ("probe_timeout", config.probe_timeout),
+            ("stream_timeout", config.stream_timeout),
+            ("stream_sample", config.stream_sample),
+        ];
+        for (key, seconds) in durations {
+            // `inf` is valid TOML, and too long for a `Duration`
+            if seconds.is_nan() || seconds <= 0.0 || Duration::try_from_secs_f64(seconds).is_err() {
+                anyhow::bail!("{} in {} must be a number of seconds above 0", key, path.display());
+            }
+        }
+        Template::parse_file_name(&config.rename).with_context(|| format!("rename in {}", path.display()))?;
+        organize::Pattern::parse(&config.organize).with_context(|| format!("organize in {}", path.display()))?;
+        if config.max_depth == Some(0) {
+            anyhow::bail!("max_depth in {} must be at least 1", path.display());
+        }
+        for (key, size) in [("min_size", &config.min_size), ("max_size", &config.max_size)] {
+            if size.as_deref().is_some_and(|size| query::parse_size(size).is_none()) {
+                anyhow::bail!("{} in {} must be a size such as \"100MB\"", key, path.display());
+            }
+        }
+        for (name, text) in &config.templates {
+            Template::parse(text).with_context(|| format!("template '{}' in {}", name, path.display()))?;
+        }
+        if config.brand.color.as_deref().is_some_and(|color| report::parse_hex(color).is_none()) {
+            anyhow::bail!("brand.color in {} must be #rrggbb", path.display());
+        }
+        Ok(config)
+    }
+}
+
+/// Writes `presets` into the config file at `path` (or the default location),
+/// keeping the file's other settings. Returns the path written.
+pub fn save_presets(path: Option<&Path>, presets: &[FilterPreset]) -> Result<PathBuf> {
+    save_setting(path, "presets", toml::Value::try_from(presets)?)
+}
+
+/// Writes the Files table's `columns` the same way, for the column picker.
+pub fn save_columns(path: Option<&Path>, columns: &[ColumnSpec]) -> Result<PathBuf> {
+    save_setting(path, "columns", toml::Value::try_from(columns)?)
+}
+
+fn save_setting(path: Option<&Path>, key: &str, value: toml::Value) -> Result<PathBuf> {
+    let path = path
+        .map(Path::to_path_buf)
+        .or_else(default_config_path)
+        .context("no config directory found, pass --config")?;
+    // Edited in place, so the user's comments, key order and layout survive
+    let mut document = if path.exists() {
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        contents
+            .parse::<DocumentMut>()
+            .with_context(|| format!("parsing config {}", path.display()))?
+    } else {
+        DocumentMut::new()
+    };
+    // Written out on its own first, for the `[[presets]]` layout a person would use
+    let mut setting = toml::Table::new();
+    setting.insert(key.to_string(), value);
+    let mut written = toml::to_string_pretty(&setting)?.parse::<DocumentMut>()?;
+    let mut item = written.remove(key).context("setting went missing while formatting")?;
+    // In the old setting's place, with its comments and spacing; a new one goes last
+    let first_in_file = document.as_table().is_empty();
+    let old = document.get_mut(key);
+    let old_table = old.as_deref().and_then(|old| match old {
+        Item::Table(table) => Some(table),
+        Item::ArrayOfTables(tables) => tables.iter().next(),
+        _ => None,
+    });
+    let (position, decor) = (old_table.and_then(Table::position), old_table.map(|table| table.decor().clone()));
+    let tables: Vec<&mut Table> = match &mut item {
+        Item::Value(value) => {
+            if let Some(Item::Value(old)) = old.as_deref() {
+                *value.decor_mut() = old.decor().clone();
+            }
+            Vec::new()
+        }
+        Item::Table(table) => vec![table],
+        Item::ArrayOfTables(tables) => tables.iter_mut().collect(),
+        Item::None => Vec::new(),
+    };
+    for (i, table) in tables.into_iter().enumerate() {
+        table.set_position(position);
+        match (&decor, i) {
+            (Some(decor), 0) => *table.decor_mut() = decor.clone(),
+            (None, 0) if first_in_file => {}
+            _ => table.decor_mut().set_prefix("\n"),
+        }
+    }
+    match old {
+        // Replaced in its slot, which keeps the comments above the key
+        Some(old) => *old = item,
+        None => {
+            document.insert(key, item);
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    fs::write(&path, document.to_string())
+        .with_context(|| format!("writing config {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(name: &str, contents: Option<&str>, save: impl Fn(&Path) -> Result<PathBuf>) -> String {
+        let dir = env::temp_dir().join(format!("media-scout-config-{}-{}", name, std::process::id()));
+        let path = dir.join("config.toml");
+        let _ = fs::remove_dir_all(&dir);
+        if let Some(contents) = contents {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(&path, contents).unwrap();
+        }
+        save(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        written
+    }
+
+    fn load(contents: &str) -> Result<Config> {
+        let path = env::temp_dir().join(format!("media-scout-load-{}-{}.toml", std::process::id(), contents.len()));
+        fs::write(&path, contents).unwrap();
+        let config = Config::load(Some(&path));
+        let _ = fs::remove_file(&path);
+        config
+    }
+
+    fn preset(name: &str, query: &str) -> FilterPreset {
+        FilterPreset { name: name.to_string(), query: query.to_string() }
+    }
+
+    const CONFIG: &str = r#"# media-scout settings
+ffprobe = "/opt/ffmpeg/bin/ffprobe"   # the build with libvmaf
+
+parallelism = 8
+
+[[presets]]
+name = "old"
+query = "codec=H.264"
+
+[brand]
+# shown on PDF reports
+name = "Studio"
+"#;
+
+    #[test]
+    fn timeouts_must_fit_a_duration() {
+        for value in ["0", "-1", "nan", "inf", "1e300"] {
+            let error = load(&format!("probe_timeout = {}\n", value)).unwrap_err().to_string();
+            assert!(error.contains("probe_timeout in") && error.ends_with("must be a number of seconds above 0"));
+        }
+        assert_eq!(load("stream_timeout = 2.5\n").unwrap().stream_timeout, 2.5);
+    }
+
+    #[test]
+    fn saving_presets_keeps_the_rest_of_the_file() {
+        let presets = [preset("Delivery", "codec=H.264 AND fps=25"), preset("4K", "resolution>=2160")];
+        let written = saved("presets", Some(CONFIG), |path| save_presets(Some(path), &presets));
+        assert!(written.starts_with("# media-scout settings\nffprobe = \"/opt/ffmpeg/bin/ffprobe\"   # the build"));
+        assert!(written.contains("\nparallelism = 8\n\n[[presets]]\nname = \"Delivery\""));
+        assert!(written.contains("[brand]\n# shown on PDF reports\nname = \"Studio\"\n"));
+        assert!(!written.contains("\"old\""));
+        // The new presets take the old one's place, ahead of [brand]
+        let delivery = written.find("name = \"Delivery\"").unwrap();
+        assert!(delivery < written.find("[brand]").unwrap());
+        assert!(delivery < written.find("name = \"4K\"").unwrap());
+        let config: Config = toml::from_str(&written).unwrap();
+        assert_eq!(config.presets.len(), 2);
+        assert_eq!(config.parallelism, 8);
+        assert_eq!(config.brand.name.as_deref(), Some("Studio"));
+    }
+
+    #[test]
+    fn a_new_setting_goes_after_the_other_keys() {
+        let columns = [ColumnSpec::Plain(columns::Column::Name), ColumnSpec::Plain(columns::Column::Codec)];
+        let written = saved("columns", Some(CONFIG), |path| save_columns(Some(path), &columns));
+        // A plain value has to come before the first table
+        let columns_text = "columns = [\n    \"name\",\n    \"codec\",\n]\n";
+        assert_eq!(written.replacen(columns_text, "", 1), CONFIG);
+        let config: Config = toml::from_str(&written).unwrap();
+        assert_eq!(config.columns, columns);
+        assert_eq!(config.presets.len(), 1);
+    }
+
+    #[test]
+    fn a_replaced_value_keeps_its_comments() {
+        let config = "# picked in the TUI\ncolumns = [\"name\"] # for the review room\nparallelism = 2\n";
+        let written = saved("existing", Some(config), |path| save_columns(Some(path), &[]));
+        assert_eq!(written, "# picked in the TUI\ncolumns = [] # for the review room\nparallelism = 2\n");
+    }
+
+    #[test]
+    fn saving_creates_a_missing_file() {
+        let written = saved("new", None, |path| save_presets(Some(path), &[preset("Hap", "codec~Hap")]));
+        assert!(written.starts_with("[[presets]]\n"));
+        let config: Config = toml::from_str(&written).unwrap();
+        assert_eq!(config.presets[0].query, "codec~Hap");
+    }
+}
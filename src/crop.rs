@@ -0,0 +1,146 @@
+//! Active picture area found by ffmpeg's cropdetect, for spotting letterboxed and
+//! pillarboxed files whose stored resolution overstates the picture.
+//!
+//! Frames are sampled from several points in the clip and their picture areas
+//! combined, so a dark opening scene doesn't pass for bars.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+/// Points in the clip sampled, and frames read at each.
+const SAMPLES: usize = 5;
+const FRAMES_PER_SAMPLE: &str = "10";
+/// Bars thinner than this share of the frame are treated as encoder padding, not matting.
+const TOLERANCE: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Crop {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Crop {
+    /// `1920x800`
+    pub fn size(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+
+    /// Letterbox, Pillarbox, Windowbox or Full, against the stored `WxH` resolution.
+    pub fn matte(&self, resolution: &str) -> &'static str {
+        let Some((width, height)) = resolution
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse::<f64>().ok()?, h.parse::<f64>().ok()?)))
+        else {
+            return "Unknown";
+        };
+        let bars = |active: u32, full: f64| (active as f64) < full * (1.0 - TOLERANCE);
+        match (bars(self.height, height), bars(self.width, width)) {
+            (true, true) => "Windowbox",
+            (true, false) => "Letterbox",
+            (false, true) => "Pillarbox",
+            (false, false) => "Full",
+        }
+    }
+
+    /// The smallest area covering both.
+    fn union(self, other: Crop) -> Crop {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Crop { width: right - x, height: bottom - y, x, y }
+    }
+}
+
+/// Runs cropdetect on one file at a time on a background thread.
+pub struct CropDetector {
+    job_tx: Sender<(String, Option<f64>)>,
+    results: Receiver<(String, Result<Crop, String>)>,
+}
+
+impl CropDetector {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<(String, Option<f64>)>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for (path, duration) in job_rx {
+                let result = detect(&ffmpeg, &path, duration);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, path: String, duration: Option<f64>) {
+        // The worker only stops when the detector is dropped
+        let _ = self.job_tx.send((path, duration));
+    }
+
+    /// Files measured since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Crop, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn detect(ffmpeg: &str, path: &str, duration: Option<f64>) -> Result<Crop, String> {
+    let times: Vec<f64> = match duration.filter(|d| *d > 0.0) {
+        Some(duration) => (0..SAMPLES)
+            .map(|i| duration * (i as f64 + 0.5) / SAMPLES as f64)
+            .collect(),
+        None => vec![0.0],
+    };
+    let mut area: Option<Crop> = None;
+    for time in times {
+        if let Some(crop) = detect_at(ffmpeg, path, time)? {
+            area = Some(area.map_or(crop, |area| area.union(crop)));
+        }
+    }
+    area.ok_or_else(|| "cropdetect found no picture".to_string())
+}
+
+/// The picture area of a few frames from `time` on; `None` when they are all black.
+fn detect_at(ffmpeg: &str, path: &str, time: f64) -> Result<Option<Crop>, String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-ss", &format!("{:.3}", time)])
+        .args(sequence::input_args(path))
+        .args(["-i", path])
+        .args(["-map", "0:v:0", "-vf", "cropdetect=round=2:reset=0", "-frames:v", FRAMES_PER_SAMPLE])
+        .args(["-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    // With reset=0 each line covers every frame so far, so the last one is the answer:
+    // `[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1919 y1:140 y2:939 w:1920 h:800 x:0 y:140 pts:0 t:0.0 crop=1920:800:0:140`
+    let Some((line, start)) = stderr.lines().rev().find_map(|line| Some((line, line.rfind("crop=")?))) else {
+        return Ok(None);
+    };
+    let values: Vec<i64> = line[start + "crop=".len()..]
+        .split(':')
+        .filter_map(|v| v.trim().parse().ok())
+        .collect();
+    match values[..] {
+        // All-black frames come out with a negative size
+        [width, height, x, y] if width > 0 && height > 0 => Ok(Some(Crop {
+            width: width as u32,
+            height: height as u32,
+            x: x.max(0) as u32,
+            y: y.max(0) as u32,
+        })),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,63 @@
+//! Finds byte-identical copies of a file saved under different names. Hashing whole
+//! clips would take minutes on an archive drive, so the fingerprint covers the size
+//! and three samples of the content: files that match on all of them are, in
+//! practice, the same file.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::model::MediaInfo;
+
+/// Bytes read from the start, middle and end of each file.
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Hex digest of the file's size and sampled content.
+pub fn fingerprint(path: &Path, size: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hash = Fnv::new();
+    hash.write(&size.to_le_bytes());
+
+    let middle = (size / 2).saturating_sub(SAMPLE_SIZE / 2);
+    let end = size.saturating_sub(SAMPLE_SIZE);
+    let mut buffer = vec![0u8; SAMPLE_SIZE as usize];
+    for offset in [0, middle, end] {
+        file.seek(SeekFrom::Start(offset))?;
+        let read = file.by_ref().take(SAMPLE_SIZE).read(&mut buffer)?;
+        hash.write(&buffer[..read]);
+    }
+    Ok(format!("{:016x}", hash.0))
+}
+
+/// 64-bit FNV-1a, stable across runs unlike the std hasher.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Indices of files sharing a fingerprint, in groups of two or more ordered by
+/// their first member.
+pub fn groups(files: &[MediaInfo]) -> Vec<Vec<usize>> {
+    let mut by_hash: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        if let Some(fingerprint) = &file.fingerprint {
+            by_hash.entry(fingerprint).or_default().push(i);
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = by_hash.into_values().filter(|g| g.len() > 1).collect();
+    groups.sort_by_key(|group| group[0]);
+    groups
+}
@@ -0,0 +1,129 @@
+//! What a set of files would take up re-encoded to another codec, for sizing the
+//! drives of a media server before the transcode is run. Only the video is
+//! counted; the audio of a show file is small next to it.
+
+use crate::MediaInfo;
+
+/// ProRes data rates scale with the picture; Apple's figures are for 1080p at 29.97.
+const PRORES_REFERENCE: f64 = 1920.0 * 1080.0 * 29.97;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Hap,
+    HapAlpha,
+    HapQ,
+    HapQAlpha,
+    ProResProxy,
+    ProResLt,
+    ProRes422,
+    ProResHq,
+    ProRes4444,
+    /// At a bitrate in Mbps
+    H264(f64),
+    H265(f64),
+}
+
+impl Target {
+    /// The targets offered, the delivery codecs at `mbps`.
+    pub fn all(mbps: f64) -> [Target; 11] {
+        [
+            Target::Hap,
+            Target::HapAlpha,
+            Target::HapQ,
+            Target::HapQAlpha,
+            Target::ProResProxy,
+            Target::ProResLt,
+            Target::ProRes422,
+            Target::ProResHq,
+            Target::ProRes4444,
+            Target::H264(mbps),
+            Target::H265(mbps),
+        ]
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Target::Hap => "Hap".to_string(),
+            Target::HapAlpha => "Hap Alpha".to_string(),
+            Target::HapQ => "Hap Q".to_string(),
+            Target::HapQAlpha => "Hap Q Alpha".to_string(),
+            Target::ProResProxy => "ProRes 422 Proxy".to_string(),
+            Target::ProResLt => "ProRes 422 LT".to_string(),
+            Target::ProRes422 => "ProRes 422".to_string(),
+            Target::ProResHq => "ProRes 422 HQ".to_string(),
+            Target::ProRes4444 => "ProRes 4444".to_string(),
+            Target::H264(mbps) => format!("H.264 at {} Mbps", mbps),
+            Target::H265(mbps) => format!("H.265 at {} Mbps", mbps),
+        }
+    }
+
+    /// Video bits per second for `file`, `None` when the codec's rate depends on a
+    /// frame size or rate the file doesn't have.
+    pub fn bitrate(self, file: &MediaInfo) -> Option<f64> {
+        // Hap stores the frame as 4x4 texture blocks; the size is before Snappy,
+        // which takes a little off depending on the picture
+        let hap = |bytes_per_pixel: f64| -> Option<f64> {
+            let (width, height) = dimensions(file)?;
+            let padded = (width.div_ceil(4) * 4 * height.div_ceil(4) * 4) as f64;
+            Some(padded * bytes_per_pixel * 8.0 * frame_rate(file)?)
+        };
+        let prores = |mbps: f64| -> Option<f64> {
+            let (width, height) = dimensions(file)?;
+            Some(mbps * 1e6 * (width * height) as f64 * frame_rate(file)? / PRORES_REFERENCE)
+        };
+        match self {
+            Target::Hap => hap(0.5),
+            Target::HapAlpha | Target::HapQ => hap(1.0),
+            Target::HapQAlpha => hap(1.5),
+            Target::ProResProxy => prores(45.0),
+            Target::ProResLt => prores(102.0),
+            Target::ProRes422 => prores(147.0),
+            Target::ProResHq => prores(220.0),
+            Target::ProRes4444 => prores(330.0),
+            Target::H264(mbps) | Target::H265(mbps) => Some(mbps * 1e6),
+        }
+    }
+}
+
+pub struct Estimate {
+    pub bytes: u64,
+    /// Size of the same files now
+    pub current: u64,
+    pub files: usize,
+    /// Files left out: stills, live streams and anything without a duration or frame size
+    pub skipped: usize,
+}
+
+impl Estimate {
+    /// How many times the current size the transcode would be.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.current > 0).then(|| self.bytes as f64 / self.current as f64)
+    }
+}
+
+/// Duration times the target's bitrate, summed over the files with video.
+pub fn estimate(target: Target, files: &[&MediaInfo]) -> Estimate {
+    let mut estimate = Estimate { bytes: 0, current: 0, files: 0, skipped: 0 };
+    for file in files {
+        let size = match (file.duration, target.bitrate(file)) {
+            (Some(duration), Some(bitrate)) if !file.audio_only && duration > 0.0 => duration * bitrate / 8.0,
+            _ => {
+                estimate.skipped += 1;
+                continue;
+            }
+        };
+        estimate.bytes += size as u64;
+        estimate.current += file.file_size.unwrap_or(0);
+        estimate.files += 1;
+    }
+    estimate
+}
+
+fn dimensions(file: &MediaInfo) -> Option<(u64, u64)> {
+    let (width, height) = file.resolution.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn frame_rate(file: &MediaInfo) -> Option<f64> {
+    file.frame_rate.parse().ok().filter(|rate: &f64| *rate > 0.0)
+}
@@ -0,0 +1,245 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::{
+    compat,
+    compat::MediaServer,
+    model::{
+        display_aspect, display_resolution, faststart_label, format_aspect, format_audio_layout, format_bit_depth,
+        format_duration, format_size, frame_rate_mode, matte, yes_no, MediaInfo,
+    },
+    report::{self, ReportFormat, ReportOptions, REPORT_TITLE},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    Table,
+    Json,
+    Csv,
+    /// The reports of the `report` module, with totals and every file's details
+    Html,
+    Markdown,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn report_format(self) -> Option<ReportFormat> {
+        match self {
+            ExportFormat::Html => Some(ReportFormat::Html),
+            ExportFormat::Markdown => Some(ReportFormat::Markdown),
+            ExportFormat::Pdf => Some(ReportFormat::Pdf),
+            _ => None,
+        }
+    }
+}
+
+pub const COLUMNS: &[&str] = &[
+    "Name", "Container", "Codec", "Resolution", "Rotation", "Display Resolution", "SAR", "DAR", "FPS",
+    "Frame Rate Mode", "Scan", "Bitrate(Mbps)", "Duration", "Frames", "Missing Frames", "Timecode",
+    "Chapters", "Title", "Artist", "Encoder", "Created", "Size", "Pixel Format", "Bit Depth",
+    "Color Primaries", "Color Transfer", "Color Space", "HDR", "Alpha", "Audio Codec", "Audio Channels",
+    "Sample Rate", "Audio Bit Depth", "Audio Languages", "Audio Tracks", "Subtitles", "Closed Captions",
+    "Faststart", "Active Picture", "Matte", "ICC Profile", "Camera", "Lens", "Captured", "Exposure",
+    "MXF Pattern", "MXF Essence", "MXF Writer", "Resolume", "TouchDesigner", "Millumin",
+    "Loudness (LUFS)", "True Peak (dBTP)", "Loudness Range (LU)", "Checksum Algorithm", "Checksum",
+    "VMAF", "PSNR (dB)", "SSIM", "Quality Reference", "HW Decode",
+    "Tags", "Note", "Rating", "Flag", "Path",
+];
+
+fn row(file: &MediaInfo) -> Vec<String> {
+    let mut row = vec![
+        format!("{}.{}", file.name, file.container),
+        file.container.clone(),
+        file.codec.clone(),
+        file.resolution.clone(),
+        file.rotation.to_string(),
+        display_resolution(file),
+        file.sample_aspect_ratio.clone(),
+        format_aspect(display_aspect(file)),
+        file.frame_rate.clone(),
+        frame_rate_mode(file.variable_frame_rate).to_string(),
+        file.scan.clone(),
+        file.bitrate.clone(),
+        format_duration(file.duration),
+        file.sequence.as_ref().map_or(String::new(), |s| s.frames.to_string()),
+        file.sequence.as_ref().map_or(String::new(), |s| s.missing().to_string()),
+        file.timecode.clone().unwrap_or_default(),
+        file.chapters.len().to_string(),
+        file.title.clone().unwrap_or_default(),
+        file.artist.clone().unwrap_or_default(),
+        file.encoder.clone().unwrap_or_default(),
+        file.creation_time.clone().unwrap_or_default(),
+        format_size(file.file_size),
+        file.pixel_format.clone(),
+        format_bit_depth(file.bit_depth),
+        file.color_primaries.clone(),
+        file.color_transfer.clone(),
+        file.color_space.clone(),
+        file.hdr_format.clone(),
+        yes_no(file.has_alpha).to_string(),
+        file.audio_codec.clone(),
+        file.audio_channels.map_or(String::new(), |c| c.to_string()),
+        file.audio_sample_rate.map_or(String::new(), |rate| rate.to_string()),
+        file.audio_bit_depth.map_or(String::new(), |bits| bits.to_string()),
+        file.audio_languages.join("; "),
+        format_audio_layout(file),
+        file.subtitles.join("; "),
+        yes_no(file.closed_captions).to_string(),
+        faststart_label(file.faststart).to_string(),
+        file.crop.map_or(String::new(), |crop| crop.size()),
+        matte(file).to_string(),
+    ];
+    let image = file.image.clone().unwrap_or_default();
+    row.extend(
+        [image.color_profile, image.camera, image.lens, image.captured, image.exposure]
+            .map(Option::unwrap_or_default),
+    );
+    let mxf = file.mxf.clone().unwrap_or_default();
+    row.extend([mxf.operational_pattern.unwrap_or_default(), mxf.essence, mxf.writer.unwrap_or_default()]);
+    for server in MediaServer::ALL {
+        row.push(compat::check(server, file).support.label().to_string());
+    }
+    match &file.loudness {
+        Some(l) => row.extend([l.integrated, l.true_peak, l.range].map(|value| format!("{:.1}", value))),
+        None => row.extend([String::new(), String::new(), String::new()]),
+    }
+    match &file.checksum {
+        Some(checksum) => row.extend([checksum.algorithm.label().to_string(), checksum.digest.clone()]),
+        None => row.extend([String::new(), String::new()]),
+    }
+    match &file.quality {
+        Some(q) => row.extend([
+            q.vmaf.map_or(String::new(), |vmaf| format!("{:.2}", vmaf)),
+            format!("{:.2}", q.psnr),
+            format!("{:.4}", q.ssim),
+            q.reference.clone(),
+        ]),
+        None => row.extend([String::new(), String::new(), String::new(), String::new()]),
+    }
+    row.push(file.hw_decode.as_ref().map_or("", |hw| hw.label()).to_string());
+    let note = file.note.clone().unwrap_or_default();
+    row.extend([
+        note.tags.join("; "),
+        note.text,
+        note.rating.map_or(String::new(), |stars| stars.to_string()),
+        note.flag.map_or("", |flag| flag.label()).to_string(),
+        file.path.clone(),
+    ]);
+    row
+}
+
+/// Every field by column name, in export order.
+pub fn fields(file: &MediaInfo) -> Vec<(&'static str, String)> {
+    COLUMNS.iter().copied().zip(row(file)).collect()
+}
+
+/// One "Column: value" line per field, for pasting a single file's metadata.
+pub fn summary(file: &MediaInfo) -> String {
+    fields(file)
+        .into_iter()
+        .map(|(column, value)| format!("{}: {}", column, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render(files: &[&MediaInfo], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Table => render_table(files),
+        ExportFormat::Json => render_json(files),
+        ExportFormat::Csv => render_csv(files),
+        // PDF is binary and only comes out right written as bytes, as `report::render` returns it
+        ExportFormat::Html | ExportFormat::Markdown | ExportFormat::Pdf => {
+            let options = ReportOptions { title: REPORT_TITLE, filter: None, profile: None, brand: None };
+            let bytes = report::render(files, &options, format.report_format().unwrap_or_default());
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+    }
+}
+
+fn render_table(files: &[&MediaInfo]) -> String {
+    let rows: Vec<Vec<String>> = files.iter().map(|f| row(f)).collect();
+    let mut widths: Vec<usize> = COLUMNS.iter().map(|c| c.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = format_line(COLUMNS.to_vec());
+    out.push('\n');
+    for row in &rows {
+        out.push_str(&format_line(row.iter().map(String::as_str).collect()));
+        out.push('\n');
+    }
+    out
+}
+
+fn render_json(files: &[&MediaInfo]) -> String {
+    // The raw ffprobe dump would dwarf everything else, so it's left out
+    let entries: Vec<Value> = files
+        .iter()
+        .map(|file| {
+            let mut value = serde_json::to_value(file).unwrap_or(Value::Null);
+            if let Some(object) = value.as_object_mut() {
+                object.remove("raw_output");
+            }
+            value
+        })
+        .collect();
+    serde_json::to_string_pretty(&entries).unwrap_or_default() + "\n"
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Rows of cells, the header first, as a Markdown table.
+pub fn markdown_table(rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        out.push_str(&line(row.iter().map(|cell| cell.replace('|', "\\|").replace('\n', " ")).collect()));
+        if i == 0 {
+            out.push_str(&line(row.iter().map(|_| "---".to_string()).collect()));
+        }
+    }
+    out
+}
+
+/// Rows of cells as tab-separated values, which spreadsheets split into columns
+/// when pasted.
+pub fn tsv(rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace(['\t', '\n'], " ")).collect();
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+fn render_csv(files: &[&MediaInfo]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for file in files {
+        let fields: Vec<String> = row(file).iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,651 @@
+//! The Filters tab and filter queries: what a single condition such as `fps>=50`
+//! matches, and [`FilterSet`], a parsed query ready to test files against.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{
+        chroma_subsampling, display_aspect, faststart_label, format_aspect, format_bit_depth, format_duration,
+        format_languages, format_loudness, format_quality, format_sample_rate, format_size, frame_rate_mode,
+        is_anamorphic, is_hdr, matte, orientation, pixel_count, yes_no, MediaInfo,
+    },
+    probe::{date_number, parse_sample_rate},
+    query,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterOptions {
+    pub containers: Vec<String>,
+    pub codecs: Vec<String>,
+    pub resolutions: Vec<String>,
+    pub frame_rates: Vec<String>,
+    pub bitrates: Vec<String>,
+    pub durations: Vec<String>,
+    pub file_sizes: Vec<String>,
+    pub bit_depths: Vec<String>,
+    pub chroma_subsamplings: Vec<String>,
+    pub hdr_formats: Vec<String>,
+    pub alpha: Vec<String>,
+    pub audio_codecs: Vec<String>,
+    pub faststart: Vec<String>,
+    pub subtitles: Vec<String>,
+    pub captions: Vec<String>,
+    pub frame_rate_modes: Vec<String>,
+    pub scan_types: Vec<String>,
+    pub orientations: Vec<String>,
+    pub anamorphic: Vec<String>,
+}
+
+impl Default for FilterOptions {
+    fn default() -> Self {
+        Self {
+            containers: vec![
+                "mp4".to_string(),
+                "mov".to_string(),
+                "avi".to_string(),
+                "mkv".to_string(),
+                "jpg".to_string(),
+                "png".to_string(),
+            ],
+            codecs: vec![
+                "H.264".to_string(),
+                "H.265".to_string(),
+                "VP9".to_string(),
+                "AV1".to_string(),
+                "Hap".to_string(),
+                "DXV3".to_string(),
+                "ProRes".to_string(),
+                "DNxHD".to_string(),
+                "DNxHR".to_string(),
+                "NotchLC".to_string(),
+            ],
+            resolutions: vec![
+                "1920x1080".to_string(),
+                "1280x720".to_string(),
+                "3840x2160".to_string(),
+                "2560x1440".to_string(),
+                ">=2160".to_string(),
+                "<720".to_string(),
+            ],
+            frame_rates: vec![
+                "23.976".to_string(),
+                "24".to_string(),
+                "25".to_string(),
+                "29.97".to_string(),
+                "30".to_string(),
+                "50".to_string(),
+                "59.94".to_string(),
+                "60".to_string(),
+            ],
+            bitrates: vec![
+                "<5".to_string(),
+                "5-10".to_string(),
+                "10-50".to_string(),
+                ">50".to_string(),
+            ],
+            durations: vec![
+                "<30s".to_string(),
+                "30s-10m".to_string(),
+                "10m-1h".to_string(),
+                ">1h".to_string(),
+            ],
+            file_sizes: vec![
+                "<100MiB".to_string(),
+                "100MiB-1GiB".to_string(),
+                ">1GiB".to_string(),
+            ],
+            bit_depths: vec![
+                "8-bit".to_string(),
+                "10-bit".to_string(),
+                "12-bit".to_string(),
+            ],
+            chroma_subsamplings: vec![
+                "4:2:0".to_string(),
+                "4:2:2".to_string(),
+                "4:4:4".to_string(),
+            ],
+            hdr_formats: vec![
+                "SDR".to_string(),
+                "HDR".to_string(),
+                "HDR10".to_string(),
+                "HDR10+".to_string(),
+                "HLG".to_string(),
+                "Dolby Vision".to_string(),
+            ],
+            alpha: vec!["Yes".to_string(), "No".to_string()],
+            audio_codecs: vec![
+                "AAC".to_string(),
+                "PCM".to_string(),
+                "AC-3".to_string(),
+                "Opus".to_string(),
+                "None".to_string(),
+            ],
+            faststart: vec!["Yes".to_string(), "No".to_string()],
+            subtitles: vec!["Yes".to_string(), "No".to_string()],
+            captions: vec!["Yes".to_string(), "No".to_string()],
+            frame_rate_modes: vec!["CFR".to_string(), "VFR".to_string()],
+            scan_types: vec![
+                "Progressive".to_string(),
+                "Interlaced".to_string(),
+                "Telecined".to_string(),
+            ],
+            orientations: vec!["Landscape".to_string(), "Portrait".to_string()],
+            anamorphic: vec!["Yes".to_string(), "No".to_string()],
+        }
+    }
+}
+
+impl FilterOptions {
+    /// Flattens every option into the order shown on the Filters tab.
+    pub fn entries(&self) -> Vec<(FilterType, String)> {
+        let groups = [
+            (FilterType::Container, &self.containers),
+            (FilterType::Codec, &self.codecs),
+            (FilterType::Resolution, &self.resolutions),
+            (FilterType::FrameRate, &self.frame_rates),
+            (FilterType::Bitrate, &self.bitrates),
+            (FilterType::Duration, &self.durations),
+            (FilterType::FileSize, &self.file_sizes),
+            (FilterType::BitDepth, &self.bit_depths),
+            (FilterType::Chroma, &self.chroma_subsamplings),
+            (FilterType::Hdr, &self.hdr_formats),
+            (FilterType::Alpha, &self.alpha),
+            (FilterType::AudioCodec, &self.audio_codecs),
+            (FilterType::Faststart, &self.faststart),
+            (FilterType::Subtitles, &self.subtitles),
+            (FilterType::Captions, &self.captions),
+            (FilterType::FrameRateMode, &self.frame_rate_modes),
+            (FilterType::Scan, &self.scan_types),
+            (FilterType::Orientation, &self.orientations),
+            (FilterType::Anamorphic, &self.anamorphic),
+        ];
+        groups
+            .into_iter()
+            .flat_map(|(filter_type, values)| {
+                values.iter().map(move |v| (filter_type.clone(), v.clone()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterType {
+    Container,
+    Codec,
+    Resolution,
+    FrameRate,
+    Bitrate,
+    Duration,
+    Timecode,
+    Chapters,
+    Encoder,
+    Created,
+    ColorProfile,
+    Camera,
+    FileSize,
+    BitDepth,
+    Chroma,
+    Hdr,
+    Alpha,
+    FrameRateMode,
+    AudioCodec,
+    AudioChannels,
+    SampleRate,
+    AudioTracks,
+    AudioLanguage,
+    Subtitles,
+    Captions,
+    Faststart,
+    Scan,
+    Matte,
+    Rotation,
+    Orientation,
+    Aspect,
+    Anamorphic,
+    OperationalPattern,
+    Essence,
+    Loudness,
+    TruePeak,
+    LoudnessRange,
+    Tag,
+    Note,
+    Rating,
+    Flag,
+    Vmaf,
+    Psnr,
+    Ssim,
+    HwDecode,
+}
+
+impl FilterType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterType::Container => "Container",
+            FilterType::Codec => "Codec",
+            FilterType::Resolution => "Resolution",
+            FilterType::FrameRate => "FPS",
+            FilterType::Bitrate => "Bitrate (Mbps)",
+            FilterType::Duration => "Duration",
+            FilterType::Timecode => "Timecode",
+            FilterType::Chapters => "Chapters",
+            FilterType::Encoder => "Encoder",
+            FilterType::Created => "Created",
+            FilterType::ColorProfile => "ICC profile",
+            FilterType::Camera => "Camera",
+            FilterType::FileSize => "Size",
+            FilterType::BitDepth => "Bit depth",
+            FilterType::Chroma => "Chroma",
+            FilterType::Hdr => "HDR",
+            FilterType::Alpha => "Alpha",
+            FilterType::AudioCodec => "Audio",
+            FilterType::AudioChannels => "Channels",
+            FilterType::SampleRate => "Sample rate",
+            FilterType::AudioTracks => "Audio tracks",
+            FilterType::AudioLanguage => "Audio language",
+            FilterType::Subtitles => "Subtitles",
+            FilterType::Captions => "Captions",
+            FilterType::Faststart => "Faststart",
+            FilterType::Scan => "Scan",
+            FilterType::Matte => "Matte",
+            FilterType::Rotation => "Rotation",
+            FilterType::Orientation => "Orientation",
+            FilterType::Aspect => "Aspect ratio",
+            FilterType::Anamorphic => "Anamorphic",
+            FilterType::OperationalPattern => "MXF pattern",
+            FilterType::Essence => "MXF essence",
+            FilterType::Loudness => "Loudness (LUFS)",
+            FilterType::TruePeak => "True peak (dBTP)",
+            FilterType::LoudnessRange => "Loudness range (LU)",
+            FilterType::FrameRateMode => "Frame rate mode",
+            FilterType::Tag => "Tag",
+            FilterType::Note => "Note",
+            FilterType::Rating => "Rating",
+            FilterType::Flag => "Flag",
+            FilterType::Vmaf => "VMAF",
+            FilterType::Psnr => "PSNR (dB)",
+            FilterType::Ssim => "SSIM",
+            FilterType::HwDecode => "HW decode",
+        }
+    }
+}
+
+impl FilterType {
+    /// The file's value for this field as shown in the table.
+    pub fn text_value(&self, file: &MediaInfo) -> String {
+        match self {
+            FilterType::Container => file.container.clone(),
+            FilterType::Codec => file.codec.clone(),
+            FilterType::Resolution => file.resolution.clone(),
+            FilterType::FrameRate => file.frame_rate.clone(),
+            FilterType::Bitrate => file.bitrate.clone(),
+            FilterType::Duration => format_duration(file.duration),
+            FilterType::Timecode => file.timecode.clone().unwrap_or_else(|| "None".to_string()),
+            FilterType::Chapters => file.chapters.len().to_string(),
+            FilterType::Encoder => file.encoder.clone().unwrap_or_else(|| "Unknown".to_string()),
+            FilterType::Created => file.creation_time.clone().unwrap_or_else(|| "Unknown".to_string()),
+            FilterType::ColorProfile => file
+                .image
+                .as_ref()
+                .and_then(|image| image.color_profile.clone())
+                .unwrap_or_else(|| "None".to_string()),
+            FilterType::Camera => file
+                .image
+                .as_ref()
+                .and_then(|image| image.camera.clone())
+                .unwrap_or_else(|| "None".to_string()),
+            FilterType::FileSize => format_size(file.file_size),
+            FilterType::BitDepth => format_bit_depth(file.bit_depth),
+            FilterType::Chroma => chroma_subsampling(&file.pixel_format).to_string(),
+            FilterType::Hdr => file.hdr_format.clone(),
+            FilterType::Alpha => yes_no(file.has_alpha).to_string(),
+            FilterType::AudioCodec => file.audio_codec.clone(),
+            FilterType::AudioChannels => file
+                .audio_channels
+                .map_or("Unknown".to_string(), |c| c.to_string()),
+            FilterType::SampleRate => format_sample_rate(file.audio_sample_rate),
+            FilterType::AudioTracks => file.audio_track_channels.len().to_string(),
+            FilterType::AudioLanguage => format_languages(file),
+            FilterType::Subtitles => yes_no(!file.subtitles.is_empty()).to_string(),
+            FilterType::Captions => yes_no(file.closed_captions).to_string(),
+            FilterType::Faststart => faststart_label(file.faststart).to_string(),
+            FilterType::Scan => file.scan.clone(),
+            FilterType::Matte => matte(file).to_string(),
+            FilterType::Rotation => file.rotation.to_string(),
+            FilterType::Orientation => orientation(file).to_string(),
+            FilterType::Aspect => format_aspect(display_aspect(file)),
+            FilterType::Anamorphic => yes_no(is_anamorphic(file)).to_string(),
+            FilterType::OperationalPattern => file
+                .mxf
+                .as_ref()
+                .and_then(|mxf| mxf.operational_pattern.clone())
+                .unwrap_or_else(|| "None".to_string()),
+            FilterType::Essence => file.mxf.as_ref().map_or("None".to_string(), |mxf| mxf.essence.clone()),
+            FilterType::Loudness => format_loudness(file, |l| l.integrated),
+            FilterType::TruePeak => format_loudness(file, |l| l.true_peak),
+            FilterType::LoudnessRange => format_loudness(file, |l| l.range),
+            FilterType::FrameRateMode => frame_rate_mode(file.variable_frame_rate).to_string(),
+            FilterType::Tag => match file.note.as_ref().filter(|note| !note.tags.is_empty()) {
+                Some(note) => note.tags.join(", "),
+                None => "None".to_string(),
+            },
+            FilterType::Note => file.note.as_ref().map_or(String::new(), |note| note.text.clone()),
+            FilterType::Rating => file
+                .note
+                .as_ref()
+                .and_then(|note| note.rating)
+                .map_or("None".to_string(), |stars| stars.to_string()),
+            FilterType::Flag => file
+                .note
+                .as_ref()
+                .and_then(|note| note.flag)
+                .map_or("None", |flag| flag.label())
+                .to_string(),
+            FilterType::Vmaf => format_quality(file, |q| q.vmaf, 1),
+            FilterType::Psnr => format_quality(file, |q| Some(q.psnr), 1),
+            FilterType::Ssim => format_quality(file, |q| Some(q.ssim), 4),
+            FilterType::HwDecode => file.hw_decode.as_ref().map_or("Unknown", |hw| hw.label()).to_string(),
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            FilterType::Resolution
+                | FilterType::FrameRate
+                | FilterType::Bitrate
+                | FilterType::Duration
+                | FilterType::FileSize
+                | FilterType::BitDepth
+                | FilterType::AudioChannels
+                | FilterType::SampleRate
+                | FilterType::AudioTracks
+                | FilterType::Chapters
+                | FilterType::Created
+                | FilterType::Rotation
+                | FilterType::Loudness
+                | FilterType::TruePeak
+                | FilterType::LoudnessRange
+                | FilterType::Rating
+                | FilterType::Vmaf
+                | FilterType::Psnr
+                | FilterType::Ssim
+        )
+    }
+
+    /// Parses a filter value into the unit numeric comparisons use: Mbps, fps,
+    /// seconds, bytes, bits, Hz, and for resolution either pixels ("WxH") or lines.
+    pub fn parse_number(&self, value: &str) -> Option<f64> {
+        let value = value.trim();
+        match self {
+            FilterType::Bitrate | FilterType::FrameRate => value.parse().ok(),
+            FilterType::Resolution if value.contains('x') => pixel_count(value).map(|p| p as f64),
+            FilterType::Resolution => value.parse().ok(),
+            FilterType::Duration => query::parse_duration(value),
+            FilterType::FileSize => query::parse_size(value),
+            FilterType::BitDepth => value.trim_end_matches("-bit").parse().ok(),
+            FilterType::AudioChannels | FilterType::AudioTracks | FilterType::Chapters => value.parse().ok(),
+            FilterType::Rating => value.trim_end_matches("stars").trim().parse().ok(),
+            FilterType::Vmaf | FilterType::Ssim => value.parse().ok(),
+            FilterType::Psnr => value.trim_end_matches("dB").trim().parse().ok(),
+            FilterType::SampleRate => parse_sample_rate(value),
+            FilterType::Created => date_number(value),
+            FilterType::Rotation => value.trim_end_matches('°').parse().ok(),
+            FilterType::Loudness => value.trim_end_matches("LUFS").trim().parse().ok(),
+            FilterType::TruePeak => value.trim_end_matches("dBTP").trim().parse().ok(),
+            FilterType::LoudnessRange => value.trim_end_matches("LU").trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The file's value in the unit `parse_number` produces for `filter_value`.
+    pub fn file_number(&self, file: &MediaInfo, filter_value: &str) -> Option<f64> {
+        match self {
+            FilterType::Bitrate => file.bitrate.parse().ok(),
+            FilterType::FrameRate => file.frame_rate.parse().ok(),
+            FilterType::Resolution if filter_value.contains('x') => {
+                pixel_count(&file.resolution).map(|p| p as f64)
+            }
+            FilterType::Resolution => file.resolution.split_once('x')?.1.parse().ok(),
+            FilterType::Duration => file.duration,
+            FilterType::FileSize => file.file_size.map(|size| size as f64),
+            FilterType::BitDepth => file.bit_depth.map(f64::from),
+            FilterType::AudioChannels => file.audio_channels.map(f64::from),
+            FilterType::SampleRate => file.audio_sample_rate.map(f64::from),
+            FilterType::AudioTracks => Some(file.audio_track_channels.len() as f64),
+            FilterType::Chapters => Some(file.chapters.len() as f64),
+            FilterType::Created => date_number(file.creation_time.as_deref()?.get(..10)?),
+            FilterType::Rotation => Some(f64::from(file.rotation)),
+            FilterType::Loudness => file.loudness.map(|l| l.integrated),
+            FilterType::TruePeak => file.loudness.map(|l| l.true_peak),
+            FilterType::LoudnessRange => file.loudness.map(|l| l.range),
+            FilterType::Rating => file.note.as_ref()?.rating.map(f64::from),
+            FilterType::Vmaf => file.quality.as_ref()?.vmaf,
+            FilterType::Psnr => file.quality.as_ref().map(|q| q.psnr),
+            FilterType::Ssim => file.quality.as_ref().map(|q| q.ssim),
+            _ => None,
+        }
+    }
+}
+
+/// How a filter compares the file's value against its own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterOp {
+    /// Substring match, or membership in the option's category for bucketed fields.
+    /// This is what the Filters tab uses.
+    Contains,
+    Equals,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    /// Inclusive range, written `low-high` (e.g. `10-50`, `30s-10m`).
+    Between,
+}
+
+impl FilterOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            FilterOp::Contains => "~",
+            FilterOp::Equals => "=",
+            FilterOp::Greater => ">",
+            FilterOp::GreaterOrEqual => ">=",
+            FilterOp::Less => "<",
+            FilterOp::LessOrEqual => "<=",
+            // Ranges are written as `field=low-high`
+            FilterOp::Between => "=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveFilter {
+    pub filter_type: FilterType,
+    pub op: FilterOp,
+    pub value: String,
+    /// Excludes the files the filter would otherwise match
+    pub negated: bool,
+}
+
+impl ActiveFilter {
+    /// Builds a filter, reading an exact match against a "low-high" value on a
+    /// numeric field as a range.
+    pub fn new(filter_type: FilterType, op: FilterOp, value: String) -> Self {
+        let mut filter = Self {
+            filter_type,
+            op,
+            value,
+            negated: false,
+        };
+        if matches!(op, FilterOp::Equals | FilterOp::Contains) && filter.range().is_some() {
+            filter.op = FilterOp::Between;
+        }
+        filter
+    }
+
+    /// A filter as toggled from an entry on the Filters tab. Numeric fields take a
+    /// comparison or range ("<30s", ">=2160", "10-50") and otherwise match exactly,
+    /// so a bitrate of "1" no longer matches 10, 12 and 15.
+    pub fn option(filter_type: FilterType, value: String) -> Self {
+        if !filter_type.is_numeric() {
+            return Self::new(filter_type, FilterOp::Contains, value);
+        }
+        match query::parse_op(&value) {
+            Some((op, len)) if op != FilterOp::Contains => {
+                let rest = value[len..].trim().to_string();
+                Self::new(filter_type, op, rest)
+            }
+            _ => Self::new(filter_type, FilterOp::Equals, value),
+        }
+    }
+
+    pub fn matches(&self, file: &MediaInfo) -> bool {
+        self.compare(file) != self.negated
+    }
+
+    /// Whether `other` is the same comparison, ignoring negation.
+    pub fn same_option(&self, other: &ActiveFilter) -> bool {
+        self.filter_type == other.filter_type && self.op == other.op && self.value == other.value
+    }
+
+    pub fn compare(&self, file: &MediaInfo) -> bool {
+        match self.op {
+            FilterOp::Contains => self.matches_option(file),
+            // Pixel counts can't tell 1920x1080 from 1080x1920
+            FilterOp::Equals
+                if self.filter_type == FilterType::Resolution && self.value.contains('x') =>
+            {
+                file.resolution.eq_ignore_ascii_case(self.value.trim())
+            }
+            // A file with several tags has each of them
+            FilterOp::Equals if self.filter_type == FilterType::Tag => {
+                let value = self.value.trim();
+                match file.note.as_ref() {
+                    Some(note) if !note.tags.is_empty() => note.tags.iter().any(|t| t.eq_ignore_ascii_case(value)),
+                    _ => value.eq_ignore_ascii_case("None"),
+                }
+            }
+            FilterOp::Equals => match self.numbers(file) {
+                Some((actual, target)) => (actual - target).abs() < 1e-9,
+                None => self.filter_type.text_value(file).eq_ignore_ascii_case(&self.value),
+            },
+            FilterOp::Between => {
+                let (Some((low, high)), Some(actual)) =
+                    (self.range(), self.filter_type.file_number(file, &self.value))
+                else {
+                    return false;
+                };
+                (low..=high).contains(&actual)
+            }
+            op => {
+                let Some((actual, target)) = self.numbers(file) else {
+                    return false;
+                };
+                match op {
+                    FilterOp::Greater => actual > target,
+                    FilterOp::GreaterOrEqual => actual >= target,
+                    FilterOp::Less => actual < target,
+                    _ => actual <= target,
+                }
+            }
+        }
+    }
+
+    pub fn matches_option(&self, file: &MediaInfo) -> bool {
+        match self.filter_type {
+            FilterType::Hdr if self.value == "HDR" => is_hdr(&file.hdr_format),
+            FilterType::Chroma
+            | FilterType::Hdr
+            | FilterType::Alpha
+            | FilterType::FrameRateMode
+            | FilterType::AudioCodec
+            | FilterType::Subtitles
+            | FilterType::Captions
+            | FilterType::Faststart
+            | FilterType::Matte
+            | FilterType::Orientation
+            | FilterType::Aspect
+            | FilterType::Anamorphic
+            | FilterType::OperationalPattern
+            | FilterType::Flag
+            | FilterType::HwDecode => self.filter_type.text_value(file) == self.value,
+            _ => self.filter_type.text_value(file).contains(&self.value),
+        }
+    }
+
+    /// The file's and the filter's values as comparable numbers, for numeric fields.
+    pub fn numbers(&self, file: &MediaInfo) -> Option<(f64, f64)> {
+        Some((
+            self.filter_type.file_number(file, &self.value)?,
+            self.filter_type.parse_number(&self.value)?,
+        ))
+    }
+
+    /// The bounds of a "low-high" value, in either order.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.value.match_indices('-').find_map(|(i, _)| {
+            let low = self.filter_type.parse_number(&self.value[..i])?;
+            let high = self.filter_type.parse_number(&self.value[i + 1..])?;
+            Some((low.min(high), low.max(high)))
+        })
+    }
+}
+
+/// Whether `file` matches every group, each through any one of its filters.
+pub fn matches_groups(groups: &[Vec<ActiveFilter>], file: &MediaInfo) -> bool {
+    groups.iter().all(|group| group.iter().any(|filter| filter.matches(file)))
+}
+
+/// A parsed filter query: groups joined by `AND`, each matching through any one of
+/// its `OR`ed filters.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FilterSet {
+    pub groups: Vec<Vec<ActiveFilter>>,
+}
+
+impl FilterSet {
+    /// Reads a query such as `codec=H.265 OR AV1 AND fps>=50`.
+    pub fn parse(query: &str) -> Result<Self> {
+        Ok(Self { groups: query::parse(query)? })
+    }
+
+    pub fn matches(&self, file: &MediaInfo) -> bool {
+        matches_groups(&self.groups, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(filter_type: FilterType, value: &str) -> ActiveFilter {
+        ActiveFilter::new(filter_type, FilterOp::Equals, value.to_string())
+    }
+
+    #[test]
+    fn ranges_are_read_in_the_field_units() {
+        assert_eq!(filter(FilterType::Bitrate, "10-50").range(), Some((10.0, 50.0)));
+        assert_eq!(filter(FilterType::Duration, "30s-10m").range(), Some((30.0, 600.0)));
+        assert_eq!(filter(FilterType::FileSize, "1k-2k").range(), Some((1024.0, 2048.0)));
+    }
+
+    #[test]
+    fn reversed_ranges_are_put_in_order() {
+        assert_eq!(filter(FilterType::Bitrate, "50-10").range(), Some((10.0, 50.0)));
+    }
+
+    #[test]
+    fn negative_bounds_split_on_the_right_dash() {
+        assert_eq!(filter(FilterType::Loudness, "-23--16").range(), Some((-23.0, -16.0)));
+    }
+
+    #[test]
+    fn a_range_makes_the_filter_a_between() {
+        assert_eq!(filter(FilterType::Bitrate, "10-50").op, FilterOp::Between);
+        assert_eq!(filter(FilterType::Bitrate, "10").op, FilterOp::Equals);
+        assert_eq!(filter(FilterType::Bitrate, "10").range(), None);
+        // Dashes in text fields are just text
+        assert_eq!(filter(FilterType::Codec, "MPEG-4").op, FilterOp::Equals);
+    }
+}
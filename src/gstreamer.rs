@@ -0,0 +1,188 @@
+//! Translates `gst-discoverer-1.0 -v` output into the ffprobe JSON that
+//! normalization reads. The discoverer prints an indented outline rather than
+//! anything structured, and names codecs by their caps descriptions
+//! (`H.264 (High Profile)`, `MPEG-4 AAC`), so this reads it line by line.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+
+pub fn translate(output: &str) -> Result<Value> {
+    let mut streams: Vec<Map<String, Value>> = Vec::new();
+    let mut format = Map::new();
+    let mut container = None;
+    // Lines indented under `Tags:` belong to the stream's tag list, not the stream
+    let mut tags_indent = None;
+
+    for line in output.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let line = line.trim();
+        if tags_indent.is_some_and(|tags| indent <= tags) {
+            tags_indent = None;
+        }
+        if let Some((kind, description)) = section(line) {
+            tags_indent = None;
+            match kind {
+                "container" => container = Some(description.to_string()),
+                "video" | "audio" | "subtitles" => {
+                    let mut stream = Map::new();
+                    let codec_type = if kind == "subtitles" { "subtitle" } else { kind };
+                    let (codec, profile) = codec(kind, description);
+                    stream.insert("index".to_string(), json!(streams.len()));
+                    stream.insert("codec_type".to_string(), json!(codec_type));
+                    stream.insert("codec_name".to_string(), json!(codec));
+                    if let Some(profile) = profile {
+                        stream.insert("profile".to_string(), json!(profile));
+                    }
+                    stream.insert("tags".to_string(), json!({}));
+                    streams.push(stream);
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':').map(|(k, v)| (k.trim(), v.trim())) else {
+            continue;
+        };
+        if key == "Tags" {
+            tags_indent = Some(indent);
+            continue;
+        }
+        if key == "Duration" && indent <= 2 {
+            if let Some(seconds) = duration(value) {
+                format.insert("duration".to_string(), json!(format!("{:.6}", seconds)));
+            }
+            continue;
+        }
+        let Some(stream) = streams.last_mut() else {
+            continue;
+        };
+        if tags_indent.is_some() {
+            let tags = stream.entry("tags").or_insert_with(|| json!({}));
+            match key {
+                "language code" => tags["language"] = json!(value),
+                "title" => tags["title"] = json!(value),
+                _ => {}
+            }
+            continue;
+        }
+        let number = value.split_whitespace().next().unwrap_or_default();
+        match key {
+            "Width" => set(stream, "width", number.parse::<u64>().ok()),
+            "Height" => set(stream, "height", number.parse::<u64>().ok()),
+            "Frame rate" => {
+                stream.insert("avg_frame_rate".to_string(), json!(number));
+                stream.insert("r_frame_rate".to_string(), json!(number));
+            }
+            "Pixel aspect ratio" => set(stream, "sample_aspect_ratio", Some(number.replace('/', ":"))),
+            "Interlaced" => {
+                let order = if value == "true" { "tt" } else { "progressive" };
+                stream.insert("field_order".to_string(), json!(order));
+            }
+            "Bitrate" if number != "0" => set(stream, "bit_rate", Some(number.to_string())),
+            "Channels" => set(stream, "channels", number.parse::<u64>().ok()),
+            "Sample rate" => set(stream, "sample_rate", Some(number.to_string())),
+            "Depth" if stream["codec_type"] == "audio" => set(stream, "bits_per_raw_sample", Some(number.to_string())),
+            "Language" => {
+                let tags = stream.entry("tags").or_insert_with(|| json!({}));
+                tags["language"] = json!(value);
+            }
+            _ => {}
+        }
+    }
+
+    if container.is_none() && streams.is_empty() {
+        return Err(anyhow!("the discoverer found no streams"));
+    }
+    let description = container.unwrap_or_default();
+    format.insert("format_name".to_string(), json!(demuxer(&description)));
+    format.insert("tags".to_string(), json!({}));
+    Ok(json!({
+        "streams": streams,
+        "format": format,
+        "analyzer_output": output,
+    }))
+}
+
+fn set(object: &mut Map<String, Value>, key: &str, value: Option<impl Into<Value>>) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), value.into());
+    }
+}
+
+/// `video #1: H.264 (High Profile)` -> (`video`, `H.264 (High Profile)`). Older
+/// discoverers print the container without a number.
+fn section(line: &str) -> Option<(&str, &str)> {
+    let (head, description) = line.split_once(": ")?;
+    let kind = head.split(" #").next()?;
+    let numbered = head.contains(" #") || kind == "container";
+    let known = matches!(kind, "container" | "video" | "audio" | "subtitles");
+    (known && numbered).then_some((kind, description.trim()))
+}
+
+/// The ffmpeg codec name and profile for a caps description.
+fn codec(kind: &str, description: &str) -> (String, Option<String>) {
+    // The parenthesised part is a profile for video and an alias for audio
+    let (name, profile) = match description.split_once(" (") {
+        Some((name, rest)) => {
+            let profile = rest.trim_end_matches(')').trim_end_matches(" Profile").to_string();
+            (name, Some(profile))
+        }
+        None => (description, None),
+    };
+    let profile = profile.filter(|_| kind == "video");
+    let codec = match (kind, name) {
+        (_, "H.264") => "h264",
+        (_, "H.265") => "hevc",
+        (_, "AV1") => "av1",
+        (_, "VP8") => "vp8",
+        (_, "VP9") => "vp9",
+        (_, "MPEG-2 Video") => "mpeg2video",
+        (_, "MPEG-4 Video") => "mpeg4",
+        (_, "Apple ProRes") | (_, "Apple ProRes 422") => "prores",
+        (_, "Avid DNxHD") => "dnxhd",
+        (_, "DV") => "dvvideo",
+        (_, "JPEG") => "mjpeg",
+        (_, "JPEG 2000") => "jpeg2000",
+        (_, "PNG image") => "png",
+        (_, "MPEG-4 AAC") => "aac",
+        (_, "MPEG-1 Layer 3") => "mp3",
+        (_, "AC-3") => "ac3",
+        (_, "E-AC-3") => "eac3",
+        (_, "Opus") => "opus",
+        (_, "Vorbis") => "vorbis",
+        (_, "Free Lossless Audio Codec") | (_, "FLAC") => "flac",
+        (_, "Timed Text") => "mov_text",
+        (_, "UTF-8 text") => "subrip",
+        ("audio", pcm) if pcm.contains("PCM") => {
+            let depth = pcm.split_whitespace().find_map(|word| word.strip_suffix("-bit")).unwrap_or("16");
+            let endian = if pcm.contains("big-endian") { "be" } else { "le" };
+            return (format!("pcm_s{}{}", depth, endian), None);
+        }
+        (_, other) => return (other.to_lowercase(), profile),
+    };
+    (codec.to_string(), profile)
+}
+
+/// `0:01:02.500000000` -> 62.5
+fn duration(value: &str) -> Option<f64> {
+    let mut parts = value.split(':').rev();
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next().unwrap_or("0").parse().ok()?;
+    let hours: f64 = parts.next().unwrap_or("0").parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+fn demuxer(container: &str) -> &str {
+    match container.split(" (").next().unwrap_or(container) {
+        "Quicktime" | "ISO MP4/M4A" | "ISO-MP4" => "mov,mp4,m4a,3gp,3g2,mj2",
+        "Matroska" | "WebM" => "matroska,webm",
+        "MXF" => "mxf",
+        "MPEG-2 Transport Stream" => "mpegts",
+        "MPEG-2 System Stream" => "mpeg",
+        "AVI" => "avi",
+        "WAV" => "wav",
+        "AIFF" => "aiff",
+        "Ogg" => "ogg",
+        other => other,
+    }
+}
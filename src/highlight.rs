@@ -0,0 +1,87 @@
+//! Config-defined rules that color table rows, e.g. red when `bitrate>100`.
+
+use anyhow::{anyhow, Context, Result};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{filter::{matches_groups, ActiveFilter}, model::MediaInfo, query, theme::Theme, validate::Verdict};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighlightRule {
+    /// Filter query the file must match; empty matches every file
+    #[serde(default)]
+    pub when: String,
+    /// Only matches files with this verdict under the active QC profile
+    #[serde(default)]
+    pub qc: Option<Verdict>,
+    /// A color name such as "red" or "lightblue", "#rrggbb", or a theme role such as
+    /// "bad" to follow the theme
+    pub color: String,
+}
+
+struct CompiledRule {
+    filters: Vec<Vec<ActiveFilter>>,
+    qc: Option<Verdict>,
+    color: Color,
+}
+
+pub struct Highlighter {
+    rules: Vec<CompiledRule>,
+}
+
+impl Highlighter {
+    /// Parses every rule up front so a typo is reported at startup.
+    pub fn new(rules: &[HighlightRule], theme: &Theme) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let filters = query::parse(&rule.when)
+                    .with_context(|| format!("highlight rule '{}'", rule.when))?;
+                let color = theme.role(&rule.color).or_else(|| parse_color(&rule.color)).ok_or_else(|| {
+                    anyhow!("highlight rule '{}': unknown color '{}'", rule.when, rule.color)
+                })?;
+                Ok(CompiledRule { filters, qc: rule.qc, color })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Color of the first rule `file` matches. `verdict` is the file's result
+    /// under the active QC profile, if any.
+    pub fn color(&self, file: &MediaInfo, verdict: Option<Verdict>) -> Option<Color> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.qc.is_none_or(|qc| Some(qc) == verdict) && matches_groups(&rule.filters, file)
+            })
+            .map(|rule| rule.color)
+    }
+}
+
+pub fn parse_color(name: &str) -> Option<Color> {
+    let name = name.trim().to_lowercase();
+    if let Some(hex) = name.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+        return Some(Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8));
+    }
+    let color = match name.replace(['_', '-', ' '], "").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
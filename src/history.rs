@@ -0,0 +1,88 @@
+//! Recently added paths, kept in `history` next to the config file so the Add File
+//! dialog can recall them with Up and Down.
+
+use anyhow::{Context, Result};
+use std::{fs, path::PathBuf};
+
+use crate::config;
+
+/// Entries kept on disk; older ones are dropped.
+const MAX_ENTRIES: usize = 200;
+
+pub struct History {
+    /// Oldest first
+    entries: Vec<String>,
+    file: Option<PathBuf>,
+    /// Entry shown in the input while browsing, counted from the newest
+    position: Option<usize>,
+    /// What was typed before browsing started, restored past the newest entry
+    draft: String,
+}
+
+impl History {
+    /// Reads the history file; a missing or unreadable one starts empty.
+    pub fn load() -> Self {
+        let file = config::config_dir().map(|dir| dir.join("history"));
+        let entries = file
+            .as_ref()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .map(|text| text.lines().filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Self { entries, file, position: None, draft: String::new() }
+    }
+
+    /// Records `path` as the most recent entry and saves the history.
+    pub fn push(&mut self, path: &str) -> Result<()> {
+        // Absolute, so entries still work from another directory
+        let path = fs::canonicalize(path).map_or(path.to_string(), |p| p.to_string_lossy().to_string());
+        self.entries.retain(|entry| *entry != path);
+        self.entries.push(path);
+        let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..excess);
+
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+        fs::write(file, text).with_context(|| format!("writing history {}", file.display()))
+    }
+
+    /// The next older entry, remembering `current` as the draft when browsing starts.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            None => {
+                self.draft = current.to_string();
+                0
+            }
+            Some(position) => position + 1,
+        };
+        if position >= self.entries.len() {
+            return None;
+        }
+        self.position = Some(position);
+        Some(&self.entries[self.entries.len() - 1 - position])
+    }
+
+    /// The next newer entry, or the draft once past the newest.
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.position? {
+            0 => {
+                self.position = None;
+                Some(&self.draft)
+            }
+            position => {
+                self.position = Some(position - 1);
+                Some(&self.entries[self.entries.len() - position])
+            }
+        }
+    }
+
+    /// Stops browsing, e.g. when the dialog closes.
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+}
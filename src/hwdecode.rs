@@ -0,0 +1,145 @@
+//! Whether this machine could decode a file in hardware: the acceleration APIs
+//! ffmpeg lists under `-hwaccels` and can open a device for, against what the
+//! decoders behind each API typically take. The codec rules are the common
+//! ground of current GPUs; an older card may lack AV1 or 10-bit support.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{model::chroma_subsampling, MediaInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Api {
+    VideoToolbox,
+    Nvdec,
+    Vaapi,
+    Qsv,
+}
+
+impl Api {
+    pub const ALL: [Api; 4] = [Api::VideoToolbox, Api::Nvdec, Api::Vaapi, Api::Qsv];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Api::VideoToolbox => "VideoToolbox",
+            Api::Nvdec => "NVDEC",
+            Api::Vaapi => "VAAPI",
+            Api::Qsv => "QSV",
+        }
+    }
+
+    /// The name ffmpeg gives it in `-hwaccels` and `-init_hw_device`.
+    fn hwaccel(self) -> &'static str {
+        match self {
+            Api::VideoToolbox => "videotoolbox",
+            Api::Nvdec => "cuda",
+            Api::Vaapi => "vaapi",
+            Api::Qsv => "qsv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HwDecode {
+    /// The first of the host's APIs that takes the file, `None` for software decoding
+    pub api: Option<Api>,
+    pub reason: String,
+}
+
+impl HwDecode {
+    /// The API, or "No".
+    pub fn label(&self) -> &'static str {
+        self.api.map_or("No", Api::label)
+    }
+}
+
+/// The APIs that work on this machine, in the order of `Api::ALL`. A listed API
+/// isn't enough, ffmpeg lists what it was built with whatever the hardware.
+pub fn detect(ffmpeg: &str) -> Vec<Api> {
+    let Ok(output) = Command::new(ffmpeg)
+        .args(["-hide_banner", "-hwaccels"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    else {
+        return Vec::new();
+    };
+    let listed = String::from_utf8_lossy(&output.stdout).to_string();
+    Api::ALL
+        .into_iter()
+        .filter(|api| listed.split_whitespace().any(|word| word == api.hwaccel()))
+        .filter(|api| opens_device(ffmpeg, *api))
+        .collect()
+}
+
+/// `detect` on its own thread, since opening each device takes a moment.
+pub fn detect_in_background(ffmpeg: String) -> Receiver<Vec<Api>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(detect(&ffmpeg));
+    });
+    rx
+}
+
+fn opens_device(ffmpeg: &str, api: Api) -> bool {
+    Command::new(ffmpeg)
+        .args(["-hide_banner", "-v", "error", "-init_hw_device", api.hwaccel()])
+        .args(["-f", "lavfi", "-i", "nullsrc=s=64x64:d=0.1", "-frames:v", "1", "-f", "null", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether `api` decodes the codec at this depth and chroma subsampling.
+fn supports(api: Api, codec: &str, depth: u8, chroma: &str) -> bool {
+    let yuv420 = chroma == "4:2:0";
+    match codec {
+        "H.264" => depth <= 8 && yuv420,
+        "H.265" => match api {
+            Api::VideoToolbox | Api::Qsv => depth <= 10 && matches!(chroma, "4:2:0" | "4:2:2"),
+            Api::Nvdec => depth <= 12 && matches!(chroma, "4:2:0" | "4:4:4"),
+            Api::Vaapi => depth <= 10 && yuv420,
+        },
+        "VP9" | "AV1" => depth <= 10 && yuv420,
+        "VP8" => api != Api::VideoToolbox && depth <= 8,
+        "MPEG-2" => depth <= 8 && yuv420,
+        "MPEG-4" => matches!(api, Api::Nvdec | Api::Vaapi) && depth <= 8,
+        codec if codec.starts_with("ProRes") => api == Api::VideoToolbox,
+        _ => false,
+    }
+}
+
+/// Which of `apis`, the host's, would decode `file`, and why not when none would.
+pub fn check(apis: &[Api], file: &MediaInfo) -> HwDecode {
+    let codec = file.codec.as_str();
+    let software = |reason: String| HwDecode { api: None, reason };
+    if file.audio_only || codec == "None" {
+        return software("no video stream".to_string());
+    }
+    if file.image.is_some() || file.sequence.is_some() {
+        return software("still images are decoded in software".to_string());
+    }
+    if apis.is_empty() {
+        return software("no hardware decoder on this machine".to_string());
+    }
+    let depth = file.bit_depth.unwrap_or(8);
+    // Most things of unknown layout are 4:2:0
+    let chroma = match chroma_subsampling(&file.pixel_format) {
+        "Unknown" => "4:2:0",
+        chroma => chroma,
+    };
+    match apis.iter().copied().find(|api| supports(*api, codec, depth, chroma)) {
+        Some(api) => HwDecode { api: Some(api), reason: format!("{} decodes {}", api.label(), codec) },
+        None if apis.iter().any(|api| supports(*api, codec, 8, "4:2:0")) => {
+            software(format!("{}-bit {} {} is beyond the hardware decoder", depth, chroma, codec))
+        }
+        None => software(format!("{} has no hardware decoder here", codec)),
+    }
+}
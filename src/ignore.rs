@@ -0,0 +1,270 @@
+//! Ignore rules for directory scans, in `.gitignore` syntax: the config's `ignore`
+//! list, relative to each directory scanned, and a `.scoutignore` file in any
+//! directory, relative to it. `*.proxy.mov` leaves out matching files at any depth,
+//! `Cache/` directories only, `/Renders/old` what's at that path and `!keep.mov`
+//! brings back what an earlier rule left out; the last rule to match wins.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const FILE_NAME: &str = ".scoutignore";
+
+/// Hidden files, AppleDouble `._` files among them, and the resource forks the
+/// macOS archiver leaves in zip files.
+pub const DEFAULT_RULES: [&str; 2] = [".*", "__MACOSX/"];
+
+#[derive(Debug, Clone)]
+struct Rule {
+    segments: Vec<String>,
+    /// Matched against the whole path from the rule's directory, not just the name
+    anchored: bool,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            // `\#` and `\!` for names that start with them
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = line.strip_suffix('/').map_or((false, line), |rest| (true, rest));
+        let segments: Vec<String> =
+            line.split('/').filter(|segment| !segment.is_empty()).map(String::from).collect();
+        if segments.is_empty() {
+            return None;
+        }
+        Some(Rule { segments, anchored: line.contains('/'), negated, dir_only })
+    }
+
+    /// Whether the rule matches a path, given as its components below the rule's directory.
+    fn matches(&self, relative: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            segments_match(&self.segments, relative)
+        } else {
+            relative.last().is_some_and(|name| wildcard(&self.segments[0], name))
+        }
+    }
+}
+
+fn segments_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| segments_match(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| wildcard(first, name) && segments_match(rest, path)),
+    }
+}
+
+/// `*`, `?`, `[a-z]`, `[!0-9]` and `\` escapes, within one path component.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some('*') => (0..=name.len()).any(|skip| matches(&pattern[1..], &name[skip..])),
+            Some('?') => !name.is_empty() && matches(&pattern[1..], &name[1..]),
+            Some('[') => match name.first().and_then(|&c| class(&pattern[1..], c)) {
+                Some((found, length)) => found && matches(&pattern[1 + length..], &name[1..]),
+                None => name.first() == Some(&'[') && matches(&pattern[1..], &name[1..]),
+            },
+            Some('\\') if pattern.len() > 1 => {
+                name.first() == Some(&pattern[1]) && matches(&pattern[2..], &name[1..])
+            }
+            Some(c) => name.first() == Some(c) && matches(&pattern[1..], &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// A `[...]` class, from past its `[`: whether `c` is in it and how many characters
+/// it takes up. `None` when it isn't closed, and the `[` is a plain character.
+fn class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let negated = matches!(pattern.first(), Some('!' | '^'));
+    let start = usize::from(negated);
+    // A `]` right at the start is a member, not the end
+    let end = start + 1 + pattern.get(start + 1..)?.iter().position(|&x| x == ']')?;
+    let set = &pattern[start..end];
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            found |= (set[i]..=set[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+    Some((found != negated, end + 1))
+}
+
+/// The rules in force during a scan. Each directory entered adds its
+/// `.scoutignore`, and the directory the scan started from the config's rules.
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    config: Vec<Rule>,
+    /// By the directory they're relative to, from the top of the scan down
+    layers: Vec<(PathBuf, Vec<Rule>)>,
+}
+
+impl Ignore {
+    pub fn new(lines: &[String]) -> Self {
+        Ignore { config: lines.iter().filter_map(|line| Rule::parse(line)).collect(), layers: Vec::new() }
+    }
+
+    /// Starts applying the rules of `dir`, once the scan goes into it.
+    pub fn enter(&mut self, dir: &Path) {
+        let mut rules = if self.layers.is_empty() { self.config.clone() } else { Vec::new() };
+        if let Ok(text) = fs::read_to_string(dir.join(FILE_NAME)) {
+            rules.extend(text.lines().filter_map(Rule::parse));
+        }
+        self.layers.push((dir.to_path_buf(), rules));
+    }
+
+    /// Stops applying the rules of the directory entered last.
+    pub fn leave(&mut self) {
+        self.layers.pop();
+    }
+
+    /// Whether `path`, in the directory entered last, is left out.
+    pub fn ignores(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for (dir, rules) in &self.layers {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative: Vec<String> = relative.iter().map(|c| c.to_string_lossy().to_string()).collect();
+            for rule in rules.iter().filter(|rule| rule.matches(&relative, is_dir)) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Whether a scan of `root` would leave out `path`, below it, or a directory on
+    /// the way down to it; for files that turn up in a watched folder.
+    pub fn hides(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let mut scope = Ignore { config: self.config.clone(), layers: Vec::new() };
+        scope.enter(root);
+        let components: Vec<_> = relative.iter().collect();
+        let mut current = root.to_path_buf();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let is_dir = i + 1 < components.len();
+            if scope.ignores(&current, is_dir) {
+                return true;
+            }
+            if is_dir {
+                scope.enter(&current);
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &str = "/scan";
+
+    fn ignore(rules: &[&str]) -> Ignore {
+        let mut ignore = Ignore::new(&rules.iter().map(|rule| rule.to_string()).collect::<Vec<_>>());
+        ignore.enter(Path::new(ROOT));
+        ignore
+    }
+
+    fn ignores(ignore: &Ignore, path: &str, is_dir: bool) -> bool {
+        ignore.ignores(&Path::new(ROOT).join(path), is_dir)
+    }
+
+    #[test]
+    fn a_name_pattern_matches_at_any_depth() {
+        let rules = ignore(&["*_proxy.mov"]);
+        assert!(ignores(&rules, "a_proxy.mov", false));
+        assert!(ignores(&rules, "Shoot/Day 1/b_proxy.mov", false));
+        assert!(!ignores(&rules, "Shoot/b.mov", false));
+    }
+
+    #[test]
+    fn a_trailing_slash_matches_directories_only() {
+        let rules = ignore(&["Cache/"]);
+        assert!(ignores(&rules, "Cache", true));
+        assert!(ignores(&rules, "Project/Cache", true));
+        assert!(!ignores(&rules, "Project/Cache", false));
+    }
+
+    #[test]
+    fn a_slash_anchors_to_the_rules_directory() {
+        let rules = ignore(&["/Renders/old"]);
+        assert!(ignores(&rules, "Renders/old", true));
+        assert!(!ignores(&rules, "Project/Renders/old", true));
+        assert!(!ignores(&rules, "old", true));
+    }
+
+    #[test]
+    fn negation_brings_a_file_back() {
+        let rules = ignore(&["*.mov", "!keep.mov"]);
+        assert!(ignores(&rules, "drop.mov", false));
+        assert!(!ignores(&rules, "keep.mov", false));
+        assert!(!ignores(&rules, "Sub/keep.mov", false));
+    }
+
+    #[test]
+    fn the_last_matching_rule_wins() {
+        assert!(ignores(&ignore(&["!keep.mov", "*.mov"]), "keep.mov", false));
+        assert!(!ignores(&ignore(&["*.mov", "!keep.mov"]), "keep.mov", false));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        let rules = ignore(&["Media/**/tmp"]);
+        assert!(ignores(&rules, "Media/tmp", true));
+        assert!(ignores(&rules, "Media/a/b/tmp", true));
+        assert!(!ignores(&rules, "Other/tmp", true));
+    }
+
+    #[test]
+    fn comments_blanks_and_escapes() {
+        let rules = ignore(&["# comment", "", "\\#take1.mov"]);
+        assert!(ignores(&rules, "#take1.mov", false));
+        assert!(!ignores(&rules, "comment", false));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard("clip_??.mxf", "clip_01.mxf"));
+        assert!(!wildcard("clip_??.mxf", "clip_1.mxf"));
+        assert!(wildcard("[a-c]*.mov", "b roll.mov"));
+        assert!(!wildcard("[!0-9]*", "1.mov"));
+        assert!(wildcard("[]]x", "]x"));
+        // An unclosed class is a plain `[`
+        assert!(wildcard("[a", "[a"));
+        assert!(wildcard("a\\*", "a*"));
+        assert!(!wildcard("a\\*", "ab"));
+    }
+
+    #[test]
+    fn hides_checks_each_directory_on_the_way_down() {
+        let rules = Ignore::new(&["Cache/".to_string()]);
+        assert!(rules.hides(Path::new(ROOT), Path::new("/scan/Cache/sub/clip.mov")));
+        assert!(!rules.hides(Path::new(ROOT), Path::new("/scan/Footage/clip.mov")));
+        assert!(!rules.hides(Path::new(ROOT), Path::new("/elsewhere/Cache/clip.mov")));
+    }
+}
@@ -0,0 +1,178 @@
+//! What ffprobe doesn't say about still images: the embedded ICC profile and the
+//! EXIF capture data. Dimensions, bit depth and alpha still come from ffprobe.
+
+use exif::{In, Tag};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageInfo {
+    /// Description of the embedded ICC profile, e.g. `Display P3`, or `sRGB` for a
+    /// PNG sRGB chunk
+    pub color_profile: Option<String>,
+    /// `Make Model`
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    /// `2024-03-01 10:00:00`, in the camera's local time
+    pub captured: Option<String>,
+    /// `1/250 s, f/2.8, ISO 100, 35 mm`
+    pub exposure: Option<String>,
+    /// Clockwise degrees viewers turn the image by, from the EXIF orientation
+    pub rotation: u16,
+}
+
+pub fn read(path: &Path) -> ImageInfo {
+    let mut info = ImageInfo {
+        color_profile: fs::read(path).ok().and_then(|data| color_profile(&data)),
+        ..ImageInfo::default()
+    };
+    let Some(exif) = File::open(path)
+        .ok()
+        .and_then(|file| exif::Reader::new().read_from_container(&mut BufReader::new(file)).ok())
+    else {
+        return info;
+    };
+    let text = |tag: Tag| {
+        let field = exif.get_field(tag, In::PRIMARY)?;
+        let value = field.display_value().with_unit(&exif).to_string();
+        let value = value.trim_matches('"').trim().to_string();
+        (!value.is_empty()).then_some(value)
+    };
+
+    info.camera = match (text(Tag::Make), text(Tag::Model)) {
+        // Most models already start with the make, e.g. `Canon EOS R5`
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => model.or(make),
+    };
+    info.lens = text(Tag::LensModel);
+    info.captured = text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime));
+    let exposure: Vec<String> = [
+        text(Tag::ExposureTime),
+        text(Tag::FNumber),
+        text(Tag::PhotographicSensitivity).map(|iso| format!("ISO {}", iso)),
+        text(Tag::FocalLength),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    info.exposure = (!exposure.is_empty()).then(|| exposure.join(", "));
+    let orientation = exif.get_field(Tag::Orientation, In::PRIMARY).and_then(|f| f.value.get_uint(0));
+    // Mirrored orientations (2, 4, 5, 7) are rare enough to report by their turn alone
+    info.rotation = match orientation {
+        Some(3 | 4) => 180,
+        Some(5 | 6) => 90,
+        Some(7 | 8) => 270,
+        _ => 0,
+    };
+    info
+}
+
+fn color_profile(data: &[u8]) -> Option<String> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_icc(data).as_deref().and_then(icc_description)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_profile(data)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        webp_icc(data).and_then(icc_description)
+    } else {
+        None
+    }
+}
+
+/// JPEG splits the profile across APP2 segments tagged `ICC_PROFILE`, each
+/// numbered, which are joined in order.
+fn jpeg_icc(data: &[u8]) -> Option<Vec<u8>> {
+    let mut chunks = Vec::new();
+    let mut i = 2;
+    while i + 4 <= data.len() && data[i] == 0xFF {
+        let marker = data[i + 1];
+        // Start of scan: no more metadata segments
+        if marker == 0xDA {
+            break;
+        }
+        let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        let segment = data.get(i + 4..i + 2 + length)?;
+        if marker == 0xE2 && segment.starts_with(b"ICC_PROFILE\0") && segment.len() > 14 {
+            chunks.push((segment[12], &segment[14..]));
+        }
+        i += 2 + length;
+    }
+    if chunks.is_empty() {
+        return None;
+    }
+    chunks.sort_by_key(|(sequence, _)| *sequence);
+    Some(chunks.into_iter().flat_map(|(_, chunk)| chunk.iter().copied()).collect())
+}
+
+fn png_profile(data: &[u8]) -> Option<String> {
+    let mut i = 8;
+    while i + 8 <= data.len() {
+        let length = be_u32(data, i)?;
+        let kind = &data[i + 4..i + 8];
+        let body = data.get(i + 8..i + 8 + length)?;
+        match kind {
+            // Profile name, NUL, compression method, then the zlib-compressed profile
+            b"iCCP" => {
+                let name_end = body.iter().position(|&b| b == 0)?;
+                let profile = miniz_oxide::inflate::decompress_to_vec_zlib(body.get(name_end + 2..)?).ok();
+                let name = String::from_utf8_lossy(&body[..name_end]).to_string();
+                return profile.as_deref().and_then(icc_description).or(Some(name));
+            }
+            b"sRGB" => return Some("sRGB".to_string()),
+            b"IDAT" | b"IEND" => return None,
+            _ => {}
+        }
+        i += 12 + length;
+    }
+    None
+}
+
+fn webp_icc(data: &[u8]) -> Option<&[u8]> {
+    let mut i = 12;
+    while i + 8 <= data.len() {
+        let length = u32::from_le_bytes(data[i + 4..i + 8].try_into().ok()?) as usize;
+        if &data[i..i + 4] == b"ICCP" {
+            return data.get(i + 8..i + 8 + length);
+        }
+        // Chunks are padded to an even length
+        i += 8 + length + length % 2;
+    }
+    None
+}
+
+/// The profile's `desc` tag: ASCII in v2 profiles, UTF-16 `mluc` records in v4.
+fn icc_description(profile: &[u8]) -> Option<String> {
+    let tag_count = be_u32(profile, 128)?;
+    let (offset, size) = (0..tag_count).map(|i| 132 + i * 12).find_map(|entry| {
+        if profile.get(entry..entry + 4)? != b"desc" {
+            return None;
+        }
+        Some((be_u32(profile, entry + 4)?, be_u32(profile, entry + 8)?))
+    })?;
+    let tag = profile.get(offset..offset + size)?;
+    let description = match tag.get(..4)? {
+        b"desc" => String::from_utf8_lossy(tag.get(12..12 + be_u32(tag, 8)?)?).to_string(),
+        // The first record is enough; it's English in practice
+        b"mluc" => {
+            let (length, start) = (be_u32(tag, 20)?, be_u32(tag, 24)?);
+            let units: Vec<u16> = tag
+                .get(start..start + length)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let description = description.trim_matches(char::from(0)).trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<usize> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize)
+}
@@ -0,0 +1,125 @@
+//! Progressive, interlaced or telecined: first read from the stream's field_order
+//! tag at analysis, then measured on demand with ffmpeg's idet filter, since
+//! plenty of interlaced masters are tagged progressive or not tagged at all.
+
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+pub const PROGRESSIVE: &str = "Progressive";
+pub const TFF: &str = "Interlaced TFF";
+pub const BFF: &str = "Interlaced BFF";
+pub const TELECINED: &str = "Telecined";
+pub const UNKNOWN: &str = "Unknown";
+
+/// Frames idet looks at; enough to be sure without decoding a feature film.
+const FRAMES: &str = "1000";
+/// Share of frames with a repeated field above which progressive content counts as
+/// telecined. 3:2 pulldown repeats a field in two of every five frames.
+const REPEAT_SHARE: f64 = 0.15;
+
+/// The scan type the container claims. ffprobe's field_order names the field
+/// coded first, then the one displayed first, so `bt` still shows top first.
+pub fn from_field_order(field_order: Option<&str>) -> &'static str {
+    match field_order {
+        Some("progressive") => PROGRESSIVE,
+        Some("tt" | "bt") => TFF,
+        Some("bb" | "tb") => BFF,
+        _ => UNKNOWN,
+    }
+}
+
+/// Column text: short enough for a narrow column.
+pub fn short_label(scan: &str) -> &'static str {
+    match scan {
+        PROGRESSIVE => "Prog",
+        TFF => "TFF",
+        BFF => "BFF",
+        TELECINED => "3:2",
+        _ => "?",
+    }
+}
+
+pub fn is_interlaced(scan: &str) -> bool {
+    matches!(scan, TFF | BFF | TELECINED)
+}
+
+/// Runs idet on one file at a time on a background thread.
+pub struct ScanDetector {
+    path_tx: Sender<String>,
+    results: Receiver<(String, Result<&'static str, String>)>,
+}
+
+impl ScanDetector {
+    pub fn new(ffmpeg: String) -> Self {
+        let (path_tx, path_rx) = mpsc::channel::<String>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for path in path_rx {
+                let result = detect(&ffmpeg, &path);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { path_tx, results }
+    }
+
+    pub fn queue(&self, path: String) {
+        // The worker only stops when the detector is dropped
+        let _ = self.path_tx.send(path);
+    }
+
+    /// Files measured since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<&'static str, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn detect(ffmpeg: &str, path: &str) -> Result<&'static str, String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin"])
+        .args(sequence::input_args(path))
+        .args(["-i", path, "-map", "0:v:0", "-an"])
+        .args(["-vf", "idet", "-frames:v", FRAMES, "-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    // `Repeated Fields: Neither:   990 Top:     5 Bottom:     5` and
+    // `Multi frame detection: TFF:     0 BFF:     0 Progressive:   998 Undetermined:     2`
+    let line = |prefix: &str| stderr.lines().rev().find(|line| line.contains(prefix));
+    let (Some(repeated), Some(multi)) = (line("Repeated Fields:"), line("Multi frame detection:")) else {
+        return Err("no idet summary in ffmpeg output".to_string());
+    };
+    let neither = count(repeated, "Neither:");
+    let repeats = count(repeated, "Top:") + count(repeated, "Bottom:");
+    let tff = count(multi, "TFF:");
+    let bff = count(multi, "BFF:");
+    let progressive = count(multi, "Progressive:");
+
+    Ok(if tff + bff > progressive {
+        if tff >= bff { TFF } else { BFF }
+    } else if progressive == 0 {
+        UNKNOWN
+    } else if repeats as f64 > (neither + repeats) as f64 * REPEAT_SHARE {
+        TELECINED
+    } else {
+        PROGRESSIVE
+    })
+}
+
+fn count(line: &str, key: &str) -> u64 {
+    line.find(key)
+        .and_then(|i| line[i + key.len()..].split_whitespace().next()?.parse().ok())
+        .unwrap_or(0)
+}
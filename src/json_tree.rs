@@ -0,0 +1,344 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::ListState,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::Theme;
+
+/// One visible line of the tree. `path` identifies the node across redraws,
+/// e.g. `streams/0/codec_name`.
+pub struct TreeRow<'a> {
+    pub path: String,
+    pub depth: usize,
+    pub key: String,
+    pub value: &'a Value,
+}
+
+/// Collapsible view over a parsed ffprobe document.
+pub struct JsonTree {
+    root: Value,
+    expanded: HashSet<String>,
+    pub list_state: ListState,
+    query: String,
+    matches: Vec<String>,
+    match_index: usize,
+}
+
+impl Default for JsonTree {
+    fn default() -> Self {
+        Self::new(Value::Null)
+    }
+}
+
+impl JsonTree {
+    /// Parses ffprobe output. Anything that isn't JSON is shown as a single text node.
+    pub fn from_raw(raw: &str) -> Self {
+        let root = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+        Self::new(root)
+    }
+
+    fn new(root: Value) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self {
+            root,
+            expanded: HashSet::new(),
+            list_state,
+            query: String::new(),
+            matches: Vec::new(),
+            match_index: 0,
+        }
+    }
+
+    pub fn rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = Vec::new();
+        push_children(&self.root, "", 0, &self.expanded, &mut rows);
+        rows
+    }
+
+    fn selected_row(&self) -> Option<TreeRow<'_>> {
+        let i = self.list_state.selected()?;
+        self.rows().into_iter().nth(i)
+    }
+
+    pub fn next(&mut self) {
+        let len = self.rows().len();
+        let i = self.list_state.selected().map_or(0, |i| (i + 1).min(len.saturating_sub(1)));
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = self.list_state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.list_state.select(Some(i));
+    }
+
+    pub fn is_expanded(&self, path: &str) -> bool {
+        self.expanded.contains(path)
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(row) = self.selected_row().filter(|r| is_container(r.value)) {
+            let path = row.path;
+            self.expanded.insert(path);
+        }
+    }
+
+    /// Collapses the selected node, or jumps to its parent if it's already collapsed.
+    pub fn collapse(&mut self) {
+        let Some(path) = self.selected_row().map(|r| r.path) else {
+            return;
+        };
+        if self.expanded.remove(&path) {
+            return;
+        }
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            let parent = parent.to_string();
+            if let Some(i) = self.rows().iter().position(|r| r.path == parent) {
+                self.list_state.select(Some(i));
+            }
+            self.expanded.remove(&parent);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        let Some(path) = self.selected_row().map(|r| r.path) else {
+            return;
+        };
+        if !self.expanded.remove(&path) {
+            self.expand();
+        }
+    }
+
+    pub fn expand_all(&mut self) {
+        let mut paths = Vec::new();
+        collect_container_paths(&self.root, "", &mut paths);
+        self.expanded.extend(paths);
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+        self.list_state.select(Some(0));
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// "current/total" for the status line, empty when there's no query.
+    pub fn match_status(&self) -> String {
+        match (self.query.is_empty(), self.matches.is_empty()) {
+            (true, _) => String::new(),
+            (false, true) => "no matches".to_string(),
+            (false, false) => format!("{}/{}", self.match_index + 1, self.matches.len()),
+        }
+    }
+
+    /// Searches keys and scalar values across the whole tree, collapsed nodes
+    /// included, and jumps to the first hit.
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.matches.clear();
+        self.match_index = 0;
+        if !query.is_empty() {
+            let needle = query.to_lowercase();
+            collect_matches(&self.root, "", &needle, &mut self.matches);
+        }
+        self.reveal_current_match();
+    }
+
+    pub fn next_match(&mut self) {
+        if !self.matches.is_empty() {
+            self.match_index = (self.match_index + 1) % self.matches.len();
+            self.reveal_current_match();
+        }
+    }
+
+    pub fn previous_match(&mut self) {
+        if !self.matches.is_empty() {
+            self.match_index = (self.match_index + self.matches.len() - 1) % self.matches.len();
+            self.reveal_current_match();
+        }
+    }
+
+    /// Expands every ancestor of the current match and moves the cursor onto it.
+    fn reveal_current_match(&mut self) {
+        let Some(path) = self.matches.get(self.match_index).cloned() else {
+            return;
+        };
+        let mut prefix = String::new();
+        let segments: Vec<&str> = path.split('/').collect();
+        for segment in &segments[..segments.len() - 1] {
+            prefix = child_path(&prefix, segment);
+            self.expanded.insert(prefix.clone());
+        }
+        if let Some(i) = self.rows().iter().position(|r| r.path == path) {
+            self.list_state.select(Some(i));
+        }
+    }
+
+    /// Text for the selected node: scalars as-is, containers as pretty JSON.
+    pub fn selected_text(&self) -> Option<String> {
+        self.selected_row().map(|row| match row.value {
+            Value::String(s) => s.clone(),
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        })
+    }
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", parent, key)
+    }
+}
+
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn push_children<'a>(
+    value: &'a Value,
+    path: &str,
+    depth: usize,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<TreeRow<'a>>,
+) {
+    if !is_container(value) {
+        // A non-JSON document is a single root string
+        if !value.is_null() && path.is_empty() {
+            rows.push(TreeRow { path: String::new(), depth, key: String::new(), value });
+        }
+        return;
+    }
+    for (key, child) in children(value) {
+        let child_path = child_path(path, &key);
+        let open = expanded.contains(&child_path);
+        rows.push(TreeRow { path: child_path.clone(), depth, key, value: child });
+        if open {
+            push_children(child, &child_path, depth + 1, expanded, rows);
+        }
+    }
+}
+
+fn collect_container_paths(value: &Value, path: &str, paths: &mut Vec<String>) {
+    for (key, child) in children(value) {
+        if is_container(child) {
+            let child_path = child_path(path, &key);
+            collect_container_paths(child, &child_path, paths);
+            paths.push(child_path);
+        }
+    }
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        Value::Null => "null".to_string(),
+        Value::Object(_) | Value::Array(_) => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn scalar_color(value: &Value, theme: &Theme) -> Color {
+    match value {
+        Value::String(_) => theme.good,
+        Value::Number(_) => theme.marked,
+        Value::Bool(_) => theme.accent,
+        _ => theme.faint,
+    }
+}
+
+fn collect_matches(value: &Value, path: &str, needle: &str, matches: &mut Vec<String>) {
+    for (key, child) in children(value) {
+        let child_path = child_path(path, &key);
+        if key.to_lowercase().contains(needle) || scalar_text(child).to_lowercase().contains(needle) {
+            matches.push(child_path.clone());
+        }
+        collect_matches(child, &child_path, needle, matches);
+    }
+}
+
+/// Splits `text` into spans, marking every case-insensitive occurrence of `query`.
+fn highlighted(text: &str, style: Style, query: &str, theme: &Theme) -> Vec<Span<'static>> {
+    if query.is_empty() {
+        return vec![Span::styled(text.to_string(), style)];
+    }
+    let lower = text.to_lowercase();
+    let needle = query.to_lowercase();
+    // Lowercasing can change byte lengths for some scripts; skip highlighting then
+    if lower.len() != text.len() {
+        return vec![Span::styled(text.to_string(), style)];
+    }
+
+    // Reversed, so the match reads on whatever background the terminal has
+    let match_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::REVERSED | Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find(&needle) {
+        let start = pos + offset;
+        let end = start + needle.len();
+        if start > pos {
+            spans.push(Span::styled(text[pos..start].to_string(), style));
+        }
+        spans.push(Span::styled(text[start..end].to_string(), match_style));
+        pos = end;
+    }
+    if pos < text.len() {
+        spans.push(Span::styled(text[pos..].to_string(), style));
+    }
+    spans
+}
+
+/// Short description of a collapsed container; streams also get their type and codec.
+fn container_summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let hint = match (map.get("codec_type"), map.get("codec_name")) {
+                (Some(Value::String(t)), Some(Value::String(c))) => format!(" {} {}", t, c),
+                (Some(Value::String(t)), _) => format!(" {}", t),
+                _ => String::new(),
+            };
+            format!("{{…}} {} keys{}", map.len(), hint)
+        }
+        Value::Array(items) => format!("[…] {} items", items.len()),
+        _ => String::new(),
+    }
+}
+
+pub fn row_line(row: &TreeRow, expanded: bool, query: &str, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ".repeat(row.depth))];
+    if is_container(row.value) {
+        let marker = if expanded { "▾ " } else { "▸ " };
+        spans.push(Span::styled(marker, Style::default().fg(theme.accent)));
+    } else {
+        spans.push(Span::raw("  "));
+    }
+    if !row.key.is_empty() {
+        spans.extend(highlighted(&row.key, Style::default().fg(theme.info), query, theme));
+        spans.push(Span::raw(": "));
+    }
+    if is_container(row.value) {
+        spans.push(Span::styled(
+            container_summary(row.value),
+            Style::default().fg(theme.muted),
+        ));
+    } else {
+        let style = Style::default().fg(scalar_color(row.value, theme));
+        spans.extend(highlighted(&scalar_text(row.value), style, query, theme));
+    }
+    Line::from(spans)
+}
@@ -0,0 +1,422 @@
+//! Key bindings of the main view, with overrides from the `[keys]` table of the
+//! config, e.g. `quit = "Q"` or `next = ["h", "down"]`.
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Something a key does on the Files and Filters tabs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    AddFile,
+    RawOutput,
+    Reanalyze,
+    CycleAnalyzer,
+    CopyPath,
+    CopyMetadata,
+    CopyRaw,
+    CopyTable,
+    CopyTableMarkdown,
+    Open,
+    Reveal,
+    Mark,
+    Remove,
+    Clear,
+    Projects,
+    Undo,
+    Rename,
+    Organize,
+    Trash,
+    Query,
+    Details,
+    Columns,
+    Thumbnail,
+    Sort,
+    ReverseSort,
+    GroupBy,
+    Fold,
+    Search,
+    CycleProfile,
+    Fixes,
+    QueueFixes,
+    Transcodes,
+    Duplicates,
+    Similar,
+    Compare,
+    Bitrate,
+    Gop,
+    Chapters,
+    EditTags,
+    Note,
+    Accept,
+    Reject,
+    Verify,
+    Loudness,
+    Reference,
+    Quality,
+    Blanks,
+    Scan,
+    Crop,
+    Checksum,
+    Benchmark,
+    Cancel,
+    Report,
+    Log,
+    Help,
+    Palette,
+    Previous,
+    Next,
+    NextTab,
+    ExcludeFilter,
+    SavePreset,
+}
+
+impl Action {
+    /// Every action, in the order the help screen lists them.
+    pub const ALL: [Action; 62] = [
+        Action::Quit,
+        Action::AddFile,
+        Action::RawOutput,
+        Action::Reanalyze,
+        Action::CycleAnalyzer,
+        Action::CopyPath,
+        Action::CopyMetadata,
+        Action::CopyRaw,
+        Action::CopyTable,
+        Action::CopyTableMarkdown,
+        Action::Open,
+        Action::Reveal,
+        Action::Mark,
+        Action::Remove,
+        Action::Clear,
+        Action::Projects,
+        Action::Undo,
+        Action::Rename,
+        Action::Organize,
+        Action::Trash,
+        Action::Query,
+        Action::Details,
+        Action::Columns,
+        Action::Thumbnail,
+        Action::Sort,
+        Action::ReverseSort,
+        Action::GroupBy,
+        Action::Fold,
+        Action::Search,
+        Action::CycleProfile,
+        Action::Fixes,
+        Action::QueueFixes,
+        Action::Transcodes,
+        Action::Duplicates,
+        Action::Similar,
+        Action::Compare,
+        Action::Bitrate,
+        Action::Gop,
+        Action::Chapters,
+        Action::EditTags,
+        Action::Note,
+        Action::Accept,
+        Action::Reject,
+        Action::Verify,
+        Action::Loudness,
+        Action::Reference,
+        Action::Quality,
+        Action::Blanks,
+        Action::Scan,
+        Action::Crop,
+        Action::Checksum,
+        Action::Benchmark,
+        Action::Cancel,
+        Action::Report,
+        Action::Log,
+        Action::Help,
+        Action::Palette,
+        Action::Previous,
+        Action::Next,
+        Action::NextTab,
+        Action::ExcludeFilter,
+        Action::SavePreset,
+    ];
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::AddFile => &["a"],
+            Action::RawOutput => &["r"],
+            Action::Reanalyze => &["R"],
+            Action::CycleAnalyzer => &["A"],
+            Action::CopyPath => &["y"],
+            Action::CopyMetadata => &["Y"],
+            Action::CopyRaw => &["J"],
+            Action::CopyTable => &["ctrl+y"],
+            Action::CopyTableMarkdown => &["m"],
+            Action::Open => &["o"],
+            Action::Reveal => &["O"],
+            Action::Mark => &["space"],
+            Action::Remove => &["d", "delete"],
+            Action::Clear => &["c"],
+            Action::Projects => &["N"],
+            Action::Undo => &["u"],
+            Action::Rename => &["ctrl+r"],
+            Action::Organize => &["ctrl+o"],
+            Action::Trash => &["ctrl+d"],
+            Action::Query => &[":"],
+            Action::Details => &["i"],
+            Action::Columns => &["H"],
+            Action::Thumbnail => &["t"],
+            Action::Sort => &["s"],
+            Action::ReverseSort => &["S"],
+            Action::GroupBy => &["b"],
+            Action::Fold => &["z"],
+            Action::Search => &["/"],
+            Action::CycleProfile => &["v"],
+            Action::Fixes => &["f"],
+            Action::QueueFixes => &["F"],
+            Action::Transcodes => &["T"],
+            Action::Duplicates => &["D"],
+            Action::Similar => &["P"],
+            Action::Compare => &["="],
+            Action::Bitrate => &["B"],
+            Action::Gop => &["I"],
+            Action::Chapters => &["M"],
+            Action::EditTags => &["E"],
+            Action::Note => &["n"],
+            Action::Accept => &["+"],
+            Action::Reject => &["-"],
+            Action::Verify => &["V"],
+            Action::Loudness => &["L"],
+            Action::Reference => &["U"],
+            Action::Quality => &["Q"],
+            Action::Blanks => &["X"],
+            Action::Scan => &["W"],
+            Action::Crop => &["C"],
+            Action::Checksum => &["K"],
+            Action::Benchmark => &["e"],
+            Action::Cancel => &["ctrl+c"],
+            Action::Report => &["w"],
+            Action::Log => &["l"],
+            Action::Help => &["h"],
+            Action::Palette => &["ctrl+p"],
+            Action::Previous => &["up", "k"],
+            Action::Next => &["down", "j"],
+            Action::NextTab => &["tab"],
+            Action::ExcludeFilter => &["x"],
+            Action::SavePreset => &["p"],
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit application",
+            Action::AddFile => "Add file (or paste/drag paths onto the window)",
+            Action::RawOutput => "Show raw FFprobe output",
+            Action::Reanalyze => "Re-analyze marked or selected files, bypassing the cache",
+            Action::CycleAnalyzer => "Switch analyzer (ffprobe, MediaInfo, GStreamer) for comparing readings",
+            Action::CopyPath => "Copy the paths of the marked or selected files, one per line",
+            Action::CopyMetadata => "Copy the metadata summaries of the marked or selected files",
+            Action::CopyRaw => "Copy raw FFprobe JSON",
+            Action::CopyTable => "Copy the filtered table (or its marked rows) as TSV, for spreadsheets",
+            Action::CopyTableMarkdown => "Copy the filtered table as a Markdown table",
+            Action::Open => "Open in player",
+            Action::Reveal => "Reveal in file manager",
+            Action::Mark => "Mark/unmark file for batch operations (toggle filter on the Filters tab)",
+            Action::Remove => "Remove selected or marked files (delete preset on the Filters tab)",
+            Action::Clear => "Clear all files",
+            Action::Projects => "Switch to or create a project, a named file list with its own filters",
+            Action::Undo => "Undo the last removal, clear, rename or move",
+            Action::Rename => "Rename the marked files on disk after their metadata",
+            Action::Organize => "Copy or move the filtered files into folders after their metadata",
+            Action::Trash => "Delete the marked files from disk, to the trash, after asking",
+            Action::Query => "Filter query (codec=H.265 OR AV1 AND bitrate>20 AND NOT hdr=SDR)",
+            Action::Details => "Toggle details panel",
+            Action::Columns => "Choose the columns of the Files table, their order and widths",
+            Action::Thumbnail => "Toggle frame preview in the details panel",
+            Action::Sort => "Cycle sort column",
+            Action::ReverseSort => "Reverse sort order",
+            Action::GroupBy => "Group rows by codec, container, resolution or folder (cycle)",
+            Action::Fold => "Fold or unfold the selected group",
+            Action::Search => "Search file names and paths (Esc clears)",
+            Action::CycleProfile => "Cycle QC profile",
+            Action::Fixes => "Suggested ffmpeg fixes for the selected file (t queues one)",
+            Action::QueueFixes => "Queue the first fix for marked or selected files",
+            Action::Transcodes => "Transcode queue",
+            Action::Duplicates => "Duplicate files (identical copies share a number in the Dup column)",
+            Action::Similar => "Find visually similar clips by sampling frames (shown as ~N in Dup)",
+            Action::Compare => "Compare the marked files side by side, the fields they differ on highlighted",
+            Action::Bitrate => "Bitrate over time for the selected file, with peaks highlighted",
+            Action::Gop => "GOP structure: keyframe interval, its regularity and B-frames",
+            Action::Chapters => "Chapter marks of the selected file with their time ranges",
+            Action::EditTags => "Edit the title, comment and audio languages of the selected file",
+            Action::Note => "Review tags and a note on marked or selected files (Tags and Note columns)",
+            Action::Accept => "Flag marked or selected files accepted, or clear the flag (Flag column)",
+            Action::Reject => "Flag marked or selected files rejected, or clear the flag (Flag column)",
+            Action::Verify => "Verify marked or selected files decode without errors",
+            Action::Loudness => "Measure EBU R128 loudness of marked or selected files (LUFS column)",
+            Action::Reference => "Use the selected file as the reference for quality metrics, or stop using it",
+            Action::Quality => "VMAF, PSNR and SSIM of marked or selected encodes against the reference",
+            Action::Blanks => "Find black frames and silence in marked or selected files (shown in details)",
+            Action::Scan => "Detect interlaced or telecined video with idet (Scan column)",
+            Action::Crop => "Detect letterboxing and pillarboxing with cropdetect (Picture column)",
+            Action::Checksum => "Checksum marked or selected files (algorithm set by `checksum` in the config)",
+            Action::Benchmark => "Decode the selected file as fast as possible, to see if it plays in realtime",
+            Action::Cancel => "Cancel the analysis and background passes under way, stopping ffmpeg and ffprobe",
+            Action::Report => "Write a report of the filtered or marked files (`report` in the config)",
+            Action::Log => "Show the errors and warnings of the session, ffprobe's among them",
+            Action::Help => "Show this help",
+            Action::Palette => "Command palette: find and run any command by name",
+            Action::Previous => "Previous file",
+            Action::Next => "Next file",
+            Action::NextTab => "Switch tabs",
+            Action::ExcludeFilter => "Toggle exclude filter (Filters tab)",
+            Action::SavePreset => "Save active filters as a preset",
+        }
+    }
+}
+
+/// One key or several for an action in the config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn keys(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Key {
+    code: KeyCode,
+    // Only Ctrl and Alt; Shift is part of the character
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// `q`, `Q`, `space`, `delete`, `ctrl+d`, `alt+down`, `f5` ...
+    fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // A lone `+` or `-` is a key of its own
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                _ => bail!("unknown modifier '{}' in key '{}'", modifier, text),
+            };
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "esc" | "escape" => KeyCode::Esc,
+                function => match function.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", text),
+                },
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        self.code == event.code && self.modifiers == modifiers
+    }
+
+    fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        label + &key
+    }
+}
+
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Keymap {
+    /// The default bindings with `overrides` applied. An override replaces all of an
+    /// action's default keys and takes its keys away from any action they were bound
+    /// to by default, so `next = "h"` leaves help without a key unless it's rebound.
+    pub fn new(overrides: &BTreeMap<Action, KeyList>) -> Result<Self> {
+        let mut bindings: Vec<(Key, Action)> = Vec::new();
+        for (&action, keys) in overrides {
+            for text in keys.keys() {
+                let key = Key::parse(text).map_err(|e| anyhow!("keys.{}: {}", name(action), e))?;
+                if let Some((_, other)) = bindings.iter().find(|(bound, _)| *bound == key) {
+                    bail!("keys: '{}' is bound to both {} and {}", text, name(*other), name(action));
+                }
+                bindings.push((key, action));
+            }
+        }
+        let overridden = bindings.len();
+        for action in Action::ALL.into_iter().filter(|action| !overrides.contains_key(action)) {
+            for text in action.default_keys() {
+                let key = Key::parse(text).expect("default keys parse");
+                if !bindings[..overridden].iter().any(|(bound, _)| *bound == key) {
+                    bindings.push((key, action));
+                }
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings.iter().find(|(key, _)| key.matches(event)).map(|(_, action)| *action)
+    }
+
+    /// The keys bound to `action` as shown in the help, e.g. `d/Del`; empty when none is.
+    pub fn label(&self, action: Action) -> String {
+        let labels: Vec<String> =
+            self.bindings.iter().filter(|(_, bound)| *bound == action).map(|(key, _)| key.label()).collect();
+        labels.join("/")
+    }
+}
+
+/// The action's name in the config file.
+fn name(action: Action) -> String {
+    serde_json::to_value(action).ok().and_then(|value| value.as_str().map(str::to_string)).unwrap_or_default()
+}
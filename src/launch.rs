@@ -0,0 +1,65 @@
+//! Hands files to other applications: a media player, or the system file manager.
+
+use anyhow::{Context, Result};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// Opens `path` with `player`, or the platform's default application when it's empty.
+pub fn open(player: &[String], path: &str) -> Result<()> {
+    let mut command = match player.split_first() {
+        Some((program, args)) => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        None => default_opener(),
+    };
+    command.arg(path);
+    spawn(command)
+}
+
+/// Shows `path` in Finder or Explorer with the file selected. Linux file
+/// managers have no common way to select a file, so the folder is opened.
+pub fn reveal(path: &str) -> Result<()> {
+    let mut command;
+    if cfg!(target_os = "macos") {
+        command = Command::new("open");
+        command.args(["-R", path]);
+    } else if cfg!(windows) {
+        command = Command::new("explorer");
+        command.arg(format!("/select,{}", path));
+    } else {
+        command = default_opener();
+        command.arg(Path::new(path).parent().unwrap_or(Path::new(".")));
+    }
+    spawn(command)
+}
+
+fn default_opener() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // `start` treats its first quoted argument as the window title
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    }
+}
+
+/// Starts `command` without tying up the terminal, reaping it once it exits.
+fn spawn(mut command: Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("could not start '{}'", program))?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
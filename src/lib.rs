@@ -0,0 +1,75 @@
+//! The probing, normalization and filtering behind media-scout, usable without the
+//! TUI, e.g. from an ingest daemon:
+//!
+//! ```no_run
+//! use media_scout::{probe_file, FilterSet, ProbeOptions};
+//!
+//! let info = probe_file("clip.mov", &ProbeOptions::default())?;
+//! let delivery = FilterSet::parse("codec=H.264 AND resolution=1920x1080 AND fps=25")?;
+//! println!("{} {}", info.name, if delivery.matches(&info) { "passes" } else { "fails" });
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The background passes (loudness, crop, checksums ...) live in their own modules
+//! and work the same way for any caller: `queue` paths, then `poll` for results.
+//! Running them over a session stays in the TUI: its worker pool with the analysis
+//! cache in front, which passes a file gets queued for, and writing their results
+//! back into each [`MediaInfo`]. A caller threads `probe_file` or an [`Analyzer`]
+//! itself and copies the pass results it wants onto its files.
+
+pub mod analyzer;
+pub mod benchmark;
+pub mod blanks;
+pub mod cache;
+pub mod cancel;
+pub mod checksum;
+pub mod columns;
+pub mod compat;
+pub mod config;
+pub mod crop;
+pub mod dedupe;
+pub mod estimate;
+pub mod export;
+pub mod filter;
+pub mod gstreamer;
+pub mod highlight;
+pub mod hwdecode;
+pub mod ignore;
+pub mod image;
+pub mod interlace;
+pub mod keymap;
+pub mod log;
+pub mod loudness;
+pub mod mediainfo;
+pub mod model;
+pub mod mxf;
+pub mod notes;
+pub mod organize;
+pub mod packets;
+pub mod paste;
+pub mod pdf;
+pub mod phash;
+pub mod probe;
+pub mod quality;
+pub mod query;
+pub mod remedy;
+pub mod rename;
+pub mod report;
+pub mod s3;
+pub mod sequence;
+pub mod ssh;
+pub mod tags;
+pub mod template;
+pub mod theme;
+pub mod thumbnail;
+pub mod transcode;
+pub mod trash;
+pub mod validate;
+pub mod verify;
+pub mod watch;
+
+pub use analyzer::{analyzer, Analyzer, Backend};
+pub use config::Config;
+pub use filter::{ActiveFilter, FilterOp, FilterSet, FilterType};
+pub use model::MediaInfo;
+pub use probe::{probe_file, ProbeOptions};
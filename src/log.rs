@@ -0,0 +1,67 @@
+//! The errors and warnings of a session: analyses that failed, what ffprobe had
+//! to say about the files it read, passes and file operations that went wrong.
+//! A notification only shows for a few seconds; the log keeps them, with the time
+//! each came in, for the log view.
+
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Entries kept; past it the oldest go.
+const CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl Level {
+    pub fn label(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Time of day in UTC, `HH:MM:SS`
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Log {
+    entries: VecDeque<Entry>,
+}
+
+impl Log {
+    pub fn push(&mut self, level: Level, message: String) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry { time: clock(), level, message });
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> &VecDeque<Entry> {
+        &self.entries
+    }
+
+    pub fn count(&self, level: Level) -> usize {
+        self.entries.iter().filter(|entry| entry.level == level).count()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+fn clock() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) % 86_400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
@@ -0,0 +1,89 @@
+//! EBU R128 loudness of a file's audio, measured with ffmpeg's loudnorm filter in
+//! analysis-only mode. Every sample has to be decoded, so it runs on demand in the
+//! background.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Loudness {
+    /// Integrated loudness in LUFS; EBU R128 targets -23, streaming platforms around -14
+    pub integrated: f64,
+    /// Maximum true peak in dBTP
+    pub true_peak: f64,
+    /// Loudness range in LU
+    pub range: f64,
+}
+
+/// Measures one file at a time; decoding is CPU-bound enough that running several
+/// at once would starve the probes.
+pub struct LoudnessMeter {
+    path_tx: Sender<String>,
+    results: Receiver<(String, Result<Loudness, String>)>,
+}
+
+impl LoudnessMeter {
+    pub fn new(ffmpeg: String) -> Self {
+        let (path_tx, path_rx) = mpsc::channel::<String>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for path in path_rx {
+                let result = measure(&ffmpeg, &path);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { path_tx, results }
+    }
+
+    pub fn queue(&self, path: String) {
+        // The worker only stops when the meter is dropped
+        let _ = self.path_tx.send(path);
+    }
+
+    /// Files measured since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Loudness, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn measure(ffmpeg: &str, path: &str) -> Result<Loudness, String> {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin"])
+        .args(sequence::input_args(path))
+        .args(["-i", path, "-map", "0:a:0", "-vn"])
+        .args(["-af", "loudnorm=print_format=json", "-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    // The summary is the last JSON object in the log, with every value a string
+    let json = stderr
+        .rfind('{')
+        .and_then(|start| serde_json::from_str::<Value>(stderr[start..].trim()).ok())
+        .ok_or("no loudness summary in ffmpeg output")?;
+    let value = |key: &str| -> Result<f64, String> {
+        json[key]
+            .as_str()
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or(format!("missing {} in loudness summary", key))
+    };
+    Ok(Loudness {
+        integrated: value("input_i")?,
+        true_peak: value("input_tp")?,
+        range: value("input_lra")?,
+    })
+}
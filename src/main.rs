@@ -1,528 +1,6249 @@
-use anyhow::Result;
+mod compare;
+mod complete;
+mod history;
+mod json_tree;
+mod launch;
+mod palette;
+mod project;
+mod raw_store;
+mod tree;
+
+use anyhow::{Context, Result};
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
+        KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    cursor, execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span, Text},
+    text::{Line, Span},
+    symbols,
     widgets::{
-        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState,
-        Tabs, Wrap,
+        block::{self, Title}, Axis, Block, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, ListState, Paragraph,
+        Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Sparkline, Table, TableState, Tabs, Wrap,
     },
     Frame, Terminal,
 };
-use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
-    io::{self, Stdout},
-    path::Path,
-    process::Command,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
     time::{Duration, Instant},
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct MediaInfo {
-    name: String,
-    container: String,
-    codec: String,
-    resolution: String,
-    frame_rate: String,
-    bitrate: String,
-    path: String,
-    raw_output: String,
-}
-
-#[derive(Debug, Clone)]
-struct FilterOptions {
-    containers: Vec<String>,
-    codecs: Vec<String>,
-    resolutions: Vec<String>,
-    frame_rates: Vec<String>,
-    bitrates: Vec<String>,
-}
-
-impl Default for FilterOptions {
-    fn default() -> Self {
-        Self {
-            containers: vec![
-                "mp4".to_string(),
-                "mov".to_string(),
-                "avi".to_string(),
-                "mkv".to_string(),
-                "jpg".to_string(),
-                "png".to_string(),
-            ],
-            codecs: vec![
-                "H.264".to_string(),
-                "H.265".to_string(),
-                "VP9".to_string(),
-                "AV1".to_string(),
-                "Hap".to_string(),
-                "DXV3".to_string(),
-            ],
-            resolutions: vec![
-                "1920x1080".to_string(),
-                "1280x720".to_string(),
-                "3840x2160".to_string(),
-                "2560x1440".to_string(),
-            ],
-            frame_rates: vec![
-                "24".to_string(),
-                "25".to_string(),
-                "30".to_string(),
-                "50".to_string(),
-                "60".to_string(),
-            ],
-            bitrates: vec![
-                "1".to_string(),
-                "5".to_string(),
-                "10".to_string(),
-                "15".to_string(),
-                "20".to_string(),
-            ],
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-enum FilterType {
+use media_scout::{
+    analyzer::{self, Analyzer},
+    benchmark::{self, Benchmarker},
+    blanks::{self, BlankDetector, Position},
+    cache::AnalysisCache,
+    cancel,
+    checksum::{self, Checksummer},
+    columns::{self, Column, ColumnSpec},
+    compat::{self, MediaServer},
+    config::{self, Config, FilterPreset},
+    crop::CropDetector,
+    dedupe,
+    estimate::{self, Estimate, Target},
+    export::{self, ExportFormat},
+    filter::{matches_groups, ActiveFilter, FilterOptions, FilterSet},
+    highlight::Highlighter,
+    hwdecode::{self, Api},
+    keymap::{Action, Keymap},
+    log::{Level, Log},
+    theme::Theme,
+    interlace::{self, ScanDetector},
+    loudness::LoudnessMeter,
+    model::{
+        chroma_subsampling, display_aspect, display_resolution, faststart_label, format_aspect,
+        format_audio_layout, format_audio_summary, format_bit_depth, format_bitrate, format_duration,
+        format_languages, format_resolution, format_size, frame_rate_mode, is_anamorphic, matte, orientation,
+        pixel_count, yes_no, Checksum, MediaInfo,
+    },
+    notes::{self, Flag, Note, Notes},
+    packets::{self, Packet, PacketProber},
+    paste,
+    phash::{self, FrameHasher, Hashes},
+    quality::{self, QualityMeter},
+    probe::{
+        audio_bit_depth, check_ffprobe, expand_path, first_stream, format_tag, is_media_file, is_stream, is_url,
+        ProbeOptions, Scan, ScanOptions, FFPROBE_INSTALL_HINT,
+    },
+    organize::{self, Operation, Placement},
+    report::{self, Brand, ReportFormat, ReportOptions, REPORT_TITLE},
+    query, remedy,
+    s3::S3,
+    ssh::Ssh,
+    rename::{self, Rename},
+    tags::{self, TagEdit, TagWriter},
+    template::Template,
+    thumbnail::{self, Graphics, Thumbnail, Thumbnailer},
+    transcode::{JobStatus, TranscodeQueue},
+    trash,
+    validate::{ValidationProfile, Verdict},
+    verify::{Integrity, Verifier},
+    watch::FolderWatcher,
+};
+
+use compare::Comparison;
+use complete::PathCompletion;
+use history::History;
+use json_tree::JsonTree;
+use palette::{CommandPalette, Entry};
+use tree::{DirTree, TreeRow};
+use project::Project;
+use raw_store::RawStore;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortKey {
+    None,
+    Name,
     Container,
     Codec,
     Resolution,
     FrameRate,
     Bitrate,
+    Duration,
+    FileSize,
+    BitDepth,
+    Created,
+    Rating,
+}
+
+impl SortKey {
+    fn next(self) -> Self {
+        match self {
+            SortKey::None => SortKey::Name,
+            SortKey::Name => SortKey::Container,
+            SortKey::Container => SortKey::Codec,
+            SortKey::Codec => SortKey::Resolution,
+            SortKey::Resolution => SortKey::FrameRate,
+            SortKey::FrameRate => SortKey::Bitrate,
+            SortKey::Bitrate => SortKey::Duration,
+            SortKey::Duration => SortKey::FileSize,
+            SortKey::FileSize => SortKey::BitDepth,
+            SortKey::BitDepth => SortKey::Created,
+            SortKey::Created => SortKey::Rating,
+            SortKey::Rating => SortKey::None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::None => "none",
+            SortKey::Name => "name",
+            SortKey::Container => "container",
+            SortKey::Codec => "codec",
+            SortKey::Resolution => "resolution",
+            SortKey::FrameRate => "fps",
+            SortKey::Bitrate => "bitrate",
+            SortKey::Duration => "duration",
+            SortKey::FileSize => "size",
+            SortKey::BitDepth => "bit depth",
+            SortKey::Created => "creation date",
+            SortKey::Rating => "rating",
+        }
+    }
+
+    /// The sort key of a Files table column, for sorting by clicking its header.
+    fn for_column(column: Column) -> Option<Self> {
+        let key = match column {
+            Column::Name => SortKey::Name,
+            Column::Container => SortKey::Container,
+            Column::Codec => SortKey::Codec,
+            Column::Resolution => SortKey::Resolution,
+            Column::Fps => SortKey::FrameRate,
+            Column::Bitrate => SortKey::Bitrate,
+            Column::Duration => SortKey::Duration,
+            Column::Size => SortKey::FileSize,
+            Column::Depth => SortKey::BitDepth,
+            Column::Created => SortKey::Created,
+            Column::Rating => SortKey::Rating,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    fn compare(self, a: &MediaInfo, b: &MediaInfo) -> Ordering {
+        match self {
+            SortKey::None => Ordering::Equal,
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Container => a.container.cmp(&b.container),
+            SortKey::Codec => a.codec.cmp(&b.codec),
+            SortKey::Resolution => compare_options(pixel_count(&a.resolution), pixel_count(&b.resolution)),
+            SortKey::FrameRate => compare_options(a.frame_rate.parse::<f64>().ok(), b.frame_rate.parse::<f64>().ok()),
+            SortKey::Bitrate => compare_options(a.bitrate.parse::<f64>().ok(), b.bitrate.parse::<f64>().ok()),
+            SortKey::Duration => compare_options(a.duration, b.duration),
+            SortKey::FileSize => compare_options(a.file_size, b.file_size),
+            SortKey::BitDepth => compare_options(a.bit_depth, b.bit_depth),
+            SortKey::Created => compare_options(a.creation_time.as_ref(), b.creation_time.as_ref()),
+            // Best first, unrated last
+            SortKey::Rating => {
+                let stars = |file: &MediaInfo| file.note.as_ref().and_then(|note| note.rating).map(Reverse);
+                compare_options(stars(a), stars(b))
+            }
+        }
+    }
+}
+
+// Unknown values always sort after known ones
+fn compare_options<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// What the Files table groups its rows by, each group under a header that folds away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupBy {
+    None,
+    Codec,
+    Container,
+    Resolution,
+    Directory,
+}
+
+impl GroupBy {
+    fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Codec,
+            GroupBy::Codec => GroupBy::Container,
+            GroupBy::Container => GroupBy::Resolution,
+            GroupBy::Resolution => GroupBy::Directory,
+            GroupBy::Directory => GroupBy::None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            GroupBy::None => "none",
+            GroupBy::Codec => "codec",
+            GroupBy::Container => "container",
+            GroupBy::Resolution => "resolution",
+            GroupBy::Directory => "folder",
+        }
+    }
+
+    /// The name of the group `file` falls in.
+    fn group(self, file: &MediaInfo) -> String {
+        let name = match self {
+            GroupBy::None => String::new(),
+            GroupBy::Codec => file.codec.clone(),
+            GroupBy::Container => file.container.clone(),
+            GroupBy::Resolution if file.audio_only => "Audio only".to_string(),
+            GroupBy::Resolution => file.resolution.clone(),
+            GroupBy::Directory => {
+                Path::new(&file.path).parent().map_or(String::new(), |parent| parent.display().to_string())
+            }
+        };
+        if name.is_empty() {
+            "Unknown".to_string()
+        } else {
+            name
+        }
+    }
+
+    /// Group order: largest picture first by resolution, by name otherwise.
+    fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            GroupBy::Resolution => compare_options(pixel_count(b), pixel_count(a)).then_with(|| a.cmp(b)),
+            _ => a.to_lowercase().cmp(&b.to_lowercase()),
+        }
+    }
+}
+
+/// A line of the Files table: a file by its index in `media_files`, or with
+/// grouping on, a group's header.
+#[derive(Clone)]
+enum TableRow {
+    Group { name: String, files: Vec<usize>, collapsed: bool },
+    File(usize),
+}
+
+/// What the Files table shows. Filtering, sorting and looking for duplicates go
+/// over every file, so they're worked out again only after a change to the files,
+/// filters, sort, grouping or similarity hashes, not on every frame or keypress.
+struct View {
+    /// Indices into `media_files` in the order the table displays them
+    filtered: Vec<usize>,
+    rows: Vec<TableRow>,
+    duplicates: Vec<(Likeness, Vec<usize>)>,
+    /// Group label of each file with a copy
+    labels: HashMap<usize, String>,
+    columns: Vec<ColumnSpec>,
+}
+
+/// Whether the characters of `pattern` appear in order in `text`, ignoring case
+/// and whitespace, so "intv3" finds "intro_loop_v3.mov".
+fn fuzzy_match(text: &str, pattern: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|p| text.any(|t| t == p))
+}
+
+
+struct AnalysisJob {
+    path: String,
+    backend: analyzer::Backend,
+    // Probe even when the cache has the file, e.g. for `R`
+    refresh: bool,
+    // The project the job was queued for, see `App::generation`
+    generation: u64,
+    // The cancel count when it was queued, see `cancel::pin`
+    epoch: u64,
+}
+
+struct AnalysisResult {
+    path: String,
+    result: Result<MediaInfo>,
+    elapsed: Duration,
+    cached: bool,
+    generation: u64,
+}
+
+/// Runs the analyzers off the UI thread so large batches don't freeze the interface.
+/// `workers` threads pull from a shared queue; each job names the backend to use.
+/// Unchanged files are answered from the cache without being probed.
+fn spawn_analysis_workers(config: &Config) -> (Sender<AnalysisJob>, Receiver<AnalysisResult>) {
+    let (job_tx, job_rx) = mpsc::channel::<AnalysisJob>();
+    let (result_tx, result_rx) = mpsc::channel();
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let analyzers: Arc<HashMap<analyzer::Backend, Box<dyn Analyzer>>> = Arc::new(
+        [analyzer::Backend::Ffprobe, analyzer::Backend::Mediainfo, analyzer::Backend::Gstreamer]
+            .into_iter()
+            .map(|backend| (backend, analyzer::analyzer(backend, config)))
+            .collect(),
+    );
+    let cache = AnalysisCache::from_config(config);
+    for _ in 0..config.parallelism.max(1) {
+        let job_rx = Arc::clone(&job_rx);
+        let result_tx = result_tx.clone();
+        let analyzers = Arc::clone(&analyzers);
+        let cache = cache.clone();
+        thread::spawn(move || loop {
+            // Hold the lock only while taking a job, not while probing
+            let job = job_rx.lock().unwrap().recv();
+            let Ok(AnalysisJob { path, backend, refresh, generation, epoch }) = job else {
+                break;
+            };
+            cancel::pin(epoch);
+            let start_time = Instant::now();
+            let cached = if refresh { None } else { cache.get(&path, backend) };
+            let (result, cached) = match cached {
+                Some(info) => (Ok(info), true),
+                // Cancelled while queued, answered without starting a probe
+                None if cancel::cancelled() => (Err(cancel::interrupted().into()), false),
+                None => {
+                    let result = analyzers[&backend].analyze(&path);
+                    if let Ok(info) = &result {
+                        cache.put(info);
+                    }
+                    (result, false)
+                }
+            };
+            let elapsed = start_time.elapsed();
+            let finished = AnalysisResult { path, result, elapsed, cached, generation };
+            if result_tx.send(finished).is_err() {
+                break;
+            }
+        });
+    }
+    (job_tx, result_rx)
+}
+
+#[derive(Parser)]
+#[command(name = "media-scout", about = "Scout video, image and audio files for metadata")]
+struct Cli {
+    /// Files or directories to analyze on startup
+    paths: Vec<String>,
+
+    /// Analyze the paths and print the results instead of starting the TUI
+    #[arg(long)]
+    no_tui: bool,
+
+    /// Also read newline-separated paths from standard input
+    #[arg(long)]
+    stdin: bool,
+
+    /// Watch a directory and analyze files as they're created or modified (repeatable)
+    #[arg(long, value_name = "DIR", conflicts_with = "no_tui")]
+    watch: Vec<String>,
+
+    /// ffprobe executable to use, overriding the config file
+    #[arg(long, env = "MEDIA_SCOUT_FFPROBE", value_name = "PATH")]
+    ffprobe: Option<String>,
+
+    /// ffmpeg executable used for re-encodes, overriding the config file
+    #[arg(long, env = "MEDIA_SCOUT_FFMPEG", value_name = "PATH")]
+    ffmpeg: Option<String>,
+
+    /// Extra argument passed to ffprobe, e.g. --ffprobe-arg=-probesize --ffprobe-arg=100M (repeatable)
+    #[arg(long = "ffprobe-arg", value_name = "ARG", allow_hyphen_values = true)]
+    ffprobe_args: Vec<String>,
+
+    /// Config file to use instead of the default location
+    #[arg(long, value_name = "FILE")]
+    config: Option<std::path::PathBuf>,
+
+    /// Probe every file even when the cache has a result for it
+    #[arg(long)]
+    refresh: bool,
+
+    /// Open a named project, keeping its files and filters apart from other projects'.
+    /// With --no-tui, the project's files are analyzed along with the paths
+    #[arg(long, value_name = "NAME")]
+    project: Option<String>,
+
+    /// Don't read or write the analysis cache
+    #[arg(long, conflicts_with = "refresh")]
+    no_cache: bool,
+
+    /// Tool to analyze files with, overriding the config file
+    #[arg(long, value_enum, value_name = "TOOL")]
+    analyzer: Option<analyzer::Backend>,
+
+    /// Color theme (dark, light, high-contrast or one from the config), overriding the config file
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+
+    /// Checksum algorithm, overriding the config file. With --no-tui, also computes
+    /// a checksum of every file for the output
+    #[arg(long, value_enum, value_name = "ALGORITHM")]
+    checksum: Option<checksum::Algorithm>,
+
+    /// Output format used with --no-tui; html and markdown write a report with the
+    /// first QC profile's results
+    #[arg(long, value_enum, default_value = "table")]
+    format: ExportFormat,
+
+    /// Scan no more than N levels of folders down, 1 for the files of the folders
+    /// given alone, overriding the config
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_depth: Option<u64>,
+
+    /// Only scan files with these extensions, e.g. --ext mov,mp4, overriding the config
+    #[arg(long = "ext", value_name = "EXTENSIONS", value_delimiter = ',')]
+    extensions: Vec<String>,
+
+    /// Leave scanned files smaller than SIZE out, e.g. 100MB, overriding the config
+    #[arg(long, value_name = "SIZE", value_parser = size_arg)]
+    min_size: Option<String>,
+
+    /// Leave scanned files larger than SIZE out, overriding the config
+    #[arg(long, value_name = "SIZE", value_parser = size_arg)]
+    max_size: Option<String>,
+
+    /// Only output the files matching a filter query, with --no-tui
+    #[arg(long, value_name = "QUERY")]
+    filter: Option<String>,
+
+    /// Print each file with a template instead of --format, with --no-tui, e.g.
+    /// '{{name}} {{codec}} {{bitrate}} Mbps', or the name of one from the config
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "format")]
+    template: Option<String>,
+
+    /// Copy the files into folders after their metadata instead of printing them,
+    /// with --no-tui, e.g. '~/Library/{codec}/{height}p'
+    #[arg(long, value_name = "PATTERN", conflicts_with_all = ["format", "template"])]
+    organize: Option<String>,
+
+    /// Move the files with --organize rather than copy them
+    #[arg(long = "move", requires = "organize")]
+    move_files: bool,
+
+    /// Print where --organize would put each file without copying or moving any
+    #[arg(long, requires = "organize")]
+    dry_run: bool,
+}
+
+fn size_arg(value: &str) -> Result<String, String> {
+    match query::parse_size(value) {
+        Some(_) => Ok(value.to_string()),
+        None => Err(format!("'{}' is not a size such as 100MB", value)),
+    }
 }
 
-#[derive(Debug, Clone)]
-struct ActiveFilter {
-    filter_type: FilterType,
-    value: String,
+/// Larger cursor movements shared by every scrollable view.
+#[derive(Debug, Clone, Copy)]
+enum Jump {
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    // A few rows at a time, for the scroll wheel
+    Up(usize),
+    Down(usize),
+}
+
+impl Jump {
+    fn from_key(code: KeyCode) -> Option<Self> {
+        match code {
+            KeyCode::PageUp => Some(Jump::PageUp),
+            KeyCode::PageDown => Some(Jump::PageDown),
+            KeyCode::Home => Some(Jump::Home),
+            KeyCode::End | KeyCode::Char('G') => Some(Jump::End),
+            _ => None,
+        }
+    }
+
+    fn apply(self, current: usize, len: usize, page: usize) -> usize {
+        let last = len.saturating_sub(1);
+        match self {
+            Jump::PageUp => current.saturating_sub(page.max(1)),
+            Jump::PageDown => (current + page.max(1)).min(last),
+            Jump::Home => 0,
+            Jump::End => last,
+            Jump::Up(rows) => current.saturating_sub(rows),
+            Jump::Down(rows) => (current + rows).min(last),
+        }
+    }
+}
+
+/// Screen areas the mouse acts on, recorded as each frame is drawn.
+#[derive(Default)]
+struct ClickTargets {
+    tabs: Vec<Rect>,
+    /// Files table headers that sort by their column
+    headers: Vec<(Rect, SortKey)>,
+    /// The Files table rows on screen, and the position in the filtered list of the first
+    rows: Option<(Rect, usize)>,
+    /// The Tree tab's rows on screen, and the row of the first
+    tree_rows: Option<(Rect, usize)>,
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    (area.x..area.right()).contains(&x) && (area.y..area.bottom()).contains(&y)
+}
+
+/// How the files in a Duplicates group match.
+#[derive(Clone, Copy, PartialEq)]
+enum Likeness {
+    Identical,
+    Similar,
+}
+
+/// What a command palette entry runs.
+enum Command {
+    Action(Action),
+    Preset(usize),
+    Sort(SortKey),
+    Group(GroupBy),
+    Rate(Option<u8>),
+    ClearFilters,
+    Project(String),
+    /// Copies the files with a template from the config, by its index in `templates`
+    Template(usize),
 }
 
 enum AppMode {
     Normal,
     AddFile,
+    Query,
+    SavePreset,
+    RenamePattern,
+    Rename,
+    OrganizePattern,
+    Organize,
+    ConfirmDelete,
     ShowRawOutput,
+    Fixes,
+    Transcodes,
+    Duplicates,
+    Compare,
+    Bitrate,
+    Gop,
+    Chapters,
+    EditTags,
+    EditNote,
+    Projects,
+    Palette,
+    Columns,
+    Log,
     Help,
 }
 
+const TABS: [&str; 4] = ["Files", "Filters", "Stats", "Tree"];
+
+/// Undo steps kept; older removals and renames can't be brought back.
+const UNDO_DEPTH: usize = 20;
+
+/// The fixes view's key for queueing the selected fix; the view's keys aren't rebindable.
+const QUEUE_FIX_KEY: char = 't';
+
+/// A row taken out by `d` or `c`, with what the passes had found out about it.
+struct RemovedRow {
+    // Position in `media_files` the row had
+    index: usize,
+    file: MediaInfo,
+    marked: bool,
+    integrity: Option<Integrity>,
+    cross_check: Option<MediaInfo>,
+    raw: Option<raw_store::Entry>,
+    frame_hash: Option<Hashes>,
+}
+
+/// One removal `u` can revert.
+struct Removal {
+    // In ascending index order
+    rows: Vec<RemovedRow>,
+    // The filters `c` cleared along with the rows
+    filters: Option<Vec<Vec<ActiveFilter>>>,
+}
+
+enum UndoStep {
+    Removal(Removal),
+    /// A batch of files renamed or moved on disk, `(from, to)`
+    Rename(Vec<(String, String)>),
+}
+
+impl UndoStep {
+    /// Lets go of what the step kept, once it can no longer be undone.
+    fn discard(self) {
+        if let UndoStep::Removal(removal) = self {
+            removal.rows.into_iter().filter_map(|row| row.raw).for_each(RawStore::discard);
+        }
+    }
+}
+
+/// The tag editor's fields for one file.
+struct TagForm {
+    path: String,
+    title: Input,
+    comment: Input,
+    languages: Input,
+    // 0 title, 1 comment, 2 languages
+    focus: usize,
+}
+
+impl TagForm {
+    const LABELS: [&'static str; 3] = ["Title", "Comment", "Audio languages"];
+
+    fn fields(&self) -> [&Input; 3] {
+        [&self.title, &self.comment, &self.languages]
+    }
+
+    fn focused(&mut self) -> &mut Input {
+        match self.focus {
+            0 => &mut self.title,
+            1 => &mut self.comment,
+            _ => &mut self.languages,
+        }
+    }
+}
+
+/// The review tags and note being edited, for the selected file or every marked one.
+struct NoteForm {
+    paths: Vec<String>,
+    tags: Input,
+    text: Input,
+    // 0 tags, 1 note
+    focus: usize,
+}
+
+impl NoteForm {
+    const LABELS: [&'static str; 2] = ["Tags, comma-separated", "Note"];
+
+    fn fields(&self) -> [&Input; 2] {
+        [&self.tags, &self.text]
+    }
+
+    fn focused(&mut self) -> &mut Input {
+        match self.focus {
+            0 => &mut self.tags,
+            _ => &mut self.text,
+        }
+    }
+}
+
 struct App {
     media_files: Vec<MediaInfo>,
+    /// Built on demand from the files and dropped by `invalidate_view`
+    view: RefCell<Option<Rc<View>>>,
     table_state: TableState,
+    marked: HashSet<String>,
     filter_options: FilterOptions,
-    active_filters: Vec<ActiveFilter>,
+    /// Every group must match; a group matches when any of its filters does
+    active_filters: Vec<Vec<ActiveFilter>>,
+    filter_list_state: ListState,
+    presets: Vec<FilterPreset>,
+    // Where presets are saved, `None` for the default config location
+    config_path: Option<PathBuf>,
+    profiles: Vec<ValidationProfile>,
+    // Output templates from the config, by name
+    templates: Vec<(String, Template)>,
+    // Profile shown in the QC column, `None` hides the column
+    active_profile: Option<usize>,
+    highlighter: Highlighter,
+    keymap: Keymap,
+    theme: Theme,
+    ffmpeg: String,
+    player: Vec<String>,
+    fix_list_state: ListState,
+    /// Pattern of the last rename, offered again by the next
+    rename_pattern: String,
+    /// The rename being previewed
+    renames: Vec<Rename>,
+    rename_list_state: ListState,
+    /// Folder pattern of the last organize, and the placements being previewed
+    organize_pattern: String,
+    placements: Vec<Placement>,
+    organize_list_state: ListState,
+    /// The copies or moves under way, with how many are done and what went wrong
+    organizing: Option<(Operation, Receiver<organize::Progress>)>,
+    organize_done: (usize, usize),
+    organize_moved: Vec<(String, String)>,
+    organize_errors: Vec<String>,
+    /// Files waiting on a confirmation to be deleted from disk
+    deleting: Vec<String>,
+    chapter_state: TableState,
+    transcodes: TranscodeQueue,
+    duplicates_state: ListState,
+    frame_hasher: FrameHasher,
+    checksummer: Checksummer,
+    checksum_algorithm: checksum::Algorithm,
+    report_format: ReportFormat,
+    /// Letterhead of PDF reports
+    brand: Brand,
+    show_thumbnail: bool,
+    graphics: Graphics,
+    thumbnailer: Thumbnailer,
+    thumbnails: HashMap<String, Result<Thumbnail, String>>,
+    thumbnail_pending: HashSet<String>,
+    // Path and cells of the image the last frame wants drawn, and of the one on screen
+    thumbnail_overlay: Option<(String, Rect)>,
+    drawn_overlay: Option<(String, Rect)>,
+    // Pixel size of a terminal cell, for sizing images
+    cell_size: (u16, u16),
+    // Paths waiting for or in the checksum pass
+    checksumming: HashSet<String>,
+    loudness_meter: LoudnessMeter,
+    // Paths waiting for or in the loudness pass
+    measuring: HashSet<String>,
+    blank_detector: BlankDetector,
+    // Paths waiting for or in the black and silence pass
+    scanning_blanks: HashSet<String>,
+    scan_detector: ScanDetector,
+    // Paths waiting for or in the idet pass
+    detecting_scan: HashSet<String>,
+    crop_detector: CropDetector,
+    // Paths waiting for or in the cropdetect pass
+    detecting_crop: HashSet<String>,
+    quality_meter: QualityMeter,
+    // Encodes waiting for or in the quality pass, and the file they're scored against
+    measuring_quality: HashSet<String>,
+    reference: Option<String>,
+    benchmarker: Benchmarker,
+    // Paths waiting for or in the benchmark pass
+    benchmarking: HashSet<String>,
+    // The host's hardware decoders, `None` until detection finishes
+    hw_decoders: Option<Vec<Api>>,
+    hw_detection: Receiver<Vec<Api>>,
+    tag_form: Option<TagForm>,
+    notes: Notes,
+    note_form: Option<NoteForm>,
+    tag_writer: TagWriter,
+    // Paths being remuxed with new tags
+    writing_tags: HashSet<String>,
+    // Perceptual hashes by path, filled in by the similarity pass
+    frame_hashes: HashMap<String, Hashes>,
+    hashing: usize,
+    packet_prober: PacketProber,
+    // Video packets by path, probed when a file's bitrate graph is first opened
+    packets: HashMap<String, Result<Vec<Packet>, String>>,
+    packets_pending: HashSet<String>,
+    // File shown in the bitrate graph or GOP report
+    packet_file: Option<String>,
+    // Mbps above which the graph flags a peak, `None` for twice the average
+    peak_bitrate: Option<f64>,
+    // Whether directory scans collapse numbered stills into image sequences
+    scan_options: ScanOptions,
+    // List the objects under `s3://` prefixes and the files under `ssh://` directories
+    // that get added
+    s3: S3,
+    ssh: Ssh,
+    verifier: Verifier,
+    // Decode check results by path; the Decode column appears once there are any
+    integrity: HashMap<String, Integrity>,
+    sort_key: SortKey,
+    sort_descending: bool,
+    group_by: GroupBy,
+    // Names of the folded groups
+    collapsed_groups: HashSet<String>,
     mode: AppMode,
     input: Input,
+    // Tab completion candidates for the Add File input, while listed
+    completion: Option<PathCompletion>,
+    history: History,
     selected_tab: usize,
-    raw_output_scroll: usize,
+    json_tree: JsonTree,
+    dir_tree: DirTree,
+    // The Stats tab's transcode estimate: the chosen target, and the bitrate of
+    // the delivery codecs in Mbps
+    estimate_table: TableState,
+    estimate_mbps: f64,
+    raw_search: Option<Input>,
+    // The `/` file search, kept after the bar closes until Esc clears it
+    search: String,
+    search_input: Option<Input>,
+    query_input: Input,
+    query_error: Option<String>,
     notification: Option<(String, Instant)>,
+    // Errors and warnings of the session, kept for the log view after their notification
+    log: Log,
+    log_state: ListState,
     last_scan_time: Option<Instant>,
+    // Backend new scans use, and the row each file had from a different one
+    analyzer: analyzer::Backend,
+    cross_checks: HashMap<String, MediaInfo>,
+    // Every row's `raw_output`, moved out of memory as results arrive
+    raw_store: RawStore,
+    // Most recent last
+    undo: Vec<UndoStep>,
+    // The open project, `None` for a list that isn't saved
+    project: Option<String>,
+    // Project files queued but not analyzed yet, saved with the project all the same
+    project_pending: HashSet<String>,
+    // Counts project switches and cancels, so results queued before one are dropped
+    generation: u64,
+    project_names: Vec<String>,
+    project_list_state: ListState,
+    palette: Option<CommandPalette<Command>>,
+    comparison: Option<Comparison>,
+    click_targets: ClickTargets,
+    // Shown Files table columns in order, and the picker's copy, hidden ones included, while it's open
+    columns: Vec<ColumnSpec>,
+    column_picker: Vec<(ColumnSpec, bool)>,
+    column_list_state: ListState,
+    job_tx: Sender<AnalysisJob>,
+    result_rx: Receiver<AnalysisResult>,
+    pending_jobs: usize,
+    watcher: Option<FolderWatcher>,
+    ffprobe_error: Option<String>,
+    show_details: bool,
+    // Rows visible in the last rendered list, used for PageUp/PageDown
+    page_size: usize,
+    // First half of a `gg` chord
+    pending_g: bool,
+    // Created on first use; on X11 the copied text only lives as long as this does
+    clipboard: Option<arboard::Clipboard>,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(config: Config, config_path: Option<PathBuf>) -> Result<Self> {
+        let theme = Theme::new(&config.theme, &config.themes)?;
+        let templates = config
+            .templates
+            .iter()
+            .map(|(name, text)| Ok((name.clone(), Template::parse(text)?)))
+            .collect::<Result<_>>()?;
+        let highlighter = Highlighter::new(&config.highlights, &theme)?;
+        let keymap = Keymap::new(&config.keys)?;
+        columns::validate(&config.columns)?;
+        let ffprobe_error = check_ffprobe(&config.ffprobe).err().map(|e| e.to_string());
+        let (job_tx, result_rx) = spawn_analysis_workers(&config);
         let mut app = Self {
             media_files: Vec::new(),
+            view: RefCell::new(None),
             table_state: TableState::default(),
-            filter_options: FilterOptions::default(),
+            marked: HashSet::new(),
+            filter_options: config.filters.clone(),
             active_filters: Vec::new(),
+            filter_list_state: ListState::default(),
+            presets: config.presets.clone(),
+            config_path,
+            active_profile: (!config.profiles.is_empty()).then_some(0),
+            profiles: config.profiles.clone(),
+            templates,
+            highlighter,
+            keymap,
+            theme,
+            ffmpeg: config.ffmpeg.clone(),
+            player: config.player.clone(),
+            fix_list_state: ListState::default(),
+            rename_pattern: config.rename.clone(),
+            renames: Vec::new(),
+            rename_list_state: ListState::default(),
+            organize_pattern: config.organize.clone(),
+            placements: Vec::new(),
+            organize_list_state: ListState::default(),
+            organizing: None,
+            organize_done: (0, 0),
+            organize_moved: Vec::new(),
+            organize_errors: Vec::new(),
+            deleting: Vec::new(),
+            chapter_state: TableState::default(),
+            transcodes: TranscodeQueue::new(config.ffmpeg.clone()),
+            duplicates_state: ListState::default(),
+            frame_hasher: FrameHasher::new(config.ffmpeg.clone()),
+            checksummer: Checksummer::new(config.checksum),
+            checksum_algorithm: config.checksum,
+            report_format: config.report,
+            brand: config.brand.clone(),
+            show_thumbnail: false,
+            graphics: config.graphics.detect(),
+            thumbnailer: Thumbnailer::new(config.ffmpeg.clone()),
+            thumbnails: HashMap::new(),
+            thumbnail_pending: HashSet::new(),
+            thumbnail_overlay: None,
+            drawn_overlay: None,
+            cell_size: (8, 16),
+            checksumming: HashSet::new(),
+            loudness_meter: LoudnessMeter::new(config.ffmpeg.clone()),
+            measuring: HashSet::new(),
+            blank_detector: BlankDetector::new(config.ffmpeg.clone()),
+            scanning_blanks: HashSet::new(),
+            scan_detector: ScanDetector::new(config.ffmpeg.clone()),
+            detecting_scan: HashSet::new(),
+            crop_detector: CropDetector::new(config.ffmpeg.clone()),
+            detecting_crop: HashSet::new(),
+            quality_meter: QualityMeter::new(config.ffmpeg.clone()),
+            measuring_quality: HashSet::new(),
+            reference: None,
+            benchmarker: Benchmarker::new(config.ffmpeg.clone()),
+            benchmarking: HashSet::new(),
+            hw_decoders: None,
+            hw_detection: hwdecode::detect_in_background(config.ffmpeg.clone()),
+            tag_form: None,
+            notes: Notes::load()?,
+            note_form: None,
+            tag_writer: TagWriter::new(config.ffmpeg.clone()),
+            writing_tags: HashSet::new(),
+            frame_hashes: HashMap::new(),
+            hashing: 0,
+            packet_prober: PacketProber::new(ProbeOptions::from_config(&config)),
+            packets: HashMap::new(),
+            packets_pending: HashSet::new(),
+            packet_file: None,
+            peak_bitrate: config.peak_bitrate,
+            scan_options: ScanOptions::from_config(&config),
+            s3: S3::from_config(&config),
+            ssh: Ssh::new(&config.hosts),
+            verifier: Verifier::new(config.ffmpeg.clone()),
+            integrity: HashMap::new(),
+            sort_key: SortKey::None,
+            sort_descending: false,
+            group_by: GroupBy::None,
+            collapsed_groups: HashSet::new(),
             mode: AppMode::Normal,
             input: Input::default(),
+            completion: None,
+            history: History::load(),
             selected_tab: 0,
-            raw_output_scroll: 0,
+            json_tree: JsonTree::default(),
+            dir_tree: DirTree::default(),
+            estimate_table: TableState::default().with_selected(Some(0)),
+            estimate_mbps: 20.0,
+            raw_search: None,
+            search: String::new(),
+            search_input: None,
+            query_input: Input::default(),
+            query_error: None,
             notification: None,
+            log: Log::default(),
+            log_state: ListState::default(),
             last_scan_time: None,
+            analyzer: config.analyzer,
+            cross_checks: HashMap::new(),
+            raw_store: RawStore::new(),
+            undo: Vec::new(),
+            project: None,
+            project_pending: HashSet::new(),
+            generation: 0,
+            project_names: Vec::new(),
+            project_list_state: ListState::default(),
+            palette: None,
+            comparison: None,
+            click_targets: ClickTargets::default(),
+            columns: config.columns.clone(),
+            column_picker: Vec::new(),
+            column_list_state: ListState::default(),
+            job_tx,
+            result_rx,
+            pending_jobs: 0,
+            watcher: None,
+            ffprobe_error,
+            show_details: true,
+            page_size: 10,
+            pending_g: false,
+            clipboard: None,
         };
         app.table_state.select(Some(0));
-        app
+        app.filter_list_state.select(Some(0));
+        if let Some(error) = app.ffprobe_error.clone() {
+            app.log.push(Level::Error, error);
+        }
+        Ok(app)
     }
 
     fn add_file(&mut self, path: &str) -> Result<()> {
-        if !Path::new(path).exists() {
+        if !is_url(path) && !Path::new(path).exists() {
             self.show_notification("File does not exist".to_string());
             return Ok(());
         }
 
-        let start_time = Instant::now();
-        
-        match self.analyze_file(path) {
-            Ok(media_info) => {
-                self.media_files.push(media_info);
-                let elapsed = start_time.elapsed();
-                self.show_notification(format!("File analyzed in {:.2}s", elapsed.as_secs_f64()));
-                self.last_scan_time = Some(start_time);
-            }
+        let mut scan = match expand_path(path, &self.scan_options, &self.s3, &self.ssh) {
+            Ok(scan) => scan,
             Err(e) => {
-                self.show_notification(format!("Error analyzing file: {}", e));
+                self.show_error(format!("Could not list {}: {}", path, e));
+                return Ok(());
             }
+        };
+        self.leave_out_loaded(&mut scan);
+        if scan.paths.is_empty() {
+            self.show_notification(format!("No media files found{}", scan.summary()));
+            return Ok(());
+        }
+
+        let count = scan.paths.len();
+        for path in std::mem::take(&mut scan.paths) {
+            self.queue_analysis(path)?;
         }
-        
+        self.show_notification(format!("Queued {} file(s) for analysis{}", count, scan.summary()));
         Ok(())
     }
 
-    fn analyze_file(&self, path: &str) -> Result<MediaInfo> {
-        let output = Command::new("ffprobe")
-            .args([
-                "-i", path,
-                "-show_streams",
-                "-show_format",
-                "-hide_banner",
-                "-of", "json"
-            ])
-            .output()?;
+    /// Leaves out the files of a scan that are already in the list under another
+    /// path, reached through a symlink.
+    fn leave_out_loaded(&self, scan: &mut Scan) {
+        let local = self.media_files.iter().filter(|file| !file.path.contains("://"));
+        let loaded: HashSet<PathBuf> = local.filter_map(|file| fs::canonicalize(&file.path).ok()).collect();
+        if loaded.is_empty() {
+            return;
+        }
+        let before = scan.paths.len();
+        scan.paths.retain(|path| {
+            // The same path again is analyzed again, e.g. to refresh it
+            self.media_files.iter().any(|file| &file.path == path)
+                || !fs::canonicalize(path).is_ok_and(|real| loaded.contains(&real))
+        });
+        scan.duplicates += before - scan.paths.len();
+    }
 
-        let raw_output = String::from_utf8_lossy(&output.stdout);
-        
-        // Parse basic info from path
-        let path_obj = Path::new(path);
-        let name = path_obj.file_stem()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-        let container = path_obj.extension()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-
-        // For now, we'll parse the raw output with simple string matching
-        // In a real implementation, you'd want to use proper JSON parsing
-        let codec = self.extract_codec(&raw_output);
-        let resolution = self.extract_resolution(&raw_output);
-        let frame_rate = self.extract_frame_rate(&raw_output);
-        let bitrate = self.extract_bitrate(&raw_output);
-
-        Ok(MediaInfo {
-            name,
-            container,
-            codec,
-            resolution,
-            frame_rate,
-            bitrate,
-            path: path.to_string(),
-            raw_output: raw_output.to_string(),
-        })
+    fn open_add_file(&mut self) {
+        self.input.reset();
+        self.completion = None;
+        self.history.reset();
+        self.mode = AppMode::AddFile;
     }
 
-    fn extract_codec(&self, output: &str) -> String {
-        if output.contains("h264") {
-            "H.264".to_string()
-        } else if output.contains("hevc") || output.contains("h265") {
-            "H.265".to_string()
-        } else if output.contains("vp9") {
-            "VP9".to_string()
-        } else if output.contains("av01") {
-            "AV1".to_string()
-        } else if output.contains("hap") {
-            "Hap".to_string()
-        } else if output.contains("mjpeg") {
-            "MJPEG".to_string()
+    /// Shows the next older (or newer) recently added path in the Add File input.
+    fn browse_history(&mut self, older: bool) {
+        let current = self.input.value().to_string();
+        let entry = if older {
+            self.history.older(&current)
         } else {
-            "Unknown".to_string()
+            self.history.newer()
+        };
+        if let Some(entry) = entry {
+            self.input = Input::new(entry.to_string());
         }
     }
 
-    fn extract_resolution(&self, output: &str) -> String {
-        // Simple regex-like extraction
-        for line in output.lines() {
-            if line.contains("width") && line.contains("height") {
-                // This is a simplified extraction - in reality you'd want proper JSON parsing
-                if line.contains("1920") && line.contains("1080") {
-                    return "1920x1080".to_string();
-                } else if line.contains("1280") && line.contains("720") {
-                    return "1280x720".to_string();
-                } else if line.contains("3840") && line.contains("2160") {
-                    return "3840x2160".to_string();
+    /// Completes the Add File path as far as it's unambiguous, listing the
+    /// candidates when there's more than one.
+    fn complete_path(&mut self) {
+        let completion = PathCompletion::new(self.input.value());
+        match completion.candidates.len() {
+            0 => self.show_notification("No matching files".to_string()),
+            1 => self.input = Input::new(completion.completed(0)),
+            _ => {
+                let prefix = completion.common_prefix();
+                if prefix.len() > self.input.value().len() {
+                    self.input = Input::new(prefix);
                 }
+                self.completion = Some(completion);
             }
         }
-        "Unknown".to_string()
     }
 
-    fn extract_frame_rate(&self, output: &str) -> String {
-        if output.contains("25/1") || output.contains("\"25\"") {
-            "25".to_string()
-        } else if output.contains("30/1") || output.contains("\"30\"") {
-            "30".to_string()
-        } else if output.contains("24/1") || output.contains("\"24\"") {
-            "24".to_string()
-        } else if output.contains("60/1") || output.contains("\"60\"") {
-            "60".to_string()
-        } else {
-            "Unknown".to_string()
+    /// Fills in the highlighted candidate, listing a directory's contents next.
+    fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let Some(i) = completion.selected() else {
+            return;
+        };
+        let path = completion.completed(i);
+        self.input = Input::new(path.clone());
+        if completion.candidates[i].is_dir {
+            self.completion = Some(PathCompletion::new(&path));
         }
     }
 
-    fn extract_bitrate(&self, output: &str) -> String {
-        // Extract bitrate and convert to Mbps
-        for line in output.lines() {
-            if line.contains("bit_rate") && !line.contains("max_bit_rate") {
-                // Simplified extraction
-                if let Some(start) = line.find(":") {
-                    if let Some(end) = line[start..].find(",") {
-                        let bitrate_str = &line[start+1..start+end].trim().replace("\"", "");
-                        if let Ok(bitrate) = bitrate_str.parse::<f64>() {
-                            return format!("{:.1}", bitrate / 1_000_000.0);
-                        }
-                    }
+    /// Adds every path in pasted or dropped text, which may hold several.
+    fn add_pasted(&mut self, text: &str) -> Result<()> {
+        let mut queued = 0;
+        let mut missing = 0;
+        let mut history_error = None;
+        let mut listing_error = None;
+        let mut left_out = Scan::default();
+        for path in paste::paths(text) {
+            if !is_url(&path) && !Path::new(&path).exists() {
+                missing += 1;
+                continue;
+            }
+            if let Err(e) = self.history.push(&path) {
+                history_error = Some(e);
+            }
+            let mut scan = match expand_path(&path, &self.scan_options, &self.s3, &self.ssh) {
+                Ok(scan) => scan,
+                Err(e) => {
+                    listing_error = Some(format!("could not list {}: {}", path, e));
+                    continue;
                 }
+            };
+            self.leave_out_loaded(&mut scan);
+            for path in std::mem::take(&mut scan.paths) {
+                self.queue_analysis(path)?;
+                queued += 1;
             }
+            left_out.broken_links.append(&mut scan.broken_links);
+            left_out.skipped_links += scan.skipped_links;
+            left_out.duplicates += scan.duplicates;
+        }
+        let mut message = format!("Queued {} file(s) for analysis{}", queued, left_out.summary());
+        if missing > 0 {
+            message.push_str(&format!(", {} path(s) not found", missing));
+        }
+        if let Some(e) = listing_error {
+            message.push_str(&format!(", {}", e));
         }
-        "Unknown".to_string()
+        if let Some(e) = history_error {
+            message.push_str(&format!(" (could not save history: {:#})", e));
+        }
+        self.show_notification(message);
+        Ok(())
     }
 
-    fn show_notification(&mut self, message: String) {
-        self.notification = Some((message, Instant::now()));
+    /// Pasted text adds files from the file list, and is typed into any open input.
+    fn handle_paste(&mut self, text: &str) -> Result<()> {
+        let input = match self.mode {
+            AppMode::Normal if self.search_input.is_none() => return self.add_pasted(text),
+            AppMode::AddFile => {
+                self.input.reset();
+                self.completion = None;
+                self.mode = AppMode::Normal;
+                return self.add_pasted(text);
+            }
+            AppMode::Normal => self.search_input.as_mut(),
+            AppMode::SavePreset
+            | AppMode::RenamePattern
+            | AppMode::OrganizePattern
+            | AppMode::Projects
+            | AppMode::Palette => Some(&mut self.input),
+            AppMode::Query => Some(&mut self.query_input),
+            AppMode::ShowRawOutput => self.raw_search.as_mut(),
+            AppMode::EditTags => self.tag_form.as_mut().map(TagForm::focused),
+            AppMode::EditNote => self.note_form.as_mut().map(NoteForm::focused),
+            _ => None,
+        };
+        let Some(input) = input else {
+            return Ok(());
+        };
+        for c in text.chars().filter(|c| !c.is_control()) {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            input.handle_event(&Event::Key(key));
+        }
+        match self.mode {
+            AppMode::Normal => {
+                let search = input.value().to_string();
+                self.set_search(search);
+            }
+            AppMode::ShowRawOutput => {
+                let query = input.value().to_string();
+                self.json_tree.set_query(&query);
+            }
+            AppMode::Query => self.query_error = None,
+            AppMode::Palette => {
+                let query = input.value().to_string();
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.search(&query);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
-    fn clear_all(&mut self) {
-        self.media_files.clear();
-        self.active_filters.clear();
-        self.table_state.select(Some(0));
-        self.show_notification("All files cleared".to_string());
+    fn queue_analysis(&mut self, path: String) -> Result<()> {
+        self.send_job(path, false)
     }
 
-    fn next_file(&mut self) {
-        if self.media_files.is_empty() {
-            return;
+    /// Queues `path` for the current analyzer; `refresh` skips the cache.
+    fn send_job(&mut self, path: String, refresh: bool) -> Result<()> {
+        let (generation, epoch) = (self.generation, cancel::epoch());
+        self.job_tx.send(AnalysisJob { path, backend: self.analyzer, refresh, generation, epoch })?;
+        self.pending_jobs += 1;
+        Ok(())
+    }
+
+    fn watch_folders(&mut self, dirs: &[String]) -> Result<()> {
+        self.watcher = Some(FolderWatcher::new(dirs, self.scan_options.ignore.clone())?);
+        for dir in dirs {
+            self.add_file(dir)?;
         }
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i >= self.media_files.len() - 1 {
-                    0
-                } else {
-                    i + 1
+        Ok(())
+    }
+
+    /// Queues files the watcher has seen settle since the last tick.
+    fn poll_watcher(&mut self) -> Result<()> {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return Ok(());
+        };
+        for path in watcher.ready_paths() {
+            self.queue_analysis(path)?;
+        }
+        Ok(())
+    }
+
+    /// Collects finished analyses from the worker without blocking.
+    fn poll_analysis(&mut self) {
+        while let Ok(AnalysisResult { path, result, elapsed, cached, generation }) = self.result_rx.try_recv() {
+            self.pending_jobs = self.pending_jobs.saturating_sub(1);
+            // Queued before the project was switched
+            if generation != self.generation {
+                continue;
+            }
+            self.invalidate_view();
+            match result {
+                Ok(mut media_info) => {
+                    self.project_pending.remove(&path);
+                    for warning in &media_info.warnings {
+                        self.log.push(Level::Warning, format!("{}: {}", path, warning));
+                    }
+                    self.raw_store.insert(&path, std::mem::take(&mut media_info.raw_output));
+                    self.notes.apply(&mut media_info);
+                    if let Some(apis) = &self.hw_decoders {
+                        media_info.hw_decode = Some(hwdecode::check(apis, &media_info));
+                    }
+                    // Files seen again (e.g. re-rendered in a watched folder) are updated in place
+                    if let Some(existing) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        let previous = std::mem::replace(existing, media_info);
+                        // A reading by another tool stays around to compare against
+                        if previous.analyzer != existing.analyzer {
+                            self.cross_checks.insert(path.clone(), previous);
+                        } else {
+                            self.cross_checks.remove(&path);
+                        }
+                        // The old decode result says nothing about the new contents
+                        self.integrity.remove(&path);
+                        self.frame_hashes.remove(&path);
+                        self.thumbnails.remove(&path);
+                        self.packets.remove(&path);
+                        self.show_notification(format!("Updated {}", path));
+                    } else {
+                        let warnings = media_info.warnings.len();
+                        self.media_files.push(media_info);
+                        let mut message = match cached {
+                            true => "File loaded from the cache".to_string(),
+                            false => format!("File analyzed in {:.2}s", elapsed.as_secs_f64()),
+                        };
+                        if warnings > 0 {
+                            let log = self.keymap.label(Action::Log);
+                            message.push_str(&format!(", {} warning(s), '{}' to view", warnings, log));
+                        }
+                        self.show_notification(message);
+                    }
+                    self.last_scan_time = Some(Instant::now());
+                }
+                Err(e) => {
+                    self.show_error(format!("Error analyzing {}: {}", path, e));
                 }
             }
-            None => 0,
-        };
-        self.table_state.select(Some(i));
+        }
     }
 
-    fn previous_file(&mut self) {
-        if self.media_files.is_empty() {
-            return;
+    /// Files waiting for or in one of the background passes, analysis included.
+    fn running_jobs(&self) -> usize {
+        let checking = self.integrity.values().filter(|i| matches!(i, Integrity::Queued | Integrity::Checking));
+        let organizing = self.organizing.as_ref().map_or(0, |_| self.organize_done.1 - self.organize_done.0);
+        let passes = [
+            &self.checksumming,
+            &self.measuring,
+            &self.scanning_blanks,
+            &self.detecting_scan,
+            &self.detecting_crop,
+            &self.measuring_quality,
+            &self.benchmarking,
+        ];
+        let files: usize = passes.iter().map(|pass| pass.len()).sum();
+        self.pending_jobs + checking.count() + self.hashing + organizing + files
+    }
+
+    /// Stops the analysis and the background passes, killing the ffmpeg and ffprobe
+    /// processes they have running and dropping the files still queued. Copies and
+    /// moves stop after the file under way; transcodes and tag writes carry on.
+    fn cancel_jobs(&mut self) {
+        let running = self.running_jobs();
+        if running == 0 {
+            return self.show_notification("Nothing running to cancel".to_string());
         }
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.media_files.len() - 1
-                } else {
-                    i - 1
+        cancel::cancel_all();
+        // Analyses already queued come back failed, and are dropped like a last project's
+        self.generation += 1;
+        // The old workers quit with what they had queued, new ones take the next files
+        let ffmpeg = &self.ffmpeg;
+        self.verifier = Verifier::new(ffmpeg.clone());
+        self.frame_hasher = FrameHasher::new(ffmpeg.clone());
+        self.checksummer = Checksummer::new(self.checksum_algorithm);
+        self.loudness_meter = LoudnessMeter::new(ffmpeg.clone());
+        self.blank_detector = BlankDetector::new(ffmpeg.clone());
+        self.scan_detector = ScanDetector::new(ffmpeg.clone());
+        self.crop_detector = CropDetector::new(ffmpeg.clone());
+        self.quality_meter = QualityMeter::new(ffmpeg.clone());
+        self.benchmarker = Benchmarker::new(ffmpeg.clone());
+        self.integrity.retain(|_, i| !matches!(i, Integrity::Queued | Integrity::Checking));
+        self.hashing = 0;
+        for pass in [
+            &mut self.checksumming,
+            &mut self.measuring,
+            &mut self.scanning_blanks,
+            &mut self.detecting_scan,
+            &mut self.detecting_crop,
+            &mut self.measuring_quality,
+            &mut self.benchmarking,
+        ] {
+            pass.clear();
+        }
+        self.show_notification(format!("Cancelled {} file(s)", running));
+    }
+
+    fn open_raw_output(&mut self) {
+        let Some(path) = self.selected_file().map(|file| file.path.clone()) else {
+            self.show_notification("No file selected".to_string());
+            return;
+        };
+        self.json_tree = JsonTree::from_raw(&self.raw_store.get(&path));
+        self.raw_search = None;
+        self.mode = AppMode::ShowRawOutput;
+    }
+
+    fn copy_to_clipboard(&mut self, text: String, what: &str) {
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.show_error(format!("Clipboard unavailable: {}", e));
+                    return;
                 }
             }
-            None => 0,
-        };
-        self.table_state.select(Some(i));
+        }
+        let result = self.clipboard.as_mut().map(|c| c.set_text(text));
+        match result {
+            Some(Err(e)) => self.show_error(format!("Copy failed: {}", e)),
+            _ => self.show_notification(format!("Copied {} to clipboard", what)),
+        }
     }
 
-    fn get_filtered_files(&self) -> Vec<&MediaInfo> {
-        if self.active_filters.is_empty() {
-            return self.media_files.iter().collect();
+    /// Copies `text(file)` for the marked files, or the selected one, e.g. their
+    /// paths a line each. `what` names one and several of them for the notification.
+    fn copy_targets(&mut self, what: (&str, &str), text: fn(&MediaInfo) -> String) {
+        let targets = self.target_indices();
+        if targets.is_empty() {
+            return self.show_notification("No file selected".to_string());
         }
+        let texts: Vec<String> = targets.iter().map(|&i| text(&self.media_files[i])).collect();
+        // Summaries run over several lines, so a blank one keeps them apart
+        let separator = if texts.iter().any(|text| text.contains('\n')) { "\n\n" } else { "\n" };
+        let what = match texts.len() {
+            1 => what.0.to_string(),
+            n => format!("{} {}", n, what.1),
+        };
+        self.copy_to_clipboard(texts.join(separator), &what);
+    }
 
-        self.media_files
-            .iter()
-            .filter(|file| {
-                self.active_filters.iter().all(|filter| {
-                    match filter.filter_type {
-                        FilterType::Container => file.container.contains(&filter.value),
-                        FilterType::Codec => file.codec.contains(&filter.value),
-                        FilterType::Resolution => file.resolution.contains(&filter.value),
-                        FilterType::FrameRate => file.frame_rate.contains(&filter.value),
-                        FilterType::Bitrate => file.bitrate.contains(&filter.value),
-                    }
-                })
-            })
-            .collect()
+    /// Copies the Files table as it's filtered and sorted, with the columns it shows,
+    /// as tab-separated values or a Markdown table. Only the marked rows when any are.
+    fn copy_table(&mut self, markdown: bool) {
+        let indices = self.filtered_targets();
+        if indices.is_empty() {
+            return self.show_notification("No files to copy".to_string());
+        }
+        let view = self.view();
+        let columns: Vec<Column> = view.columns.iter().map(|c| c.column()).collect();
+        let profile = self.profile();
+        let mut rows = vec![columns.iter().map(|column| column.header().to_string()).collect::<Vec<_>>()];
+        for &i in &indices {
+            let file = &self.media_files[i];
+            let verdict = profile.map(|profile| profile.check(file).verdict);
+            let duplicate = view.labels.get(&i).map(String::as_str);
+            rows.push(columns.iter().map(|&column| cell_text(self, file, column, verdict, duplicate)).collect());
+        }
+        let text = match markdown {
+            true => export::markdown_table(&rows),
+            false => export::tsv(&rows),
+        };
+        let what = format!("{} row(s) as {}", indices.len(), if markdown { "Markdown" } else { "TSV" });
+        self.copy_to_clipboard(text, &what);
+    }
+
+    fn copy_raw_output(&mut self) {
+        let Some(path) = self.selected_file().map(|file| file.path.clone()) else {
+            self.show_notification("No file selected".to_string());
+            return;
+        };
+        let raw = self.raw_store.get(&path);
+        self.copy_to_clipboard(raw, "ffprobe JSON");
+    }
+
+    /// Plays the selected file, or shows it in the file manager with `reveal`.
+    fn launch_selected(&mut self, reveal: bool) {
+        let Some(path) = self.selected_file().map(|f| f.path.clone()) else {
+            self.show_notification("No file selected".to_string());
+            return;
+        };
+        if reveal && is_url(&path) {
+            self.show_notification("URLs have no folder to show".to_string());
+            return;
+        }
+        let result = if reveal {
+            launch::reveal(&path)
+        } else {
+            launch::open(&self.player, &path)
+        };
+        if let Err(e) = result {
+            self.show_error(format!("Could not open {}: {:#}", path, e));
+        }
+    }
+
+    fn show_notification(&mut self, message: String) {
+        self.notification = Some((message, Instant::now()));
+    }
+
+    /// Notifies of an error and keeps it in the log.
+    fn show_error(&mut self, message: String) {
+        self.log.push(Level::Error, message.clone());
+        self.show_notification(message);
+    }
+
+    /// Opens the log on its latest entry.
+    fn open_log(&mut self) {
+        self.log_state.select(self.log.entries().len().checked_sub(1));
+        self.mode = AppMode::Log;
+    }
+
+    fn copy_log_entry(&mut self) {
+        let Some(entry) = self.log_state.selected().and_then(|i| self.log.entries().get(i)) else {
+            return self.show_notification("Nothing in the log".to_string());
+        };
+        let text = format!("{} {} {}", entry.time, entry.level.label(), entry.message);
+        self.copy_to_clipboard(text, "the log entry");
+    }
+
+    fn clear_log(&mut self) {
+        self.log.clear();
+        self.log_state.select(None);
+    }
+
+    fn clear_all(&mut self) {
+        if self.media_files.is_empty() {
+            return;
+        }
+        let rows = (0..self.media_files.len()).rev().map(|index| self.take_row(index)).collect::<Vec<_>>();
+        let filters = std::mem::take(&mut self.active_filters);
+        self.invalidate_view();
+        let removal = Removal { rows: rows.into_iter().rev().collect(), filters: Some(filters) };
+        self.push_undo(UndoStep::Removal(removal));
+        self.table_state.select(Some(0));
+        self.show_notification(format!("All files cleared ({} to undo)", self.keymap.label(Action::Undo)));
+    }
+
+    /// Does what `action` is bound to; `true` when it quits.
+    fn run_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Quit => return Ok(true),
+            Action::AddFile => self.open_add_file(),
+            Action::Projects => self.open_projects(),
+            Action::Columns => self.open_columns(),
+            Action::Query => self.open_query(),
+            Action::Search => self.open_search(),
+            Action::RawOutput => self.open_raw_output(),
+            Action::Help => self.mode = AppMode::Help,
+            Action::Palette => self.open_palette(),
+            Action::Clear => self.clear_all(),
+            Action::Undo => self.undo(),
+            Action::Rename => self.open_rename(),
+            Action::Organize => self.open_organize(),
+            Action::Trash => self.open_delete(),
+            Action::Remove if self.selected_tab == 1 => self.delete_selected_preset(),
+            Action::Remove => self.remove_selected(),
+            Action::SavePreset => self.open_save_preset(),
+            Action::CycleProfile => self.cycle_profile(),
+            Action::Fixes => self.open_fixes(),
+            Action::QueueFixes => self.queue_fixes_for_targets(),
+            Action::Transcodes => self.mode = AppMode::Transcodes,
+            Action::Duplicates => self.open_duplicates(),
+            Action::Similar => self.find_similar(),
+            Action::Compare => self.open_compare(),
+            Action::Bitrate => self.open_packet_view(AppMode::Bitrate),
+            Action::Gop => self.open_packet_view(AppMode::Gop),
+            Action::Chapters => self.open_chapters(),
+            Action::EditTags => self.open_tag_editor(),
+            Action::Note => self.open_note_editor(),
+            Action::Accept => self.flag_targets(Flag::Accepted),
+            Action::Reject => self.flag_targets(Flag::Rejected),
+            Action::Verify => self.verify_targets(),
+            Action::Checksum => self.checksum_targets(),
+            Action::Report => self.write_report(),
+            Action::Log => self.open_log(),
+            Action::Loudness => self.measure_loudness_targets(),
+            Action::Reference => self.toggle_reference(),
+            Action::Quality => self.measure_quality_targets(),
+            Action::Benchmark => self.benchmark_selected(),
+            Action::Cancel => self.cancel_jobs(),
+            Action::Blanks => self.detect_blanks_targets(),
+            Action::Scan => self.detect_scan_targets(),
+            Action::Crop => self.detect_crop_targets(),
+            Action::Reanalyze => self.reanalyze_targets()?,
+            Action::CycleAnalyzer => self.cycle_analyzer(),
+            Action::CopyPath => self.copy_targets(("path", "paths"), |f| f.path.clone()),
+            Action::CopyMetadata => self.copy_targets(("metadata", "summaries"), export::summary),
+            Action::CopyRaw => self.copy_raw_output(),
+            Action::CopyTable => self.copy_table(false),
+            Action::CopyTableMarkdown => self.copy_table(true),
+            Action::Open => self.launch_selected(false),
+            Action::Reveal => self.launch_selected(true),
+            Action::Sort => self.cycle_sort(),
+            Action::GroupBy => self.cycle_group_by(),
+            Action::Fold if self.selected_tab == 3 => self.toggle_tree_folder(),
+            Action::Fold => self.toggle_group(),
+            Action::Details => self.show_details = !self.show_details,
+            Action::Thumbnail => self.show_thumbnail = !self.show_thumbnail,
+            Action::ReverseSort => self.toggle_sort_direction(),
+            Action::Next if self.selected_tab == 3 => self.jump_tree(Jump::Down(1)),
+            Action::Previous if self.selected_tab == 3 => self.jump_tree(Jump::Up(1)),
+            Action::Mark if self.selected_tab == 3 => self.mark_tree_row(),
+            Action::Next if self.selected_tab == 2 => self.jump_estimate(Jump::Down(1)),
+            Action::Previous if self.selected_tab == 2 => self.jump_estimate(Jump::Up(1)),
+            Action::Next if self.selected_tab == 1 => self.next_filter_option(),
+            Action::Previous if self.selected_tab == 1 => self.previous_filter_option(),
+            Action::Mark if self.selected_tab == 1 => self.toggle_selected_filter(false),
+            Action::ExcludeFilter if self.selected_tab == 1 => self.toggle_selected_filter(true),
+            Action::ExcludeFilter => {}
+            Action::Mark => self.toggle_mark(),
+            Action::Next => self.next_file(),
+            Action::Previous => self.previous_file(),
+            Action::NextTab => self.selected_tab = (self.selected_tab + 1) % TABS.len(),
+        }
+        Ok(false)
+    }
+
+    /// Every action, preset, sort column and project, for the command palette.
+    fn open_palette(&mut self) {
+        let mut entries: Vec<Entry<Command>> = Action::ALL
+            .into_iter()
+            .filter(|&action| action != Action::Palette)
+            .map(|action| Entry {
+                command: Command::Action(action),
+                name: action.description().to_string(),
+                keys: self.keymap.label(action),
+            })
+            .collect();
+        entries.extend(self.presets.iter().enumerate().map(|(i, preset)| Entry {
+            command: Command::Preset(i),
+            name: format!("Apply preset: {}", preset.name),
+            keys: if i < 9 { (i + 1).to_string() } else { String::new() },
+        }));
+        let mut sort_key = SortKey::None.next();
+        while sort_key != SortKey::None {
+            let name = format!("Sort by {}", sort_key.label());
+            entries.push(Entry { command: Command::Sort(sort_key), name, keys: String::new() });
+            sort_key = sort_key.next();
+        }
+        let mut group_by = GroupBy::None.next();
+        while group_by != GroupBy::None {
+            let name = format!("Group by {}", group_by.label());
+            entries.push(Entry { command: Command::Group(group_by), name, keys: String::new() });
+            group_by = group_by.next();
+        }
+        let name = "Ungroup rows".to_string();
+        entries.push(Entry { command: Command::Group(GroupBy::None), name, keys: String::new() });
+        for stars in 1..=5 {
+            let name = format!("Rate {}", notes::format_stars(stars));
+            entries.push(Entry { command: Command::Rate(Some(stars)), name, keys: format!("Alt+{}", stars) });
+        }
+        let name = "Clear the rating".to_string();
+        entries.push(Entry { command: Command::Rate(None), name, keys: "Alt+0".to_string() });
+        entries.push(Entry { command: Command::ClearFilters, name: "Clear all filters".to_string(), keys: String::new() });
+        entries.extend(project::names().into_iter().map(|name| Entry {
+            name: format!("Open project: {}", name),
+            command: Command::Project(name),
+            keys: String::new(),
+        }));
+        entries.extend(self.templates.iter().enumerate().map(|(i, (name, _))| Entry {
+            command: Command::Template(i),
+            name: format!("Copy as {}", name),
+            keys: String::new(),
+        }));
+        self.palette = Some(CommandPalette::new(entries));
+        self.input.reset();
+        self.mode = AppMode::Palette;
+    }
+
+    /// Runs the selected palette entry; `true` when it quits.
+    fn run_palette(&mut self) -> Result<bool> {
+        let palette = self.palette.take();
+        self.input.reset();
+        self.mode = AppMode::Normal;
+        let Some(entry) = palette.as_ref().and_then(CommandPalette::selected) else {
+            return Ok(false);
+        };
+        match &entry.command {
+            Command::Action(action) => return self.run_action(*action),
+            Command::Preset(index) => self.apply_preset(*index),
+            Command::Sort(sort_key) => {
+                self.sort_key = *sort_key;
+                self.invalidate_view();
+                self.show_notification(format!("Sorted by {}", sort_key.label()));
+            }
+            Command::Group(group_by) => self.set_group_by(*group_by),
+            Command::Rate(rating) => self.rate_targets(*rating),
+            Command::ClearFilters => {
+                self.active_filters.clear();
+                self.invalidate_view();
+                self.clamp_selection();
+                self.show_notification("Filters cleared".to_string());
+            }
+            Command::Project(name) => self.switch_project(name)?,
+            Command::Template(index) => self.copy_template(*index),
+        }
+        Ok(false)
+    }
+
+    /// Copies the files as the table shows them, filtered and sorted, through a template.
+    fn copy_template(&mut self, index: usize) {
+        let Some((name, template)) = self.templates.get(index) else {
+            return;
+        };
+        let files: Vec<&MediaInfo> = self.filtered_indices().iter().map(|&i| &self.media_files[i]).collect();
+        let text = template.render(&files);
+        let what = format!("{} file(s) as {}", files.len(), name);
+        self.copy_to_clipboard(text, &what);
+    }
+
+    /// Lists the shown columns in order, then the hidden ones.
+    fn open_columns(&mut self) {
+        let shown = self.columns.iter().map(|&spec| (spec, true));
+        let hidden = Column::ALL
+            .into_iter()
+            .filter(|&column| !self.columns.iter().any(|spec| spec.column() == column))
+            .map(|column| (ColumnSpec::Plain(column), false));
+        self.column_picker = shown.chain(hidden).collect();
+        self.column_list_state.select(Some(0));
+        self.mode = AppMode::Columns;
+    }
+
+    fn move_column_selection(&mut self, down: bool) {
+        let len = self.column_picker.len();
+        let current = self.column_list_state.selected().unwrap_or(0);
+        let next = if down { (current + 1) % len } else { (current + len - 1) % len };
+        self.column_list_state.select(Some(next));
+    }
+
+    /// Swaps the selected column with the one above or below it, keeping it selected.
+    fn move_selected_column(&mut self, down: bool) {
+        let current = self.column_list_state.selected().unwrap_or(0);
+        let target = match down {
+            true if current + 1 < self.column_picker.len() => current + 1,
+            false if current > 0 => current - 1,
+            _ => return,
+        };
+        self.column_picker.swap(current, target);
+        self.column_list_state.select(Some(target));
+    }
+
+    fn toggle_selected_column(&mut self) {
+        if let Some((_, shown)) = self.column_list_state.selected().and_then(|i| self.column_picker.get_mut(i)) {
+            *shown = !*shown;
+        }
+    }
+
+    /// Widens or narrows the selected column; `None` puts its default width back.
+    fn resize_selected_column(&mut self, delta: Option<i16>) {
+        if let Some((spec, _)) = self.column_list_state.selected().and_then(|i| self.column_picker.get_mut(i)) {
+            *spec = match delta {
+                Some(delta) => spec.resized(delta),
+                None => spec.with_width(None),
+            };
+        }
+    }
+
+    /// Shows the columns the picker has checked and writes them to the config.
+    fn save_columns(&mut self) {
+        let columns: Vec<ColumnSpec> =
+            self.column_picker.iter().filter(|(_, shown)| *shown).map(|&(spec, _)| spec).collect();
+        if columns.is_empty() {
+            self.show_notification("At least one column has to show".to_string());
+            return;
+        }
+        self.columns = columns;
+        self.column_picker.clear();
+        self.mode = AppMode::Normal;
+        match config::save_columns(self.config_path.as_deref(), &self.columns) {
+            Ok(_) => self.show_notification(format!("Saved {} column(s)", self.columns.len())),
+            Err(e) => self.show_error(format!("Could not save columns: {:#}", e)),
+        }
+    }
+
+    fn open_projects(&mut self) {
+        self.project_names = project::names();
+        // Not saved until it's left or the app quits
+        if let Some(name) = self.project.as_ref().filter(|name| !self.project_names.contains(name)) {
+            self.project_names.push(name.clone());
+            self.project_names.sort_by_key(|name| name.to_lowercase());
+        }
+        let current = self.project.as_ref().and_then(|name| self.project_names.iter().position(|n| n == name));
+        self.project_list_state.select(current.or((!self.project_names.is_empty()).then_some(0)));
+        self.input.reset();
+        self.mode = AppMode::Projects;
+    }
+
+    fn move_project_selection(&mut self, down: bool) {
+        let len = self.project_names.len();
+        if len == 0 {
+            return;
+        }
+        let current = self.project_list_state.selected().unwrap_or(0);
+        let next = if down { (current + 1) % len } else { (current + len - 1) % len };
+        self.project_list_state.select(Some(next));
+    }
+
+    /// Opens the typed project name, or the selected project when nothing is typed.
+    fn confirm_project(&mut self) -> Result<()> {
+        let typed = self.input.value().trim().to_string();
+        self.input.reset();
+        self.mode = AppMode::Normal;
+        let selected = self.project_list_state.selected().and_then(|i| self.project_names.get(i)).cloned();
+        match (typed.is_empty(), selected) {
+            (false, _) => self.switch_project(&typed),
+            (true, Some(name)) => self.switch_project(&name),
+            (true, None) => Ok(()),
+        }
+    }
+
+    /// Writes the open project's files and filters, if a project is open.
+    fn save_project(&self) -> Result<()> {
+        let Some(name) = &self.project else {
+            return Ok(());
+        };
+        let mut paths: Vec<String> = self.media_files.iter().map(|f| f.path.clone()).collect();
+        let mut pending: Vec<&String> = self.project_pending.iter().collect();
+        pending.sort();
+        paths.extend(pending.into_iter().cloned());
+        project::save(name, &Project { paths, query: query::to_query(&self.active_filters) })
+    }
+
+    /// Saves the open project and replaces the list with the files and filters of
+    /// `name`, which is created when it doesn't exist yet.
+    fn switch_project(&mut self, name: &str) -> Result<()> {
+        if self.project.as_deref() == Some(name) {
+            return Ok(());
+        }
+        let loaded = match project::load(name) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.show_error(format!("Could not open project: {:#}", e));
+                return Ok(());
+            }
+        };
+        if let Err(e) = self.save_project() {
+            self.show_error(format!("Could not save project: {:#}", e));
+            return Ok(());
+        }
+        // Nothing of the last project may come back into this one, undo included
+        while !self.media_files.is_empty() {
+            let row = self.take_row(self.media_files.len() - 1);
+            row.raw.into_iter().for_each(RawStore::discard);
+        }
+        self.undo.drain(..).for_each(UndoStep::discard);
+        self.project_pending.clear();
+        self.generation += 1;
+        self.active_filters = query::parse(&loaded.query).unwrap_or_default();
+        self.invalidate_view();
+        self.project = Some(name.to_string());
+        let mut missing = 0;
+        for path in loaded.paths {
+            if !is_url(&path) && !Path::new(&path).exists() {
+                missing += 1;
+                continue;
+            }
+            self.project_pending.insert(path.clone());
+            self.queue_analysis(path)?;
+        }
+        self.table_state.select(Some(0));
+        let mut message = format!("Project '{}': {} file(s)", name, self.project_pending.len());
+        if missing > 0 {
+            message.push_str(&format!(", {} no longer found", missing));
+        }
+        self.show_notification(message);
+        Ok(())
+    }
+
+    /// Takes the row at `index` out of the list along with everything kept about it.
+    fn take_row(&mut self, index: usize) -> RemovedRow {
+        let file = self.media_files.remove(index);
+        self.invalidate_view();
+        self.thumbnails.remove(&file.path);
+        self.packets.remove(&file.path);
+        RemovedRow {
+            index,
+            marked: self.marked.remove(&file.path),
+            integrity: self.integrity.remove(&file.path),
+            cross_check: self.cross_checks.remove(&file.path),
+            raw: self.raw_store.take(&file.path),
+            frame_hash: self.frame_hashes.remove(&file.path),
+            file,
+        }
+    }
+
+    fn push_undo(&mut self, step: UndoStep) {
+        self.undo.push(step);
+        if self.undo.len() > UNDO_DEPTH {
+            self.undo.remove(0).discard();
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.undo.pop() {
+            Some(UndoStep::Removal(removal)) => self.undo_removal(removal),
+            Some(UndoStep::Rename(renamed)) => {
+                let (moved, errors) = rename::undo(&renamed);
+                self.moved_files(&moved);
+                let mut message = format!("Put {} file(s) back", moved.len());
+                if let Some(error) = errors.first() {
+                    message.push_str(&format!(", {} failed: {}", errors.len(), error));
+                }
+                for error in errors {
+                    self.log.push(Level::Error, error);
+                }
+                self.show_notification(message);
+            }
+            None => self.show_notification("Nothing to undo".to_string()),
+        }
+    }
+
+    /// Puts back the rows of a `d` or `c`, where they were. Files added again since
+    /// then keep their new row.
+    fn undo_removal(&mut self, removal: Removal) {
+        let mut restored = 0;
+        for row in removal.rows {
+            if self.media_files.iter().any(|f| f.path == row.file.path) {
+                continue;
+            }
+            let path = row.file.path.clone();
+            if row.marked {
+                self.marked.insert(path.clone());
+            }
+            if let Some(integrity) = row.integrity {
+                self.integrity.insert(path.clone(), integrity);
+            }
+            if let Some(cross_check) = row.cross_check {
+                self.cross_checks.insert(path.clone(), cross_check);
+            }
+            if let Some(raw) = row.raw {
+                self.raw_store.restore(&path, raw);
+            }
+            if let Some(hash) = row.frame_hash {
+                self.frame_hashes.insert(path, hash);
+            }
+            self.media_files.insert(row.index.min(self.media_files.len()), row.file);
+            restored += 1;
+        }
+        if let Some(filters) = removal.filters {
+            if self.active_filters.is_empty() {
+                self.active_filters = filters;
+            }
+        }
+        self.invalidate_view();
+        self.clamp_selection();
+        self.show_notification(format!("Restored {} file(s)", restored));
+    }
+
+    fn next_file(&mut self) {
+        let len = self.view().rows.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.selected() {
+            Some(i) => {
+                if i >= len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    fn previous_file(&mut self) {
+        let len = self.view().rows.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    fn get_filtered_files(&self) -> Vec<&MediaInfo> {
+        self.filtered_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .collect()
+    }
+
+    /// The Files table as it stands, built first if something changed since.
+    fn view(&self) -> Rc<View> {
+        if let Some(view) = self.view.borrow().as_ref() {
+            return Rc::clone(view);
+        }
+        let filtered = self.sorted_matches();
+        let rows = self.group_rows(filtered.clone());
+        let duplicates = self.find_duplicates();
+        let labels = duplicate_labels(&duplicates);
+        let columns = self.shown_columns(!duplicates.is_empty());
+        let view = Rc::new(View { filtered, rows, duplicates, labels, columns });
+        *self.view.borrow_mut() = Some(Rc::clone(&view));
+        view
+    }
+
+    /// Has the next `view` built afresh, after a change to what the table shows.
+    fn invalidate_view(&self) {
+        self.view.take();
+    }
+
+    /// Indices into `media_files` in the order the table displays them.
+    fn filtered_indices(&self) -> Vec<usize> {
+        self.view().filtered.clone()
+    }
+
+    fn sorted_matches(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .media_files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| matches_groups(&self.active_filters, file))
+            .filter(|(_, file)| fuzzy_match(&file.path, &self.search))
+            .map(|(i, _)| i)
+            .collect();
+
+        if self.sort_key != SortKey::None {
+            indices.sort_by(|&a, &b| {
+                let ordering = self.sort_key.compare(&self.media_files[a], &self.media_files[b]);
+                if self.sort_descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        indices
+    }
+
+    /// The table's lines: the filtered files, or with grouping on, each group's
+    /// header followed by its files unless it's folded.
+    fn group_rows(&self, indices: Vec<usize>) -> Vec<TableRow> {
+        if self.group_by == GroupBy::None {
+            return indices.into_iter().map(TableRow::File).collect();
+        }
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for i in indices {
+            let name = self.group_by.group(&self.media_files[i]);
+            match positions.get(&name) {
+                Some(&position) => groups[position].1.push(i),
+                None => {
+                    positions.insert(name.clone(), groups.len());
+                    groups.push((name, vec![i]));
+                }
+            }
+        }
+        groups.sort_by(|(a, _), (b, _)| self.group_by.compare(a, b));
+        let mut rows = Vec::new();
+        for (name, files) in groups {
+            let collapsed = self.collapsed_groups.contains(&name);
+            let shown = if collapsed { Vec::new() } else { files.clone() };
+            rows.push(TableRow::Group { name, files, collapsed });
+            rows.extend(shown.into_iter().map(TableRow::File));
+        }
+        rows
+    }
+
+    /// The table row showing the file at `index` of `media_files`, if it's shown.
+    fn row_of(&self, index: usize) -> Option<usize> {
+        self.view().rows.iter().position(|row| matches!(row, TableRow::File(i) if *i == index))
+    }
+
+    fn cycle_group_by(&mut self) {
+        self.set_group_by(self.group_by.next());
+    }
+
+    fn set_group_by(&mut self, group_by: GroupBy) {
+        let selected = self.selected_index();
+        self.group_by = group_by;
+        self.collapsed_groups.clear();
+        self.invalidate_view();
+        // The cursor stays on the file it was on
+        self.table_state.select(Some(selected.and_then(|i| self.row_of(i)).unwrap_or(0)));
+        self.show_notification(format!("Grouped by {}", self.group_by.label()));
+    }
+
+    /// Folds or unfolds the selected group, or the group of the selected file,
+    /// leaving the cursor on its header.
+    fn toggle_group(&mut self) {
+        if self.group_by == GroupBy::None {
+            let key = self.keymap.label(Action::GroupBy);
+            self.show_notification(format!("Rows aren't grouped, '{}' groups them", key));
+            return;
+        }
+        let view = self.view();
+        let rows = &view.rows;
+        let Some(current) = self.table_state.selected().filter(|&i| i < rows.len()) else {
+            return;
+        };
+        // The nearest header at or above the cursor
+        let Some((row, name)) = rows[..=current].iter().enumerate().rev().find_map(|(row, r)| match r {
+            TableRow::Group { name, .. } => Some((row, name.clone())),
+            TableRow::File(_) => None,
+        }) else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&name) {
+            self.collapsed_groups.insert(name);
+        }
+        self.invalidate_view();
+        self.table_state.select(Some(row));
+    }
+
+    /// The file under the cursor, resolved against the filtered and sorted view.
+    fn selected_file(&self) -> Option<&MediaInfo> {
+        self.selected_index().map(|i| &self.media_files[i])
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let i = self.table_state.selected()?;
+        let view = self.view();
+        match view.rows.get(i)? {
+            TableRow::File(index) => Some(*index),
+            TableRow::Group { .. } => None,
+        }
+    }
+
+    /// Files a batch operation should act on: the marked set if any, else the
+    /// selection, which on a group's header is the whole group.
+    fn target_indices(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            let selected = self.table_state.selected().and_then(|i| self.view().rows.get(i).cloned());
+            return match selected {
+                Some(TableRow::File(i)) => vec![i],
+                // In `media_files` order, like the marked set
+                Some(TableRow::Group { mut files, .. }) => {
+                    files.sort_unstable();
+                    files
+                }
+                None => Vec::new(),
+            };
+        }
+        self.media_files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| self.marked.contains(&file.path))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The Files table's columns that are worth showing: the ones that describe a
+    /// pass or a kind of file appear once some file has their data.
+    fn shown_columns(&self, has_duplicates: bool) -> Vec<ColumnSpec> {
+        let files = &self.media_files;
+        let available = |column: Column| match column {
+            Column::Qc => self.profile().is_some(),
+            Column::Decode => !self.integrity.is_empty(),
+            Column::Dup => has_duplicates,
+            Column::Checksum => !self.checksumming.is_empty() || files.iter().any(|f| f.checksum.is_some()),
+            Column::Lufs => !self.measuring.is_empty() || files.iter().any(|f| f.loudness.is_some()),
+            Column::Quality => {
+                self.reference.is_some()
+                    || !self.measuring_quality.is_empty()
+                    || files.iter().any(|f| f.quality.is_some())
+            }
+            // Not worth a column on a machine without any
+            Column::HwDecode => self.hw_decoders.as_ref().is_some_and(|apis| !apis.is_empty()),
+            Column::Scan => {
+                !self.detecting_scan.is_empty()
+                    || files.iter().any(|f| f.scan_detected || interlace::is_interlaced(&f.scan))
+            }
+            Column::Picture => !self.detecting_crop.is_empty() || files.iter().any(|f| f.crop.is_some()),
+            Column::Timecode => files.iter().any(|f| f.timecode.is_some()),
+            // Untagged audio isn't worth a column
+            Column::Languages => files.iter().any(|f| f.audio_languages.iter().any(|l| l != "und")),
+            Column::Created => files.iter().any(|f| f.creation_time.is_some()),
+            Column::Audio => files.iter().any(|f| f.audio_only),
+            Column::Mxf => files.iter().any(|f| f.mxf.is_some()),
+            Column::Tags => files.iter().any(|f| f.note.as_ref().is_some_and(|n| !n.tags.is_empty())),
+            Column::Note => files.iter().any(|f| f.note.as_ref().is_some_and(|n| !n.text.is_empty())),
+            Column::Rating => files.iter().any(|f| f.note.as_ref().is_some_and(|n| n.rating.is_some())),
+            Column::Flag => files.iter().any(|f| f.note.as_ref().is_some_and(|n| n.flag.is_some())),
+            _ => true,
+        };
+        self.columns.iter().copied().filter(|c| available(c.column())).collect()
+    }
+
+    /// Byte-identical groups, then the visually similar ones found by the similarity pass.
+    fn duplicate_groups(&self) -> Vec<(Likeness, Vec<usize>)> {
+        self.view().duplicates.clone()
+    }
+
+    fn find_duplicates(&self) -> Vec<(Likeness, Vec<usize>)> {
+        let identical = dedupe::groups(&self.media_files).into_iter().map(|g| (Likeness::Identical, g));
+        let similar = phash::groups(&self.media_files, &self.frame_hashes)
+            .into_iter()
+            .map(|g| (Likeness::Similar, g));
+        identical.chain(similar).collect()
+    }
+
+    /// Lines of the Duplicates view: `None` heads each group, followed by its files.
+    fn duplicate_rows(&self) -> Vec<Option<usize>> {
+        self.duplicate_groups()
+            .into_iter()
+            .flat_map(|(_, group)| std::iter::once(None).chain(group.into_iter().map(Some)))
+            .collect()
+    }
+
+    /// Hashes sampled frames of every file not yet hashed, to find re-encodes of the same clip.
+    fn find_similar(&mut self) {
+        let pending: Vec<(String, Option<f64>)> = self
+            .media_files
+            .iter()
+            .filter(|file| !self.frame_hashes.contains_key(&file.path))
+            .map(|file| (file.path.clone(), file.duration))
+            .collect();
+        if pending.is_empty() {
+            self.open_duplicates();
+            return;
+        }
+        self.hashing += pending.len();
+        self.show_notification(format!("Hashing frames of {} file(s)", pending.len()));
+        for (path, duration) in pending {
+            self.frame_hasher.queue(path, duration);
+        }
+    }
+
+    fn poll_frame_hasher(&mut self) {
+        let results = self.frame_hasher.poll();
+        if results.is_empty() {
+            return;
+        }
+        self.invalidate_view();
+        for (path, result) in results {
+            self.hashing = self.hashing.saturating_sub(1);
+            match result {
+                // Files removed while being hashed stay removed
+                Ok(hashes) if self.media_files.iter().any(|f| f.path == path) => {
+                    self.frame_hashes.insert(path, hashes);
+                }
+                Ok(_) => {}
+                Err(e) => self.show_error(format!("Could not hash {}: {}", path, e)),
+            }
+        }
+        if self.hashing == 0 {
+            let view = self.view();
+            let similar = view.duplicates.iter().filter(|(likeness, _)| *likeness == Likeness::Similar).count();
+            let key = self.keymap.label(Action::Duplicates);
+            self.show_notification(format!("Found {} group(s) of similar clips, '{}' to view", similar, key));
+        }
+    }
+
+    fn open_duplicates(&mut self) {
+        if self.duplicate_rows().is_empty() {
+            let key = self.keymap.label(Action::Similar);
+            self.show_notification(format!("No duplicate files found, '{}' looks for similar clips", key));
+            return;
+        }
+        // The first row is a group header
+        self.duplicates_state.select(Some(1));
+        self.mode = AppMode::Duplicates;
+    }
+
+    /// Moves between files, skipping group headers.
+    fn move_duplicate_selection(&mut self, down: bool) {
+        let rows = self.duplicate_rows();
+        let mut i = self.duplicates_state.selected().unwrap_or(0);
+        loop {
+            i = match down {
+                true if i + 1 < rows.len() => i + 1,
+                false if i > 0 => i - 1,
+                _ => return,
+            };
+            if rows[i].is_some() {
+                self.duplicates_state.select(Some(i));
+                return;
+            }
+        }
+    }
+
+    fn selected_duplicate(&self) -> Option<usize> {
+        let i = self.duplicates_state.selected()?;
+        self.duplicate_rows().get(i).copied().flatten()
+    }
+
+    /// Marks the highlighted copy, so the spares can be removed in one go.
+    fn toggle_duplicate_mark(&mut self) {
+        let Some(i) = self.selected_duplicate() else {
+            return;
+        };
+        let path = self.media_files[i].path.clone();
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.move_duplicate_selection(true);
+    }
+
+    /// Returns to the file list with the highlighted copy selected.
+    fn show_duplicate_in_table(&mut self) {
+        if let Some(i) = self.selected_duplicate() {
+            self.show_in_table(i);
+        }
+    }
+
+    /// Switches to the Files tab with the file at `index` selected.
+    fn show_in_table(&mut self, i: usize) {
+        if self.group_by != GroupBy::None {
+            self.collapsed_groups.remove(&self.group_by.group(&self.media_files[i]));
+            self.invalidate_view();
+        }
+        let Some(row) = self.row_of(i) else {
+            self.show_notification("That file is hidden by the active filters or search".to_string());
+            return;
+        };
+        self.table_state.select(Some(row));
+        self.selected_tab = 0;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Shows the selected file's bitrate graph or GOP report, probing its packets the first time.
+    fn open_packet_view(&mut self, mode: AppMode) {
+        let Some(path) = self.selected_file().map(|f| f.path.clone()) else {
+            return;
+        };
+        if !self.packets.contains_key(&path) && self.packets_pending.insert(path.clone()) {
+            self.packet_prober.queue(path.clone());
+        }
+        self.packet_file = Some(path);
+        self.mode = mode;
+    }
+
+    fn poll_packets(&mut self) {
+        for (path, result) in self.packet_prober.poll() {
+            self.packets_pending.remove(&path);
+            if self.media_files.iter().any(|f| f.path == path) {
+                self.packets.insert(path, result);
+            }
+        }
+    }
+
+    /// Marks the selected file, or on a group's header, every file in the group
+    /// (unmarking them when all already are).
+    fn toggle_mark(&mut self) {
+        let selected = self.table_state.selected().and_then(|i| self.view().rows.get(i).cloned());
+        if let Some(TableRow::Group { files, .. }) = selected {
+            self.toggle_marks(&files);
+            self.next_file();
+            return;
+        }
+        let Some(path) = self.selected_file().map(|f| f.path.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.next_file();
+    }
+
+    /// Marks every file at `indices`, or unmarks them when all already are.
+    fn toggle_marks(&mut self, indices: &[usize]) {
+        let paths: Vec<String> = indices.iter().map(|&i| self.media_files[i].path.clone()).collect();
+        if paths.iter().all(|path| self.marked.contains(path)) {
+            paths.iter().for_each(|path| {
+                self.marked.remove(path);
+            });
+        } else {
+            self.marked.extend(paths);
+        }
+    }
+
+    /// The Tree tab's rows, over the same filtered files as the table.
+    fn tree_rows(&self) -> Vec<TreeRow> {
+        self.dir_tree.rows(&self.media_files, &self.filtered_indices())
+    }
+
+    fn selected_tree_row(&self) -> Option<TreeRow> {
+        let i = self.dir_tree.list_state.selected()?;
+        self.tree_rows().into_iter().nth(i)
+    }
+
+    fn jump_estimate(&mut self, jump: Jump) {
+        let len = Target::all(self.estimate_mbps).len();
+        let current = self.estimate_table.selected().unwrap_or(0);
+        self.estimate_table.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    /// Steps the H.264 and H.265 bitrate of the estimate by 5 Mbps, or 1 below 5.
+    fn change_estimate_bitrate(&mut self, up: bool) {
+        self.estimate_mbps = match up {
+            true if self.estimate_mbps >= 5.0 => self.estimate_mbps + 5.0,
+            true => self.estimate_mbps + 1.0,
+            false if self.estimate_mbps > 5.0 => self.estimate_mbps - 5.0,
+            false => (self.estimate_mbps - 1.0).max(1.0),
+        };
+    }
+
+    fn jump_tree(&mut self, jump: Jump) {
+        let len = self.tree_rows().len();
+        let current = self.dir_tree.list_state.selected().unwrap_or(0);
+        self.dir_tree.list_state.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    /// Folds or unfolds the selected folder; on a file, shows it in the Files tab.
+    fn open_tree_row(&mut self) {
+        match self.selected_tree_row() {
+            Some(TreeRow { key, entry: tree::Entry::Folder(_), .. }) => self.dir_tree.toggle(&key),
+            Some(TreeRow { entry: tree::Entry::File(i), .. }) => self.show_in_table(i),
+            None => {}
+        }
+    }
+
+    /// Unfolds the selected folder, or folds it, moving up to the folder above
+    /// when there's nothing left to fold: a file, or a folded folder.
+    fn fold_tree_row(&mut self, open: bool) {
+        let rows = self.tree_rows();
+        let Some(current) = self.dir_tree.list_state.selected().filter(|&i| i < rows.len()) else {
+            return;
+        };
+        let row = &rows[current];
+        let folder = matches!(row.entry, tree::Entry::Folder(_));
+        if open || (folder && self.dir_tree.collapse(&row.key)) {
+            if open {
+                self.dir_tree.expand(&row.key);
+            }
+            return;
+        }
+        if let Some(parent) = rows[..current].iter().rposition(|r| r.depth < row.depth) {
+            self.dir_tree.list_state.select(Some(parent));
+        }
+    }
+
+    /// Folds the selected folder or the one the selected file is in, or unfolds a folded one.
+    fn toggle_tree_folder(&mut self) {
+        match self.selected_tree_row() {
+            Some(TreeRow { key, entry: tree::Entry::Folder(_), .. }) => self.dir_tree.toggle(&key),
+            Some(_) => self.fold_tree_row(false),
+            None => {}
+        }
+    }
+
+    /// Marks the selected file, or every file under the selected folder.
+    fn mark_tree_row(&mut self) {
+        let files = match self.selected_tree_row() {
+            Some(TreeRow { entry: tree::Entry::Folder(summary), .. }) => summary.files,
+            Some(TreeRow { entry: tree::Entry::File(i), .. }) => vec![i],
+            None => return,
+        };
+        self.toggle_marks(&files);
+        self.jump_tree(Jump::Down(1));
+    }
+
+    fn remove_selected(&mut self) {
+        let targets = self.target_indices();
+        if targets.is_empty() {
+            return;
+        }
+        let rows: Vec<RemovedRow> = targets.iter().rev().map(|&index| self.take_row(index)).collect();
+        self.push_undo(UndoStep::Removal(Removal { rows: rows.into_iter().rev().collect(), filters: None }));
+        self.clamp_selection();
+        let undo = self.keymap.label(Action::Undo);
+        self.show_notification(format!("Removed {} file(s) ({} to undo)", targets.len(), undo));
+    }
+
+    fn clamp_selection(&mut self) {
+        let len = self.view().rows.len();
+        match self.table_state.selected() {
+            Some(i) if i >= len => self.table_state.select(Some(len.saturating_sub(1))),
+            None => self.table_state.select(Some(0)),
+            _ => {}
+        }
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.invalidate_view();
+        self.show_notification(format!("Sorted by {}", self.sort_key.label()));
+    }
+
+    /// Clicks select rows, switch tabs and sort by the clicked header (again to
+    /// reverse the order); the wheel scrolls the table, the Filters tab and the raw view.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        const WHEEL_ROWS: usize = 3;
+        let (x, y) = (mouse.column, mouse.row);
+        match (&self.mode, mouse.kind) {
+            (AppMode::ShowRawOutput, MouseEventKind::ScrollDown) => self.jump_raw_output(Jump::Down(WHEEL_ROWS)),
+            (AppMode::ShowRawOutput, MouseEventKind::ScrollUp) => self.jump_raw_output(Jump::Up(WHEEL_ROWS)),
+            (AppMode::Log, MouseEventKind::ScrollDown) => self.jump_log(Jump::Down(WHEEL_ROWS)),
+            (AppMode::Log, MouseEventKind::ScrollUp) => self.jump_log(Jump::Up(WHEEL_ROWS)),
+            (AppMode::Normal, MouseEventKind::ScrollDown) if self.selected_tab == 1 => {
+                self.jump_filter_options(Jump::Down(WHEEL_ROWS))
+            }
+            (AppMode::Normal, MouseEventKind::ScrollUp) if self.selected_tab == 1 => {
+                self.jump_filter_options(Jump::Up(WHEEL_ROWS))
+            }
+            (AppMode::Normal, MouseEventKind::ScrollDown) if self.selected_tab == 3 => {
+                self.jump_tree(Jump::Down(WHEEL_ROWS))
+            }
+            (AppMode::Normal, MouseEventKind::ScrollUp) if self.selected_tab == 3 => self.jump_tree(Jump::Up(WHEEL_ROWS)),
+            (AppMode::Normal, MouseEventKind::ScrollDown) => self.jump_files(Jump::Down(WHEEL_ROWS)),
+            (AppMode::Normal, MouseEventKind::ScrollUp) => self.jump_files(Jump::Up(WHEEL_ROWS)),
+            (AppMode::Normal, MouseEventKind::Down(MouseButton::Left)) => {
+                let targets = &self.click_targets;
+                if let Some(tab) = targets.tabs.iter().position(|&area| contains(area, x, y)) {
+                    self.selected_tab = tab;
+                } else if let Some(&(_, key)) = targets.headers.iter().find(|(area, _)| contains(*area, x, y)) {
+                    if self.sort_key == key {
+                        self.toggle_sort_direction();
+                    } else {
+                        self.sort_key = key;
+                        self.invalidate_view();
+                        self.show_notification(format!("Sorted by {}", key.label()));
+                    }
+                } else if let Some((area, first)) = targets.tree_rows.filter(|(area, _)| contains(*area, x, y)) {
+                    // A click on a folder folds or unfolds it too
+                    let row = first + (y - area.y) as usize;
+                    if row < self.tree_rows().len() {
+                        self.dir_tree.list_state.select(Some(row));
+                        if let Some(TreeRow { key, entry: tree::Entry::Folder(_), .. }) = self.selected_tree_row() {
+                            self.dir_tree.toggle(&key);
+                        }
+                    }
+                } else if let Some((area, first)) = targets.rows.filter(|(area, _)| contains(*area, x, y)) {
+                    let row = first + (y - area.y) as usize;
+                    let is_group = self.view().rows.get(row).map(|row| matches!(row, TableRow::Group { .. }));
+                    match is_group {
+                        Some(true) => {
+                            self.table_state.select(Some(row));
+                            self.toggle_group();
+                        }
+                        Some(false) => self.table_state.select(Some(row)),
+                        None => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn toggle_sort_direction(&mut self) {
+        self.sort_descending = !self.sort_descending;
+        self.invalidate_view();
+        let direction = if self.sort_descending { "descending" } else { "ascending" };
+        self.show_notification(format!("Sort order: {}", direction));
+    }
+
+    /// Turns a key into a jump, handling the two-key `gg` chord.
+    fn jump_for_key(&mut self, code: KeyCode) -> Option<Jump> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        if code == KeyCode::Char('g') {
+            if pending_g {
+                return Some(Jump::Home);
+            }
+            self.pending_g = true;
+            return None;
+        }
+        Jump::from_key(code)
+    }
+
+    fn jump_files(&mut self, jump: Jump) {
+        let len = self.view().rows.len();
+        let current = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    fn jump_filter_options(&mut self, jump: Jump) {
+        let len = self.filter_row_count();
+        let current = self.filter_list_state.selected().unwrap_or(0);
+        self.filter_list_state.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    fn jump_log(&mut self, jump: Jump) {
+        let len = self.log.entries().len();
+        let current = self.log_state.selected().unwrap_or(0);
+        self.log_state.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    fn jump_raw_output(&mut self, jump: Jump) {
+        let len = self.json_tree.rows().len();
+        let current = self.json_tree.list_state.selected().unwrap_or(0);
+        self.json_tree.list_state.select(Some(jump.apply(current, len, self.page_size)));
+    }
+
+    fn profile(&self) -> Option<&ValidationProfile> {
+        self.active_profile.and_then(|i| self.profiles.get(i))
+    }
+
+    /// Steps through the QC profiles, then turns the QC column off.
+    fn cycle_profile(&mut self) {
+        if self.profiles.is_empty() {
+            self.show_notification("No QC profiles configured".to_string());
+            return;
+        }
+        self.active_profile = match self.active_profile {
+            None => Some(0),
+            Some(i) if i + 1 < self.profiles.len() => Some(i + 1),
+            Some(_) => None,
+        };
+        let message = match self.profile() {
+            Some(profile) => format!("QC profile: {}", profile.name),
+            None => "QC column hidden".to_string(),
+        };
+        self.show_notification(message);
+    }
+
+    /// Asks for the pattern to rename the marked files, or the selection, with.
+    fn open_rename(&mut self) {
+        if self.target_indices().is_empty() {
+            return self.show_notification("No file selected".to_string());
+        }
+        self.input = Input::new(self.rename_pattern.clone());
+        self.mode = AppMode::RenamePattern;
+    }
+
+    /// Shows what the typed pattern would rename the files to, for a confirmation.
+    fn preview_rename(&mut self) {
+        let text = self.input.value().trim().to_string();
+        let pattern = match Template::parse_file_name(&text) {
+            Ok(pattern) => pattern,
+            Err(e) => return self.show_notification(format!("{:#}", e)),
+        };
+        let files: Vec<&MediaInfo> = self.target_indices().iter().map(|&i| &self.media_files[i]).collect();
+        self.renames = rename::plan(&pattern, &files);
+        self.rename_pattern = text;
+        self.input.reset();
+        self.rename_list_state.select(Some(0));
+        self.mode = AppMode::Rename;
+    }
+
+    fn move_rename_selection(&mut self, down: bool) {
+        let len = self.renames.len();
+        let current = self.rename_list_state.selected().unwrap_or(0);
+        let next = if down { (current + 1).min(len.saturating_sub(1)) } else { current.saturating_sub(1) };
+        self.rename_list_state.select(Some(next));
+    }
+
+    fn apply_rename(&mut self) {
+        let renames = std::mem::take(&mut self.renames);
+        self.mode = AppMode::Normal;
+        let (moved, errors) = rename::apply(&renames);
+        self.moved_files(&moved);
+        let skipped = renames.iter().filter(|r| r.skipped.is_some()).count();
+        let mut message = format!("Renamed {} file(s)", moved.len());
+        if skipped > 0 {
+            message.push_str(&format!(", {} skipped", skipped));
+        }
+        if let Some(error) = errors.first() {
+            message.push_str(&format!(", {} failed: {}", errors.len(), error));
+        }
+        for error in errors {
+            self.log.push(Level::Error, error);
+        }
+        if !moved.is_empty() {
+            message.push_str(&format!(" ({} to undo)", self.keymap.label(Action::Undo)));
+            self.push_undo(UndoStep::Rename(moved));
+        }
+        self.show_notification(message);
+    }
+
+    /// Asks before deleting the marked files, or the selection, from disk. Only
+    /// local files can be; streams, remote files and image sequences are left out.
+    fn open_delete(&mut self) {
+        let targets = self.target_indices();
+        if targets.is_empty() {
+            return self.show_notification("No file selected".to_string());
+        }
+        self.deleting = targets
+            .iter()
+            .map(|&i| &self.media_files[i])
+            .filter(|file| !file.path.contains("://") && file.sequence.is_none())
+            .map(|file| file.path.clone())
+            .collect();
+        if self.deleting.is_empty() {
+            return self.show_notification("Only local files can be deleted".to_string());
+        }
+        self.mode = AppMode::ConfirmDelete;
+    }
+
+    /// Deletes the confirmed files, to the trash unless `for_good`, and takes out
+    /// their rows.
+    fn delete_files(&mut self, for_good: bool) {
+        self.mode = AppMode::Normal;
+        let mut errors = Vec::new();
+        let mut deleted = 0;
+        for path in std::mem::take(&mut self.deleting) {
+            let result = if for_good { trash::delete(&path) } else { trash::trash(&path) };
+            if let Err(e) = result {
+                errors.push(format!("{:#}", e));
+                continue;
+            }
+            deleted += 1;
+            if let Some(index) = self.media_files.iter().position(|f| f.path == path) {
+                if let Some(raw) = self.take_row(index).raw {
+                    RawStore::discard(raw);
+                }
+            }
+        }
+        self.clamp_selection();
+        let mut message = if for_good {
+            format!("Deleted {} file(s) for good", deleted)
+        } else {
+            format!("Moved {} file(s) to the trash", deleted)
+        };
+        if let Some(error) = errors.first() {
+            message.push_str(&format!(", {} failed: {}", errors.len(), error));
+        }
+        for error in errors {
+            self.log.push(Level::Error, error);
+        }
+        self.show_notification(message);
+    }
+
+    /// Asks for the folder pattern to copy or move the marked files to, or all the
+    /// filtered ones when none are marked.
+    fn open_organize(&mut self) {
+        if self.organizing.is_some() {
+            return self.show_notification("Files are still being organized".to_string());
+        }
+        if self.filtered_targets().is_empty() {
+            return self.show_notification("No files to organize".to_string());
+        }
+        self.input = Input::new(self.organize_pattern.clone());
+        self.mode = AppMode::OrganizePattern;
+    }
+
+    /// The filtered files in table order, or only the marked ones among them when
+    /// any are: what organizing, reports and table copies act on.
+    fn filtered_targets(&self) -> Vec<usize> {
+        let filtered = self.filtered_indices();
+        let marked: Vec<usize> =
+            filtered.iter().copied().filter(|&i| self.marked.contains(&self.media_files[i].path)).collect();
+        if marked.is_empty() {
+            filtered
+        } else {
+            marked
+        }
+    }
+
+    /// Shows where the typed pattern would put each file, without touching any.
+    fn preview_organize(&mut self) {
+        let text = self.input.value().trim().to_string();
+        let pattern = match organize::Pattern::parse(&text) {
+            Ok(pattern) => pattern,
+            Err(e) => return self.show_notification(format!("{:#}", e)),
+        };
+        let files: Vec<&MediaInfo> = self.filtered_targets().iter().map(|&i| &self.media_files[i]).collect();
+        self.placements = organize::plan(&pattern, &files);
+        self.organize_pattern = text;
+        self.input.reset();
+        self.organize_list_state.select(Some(0));
+        self.mode = AppMode::Organize;
+    }
+
+    fn move_organize_selection(&mut self, down: bool) {
+        let len = self.placements.len();
+        let current = self.organize_list_state.selected().unwrap_or(0);
+        let next = if down { (current + 1).min(len.saturating_sub(1)) } else { current.saturating_sub(1) };
+        self.organize_list_state.select(Some(next));
+    }
+
+    fn start_organize(&mut self, operation: Operation) {
+        let placements = std::mem::take(&mut self.placements);
+        self.mode = AppMode::Normal;
+        let total = placements.iter().filter(|p| p.skipped.is_none()).count();
+        if total == 0 {
+            return self.show_notification("Nothing to organize, every file was skipped".to_string());
+        }
+        self.organize_done = (0, total);
+        self.organize_moved.clear();
+        self.organize_errors.clear();
+        self.organizing = Some((operation, organize::start(placements, operation)));
+    }
+
+    fn poll_organize(&mut self) {
+        let Some((operation, rx)) = &self.organizing else {
+            return;
+        };
+        let operation = *operation;
+        let mut moved = Vec::new();
+        let mut finished = false;
+        loop {
+            match rx.try_recv() {
+                Ok(progress) => {
+                    self.organize_done = (progress.done, progress.total);
+                    match progress.result {
+                        Ok(()) if operation == Operation::Move => moved.push((progress.from, progress.to)),
+                        Ok(()) => {}
+                        Err(e) => self.organize_errors.push(format!("{}: {}", progress.from, e)),
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+        // Rows follow the moved files as they go, not only at the end
+        if !moved.is_empty() {
+            self.moved_files(&moved);
+            self.organize_moved.extend(moved);
+        }
+        if !finished {
+            return;
+        }
+        self.organizing = None;
+        let (done, _) = self.organize_done;
+        let placed = done - self.organize_errors.len();
+        let verb = if operation == Operation::Move { "Moved" } else { "Copied" };
+        let mut message = format!("{} {} file(s) to {}", verb, placed, self.organize_pattern);
+        if let Some(error) = self.organize_errors.first() {
+            message.push_str(&format!(", {} failed: {}", self.organize_errors.len(), error));
+        }
+        for error in std::mem::take(&mut self.organize_errors) {
+            self.log.push(Level::Error, error);
+        }
+        let moved = std::mem::take(&mut self.organize_moved);
+        if !moved.is_empty() {
+            message.push_str(&format!(" ({} to undo)", self.keymap.label(Action::Undo)));
+            self.push_undo(UndoStep::Rename(moved));
+        }
+        self.show_notification(message);
+    }
+
+    /// Follows files renamed on disk, `(from, to)`, in their rows and in everything
+    /// kept about them by path.
+    fn moved_files(&mut self, moved: &[(String, String)]) {
+        fn rekey<V>(map: &mut HashMap<String, V>, from: &str, to: &str) {
+            if let Some(value) = map.remove(from) {
+                map.insert(to.to_string(), value);
+            }
+        }
+        self.invalidate_view();
+        for (from, to) in moved {
+            let Some(file) = self.media_files.iter_mut().find(|f| &f.path == from) else {
+                continue;
+            };
+            let path = Path::new(to);
+            file.name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            file.container = path.extension().unwrap_or_default().to_string_lossy().to_string();
+            file.path = to.clone();
+            if self.marked.remove(from) {
+                self.marked.insert(to.clone());
+            }
+            rekey(&mut self.integrity, from, to);
+            rekey(&mut self.cross_checks, from, to);
+            rekey(&mut self.frame_hashes, from, to);
+            rekey(&mut self.thumbnails, from, to);
+            rekey(&mut self.packets, from, to);
+            if let Some(raw) = self.raw_store.take(from) {
+                self.raw_store.restore(to, raw);
+            }
+            if self.reference.as_ref() == Some(from) {
+                self.reference = Some(to.clone());
+            }
+        }
+        let saved = self.notes.rename(moved).and_then(|()| self.save_project());
+        if let Err(e) = saved {
+            self.show_error(format!("Error saving after the rename: {:#}", e));
+        }
+    }
+
+    fn open_fixes(&mut self) {
+        if self.selected_file().is_none() {
+            self.show_notification("No file selected".to_string());
+            return;
+        }
+        self.fix_list_state.select(Some(0));
+        self.mode = AppMode::Fixes;
+    }
+
+    fn open_chapters(&mut self) {
+        match self.selected_file() {
+            None => self.show_notification("No file selected".to_string()),
+            Some(file) if file.chapters.is_empty() => {
+                let message = format!("{}.{} has no chapters", file.name, file.container);
+                self.show_notification(message);
+            }
+            Some(_) => {
+                self.chapter_state.select(Some(0));
+                self.mode = AppMode::Chapters;
+            }
+        }
+    }
+
+    fn open_compare(&mut self) {
+        let paths: Vec<String> = self
+            .media_files
+            .iter()
+            .filter(|file| self.marked.contains(&file.path))
+            .map(|file| file.path.clone())
+            .collect();
+        if paths.len() < 2 {
+            return self.show_notification("Mark two or more files to compare them".to_string());
+        }
+        self.comparison = Some(Comparison::new(paths));
+        self.mode = AppMode::Compare;
+    }
+
+    fn move_compare_selection(&mut self, forward: bool) {
+        let Some(comparison) = self.comparison.as_ref() else {
+            return;
+        };
+        let len = comparison.rows(&self.media_files).len();
+        let Some(comparison) = self.comparison.as_mut() else {
+            return;
+        };
+        let current = comparison.table_state.selected().unwrap_or(0);
+        let i = if forward {
+            (current + 1).min(len.saturating_sub(1))
+        } else {
+            current.saturating_sub(1)
+        };
+        comparison.table_state.select(Some(i));
+    }
+
+    fn move_chapter_selection(&mut self, forward: bool) {
+        let len = self.selected_file().map_or(0, |f| f.chapters.len());
+        let current = self.chapter_state.selected().unwrap_or(0);
+        let i = if forward {
+            (current + 1).min(len.saturating_sub(1))
+        } else {
+            current.saturating_sub(1)
+        };
+        self.chapter_state.select(Some(i));
+    }
+
+    fn fix_suggestions(&self) -> Vec<remedy::Suggestion> {
+        self.selected_file()
+            .map(|file| remedy::suggestions(file, self.profile()))
+            .unwrap_or_default()
+    }
+
+    fn move_fix_selection(&mut self, forward: bool) {
+        let len = self.fix_suggestions().len();
+        let current = self.fix_list_state.selected().unwrap_or(0);
+        let i = if forward {
+            (current + 1).min(len.saturating_sub(1))
+        } else {
+            current.saturating_sub(1)
+        };
+        self.fix_list_state.select(Some(i));
+    }
+
+    fn copy_selected_fix(&mut self) {
+        let i = self.fix_list_state.selected().unwrap_or(0);
+        if let Some(command) = self.fix_suggestions().get(i).map(|s| s.command_line(&self.ffmpeg)) {
+            self.copy_to_clipboard(command, "ffmpeg command");
+        }
+    }
+
+    fn queue_selected_fix(&mut self) {
+        let Some(file) = self.selected_file() else {
+            return;
+        };
+        let (input, duration) = (file.path.clone(), file.duration);
+        let i = self.fix_list_state.selected().unwrap_or(0);
+        if let Some(suggestion) = self.fix_suggestions().into_iter().nth(i) {
+            let title = suggestion.title.clone();
+            self.transcodes.push(&input, duration, suggestion);
+            self.show_notification(format!("Queued: {}", title));
+        }
+    }
+
+    /// Queues the first suggested fix for every marked (or the selected) file.
+    fn queue_fixes_for_targets(&mut self) {
+        let mut queued = 0;
+        for i in self.target_indices() {
+            let file = &self.media_files[i];
+            let (input, duration) = (file.path.clone(), file.duration);
+            if let Some(suggestion) = remedy::suggestions(file, self.profile()).into_iter().next() {
+                self.transcodes.push(&input, duration, suggestion);
+                queued += 1;
+            }
+        }
+        self.show_notification(format!("Queued {} transcode(s)", queued));
+    }
+
+    /// Re-analyzes finished outputs so they show up next to their sources.
+    fn poll_transcodes(&mut self) -> Result<()> {
+        for (output, result) in self.transcodes.poll() {
+            match result {
+                Ok(()) => {
+                    self.show_notification(format!("Transcoded {}", output));
+                    self.queue_analysis(output)?;
+                }
+                Err(e) => self.show_error(format!("Transcode of {} failed: {}", output, e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts a full decode of every marked (or the selected) file.
+    /// Live streams have no end to decode to and are skipped.
+    fn verify_targets(&mut self) {
+        let (paths, streams): (Vec<String>, Vec<String>) = self
+            .target_indices()
+            .into_iter()
+            .map(|i| self.media_files[i].path.clone())
+            .partition(|path| !is_stream(path));
+        for path in &paths {
+            self.integrity.insert(path.clone(), Integrity::Queued);
+            self.verifier.queue(path.clone());
+        }
+        let mut message = format!("Verifying {} file(s)", paths.len());
+        if !streams.is_empty() {
+            message.push_str(&format!(", skipped {} live stream(s)", streams.len()));
+        }
+        self.show_notification(message);
+    }
+
+    /// Probes the marked or selected files again, e.g. after a re-export under the
+    /// same name, whatever the cache has. Results replace the existing rows as they
+    /// arrive.
+    fn reanalyze_targets(&mut self) -> Result<()> {
+        let paths: Vec<String> = self
+            .target_indices()
+            .into_iter()
+            .map(|i| self.media_files[i].path.clone())
+            .collect();
+        for path in &paths {
+            self.send_job(path.clone(), true)?;
+        }
+        self.show_notification(format!("Re-analyzing {} file(s)", paths.len()));
+        Ok(())
+    }
+
+    /// Switches the tool new scans and re-analyses use.
+    fn cycle_analyzer(&mut self) {
+        self.analyzer = self.analyzer.next();
+        self.show_notification(format!("Analyzing with {}", self.analyzer.label()));
+    }
+
+    /// Checksums the marked or selected files with the configured algorithm.
+    fn checksum_targets(&mut self) {
+        let (files, skipped): (Vec<&MediaInfo>, Vec<&MediaInfo>) = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .partition(|file| file.sequence.is_none() && !is_url(&file.path));
+        for file in &files {
+            if self.checksumming.insert(file.path.clone()) {
+                self.checksummer.queue(file.path.clone());
+            }
+        }
+        let mut message = format!("Computing checksums of {} file(s)", files.len());
+        if !skipped.is_empty() {
+            message.push_str(&format!(", skipped {} image sequence(s) and URL(s)", skipped.len()));
+        }
+        self.show_notification(message);
+    }
+
+    /// Measures the loudness of the marked or selected files that have audio. Live
+    /// streams never finish and are skipped.
+    fn measure_loudness_targets(&mut self) {
+        let (files, silent): (Vec<&MediaInfo>, Vec<&MediaInfo>) = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .partition(|file| file.audio_codec != "None");
+        let (streams, files): (Vec<&MediaInfo>, Vec<&MediaInfo>) =
+            files.into_iter().partition(|file| is_stream(&file.path));
+        for file in &files {
+            if self.measuring.insert(file.path.clone()) {
+                self.loudness_meter.queue(file.path.clone());
+            }
+        }
+        let mut message = format!("Measuring loudness of {} file(s)", files.len());
+        if !silent.is_empty() {
+            message.push_str(&format!(", skipped {} without audio", silent.len()));
+        }
+        if !streams.is_empty() {
+            message.push_str(&format!(", skipped {} live stream(s)", streams.len()));
+        }
+        self.show_notification(message);
+    }
+
+    fn poll_loudness(&mut self) {
+        for (path, result) in self.loudness_meter.poll() {
+            self.measuring.remove(&path);
+            self.invalidate_view();
+            match result {
+                Ok(loudness) => {
+                    // Files removed in the meantime stay removed
+                    if let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        file.loudness = Some(loudness);
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not measure {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Makes the selected file the one the quality pass scores encodes against, or
+    /// stops it being that when it already is.
+    fn toggle_reference(&mut self) {
+        let Some(file) = self.selected_file() else {
+            return self.show_notification("No file selected".to_string());
+        };
+        if self.reference.as_ref() == Some(&file.path) {
+            self.reference = None;
+            return self.show_notification("Cleared the quality reference".to_string());
+        }
+        if file.audio_only || is_stream(&file.path) {
+            return self.show_notification("The reference needs a picture and an end".to_string());
+        }
+        let path = file.path.clone();
+        self.show_notification(format!("Encodes are now scored against {}", path));
+        self.reference = Some(path);
+    }
+
+    /// Scores the marked or selected files against the reference, leaving out the
+    /// reference itself, sound files and live streams.
+    fn measure_quality_targets(&mut self) {
+        let Some(reference) = self.reference.clone() else {
+            let key = self.keymap.label(Action::Reference);
+            return self.show_notification(format!("Choose a reference first ({} on the original)", key));
+        };
+        let size = self.media_files.iter().find(|f| f.path == reference).and_then(|f| {
+            let (width, height) = f.resolution.split_once('x')?;
+            Some((width.parse().ok()?, height.parse().ok()?))
+        });
+        let Some((width, height)) = size else {
+            return self.show_notification(format!("{} is no longer in the list or has no size", reference));
+        };
+        let (encodes, skipped): (Vec<&MediaInfo>, Vec<&MediaInfo>) = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .filter(|file| file.path != reference)
+            .partition(|file| !file.audio_only && !is_stream(&file.path));
+        let encodes: Vec<String> = encodes.into_iter().map(|file| file.path.clone()).collect();
+        let skipped = skipped.len();
+        if encodes.is_empty() {
+            return self.show_notification("Select or mark an encode to compare with the reference".to_string());
+        }
+        for encode in &encodes {
+            if self.measuring_quality.insert(encode.clone()) {
+                let job = quality::Job { encode: encode.clone(), reference: reference.clone(), width, height };
+                self.quality_meter.queue(job);
+            }
+        }
+        let mut message = format!("Scoring {} file(s) against {}", encodes.len(), reference);
+        if skipped > 0 {
+            message.push_str(&format!(", skipped {} without a picture or end", skipped));
+        }
+        self.show_notification(message);
+    }
+
+    fn poll_quality(&mut self) {
+        for (path, result) in self.quality_meter.poll() {
+            self.measuring_quality.remove(&path);
+            self.invalidate_view();
+            match result {
+                Ok(quality) => {
+                    // Files removed in the meantime stay removed
+                    if let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        file.quality = Some(quality);
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not score {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Writes a report of the files as the table shows them, filtered and sorted (or
+    /// the marked ones among them), with the active QC profile's results, into the
+    /// working directory.
+    fn write_report(&mut self) {
+        let indices = self.filtered_targets();
+        if indices.is_empty() {
+            return self.show_notification("No files to report on".to_string());
+        }
+        let files: Vec<&MediaInfo> = indices.iter().map(|&i| &self.media_files[i]).collect();
+        let mut filter = query::to_query(&self.active_filters);
+        if !self.search.is_empty() {
+            let search = format!("names like \"{}\"", self.search);
+            filter = if filter.is_empty() { search } else { format!("{}, {}", filter, search) };
+        }
+        let (title, name) = match &self.project {
+            Some(project) => (format!("{} report", project), format!("{}-report", project)),
+            None => (REPORT_TITLE.to_string(), "media-scout-report".to_string()),
+        };
+        let brand = Some(&self.brand);
+        let options = ReportOptions { title: &title, filter: Some(&filter), profile: self.profile(), brand };
+        let bytes = report::render(&files, &options, self.report_format);
+        let path = Path::new(&name).with_extension(self.report_format.extension());
+        match fs::write(&path, bytes) {
+            Ok(()) => {
+                let path = std::env::current_dir().map_or(path.clone(), |dir| dir.join(&path));
+                self.show_notification(format!("Wrote a report of {} file(s) to {}", files.len(), path.display()))
+            }
+            Err(e) => self.show_error(format!("Error writing report: {}", e)),
+        }
+    }
+
+    /// Decodes the selected file flat out, to see whether this machine keeps up with
+    /// its frame rate. Sound files, stills and live streams have nothing to measure.
+    fn benchmark_selected(&mut self) {
+        let Some(file) = self.selected_file() else {
+            return self.show_notification("No file selected".to_string());
+        };
+        if file.audio_only || (file.image.is_some() && file.sequence.is_none()) || is_stream(&file.path) {
+            return self.show_notification("Only clips with video and an end can be benchmarked".to_string());
+        }
+        let path = file.path.clone();
+        let job = benchmark::Job { path: path.clone(), frame_rate: file.frame_rate.parse().ok() };
+        if self.benchmarking.insert(path.clone()) {
+            self.benchmarker.queue(job);
+        }
+        self.show_notification(format!("Benchmarking the decode of {}", path));
+    }
+
+    fn poll_benchmarks(&mut self) {
+        for (path, result) in self.benchmarker.poll() {
+            self.benchmarking.remove(&path);
+            self.invalidate_view();
+            match result {
+                Ok(benchmark) => {
+                    let mut message = format!("{}: {}", path, benchmark.summary());
+                    if benchmark.realtime.is_some_and(|realtime| realtime < 1.0) {
+                        message.push_str(", too slow to play in realtime");
+                    }
+                    self.show_notification(message);
+                    // Files removed in the meantime stay removed
+                    if let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        file.benchmark = Some(benchmark);
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not benchmark {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Fills in the HW decode column once the host's decoders are known.
+    fn poll_hw_decoders(&mut self) {
+        let Ok(apis) = self.hw_detection.try_recv() else {
+            return;
+        };
+        for file in &mut self.media_files {
+            file.hw_decode = Some(hwdecode::check(&apis, file));
+        }
+        self.hw_decoders = Some(apis);
+        self.invalidate_view();
+    }
+
+    /// Looks for black and silent stretches in the marked or selected files, leaving
+    /// out live streams, which have no end to scan to.
+    fn detect_blanks_targets(&mut self) {
+        let jobs: Vec<blanks::Job> = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .filter(|file| !self.scanning_blanks.contains(&file.path) && !is_stream(&file.path))
+            .map(|file| blanks::Job {
+                path: file.path.clone(),
+                duration: file.duration,
+                video: file.codec != "Unknown",
+                audio: file.audio_codec != "None",
+            })
+            .collect();
+        self.show_notification(format!("Scanning {} file(s) for black and silence", jobs.len()));
+        for job in jobs {
+            self.scanning_blanks.insert(job.path.clone());
+            self.blank_detector.queue(job);
+        }
+    }
+
+    fn poll_blanks(&mut self) {
+        for (path, result) in self.blank_detector.poll() {
+            self.scanning_blanks.remove(&path);
+            self.invalidate_view();
+            match result {
+                // Files removed in the meantime stay removed
+                Ok(blanks) => {
+                    let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) else {
+                        continue;
+                    };
+                    // The stretches that matter for playout are the ones at either end
+                    let edges: Vec<String> = [("black", &blanks.black), ("silence", &blanks.silence)]
+                        .into_iter()
+                        .flat_map(|(kind, segments)| segments.iter().map(move |s| (kind, s)))
+                        .filter_map(|(kind, segment)| match Position::of(segment, file.duration) {
+                            Position::Middle => None,
+                            position => Some(format!("{:.1}s of {} ({})", segment.duration(), kind, position.label())),
+                        })
+                        .collect();
+                    let message = format!("{}.{}: {}", file.name, file.container, edges.join(", "));
+                    file.blanks = Some(blanks);
+                    if !edges.is_empty() {
+                        self.show_notification(message);
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not scan {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Measures whether the marked or selected files are interlaced, whatever their tags say.
+    fn detect_scan_targets(&mut self) {
+        let paths: Vec<String> = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .filter(|file| file.codec != "Unknown")
+            .map(|file| file.path.clone())
+            .collect();
+        for path in &paths {
+            if self.detecting_scan.insert(path.clone()) {
+                self.scan_detector.queue(path.clone());
+            }
+        }
+        self.show_notification(format!("Detecting interlacing in {} file(s)", paths.len()));
+    }
+
+    fn poll_scan_detector(&mut self) {
+        for (path, result) in self.scan_detector.poll() {
+            self.detecting_scan.remove(&path);
+            self.invalidate_view();
+            match result {
+                Ok(scan) => {
+                    // Files removed in the meantime stay removed
+                    let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) else {
+                        continue;
+                    };
+                    let tagged = std::mem::replace(&mut file.scan, scan.to_string());
+                    file.scan_detected = true;
+                    if interlace::is_interlaced(scan) && tagged != scan {
+                        self.show_notification(format!("{} is {} (tagged {})", path, scan, tagged));
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not run idet on {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Finds the active picture area of the marked or selected files.
+    fn detect_crop_targets(&mut self) {
+        let jobs: Vec<(String, Option<f64>)> = self
+            .target_indices()
+            .into_iter()
+            .map(|i| &self.media_files[i])
+            .filter(|file| file.codec != "Unknown")
+            .map(|file| (file.path.clone(), file.duration))
+            .collect();
+        self.show_notification(format!("Detecting the picture area of {} file(s)", jobs.len()));
+        for (path, duration) in jobs {
+            if self.detecting_crop.insert(path.clone()) {
+                self.crop_detector.queue(path, duration);
+            }
+        }
+    }
+
+    fn poll_crop_detector(&mut self) {
+        for (path, result) in self.crop_detector.poll() {
+            self.detecting_crop.remove(&path);
+            self.invalidate_view();
+            match result {
+                Ok(crop) => {
+                    // Files removed in the meantime stay removed
+                    if let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        file.crop = Some(crop);
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not detect the picture area of {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Opens the tag editor on the selected file, filled in with its current tags.
+    fn open_tag_editor(&mut self) {
+        let Some(file) = self.selected_file() else {
+            self.show_notification("No file selected".to_string());
+            return;
+        };
+        if self.writing_tags.contains(&file.path) {
+            self.show_notification(format!("Still writing tags to {}", file.path));
+            return;
+        }
+        if file.sequence.is_some() {
+            self.show_notification("Image sequences have no container to tag".to_string());
+            return;
+        }
+        if is_url(&file.path) {
+            self.show_notification("Tags can only be written to local files".to_string());
+            return;
+        }
+        let (path, title, languages) = (file.path.clone(), file.title.clone(), file.audio_languages.join(", "));
+        let probe: Value = serde_json::from_str(&self.raw_store.get(&path)).unwrap_or(Value::Null);
+        let comment = format_tag(&probe, &["comment"]).unwrap_or_default();
+        self.tag_form = Some(TagForm {
+            path,
+            title: Input::new(title.unwrap_or_default()),
+            comment: Input::new(comment),
+            languages: Input::new(languages),
+            focus: 0,
+        });
+        self.mode = AppMode::EditTags;
+    }
+
+    /// Queues the remux for the edited tags, keeping the editor open on a bad value.
+    fn save_tags(&mut self) {
+        let Some(form) = self.tag_form.as_ref() else {
+            return;
+        };
+        let Some(file) = self.media_files.iter().find(|f| f.path == form.path) else {
+            self.tag_form = None;
+            self.mode = AppMode::Normal;
+            return;
+        };
+        let languages = match tags::parse_languages(form.languages.value(), file.audio_languages.len()) {
+            Ok(languages) => languages,
+            Err(e) => return self.show_notification(e),
+        };
+        let edit = TagEdit {
+            title: form.title.value().trim().to_string(),
+            comment: form.comment.value().trim().to_string(),
+            languages,
+            faststart: file.faststart == Some(true),
+        };
+        let path = form.path.clone();
+        self.tag_form = None;
+        self.mode = AppMode::Normal;
+        self.writing_tags.insert(path.clone());
+        self.tag_writer.queue(path.clone(), edit);
+        self.show_notification(format!("Writing tags to {}", path));
+    }
+
+    /// Opens the tags and note of the marked files, or the selected one, starting
+    /// from what the first of them has.
+    fn open_note_editor(&mut self) {
+        let targets = self.target_indices();
+        let Some(&first) = targets.first() else {
+            self.show_notification("No file selected".to_string());
+            return;
+        };
+        let note = self.media_files[first].note.clone().unwrap_or_default();
+        self.note_form = Some(NoteForm {
+            paths: self.target_paths(),
+            tags: Input::new(note.tags.join(", ")),
+            text: Input::new(note.text),
+            focus: 0,
+        });
+        self.mode = AppMode::EditNote;
+    }
+
+    /// Stores the edited note on every file the editor was opened for.
+    fn save_note(&mut self) {
+        let Some(form) = self.note_form.take() else {
+            return;
+        };
+        self.mode = AppMode::Normal;
+        let (tags, text) = (notes::parse_tags(form.tags.value()), form.text.value().trim().to_string());
+        let verb = if tags.is_empty() && text.is_empty() { "Cleared" } else { "Saved" };
+        let saved = self.update_notes(&form.paths, |note| {
+            note.tags = tags.clone();
+            note.text = text.clone();
+        });
+        if saved {
+            self.show_notification(format!("{} the note on {}", verb, describe_paths(&form.paths)));
+        }
+    }
+
+    /// Gives the marked files, or the selected one, `rating` stars, or none. When they
+    /// all have that rating already it's cleared, so the same key takes it back.
+    fn rate_targets(&mut self, rating: Option<u8>) {
+        let paths = self.target_paths();
+        if paths.is_empty() {
+            return self.show_notification("No file selected".to_string());
+        }
+        let current = |i: usize| self.media_files[i].note.as_ref().and_then(|note| note.rating);
+        let again = self.target_indices().into_iter().all(|i| current(i) == rating);
+        let rating = if again { None } else { rating };
+        if self.update_notes(&paths, |note| note.rating = rating) {
+            self.show_notification(match rating {
+                Some(stars) => format!("Rated {} {}", describe_paths(&paths), notes::format_stars(stars)),
+                None => format!("Cleared the rating of {}", describe_paths(&paths)),
+            });
+        }
+    }
+
+    /// Flags the marked files, or the selected one, the way `rate_targets` rates them.
+    fn flag_targets(&mut self, flag: Flag) {
+        let paths = self.target_paths();
+        if paths.is_empty() {
+            return self.show_notification("No file selected".to_string());
+        }
+        let current = |i: usize| self.media_files[i].note.as_ref().and_then(|note| note.flag);
+        let again = self.target_indices().into_iter().all(|i| current(i) == Some(flag));
+        let flag = if again { None } else { Some(flag) };
+        if self.update_notes(&paths, |note| note.flag = flag) {
+            self.show_notification(match flag {
+                Some(flag) => format!("{} {}", flag.label(), describe_paths(&paths)),
+                None => format!("Cleared the flag of {}", describe_paths(&paths)),
+            });
+        }
+    }
+
+    fn target_paths(&self) -> Vec<String> {
+        self.target_indices().into_iter().map(|i| self.media_files[i].path.clone()).collect()
+    }
+
+    /// Applies `edit` to the notes of `paths`, in the notes file and on their rows;
+    /// `false` when the file couldn't be written.
+    fn update_notes(&mut self, paths: &[String], edit: impl Fn(&mut Note)) -> bool {
+        if let Err(e) = self.notes.update(paths, edit) {
+            self.show_error(format!("Error saving notes: {:#}", e));
+            return false;
+        }
+        for file in self.media_files.iter_mut().filter(|f| paths.contains(&f.path)) {
+            self.notes.apply(file);
+        }
+        true
+    }
+
+    fn poll_tag_writer(&mut self) -> Result<()> {
+        for (path, result) in self.tag_writer.poll() {
+            self.writing_tags.remove(&path);
+            match result {
+                Ok(()) => {
+                    self.show_notification(format!("Wrote tags to {}", path));
+                    self.queue_analysis(path)?;
+                }
+                Err(e) => self.show_error(format!("Could not write tags to {}: {}", path, e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn poll_checksums(&mut self) {
+        for (path, result) in self.checksummer.poll() {
+            self.checksumming.remove(&path);
+            self.invalidate_view();
+            let algorithm = self.checksum_algorithm;
+            match result {
+                Ok(digest) => {
+                    // Files removed in the meantime stay removed
+                    if let Some(file) = self.media_files.iter_mut().find(|f| f.path == path) {
+                        file.checksum = Some(Checksum { algorithm, digest });
+                    }
+                }
+                Err(e) => self.show_error(format!("Could not checksum {}: {}", path, e)),
+            }
+        }
+    }
+
+    fn poll_thumbnails(&mut self) {
+        for (path, result) in self.thumbnailer.poll() {
+            self.thumbnail_pending.remove(&path);
+            if self.media_files.iter().any(|f| f.path == path) {
+                self.thumbnails.insert(path, result);
+            }
+        }
+    }
+
+    /// Draws the preview image the last frame laid out, if it isn't on screen already.
+    /// Images sit outside ratatui's buffer, so replacing one redraws the whole screen
+    /// to wipe the old picture.
+    fn draw_overlay<B: Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        if self.thumbnail_overlay == self.drawn_overlay {
+            return Ok(());
+        }
+        if self.drawn_overlay.take().is_some() {
+            write!(terminal.backend_mut(), "{}", thumbnail::clear(self.graphics))?;
+            terminal.clear()?;
+            terminal.draw(|f| ui(f, self))?;
+        }
+        let Some((path, area)) = self.thumbnail_overlay.clone() else {
+            return Ok(());
+        };
+        if let Some(Ok(thumbnail)) = self.thumbnails.get(&path) {
+            let sequence = thumbnail::escape(self.graphics, thumbnail, area.width, area.height, self.cell_size);
+            let backend = terminal.backend_mut();
+            execute!(backend, cursor::MoveTo(area.x, area.y))?;
+            write!(backend, "{}", sequence)?;
+            Write::flush(backend)?;
+        }
+        self.drawn_overlay = Some((path, area));
+        Ok(())
+    }
+
+    fn poll_verifier(&mut self) {
+        for (path, integrity) in self.verifier.poll() {
+            self.invalidate_view();
+            match &integrity {
+                Integrity::Corrupt(errors) => {
+                    for error in errors {
+                        self.log.push(Level::Warning, format!("{}: {}", path, error));
+                    }
+                    self.show_notification(format!("Decode errors in {}: {}", path, errors[0]))
+                }
+                Integrity::Clean => self.show_notification(format!("{} decodes cleanly", path)),
+                _ => {}
+            }
+            // Files removed while their check was running stay removed
+            if let Some(entry) = self.integrity.get_mut(&path) {
+                *entry = integrity;
+            }
+        }
+    }
+
+    fn open_search(&mut self) {
+        self.search_input = Some(Input::new(self.search.clone()));
+    }
+
+    fn set_search(&mut self, search: String) {
+        self.search = search;
+        self.invalidate_view();
+        self.clamp_selection();
+    }
+
+    fn open_query(&mut self) {
+        self.query_input = Input::new(query::to_query(&self.active_filters));
+        self.query_error = None;
+        self.mode = AppMode::Query;
+    }
+
+    /// Replaces the active filters with the typed query. On a parse error the bar
+    /// stays open so the query can be fixed.
+    fn apply_query(&mut self) {
+        match query::parse(self.query_input.value()) {
+            Ok(filters) => {
+                self.active_filters = filters;
+                self.invalidate_view();
+                self.clamp_selection();
+                self.show_notification(format!(
+                    "{} filter(s) active, {} file(s) match",
+                    self.active_filter_count(),
+                    self.get_filtered_files().len()
+                ));
+                self.mode = AppMode::Normal;
+            }
+            Err(e) => self.query_error = Some(e.to_string()),
+        }
+    }
+
+    fn next_filter_option(&mut self) {
+        let len = self.filter_row_count();
+        if len == 0 {
+            return;
+        }
+        let i = self.filter_list_state.selected().map_or(0, |i| (i + 1) % len);
+        self.filter_list_state.select(Some(i));
+    }
+
+    fn previous_filter_option(&mut self) {
+        let len = self.filter_row_count();
+        if len == 0 {
+            return;
+        }
+        let i = self
+            .filter_list_state
+            .selected()
+            .map_or(0, |i| if i == 0 { len - 1 } else { i - 1 });
+        self.filter_list_state.select(Some(i));
+    }
+
+    /// The Filters tab lists presets first, then the options.
+    fn filter_row_count(&self) -> usize {
+        self.presets.len() + self.filter_options.entries().len()
+    }
+
+    fn active_filter_count(&self) -> usize {
+        self.active_filters.iter().map(Vec::len).sum()
+    }
+
+    /// Whether a Filters tab option is active: `Some(true)` when it excludes.
+    fn option_state(&self, option: &ActiveFilter) -> Option<bool> {
+        self.active_filters
+            .iter()
+            .flatten()
+            .find(|f| f.same_option(option))
+            .map(|f| f.negated)
+    }
+
+    /// Toggles the selected option as an include filter, or as an exclude filter
+    /// with `exclude`. Includes on the same field are ORed together, so checking
+    /// H.265 and AV1 shows both; excludes always apply.
+    fn toggle_selected_filter(&mut self, exclude: bool) {
+        let selected = self.filter_list_state.selected().unwrap_or(0);
+        if selected < self.presets.len() {
+            self.apply_preset(selected);
+            return;
+        }
+        let entries = self.filter_options.entries();
+        let Some((filter_type, value)) = entries.get(selected - self.presets.len()).cloned() else {
+            return;
+        };
+
+        let option = ActiveFilter::option(filter_type, value);
+        let previous = self.option_state(&option);
+        for group in &mut self.active_filters {
+            group.retain(|f| !f.same_option(&option));
+        }
+        self.active_filters.retain(|group| !group.is_empty());
+
+        if previous != Some(exclude) {
+            let filter = ActiveFilter {
+                negated: exclude,
+                ..option
+            };
+            let same_field = self.active_filters.iter_mut().find(|group| {
+                group.iter().all(|f| !f.negated && f.filter_type == filter.filter_type)
+            });
+            match same_field {
+                Some(group) if !exclude => group.push(filter),
+                _ => self.active_filters.push(vec![filter]),
+            }
+        }
+        self.invalidate_view();
+        self.clamp_selection();
+    }
+}
+
+impl App {
+    fn open_save_preset(&mut self) {
+        if self.active_filters.is_empty() {
+            self.show_notification("No active filters to save".to_string());
+            return;
+        }
+        self.input.reset();
+        self.mode = AppMode::SavePreset;
+    }
+
+    /// Saves the active filters under the typed name, replacing a preset of the same name.
+    fn save_preset(&mut self) {
+        let name = self.input.value().trim().to_string();
+        self.input.reset();
+        self.mode = AppMode::Normal;
+        if name.is_empty() {
+            return;
+        }
+        let query = query::to_query(&self.active_filters);
+        match self.presets.iter_mut().find(|p| p.name == name) {
+            Some(preset) => preset.query = query,
+            None => self.presets.push(FilterPreset { name: name.clone(), query }),
+        }
+        self.write_presets(format!("Saved preset '{}'", name));
+    }
+
+    fn apply_preset(&mut self, index: usize) {
+        let Some(preset) = self.presets.get(index) else {
+            return;
+        };
+        match query::parse(&preset.query) {
+            Ok(filters) => {
+                let name = preset.name.clone();
+                self.active_filters = filters;
+                self.invalidate_view();
+                self.clamp_selection();
+                self.show_notification(format!(
+                    "Preset '{}': {} file(s) match",
+                    name,
+                    self.get_filtered_files().len()
+                ));
+            }
+            Err(e) => {
+                let message = format!("Preset '{}' is invalid: {}", preset.name, e);
+                self.show_notification(message);
+            }
+        }
+    }
+
+    fn delete_selected_preset(&mut self) {
+        let Some(index) = self
+            .filter_list_state
+            .selected()
+            .filter(|&i| i < self.presets.len())
+        else {
+            return;
+        };
+        let preset = self.presets.remove(index);
+        self.write_presets(format!("Deleted preset '{}'", preset.name));
+    }
+
+    fn write_presets(&mut self, success: String) {
+        match config::save_presets(self.config_path.as_deref(), &self.presets) {
+            Ok(_) => self.show_notification(success),
+            Err(e) => self.show_error(format!("Could not save presets: {:#}", e)),
+        }
+    }
+
+    /// Whether the active filters are exactly what the preset selects.
+    fn preset_active(&self, preset: &FilterPreset) -> bool {
+        !self.active_filters.is_empty()
+            && query::parse(&preset.query).is_ok_and(|filters| filters == self.active_filters)
+    }
+}
+
+impl Cli {
+    /// Paths from the command line followed by any read from stdin.
+    fn input_paths(&self) -> Result<Vec<String>> {
+        let mut paths = self.paths.clone();
+        if self.stdin {
+            for line in io::stdin().lock().lines() {
+                let line = line?;
+                let line = line.trim();
+                if !line.is_empty() {
+                    paths.push(line.to_string());
+                }
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Scans the given paths synchronously and writes the results to stdout.
+fn run_headless(cli: &Cli, config: &Config, paths: &[String]) -> Result<()> {
+    let filter = cli.filter.as_deref().map(FilterSet::parse).transpose().context("--filter")?;
+    // The other analyzers only fall back on ffprobe now and then
+    let check = (config.analyzer == analyzer::Backend::Ffprobe).then(|| check_ffprobe(&config.ffprobe));
+    if let Some(Err(e)) = check {
+        anyhow::bail!("{}\n{}", e, FFPROBE_INSTALL_HINT);
+    }
+
+    let analyzer = analyzer::analyzer(config.analyzer, config);
+    let cache = AnalysisCache::from_config(config);
+    let s3 = S3::from_config(config);
+    let ssh = Ssh::new(&config.hosts);
+    let scan_options = ScanOptions::from_config(config);
+    let mut files = Vec::new();
+    let mut failures = 0;
+    let mut seen = HashSet::new();
+    for path in paths {
+        if !is_url(path) && !Path::new(path).exists() {
+            eprintln!("{}: file does not exist", path);
+            failures += 1;
+            continue;
+        }
+        let scan = match expand_path(path, &scan_options, &s3, &ssh) {
+            Ok(scan) => scan,
+            Err(e) => {
+                eprintln!("{}: could not list: {}", path, e);
+                failures += 1;
+                continue;
+            }
+        };
+        for (link, target) in &scan.broken_links {
+            eprintln!("{}: broken symlink to {}", link, target);
+        }
+        // Once per file, when the paths given overlap through symlinks
+        let media_paths = scan.paths.into_iter().filter(|media_path| {
+            fs::canonicalize(media_path).map_or(true, |real| seen.insert(real))
+        });
+        for media_path in media_paths {
+            let cached = if cli.refresh { None } else { cache.get(&media_path, config.analyzer) };
+            let result = cached.map(Ok).unwrap_or_else(|| {
+                let result = analyzer.analyze(&media_path);
+                if let Ok(info) = &result {
+                    cache.put(info);
+                }
+                result
+            });
+            match result {
+                Ok(mut media_info) if cli.checksum.is_some() => {
+                    match checksum::compute(config.checksum, &media_path) {
+                        Ok(digest) => {
+                            media_info.checksum = Some(Checksum { algorithm: config.checksum, digest })
+                        }
+                        Err(e) => {
+                            eprintln!("{}: error computing checksum: {}", media_path, e);
+                            failures += 1;
+                        }
+                    }
+                    files.push(media_info);
+                }
+                Ok(media_info) => files.push(media_info),
+                Err(e) => {
+                    eprintln!("{}: error analyzing file: {}", media_path, e);
+                    failures += 1;
+                }
+            }
+        }
+    }
+
+    let notes = Notes::load()?;
+    for file in &mut files {
+        notes.apply(file);
+    }
+    let refs: Vec<&MediaInfo> =
+        files.iter().filter(|file| filter.as_ref().is_none_or(|set| set.matches(file))).collect();
+    if let Some(pattern) = &cli.organize {
+        let failed = organize_headless(&refs, pattern, cli)?;
+        failures += failed;
+        if failures > 0 {
+            anyhow::bail!("{} file(s) could not be analyzed or organized", failures);
+        }
+        return Ok(());
+    }
+    let template = match &cli.template {
+        Some(text) => Some(match config.templates.get(text) {
+            Some(named) => Template::parse(named)?,
+            None => Template::parse(text).context("--template")?,
+        }),
+        None => None,
+    };
+    match (template, cli.format.report_format()) {
+        (Some(template), _) => print!("{}", template.render(&refs)),
+        (None, Some(format)) => {
+            let profile = config.profiles.first();
+            if format == ReportFormat::Pdf && io::stdout().is_terminal() {
+                anyhow::bail!("a PDF report is binary; redirect it to a file, as in --format pdf > report.pdf");
+            }
+            let brand = Some(&config.brand);
+            let options = ReportOptions { title: REPORT_TITLE, filter: cli.filter.as_deref(), profile, brand };
+            io::stdout().write_all(&report::render(&refs, &options, format))?;
+        }
+        (None, None) => print!("{}", export::render(&refs, cli.format)),
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} file(s) could not be analyzed", failures);
+    }
+    Ok(())
+}
+
+/// `--organize`: prints each file's place as it's copied or moved, or only where
+/// it would go with `--dry-run`. Returns how many files failed.
+fn organize_headless(files: &[&MediaInfo], pattern: &str, cli: &Cli) -> Result<usize> {
+    let pattern = organize::Pattern::parse(pattern).context("--organize")?;
+    let placements = organize::plan(&pattern, files);
+    for placement in &placements {
+        match &placement.skipped {
+            Some(reason) => eprintln!("{}: skipped, {}", placement.from, reason),
+            None if cli.dry_run => println!("{} -> {}", placement.from, placement.to),
+            None => {}
+        }
+    }
+    if cli.dry_run {
+        return Ok(0);
+    }
+    let operation = if cli.move_files { Operation::Move } else { Operation::Copy };
+    let mut failed = 0;
+    for progress in organize::start(placements, operation) {
+        match progress.result {
+            Ok(()) => println!("[{}/{}] {} -> {}", progress.done, progress.total, progress.from, progress.to),
+            Err(e) => {
+                let (done, total, verb) = (progress.done, progress.total, operation.verb());
+                eprintln!("[{}/{}] could not {} {}: {}", done, total, verb, progress.from, e);
+                failed += 1;
+            }
+        }
+    }
+    Ok(failed)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut config = Config::load(cli.config.as_deref())?;
+    if let Some(ffprobe) = &cli.ffprobe {
+        config.ffprobe = ffprobe.clone();
+    }
+    if let Some(ffmpeg) = &cli.ffmpeg {
+        config.ffmpeg = ffmpeg.clone();
+    }
+    if let Some(algorithm) = cli.checksum {
+        config.checksum = algorithm;
+    }
+    if cli.no_cache {
+        config.cache = false;
+    }
+    if let Some(backend) = cli.analyzer {
+        config.analyzer = backend;
+    }
+    if let Some(theme) = &cli.theme {
+        config.theme = theme.clone();
+    }
+    if let Some(depth) = cli.max_depth {
+        config.max_depth = Some(depth as usize);
+    }
+    if !cli.extensions.is_empty() {
+        config.extensions = cli.extensions.clone();
+    }
+    if cli.min_size.is_some() {
+        config.min_size = cli.min_size.clone();
+    }
+    if cli.max_size.is_some() {
+        config.max_size = cli.max_size.clone();
+    }
+    config.ffprobe_args.extend(cli.ffprobe_args.iter().cloned());
+    let paths = cli.input_paths()?;
+    if cli.no_tui {
+        let mut paths = paths;
+        if let Some(name) = &cli.project {
+            paths.splice(0..0, project::load(name)?.paths);
+        }
+        return run_headless(&cli, &config, &paths);
+    }
+
+    // Create app before touching the terminal so startup errors print normally
+    let mut app = App::new(config, cli.config.clone())?;
+    if let Some(name) = &cli.project {
+        app.switch_project(name)?;
+    }
+    for path in &paths {
+        app.add_file(path)?;
+    }
+    if !cli.watch.is_empty() {
+        app.watch_folders(&cli.watch)?;
     }
-}
 
-fn main() -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app and run
-    let mut app = App::new();
-    let res = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut app);
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        println!("{:?}", err);
+    }
+
+    Ok(())
+}
+
+fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    // Whether the last event may have changed what the Files table shows, say a
+    // note edited in place; one that only moves the cursor leaves the view as it is
+    let mut changed = false;
+    loop {
+        if std::mem::take(&mut changed) {
+            app.invalidate_view();
+        }
+        app.poll_watcher()?;
+        app.poll_analysis();
+        app.poll_transcodes()?;
+        app.poll_verifier();
+        app.poll_frame_hasher();
+        app.poll_checksums();
+        app.poll_loudness();
+        app.poll_quality();
+        app.poll_hw_decoders();
+        app.poll_benchmarks();
+        app.poll_blanks();
+        app.poll_scan_detector();
+        app.poll_crop_detector();
+        app.poll_tag_writer()?;
+        app.poll_thumbnails();
+        app.poll_packets();
+        app.poll_organize();
+        terminal.draw(|f| ui(f, app))?;
+        app.draw_overlay(terminal)?;
+
+        // Wake up periodically so background results show up without a keypress
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        let event = event::read()?;
+        if let Event::Paste(text) = &event {
+            changed = true;
+            app.handle_paste(text)?;
+            continue;
+        }
+        if let Event::Mouse(mouse) = event {
+            changed = !matches!(mouse.kind, MouseEventKind::ScrollUp | MouseEventKind::ScrollDown);
+            app.handle_mouse(mouse);
+            continue;
+        }
+        if let Event::Key(key) = event {
+            if key.kind == KeyEventKind::Press {
+                let moves = matches!(app.keymap.action(&key), Some(Action::Next | Action::Previous));
+                changed = !(moves && matches!(app.mode, AppMode::Normal) && app.search_input.is_none());
+                let jump = match app.mode {
+                    // A key bound to an action takes precedence over the fixed movement keys
+                    AppMode::Normal if app.search_input.is_none() && app.keymap.action(&key).is_none() => {
+                        app.jump_for_key(key.code)
+                    }
+                    AppMode::ShowRawOutput if app.raw_search.is_none() => app.jump_for_key(key.code),
+                    AppMode::Log => app.jump_for_key(key.code),
+                    _ => None,
+                };
+                if let Some(jump) = jump {
+                    match app.mode {
+                        AppMode::ShowRawOutput => app.jump_raw_output(jump),
+                        AppMode::Log => app.jump_log(jump),
+                        _ if app.selected_tab == 1 => app.jump_filter_options(jump),
+                        _ if app.selected_tab == 2 => app.jump_estimate(jump),
+                        _ if app.selected_tab == 3 => app.jump_tree(jump),
+                        _ => app.jump_files(jump),
+                    }
+                    changed = false;
+                    continue;
+                }
+
+                match app.mode {
+                    AppMode::Normal if app.search_input.is_some() => {
+                        match key.code {
+                            KeyCode::Enter => app.search_input = None,
+                            KeyCode::Esc => {
+                                app.search_input = None;
+                                app.set_search(String::new());
+                            }
+                            _ => {
+                                if let Some(input) = app.search_input.as_mut() {
+                                    input.handle_event(&Event::Key(key));
+                                    let search = input.value().to_string();
+                                    app.set_search(search);
+                                }
+                            }
+                        }
+                    }
+                    AppMode::Normal => match app.keymap.action(&key) {
+                        Some(action) => {
+                            if app.run_action(action)? {
+                                return app.save_project();
+                            }
+                        }
+                        None => match key.code {
+                            KeyCode::Esc if !app.search.is_empty() => app.set_search(String::new()),
+                            // With no search to clear, Esc stops what's running
+                            KeyCode::Esc if app.running_jobs() > 0 => app.cancel_jobs(),
+                            // Alt+0 clears the rating the way rating again does
+                            KeyCode::Char(c @ '0'..='5') if key.modifiers.contains(KeyModifiers::ALT) => {
+                                app.rate_targets(c.to_digit(10).filter(|&n| n > 0).map(|n| n as u8))
+                            }
+                            KeyCode::Char(c @ '1'..='9') => app.apply_preset(c as usize - '1' as usize),
+                            KeyCode::Enter if app.selected_tab == 1 => app.toggle_selected_filter(false),
+                            KeyCode::Enter if app.selected_tab == 3 => app.open_tree_row(),
+                            KeyCode::Left if app.selected_tab == 2 => app.change_estimate_bitrate(false),
+                            KeyCode::Right if app.selected_tab == 2 => app.change_estimate_bitrate(true),
+                            KeyCode::Left if app.selected_tab == 3 => app.fold_tree_row(false),
+                            KeyCode::Right if app.selected_tab == 3 => app.fold_tree_row(true),
+                            _ => {}
+                        },
+                    },
+                    AppMode::AddFile if app.completion.is_some() => {
+                        match key.code {
+                            KeyCode::Enter => app.accept_completion(),
+                            KeyCode::Esc => app.completion = None,
+                            KeyCode::Down | KeyCode::Tab => {
+                                if let Some(completion) = app.completion.as_mut() {
+                                    completion.next();
+                                }
+                            }
+                            KeyCode::Up | KeyCode::BackTab => {
+                                if let Some(completion) = app.completion.as_mut() {
+                                    completion.previous();
+                                }
+                            }
+                            _ => {
+                                // Keep narrowing the list as the name is typed
+                                app.input.handle_event(&Event::Key(key));
+                                app.completion = Some(PathCompletion::new(app.input.value()));
+                            }
+                        }
+                    }
+                    AppMode::AddFile => {
+                        match key.code {
+                            KeyCode::Tab => app.complete_path(),
+                            KeyCode::Up => app.browse_history(true),
+                            KeyCode::Down => app.browse_history(false),
+                            KeyCode::Enter => {
+                                // Terminals without bracketed paste type a dropped path out
+                                let text = app.input.value().to_string();
+                                if !text.trim().is_empty() {
+                                    app.add_pasted(&text)?;
+                                    app.input.reset();
+                                }
+                                app.mode = AppMode::Normal;
+                            }
+                            KeyCode::Esc => {
+                                app.input.reset();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {
+                                app.input.handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::RenamePattern => {
+                        match key.code {
+                            KeyCode::Enter => app.preview_rename(),
+                            KeyCode::Esc => {
+                                app.input.reset();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {
+                                app.input.handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::OrganizePattern => {
+                        match key.code {
+                            KeyCode::Enter => app.preview_organize(),
+                            KeyCode::Esc => {
+                                app.input.reset();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {
+                                app.input.handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::Organize => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_organize_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_organize_selection(false),
+                            KeyCode::Char('c') => app.start_organize(Operation::Copy),
+                            KeyCode::Char('m') => app.start_organize(Operation::Move),
+                            KeyCode::Char('e') => {
+                                app.input = Input::new(app.organize_pattern.clone());
+                                app.mode = AppMode::OrganizePattern;
+                            }
+                            KeyCode::Esc | KeyCode::Char('n') => {
+                                app.placements.clear();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::ConfirmDelete => {
+                        match key.code {
+                            KeyCode::Enter | KeyCode::Char('y') => app.delete_files(false),
+                            KeyCode::Char('!') => app.delete_files(true),
+                            KeyCode::Esc | KeyCode::Char('n') => {
+                                app.deleting.clear();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::Rename => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_rename_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_rename_selection(false),
+                            KeyCode::Enter | KeyCode::Char('y') => app.apply_rename(),
+                            KeyCode::Char('e') => {
+                                app.input = Input::new(app.rename_pattern.clone());
+                                app.mode = AppMode::RenamePattern;
+                            }
+                            KeyCode::Esc | KeyCode::Char('n') => {
+                                app.renames.clear();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::SavePreset => {
+                        match key.code {
+                            KeyCode::Enter => app.save_preset(),
+                            KeyCode::Esc => {
+                                app.input.reset();
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {
+                                app.input.handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::Query => {
+                        match key.code {
+                            KeyCode::Enter => app.apply_query(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {
+                                app.query_input.handle_event(&Event::Key(key));
+                                app.query_error = None;
+                            }
+                        }
+                    }
+                    AppMode::ShowRawOutput if app.raw_search.is_some() => {
+                        match key.code {
+                            KeyCode::Enter => app.raw_search = None,
+                            KeyCode::Esc => {
+                                app.raw_search = None;
+                                app.json_tree.set_query("");
+                            }
+                            _ => {
+                                if let Some(input) = app.raw_search.as_mut() {
+                                    input.handle_event(&Event::Key(key));
+                                    let query = input.value().to_string();
+                                    app.json_tree.set_query(&query);
+                                }
+                            }
+                        }
+                    }
+                    AppMode::ShowRawOutput => {
+                        match key.code {
+                            KeyCode::Char('/') => {
+                                app.raw_search = Some(Input::new(app.json_tree.query().to_string()));
+                            }
+                            KeyCode::Char('n') => app.json_tree.next_match(),
+                            KeyCode::Char('N') => app.json_tree.previous_match(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            KeyCode::Up | KeyCode::Char('k') => app.json_tree.previous(),
+                            KeyCode::Down | KeyCode::Char('j') => app.json_tree.next(),
+                            KeyCode::Right | KeyCode::Char('l') => app.json_tree.expand(),
+                            KeyCode::Left | KeyCode::Char('h') => app.json_tree.collapse(),
+                            KeyCode::Enter | KeyCode::Char(' ') => app.json_tree.toggle(),
+                            KeyCode::Char('e') => app.json_tree.expand_all(),
+                            KeyCode::Char('E') => app.json_tree.collapse_all(),
+                            KeyCode::Char('y') => {
+                                if let Some(text) = app.json_tree.selected_text() {
+                                    app.copy_to_clipboard(text, "node");
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::Fixes => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_fix_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_fix_selection(false),
+                            KeyCode::Enter | KeyCode::Char('y') => app.copy_selected_fix(),
+                            KeyCode::Char(QUEUE_FIX_KEY) => app.queue_selected_fix(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {}
+                        }
+                    }
+                    AppMode::Transcodes => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.transcodes.next(),
+                            KeyCode::Up | KeyCode::Char('k') => app.transcodes.previous(),
+                            KeyCode::Char('c') => app.transcodes.clear_finished(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {}
+                        }
+                    }
+                    AppMode::Duplicates => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_duplicate_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_duplicate_selection(false),
+                            KeyCode::Char(' ') => app.toggle_duplicate_mark(),
+                            KeyCode::Char('p') => app.find_similar(),
+                            KeyCode::Enter => app.show_duplicate_in_table(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {}
+                        }
+                    }
+                    AppMode::Compare => {
+                        let Some(comparison) = app.comparison.as_mut() else {
+                            app.mode = AppMode::Normal;
+                            continue;
+                        };
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_compare_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_compare_selection(false),
+                            // Clamped to the columns that fit when drawn
+                            KeyCode::Right | KeyCode::Char('l') => comparison.offset += 1,
+                            KeyCode::Left | KeyCode::Char('h') => {
+                                comparison.offset = comparison.offset.saturating_sub(1)
+                            }
+                            KeyCode::Char('d') => comparison.toggle_only_differences(),
+                            KeyCode::Esc => {
+                                app.comparison = None;
+                                app.mode = AppMode::Normal;
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::EditTags => {
+                        let Some(form) = app.tag_form.as_mut() else {
+                            app.mode = AppMode::Normal;
+                            continue;
+                        };
+                        match key.code {
+                            KeyCode::Enter => app.save_tags(),
+                            KeyCode::Esc => {
+                                app.tag_form = None;
+                                app.mode = AppMode::Normal;
+                            }
+                            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % 3,
+                            KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + 2) % 3,
+                            _ => {
+                                form.focused().handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::EditNote => {
+                        let Some(form) = app.note_form.as_mut() else {
+                            app.mode = AppMode::Normal;
+                            continue;
+                        };
+                        match key.code {
+                            KeyCode::Enter => app.save_note(),
+                            KeyCode::Esc => {
+                                app.note_form = None;
+                                app.mode = AppMode::Normal;
+                            }
+                            KeyCode::Tab | KeyCode::BackTab | KeyCode::Down | KeyCode::Up => {
+                                form.focus = 1 - form.focus
+                            }
+                            _ => {
+                                form.focused().handle_event(&Event::Key(key));
+                            }
+                        }
+                    }
+                    AppMode::Chapters => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.move_chapter_selection(true),
+                            KeyCode::Up | KeyCode::Char('k') => app.move_chapter_selection(false),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {}
+                        }
+                    }
+                    AppMode::Bitrate | AppMode::Gop => {
+                        if key.code == KeyCode::Esc {
+                            app.mode = AppMode::Normal;
+                        }
+                    }
+                    AppMode::Palette => match key.code {
+                        KeyCode::Enter => {
+                            if app.run_palette()? {
+                                return app.save_project();
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app.palette = None;
+                            app.input.reset();
+                            app.mode = AppMode::Normal;
+                        }
+                        KeyCode::Down | KeyCode::Tab => app.palette.iter_mut().for_each(CommandPalette::next),
+                        KeyCode::Up | KeyCode::BackTab => app.palette.iter_mut().for_each(CommandPalette::previous),
+                        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.palette.iter_mut().for_each(CommandPalette::next)
+                        }
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.palette.iter_mut().for_each(CommandPalette::previous)
+                        }
+                        _ => {
+                            app.input.handle_event(&Event::Key(key));
+                            let query = app.input.value().to_string();
+                            app.palette.iter_mut().for_each(|palette| palette.search(&query));
+                        }
+                    },
+                    AppMode::Columns => match key.code {
+                        KeyCode::Enter => app.save_columns(),
+                        KeyCode::Esc => {
+                            app.column_picker.clear();
+                            app.mode = AppMode::Normal;
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => app.move_column_selection(true),
+                        KeyCode::Up | KeyCode::Char('k') => app.move_column_selection(false),
+                        KeyCode::Char('J') => app.move_selected_column(true),
+                        KeyCode::Char('K') => app.move_selected_column(false),
+                        KeyCode::Char(' ') => app.toggle_selected_column(),
+                        KeyCode::Char('+') | KeyCode::Char('=') => app.resize_selected_column(Some(1)),
+                        KeyCode::Char('-') => app.resize_selected_column(Some(-1)),
+                        KeyCode::Char('0') => app.resize_selected_column(None),
+                        _ => {}
+                    },
+                    AppMode::Projects => match key.code {
+                        KeyCode::Enter => app.confirm_project()?,
+                        KeyCode::Esc => {
+                            app.input.reset();
+                            app.mode = AppMode::Normal;
+                        }
+                        KeyCode::Up => app.move_project_selection(false),
+                        KeyCode::Down => app.move_project_selection(true),
+                        _ => {
+                            app.input.handle_event(&Event::Key(key));
+                        }
+                    },
+                    AppMode::Log => {
+                        match key.code {
+                            KeyCode::Down | KeyCode::Char('j') => app.jump_log(Jump::Down(1)),
+                            KeyCode::Up | KeyCode::Char('k') => app.jump_log(Jump::Up(1)),
+                            KeyCode::Char('y') => app.copy_log_entry(),
+                            KeyCode::Char('c') => app.clear_log(),
+                            KeyCode::Esc => app.mode = AppMode::Normal,
+                            _ => {}
+                        }
+                    }
+                    AppMode::Help => {
+                        if key.code == KeyCode::Esc {
+                            app.mode = AppMode::Normal;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    // Set again below if the details panel shows a preview image
+    app.thumbnail_overlay = None;
+    if let Ok(size) = crossterm::terminal::window_size() {
+        if size.width > 0 && size.columns > 0 {
+            app.cell_size = (size.width / size.columns, size.height / size.rows.max(1));
+        }
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Title
+            Constraint::Length(3),  // Tabs
+            Constraint::Min(10),    // Main content
+            Constraint::Length(3),  // Status/notification
+        ])
+        .split(f.size());
+
+    // Title
+    let title = match &app.project {
+        Some(project) => format!("🎬 Video Analyzer TUI - {}", project),
+        None => "🎬 Video Analyzer TUI".to_string(),
+    };
+    let title = Paragraph::new(title)
+        .style(Style::default().fg(theme.title).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Tabs, each clickable along with the padding around its title as Tabs draws it
+    app.click_targets = ClickTargets::default();
+    let mut x = chunks[1].x + 1;
+    for title in TABS {
+        let width = title.chars().count() as u16 + 2;
+        app.click_targets.tabs.push(Rect { x, y: chunks[1].y + 1, width, height: 1 });
+        // The divider
+        x += width + 1;
+    }
+    let tabs = Tabs::new(TABS.to_vec())
+        .block(Block::default().borders(Borders::ALL))
+        .select(app.selected_tab)
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    f.render_widget(tabs, chunks[1]);
+
+    // Main content based on mode; the ones typed into the status bar keep the tab's
+    let in_tab = matches!(
+        app.mode,
+        AppMode::Normal | AppMode::Query | AppMode::SavePreset | AppMode::RenamePattern | AppMode::OrganizePattern
+    );
+    match app.mode {
+        _ if in_tab && app.selected_tab == 1 => render_filters(f, app, chunks[2]),
+        _ if in_tab && app.selected_tab == 2 => render_stats(f, app, chunks[2]),
+        _ if in_tab && app.selected_tab == 3 => render_tree(f, app, chunks[2]),
+        AppMode::Normal
+        | AppMode::Query
+        | AppMode::SavePreset
+        | AppMode::RenamePattern
+        | AppMode::OrganizePattern => render_main_content(f, app, chunks[2]),
+        AppMode::AddFile => render_add_file_dialog(f, app, chunks[2]),
+        AppMode::ShowRawOutput => render_raw_output(f, app, chunks[2]),
+        AppMode::Fixes => render_fixes(f, app, chunks[2]),
+        AppMode::Rename => render_rename(f, app, chunks[2]),
+        AppMode::Organize => render_organize(f, app, chunks[2]),
+        AppMode::ConfirmDelete => render_confirm_delete(f, app, chunks[2]),
+        AppMode::Transcodes => render_transcodes(f, app, chunks[2]),
+        AppMode::Duplicates => render_duplicates(f, app, chunks[2]),
+        AppMode::Compare => render_compare(f, app, chunks[2]),
+        AppMode::Bitrate | AppMode::Gop => render_packet_view(f, app, chunks[2]),
+        AppMode::Chapters => render_chapters(f, app, chunks[2]),
+        AppMode::EditTags => render_tag_editor(f, app, chunks[2]),
+        AppMode::EditNote => render_note_editor(f, app, chunks[2]),
+        AppMode::Projects => render_projects(f, app, chunks[2]),
+        AppMode::Columns => render_column_picker(f, app, chunks[2]),
+        AppMode::Palette => render_palette(f, app, chunks[2]),
+        AppMode::Log => render_log(f, app, chunks[2]),
+        AppMode::Help => render_help(f, &theme, &app.keymap, chunks[2]),
+    }
+
+    // Status bar, which doubles as the filter query bar
+    match app.mode {
+        AppMode::Query => render_query_bar(f, app, chunks[3]),
+        AppMode::SavePreset => render_preset_bar(f, app, chunks[3]),
+        AppMode::RenamePattern => render_rename_bar(f, app, chunks[3]),
+        AppMode::OrganizePattern => render_organize_bar(f, app, chunks[3]),
+        _ => render_status_bar(f, app, chunks[3]),
+    }
+}
+
+fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let area = match &app.search_input {
+        Some(input) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(3)])
+                .split(area);
+            let search = Paragraph::new(input.value())
+                .style(Style::default().fg(theme.accent))
+                .block(Block::default().borders(Borders::ALL).title("Search name/path"));
+            f.render_widget(search, chunks[1]);
+            f.set_cursor(chunks[1].x + input.visual_cursor() as u16 + 1, chunks[1].y + 1);
+            chunks[0]
+        }
+        None => area,
+    };
+    let view = app.view();
+    let filtered = &view.filtered;
+
+    if let (Some(error), true) = (&app.ffprobe_error, app.media_files.is_empty()) {
+        let text = vec![
+            Line::from(Span::styled(
+                "ffprobe is not available",
+                Style::default().fg(theme.bad).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(error.as_str()),
+            Line::from(""),
+            Line::from(FFPROBE_INSTALL_HINT),
+        ];
+        let message = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Files"));
+        f.render_widget(message, area);
+        return;
+    }
+
+    if filtered.is_empty() {
+        let message = if app.media_files.is_empty() {
+            let (add, help) = (app.keymap.label(Action::AddFile), app.keymap.label(Action::Help));
+            format!("No files loaded. Press '{}' to add files, '{}' for help", add, help)
+        } else {
+            "No files match the active filters or search".to_string()
+        };
+        let empty_msg = Paragraph::new(message)
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Files"));
+        f.render_widget(empty_msg, area);
+        return;
+    }
+
+    let (table_area, details_area) = match app.show_details {
+        true => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .split(area);
+            (chunks[0], Some(chunks[1]))
+        }
+        false => (area, None),
+    };
+    let table_rows = &view.rows;
+    // Only the rows on screen are built, and the filtering, sorting and grouping
+    // behind them are kept in the view, so scrolling through tens of thousands of
+    // files costs a screenful per frame. The offset into the rows lives in the
+    // table state and follows the cursor the way ratatui's would.
+    let visible = (table_area.height.saturating_sub(3) as usize).max(1);
+    let selected = app.table_state.selected().unwrap_or(0).min(table_rows.len() - 1);
+    let mut offset = app.table_state.offset().min(selected);
+    if selected >= offset + visible {
+        offset = selected + 1 - visible;
+    }
+    offset = offset.min(table_rows.len().saturating_sub(visible));
+    *app.table_state.offset_mut() = offset;
+    // Borders and the header row
+    app.page_size = visible;
+
+    let profile = app.profile();
+    let window = &table_rows[offset..(offset + visible).min(table_rows.len())];
+    let columns = &view.columns;
+    let header_cells = columns.iter().map(|c| {
+        Cell::from(c.column().header()).style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+    });
+    let header = Row::new(header_cells).height(1);
+    let widths: Vec<Constraint> = columns.iter().map(|c| c.constraint()).collect();
+    // Long names keep their end, where takes and versions usually differ; the
+    // widths are laid out the way the table will, with a spacer taking what's left
+    let mut layout = vec![Constraint::Length(3)];
+    for &width in &widths {
+        layout.extend([width, Constraint::Length(1)]);
+    }
+    layout.push(Constraint::Min(0));
+    let inner_width = table_area.width.saturating_sub(2);
+    let row_area = Rect { x: 0, y: 0, width: inner_width, height: 1 };
+    let column_widths = Layout::default().direction(Direction::Horizontal).constraints(layout).split(row_area);
+    let name_width = columns
+        .iter()
+        .position(|c| c.column() == Column::Name)
+        .map_or(0, |i| column_widths[1 + 2 * i].width as usize);
+
+    let rows = window.iter().map(|row| {
+        // Group headers are drawn over their empty row below, across every column
+        let &TableRow::File(i) = row else {
+            return Row::new(Vec::<Cell>::new()).height(1);
+        };
+        let file = &app.media_files[i];
+        let marked = app.marked.contains(&file.path);
+        let marker = if marked { "● " } else { "  " };
+        let verdict = profile.map(|profile| profile.check(file).verdict);
+        let cell = |column: Column| {
+            let text = match column {
+                Column::Name => {
+                    let name = format!("{}.{}", file.name, file.container);
+                    format!("{}{}", marker, truncate_middle(&name, name_width.saturating_sub(2)))
+                }
+                _ => cell_text(app, file, column, verdict, view.labels.get(&i).map(String::as_str)),
+            };
+            match cell_color(app, file, column, verdict) {
+                Some(color) => Cell::from(text).style(Style::default().fg(color)),
+                None => Cell::from(text),
+            }
+        };
+        let cells: Vec<Cell> = columns.iter().map(|c| cell(c.column())).collect();
+        let style = if marked {
+            Style::default().fg(theme.marked)
+        } else if let Some(color) = app.highlighter.color(file, verdict) {
+            Style::default().fg(color)
+        } else {
+            Style::default()
+        };
+        Row::new(cells).height(1).style(style)
+    });
+
+    // The selected file's whole path along the bottom border, for names cut short above
+    let path = match &table_rows[selected] {
+        TableRow::File(i) => &app.media_files[*i].path,
+        TableRow::Group { name, .. } => name,
+    };
+    let path = truncate_start(path, inner_width.saturating_sub(2) as usize);
+    let path = Title::from(Span::styled(format!(" {} ", path), Style::default().fg(theme.muted)))
+        .position(block::Position::Bottom);
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(format!("Files ({}/{}, {} marked){}",
+            filtered.len(), app.media_files.len(), app.marked.len(),
+            if app.search.is_empty() { String::new() } else { format!(" /{}", app.search) })).title(path))
+        .widths(&widths)
+        .column_spacing(1)
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+
+    let mut window_state = TableState::default().with_selected(Some(selected - offset));
+    f.render_stateful_widget(table, table_area, &mut window_state);
+    let inner = table_area.inner(&Margin { vertical: 1, horizontal: 1 });
+    app.click_targets.headers = header_areas(f, inner, columns);
+    app.click_targets.rows = Some((Rect { y: inner.y + 1, height: inner.height.saturating_sub(1), ..inner }, offset));
+    for (n, row) in window.iter().enumerate() {
+        let TableRow::Group { name, files, collapsed } = row else {
+            continue;
+        };
+        let size: u64 = files.iter().filter_map(|&i| app.media_files[i].file_size).sum();
+        let marked = files.iter().filter(|&&i| app.marked.contains(&app.media_files[i].path)).count();
+        let mut summary = format!(" ({} file(s), {}", files.len(), format_size(Some(size)));
+        if marked > 0 {
+            summary.push_str(&format!(", {} marked", marked));
+        }
+        summary.push(')');
+        let line = Line::from(vec![
+            Span::raw(if offset + n == selected { ">> " } else { "   " }),
+            Span::styled(if *collapsed { "▸ " } else { "▾ " }, Style::default().fg(theme.accent)),
+            Span::styled(name.clone(), Style::default().fg(theme.info).add_modifier(Modifier::BOLD)),
+            Span::styled(summary, Style::default().fg(theme.muted)),
+        ]);
+        let style = match offset + n == selected {
+            true => Style::default().bg(theme.selection),
+            false => Style::default(),
+        };
+        let area = Rect { y: inner.y + 1 + n as u16, height: 1, ..inner };
+        f.render_widget(Paragraph::new(line).style(style), area);
+    }
+    if table_rows.len() > visible {
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None);
+        let mut scrollbar_state = ScrollbarState::new(table_rows.len()).position(selected);
+        // Drawn over the border, below the header row
+        let track = table_area.inner(&Margin { vertical: 1, horizontal: 0 });
+        let track = Rect { y: track.y + 1, height: track.height.saturating_sub(1), ..track };
+        f.render_stateful_widget(scrollbar, track, &mut scrollbar_state);
+    }
+    if let Some(details_area) = details_area {
+        render_details(f, app, details_area);
+    }
+}
+
+/// The group label of each file with a copy: `2` for identical, `~1` for similar.
+fn duplicate_labels(groups: &[(Likeness, Vec<usize>)]) -> HashMap<usize, String> {
+    let mut labels: HashMap<usize, String> = HashMap::new();
+    let (mut identical, mut similar) = (0, 0);
+    for (likeness, group) in groups {
+        let label = match likeness {
+            Likeness::Identical => {
+                identical += 1;
+                identical.to_string()
+            }
+            Likeness::Similar => {
+                similar += 1;
+                format!("~{}", similar)
+            }
+        };
+        for i in group {
+            // Identical groups come first and keep their number
+            labels.entry(*i).or_insert_with(|| label.clone());
+        }
+    }
+    labels
+}
+
+/// A Files table cell as text, the name in full and without the mark.
+fn cell_text(
+    app: &App,
+    file: &MediaInfo,
+    column: Column,
+    verdict: Option<Verdict>,
+    duplicate: Option<&str>,
+) -> String {
+    // Sound files leave the picture columns empty; the Audio column describes them
+    let video = |text: String| if file.audio_only { "-".to_string() } else { text };
+    let or_dash = |text: Option<String>| text.unwrap_or_else(|| "-".to_string());
+    let note = file.note.as_ref();
+    match column {
+        Column::Name => format!("{}.{}", file.name, file.container),
+        Column::Qc => or_dash(verdict.map(|verdict| verdict.label().to_string())),
+        Column::Decode => match app.integrity.get(&file.path) {
+            Some(Integrity::Clean) => "OK".to_string(),
+            Some(integrity) => integrity.label().to_string(),
+            None => "-".to_string(),
+        },
+        Column::Dup => duplicate.unwrap_or_default().to_string(),
+        Column::Checksum => match &file.checksum {
+            // The start of the digest is enough to compare by eye
+            Some(checksum) => checksum.digest.chars().take(12).collect(),
+            None if app.checksumming.contains(&file.path) => "...".to_string(),
+            None => "-".to_string(),
+        },
+        Column::Lufs => match &file.loudness {
+            Some(loudness) => format!("{:.1}", loudness.integrated),
+            None if app.measuring.contains(&file.path) => "...".to_string(),
+            None => "-".to_string(),
+        },
+        Column::Quality => match &file.quality {
+            _ if app.reference.as_ref() == Some(&file.path) => "reference".to_string(),
+            _ if app.measuring_quality.contains(&file.path) => "...".to_string(),
+            Some(quality) => quality.summary(),
+            None => "-".to_string(),
+        },
+        Column::HwDecode => match &file.hw_decode {
+            Some(hw) if !file.audio_only => hw.label().to_string(),
+            _ => "-".to_string(),
+        },
+        Column::Scan => match file.scan.as_str() {
+            _ if app.detecting_scan.contains(&file.path) => "...".to_string(),
+            scan => interlace::short_label(scan).to_string(),
+        },
+        Column::Picture => match &file.crop {
+            Some(crop) => crop.size(),
+            None if app.detecting_crop.contains(&file.path) => "...".to_string(),
+            None => "-".to_string(),
+        },
+        Column::Timecode => or_dash(file.timecode.clone()),
+        Column::Languages => file.audio_languages.join(", "),
+        // The date is enough in the table, the details have the time
+        Column::Created => file.creation_time.as_deref().and_then(|t| t.get(..10)).unwrap_or("-").to_string(),
+        Column::Audio => if file.audio_only { format_audio_summary(file) } else { "-".to_string() },
+        Column::Mxf => or_dash(file.mxf.as_ref().map(|mxf| match &mxf.operational_pattern {
+            Some(pattern) => format!("{} {}", pattern, mxf.essence),
+            None => mxf.essence.clone(),
+        })),
+        Column::Tags => or_dash(note.filter(|note| !note.tags.is_empty()).map(|note| note.tags.join(", "))),
+        Column::Note => or_dash(note.filter(|note| !note.text.is_empty()).map(|note| note.text.clone())),
+        Column::Rating => or_dash(note.and_then(|note| note.rating).map(notes::format_stars)),
+        Column::Flag => or_dash(note.and_then(|note| note.flag).map(|flag| flag.label().to_string())),
+        Column::Container => file.container.clone(),
+        Column::Codec => video(file.codec.clone()),
+        Column::Resolution => video(format_resolution(file)),
+        // Anamorphic files are flagged, their stored size is narrower than what plays
+        Column::Aspect => match is_anamorphic(file) {
+            true => format!("{}*", format_aspect(display_aspect(file))),
+            false => video(format_aspect(display_aspect(file))),
+        },
+        Column::Fps => video(if file.variable_frame_rate {
+            format!("{} VFR", file.frame_rate)
+        } else {
+            file.frame_rate.clone()
+        }),
+        Column::Bitrate => format_bitrate(file),
+        Column::Duration => format_duration(file.duration),
+        Column::Size => format_size(file.file_size),
+        Column::PixFmt => video(file.pixel_format.clone()),
+        Column::Depth => video(format_bit_depth(file.bit_depth)),
+        Column::Hdr => video(file.hdr_format.clone()),
+        Column::Alpha => video(yes_no(file.has_alpha).to_string()),
+        Column::Faststart => faststart_label(file.faststart).to_string(),
+    }
+}
+
+/// The color a Files table cell stands out in, `None` for the row's own.
+fn cell_color(app: &App, file: &MediaInfo, column: Column, verdict: Option<Verdict>) -> Option<Color> {
+    let theme = &app.theme;
+    match column {
+        Column::Qc => verdict.map(|verdict| verdict.color(theme)),
+        Column::Decode => Some(match app.integrity.get(&file.path) {
+            Some(Integrity::Clean) => theme.good,
+            Some(Integrity::Corrupt(_)) => theme.bad,
+            Some(_) => theme.muted,
+            None => theme.faint,
+        }),
+        Column::Dup => Some(theme.accent),
+        Column::Quality if app.reference.as_ref() == Some(&file.path) => Some(theme.info),
+        Column::HwDecode if file.audio_only => None,
+        Column::HwDecode => file.hw_decode.as_ref().map(|hw| match hw.api {
+            Some(_) => theme.good,
+            None => theme.muted,
+        }),
+        Column::Scan if app.detecting_scan.contains(&file.path) => Some(theme.muted),
+        Column::Scan => interlace::is_interlaced(&file.scan).then_some(theme.bad),
+        Column::Picture => match &file.crop {
+            Some(_) if matte(file) == "Full" => None,
+            Some(_) => Some(theme.warning),
+            None if app.detecting_crop.contains(&file.path) => Some(theme.muted),
+            None => Some(theme.faint),
+        },
+        Column::Rating => file.note.as_ref().and_then(|note| note.rating).map(|_| theme.accent),
+        Column::Flag => file.note.as_ref().and_then(|note| note.flag).map(|flag| match flag {
+            Flag::Accepted => theme.good,
+            Flag::Rejected => theme.bad,
+        }),
+        Column::Aspect => is_anamorphic(file).then_some(theme.accent),
+        _ => None,
+    }
+}
+
+/// Where the table drew each sortable header on the first line of `inner`. The
+/// table doesn't report its column layout, so the names are found in the drawn
+/// line, left to right; a header cut short by a narrow column still matches.
+fn header_areas(f: &mut Frame, inner: Rect, columns: &[ColumnSpec]) -> Vec<(Rect, SortKey)> {
+    let buffer = f.buffer_mut();
+    let line: Vec<char> = (inner.x..inner.right())
+        .map(|x| buffer.get(x, inner.y).symbol.chars().next().unwrap_or(' '))
+        .collect();
+    let mut starts: Vec<(usize, Column)> = Vec::new();
+    let mut cursor = 0;
+    for column in columns.iter().map(|c| c.column()) {
+        let name: Vec<char> = column.header().chars().collect();
+        let found = (cursor..line.len()).find(|&start| {
+            let shown = line[start..].iter().zip(&name).take_while(|(a, b)| a == b).count();
+            let ends = line.get(start + shown).is_none_or(|&c| c == ' ');
+            shown > 0 && (shown == name.len() || ends) && (start == 0 || line[start - 1] == ' ')
+        });
+        if let Some(start) = found {
+            starts.push((start, column));
+            cursor = start + 1;
+        }
+    }
+    let ends = starts.iter().skip(1).map(|&(start, _)| start).chain([line.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .filter_map(|(&(start, column), end)| {
+            let area = Rect { x: inner.x + start as u16, y: inner.y, width: (end - start) as u16, height: 1 };
+            Some((area, SortKey::for_column(column)?))
+        })
+        .collect()
+}
+
+/// `text` cut to `width` characters by dropping its middle, `long_render_…_v03.mov`.
+fn truncate_middle(text: &str, width: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    // Most of what's kept is the end, with the version and extension
+    let head = (width - 1) / 3;
+    let tail = width - 1 - head;
+    let mut cut: String = chars[..head].iter().collect();
+    cut.push('…');
+    cut.extend(&chars[chars.len() - tail..]);
+    cut
+}
+
+/// `text` cut to `width` characters from the start, keeping the part of a path that differs.
+fn truncate_start(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut cut = "…".to_string();
+    cut.extend(text.chars().skip(count - (width - 1)));
+    cut
+}
+
+/// One file's path, or how many there are.
+fn describe_paths(paths: &[String]) -> String {
+    match paths {
+        [path] => path.clone(),
+        paths => format!("{} files", paths.len()),
+    }
+}
+
+fn detail_line(theme: &Theme, label: &str, value: impl Into<String>) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{:<12}", label), Style::default().fg(theme.accent)),
+        Span::raw(value.into()),
+    ])
+}
+
+fn section_line(theme: &Theme, title: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        title.to_string(),
+        Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
+    ))
+}
+
+/// One value the details panel compares between two readings of a file.
+type Field = fn(&MediaInfo) -> String;
+
+/// Structured summary of everything known about a file, for the side panel.
+fn detail_lines(
+    theme: &Theme,
+    file: &MediaInfo,
+    raw_output: &str,
+    profile: Option<&ValidationProfile>,
+    integrity: Option<&Integrity>,
+    cross_check: Option<&MediaInfo>,
+) -> Vec<Line<'static>> {
+    let probe: Value = serde_json::from_str(raw_output).unwrap_or(Value::Null);
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "Unknown".to_string(),
+        other => other.to_string(),
+    };
+
+    let mut lines = vec![
+        detail_line(theme, "Name", format!("{}.{}", file.name, file.container)),
+        detail_line(theme, "Path", file.path.clone()),
+        detail_line(theme, "Analyzer", file.analyzer.label()),
+        detail_line(theme, "Container", file.container.clone()),
+        detail_line(theme, "Codec", if file.audio_only { file.audio_codec.clone() } else { file.codec.clone() }),
+    ];
+    if let Some(note) = &file.note {
+        if !note.tags.is_empty() {
+            lines.push(detail_line(theme, "Tags", note.tags.join(", ")));
+        }
+        if !note.text.is_empty() {
+            lines.push(detail_line(theme, "Note", note.text.clone()));
+        }
+        if let Some(stars) = note.rating {
+            lines.push(detail_line(theme, "Rating", notes::format_stars(stars)));
+        }
+        if let Some(flag) = note.flag {
+            lines.push(detail_line(theme, "Flag", flag.label()));
+        }
+    }
+    if !file.audio_only {
+        let resolution = match file.rotation {
+            0 => format!("{} ({})", file.resolution, orientation(file).to_lowercase()),
+            rotation => format!(
+                "{} stored, {} displayed (rotated {}°, {})",
+                file.resolution,
+                display_resolution(file),
+                rotation,
+                orientation(file).to_lowercase()
+            ),
+        };
+        let aspect = format_aspect(display_aspect(file));
+        let aspect = match is_anamorphic(file) {
+            true => format!("{} display, SAR {} (anamorphic)", aspect, file.sample_aspect_ratio),
+            false => format!("{} display, square pixels", aspect),
+        };
+        lines.push(detail_line(theme, "Resolution", resolution));
+        lines.push(detail_line(theme, "Aspect", aspect));
+    }
+    // Timing, streams and muxing mean nothing for a still
+    let still = file.image.is_some();
+    if !still && !file.audio_only {
+        let fps = format!("{} ({})", file.frame_rate, frame_rate_mode(file.variable_frame_rate));
+        let scan = format!("{} ({})", file.scan, if file.scan_detected { "idet" } else { "field_order tag" });
+        lines.extend([
+            detail_line(theme, "FPS", fps),
+            detail_line(theme, "Scan", scan),
+        ]);
+    }
+    if file.audio_only {
+        lines.push(detail_line(theme, "Audio", format_audio_summary(file)));
+    }
+    if !still {
+        lines.extend([
+            detail_line(theme, "Bitrate", format!("{} Mbps", format_bitrate(file))),
+            detail_line(theme, "Duration", match (file.duration, is_stream(&file.path)) {
+                (None, true) => "Live".to_string(),
+                (duration, _) => format_duration(duration),
+            }),
+            detail_line(theme, "Timecode", file.timecode.clone().unwrap_or_else(|| "None".to_string())),
+            detail_line(theme, "Chapters", file.chapters.len().to_string()),
+        ]);
+    }
+    if let Some(sequence) = &file.sequence {
+        let mut frames = format!("{} ({})", sequence.frames, sequence.range());
+        if sequence.missing() > 0 {
+            frames.push_str(&format!(", {} missing", sequence.missing()));
+        }
+        lines.push(detail_line(theme, "Frames", frames));
+    }
+    lines.extend([
+        detail_line(theme, "Created", file.creation_time.clone().unwrap_or_else(|| "Unknown".to_string())),
+        detail_line(theme, "Encoder", file.encoder.clone().unwrap_or_else(|| "Unknown".to_string())),
+        detail_line(theme, "Size", format_size(file.file_size)),
+    ]);
+    if !still {
+        let subtitles = match file.subtitles.is_empty() {
+            true => "None".to_string(),
+            false => file.subtitles.join(", "),
+        };
+        lines.extend([
+            detail_line(theme, "Faststart", faststart_label(file.faststart)),
+            detail_line(theme, "Languages", format_languages(file)),
+            detail_line(theme, "Tracks", format_audio_layout(file)),
+            detail_line(theme, "Subtitles", subtitles),
+            detail_line(theme, "Captions", if file.closed_captions { "CEA-608/708" } else { "None" }),
+        ]);
+    }
+    if let Some(title) = &file.title {
+        lines.push(detail_line(theme, "Title", title.clone()));
+    }
+    if let Some(artist) = &file.artist {
+        lines.push(detail_line(theme, "Artist", artist.clone()));
+    }
+    if let Some(crop) = &file.crop {
+        let picture = format!("{} at {},{} ({})", crop.size(), crop.x, crop.y, matte(file));
+        lines.push(detail_line(theme, "Picture", picture));
+    }
+    if let Some(loudness) = &file.loudness {
+        let loudness = format!(
+            "{:.1} LUFS, true peak {:.1} dBTP, range {:.1} LU",
+            loudness.integrated, loudness.true_peak, loudness.range
+        );
+        lines.push(detail_line(theme, "Loudness", loudness));
+    }
+    if let Some(quality) = &file.quality {
+        let mut scores = vec![format!("PSNR {:.2} dB", quality.psnr), format!("SSIM {:.4}", quality.ssim)];
+        if let Some(vmaf) = quality.vmaf {
+            scores.insert(0, format!("VMAF {:.2}", vmaf));
+        }
+        let reference = Path::new(&quality.reference).file_name().unwrap_or_default().to_string_lossy();
+        lines.push(detail_line(theme, "Quality", format!("{} against {}", scores.join(", "), reference)));
+    }
+    if let Some(benchmark) = &file.benchmark {
+        let timing = format!("{} frames in {:.1}s", benchmark.frames, benchmark.seconds);
+        lines.push(detail_line(theme, "Benchmark", format!("{} ({})", benchmark.summary(), timing)));
+    }
+    if let Some(hw) = &file.hw_decode {
+        let decode = match hw.api {
+            Some(_) => hw.reason.clone(),
+            None => format!("No, {}", hw.reason),
+        };
+        lines.push(detail_line(theme, "HW decode", decode));
+    }
+    if let Some(checksum) = &file.checksum {
+        lines.push(detail_line(theme, checksum.algorithm.label(), checksum.digest.clone()));
+    }
+
+    if let Some(profile) = profile {
+        let report = profile.check(file);
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, &format!("QC - {}", profile.name)));
+        lines.push(Line::from(Span::styled(
+            report.verdict.label(),
+            Style::default().fg(report.verdict.color(theme)).add_modifier(Modifier::BOLD),
+        )));
+        for (severity, reason) in report.reasons {
+            lines.push(Line::from(Span::styled(
+                format!("{} {}", severity.label(), reason),
+                Style::default().fg(severity.color(theme)),
+            )));
+        }
+    }
+
+    if let Some(integrity) = integrity {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, "Decode check"));
+        match integrity {
+            Integrity::Corrupt(errors) => {
+                for error in errors {
+                    lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(theme.bad))));
+                }
+            }
+            Integrity::Clean => lines.push(Line::from(Span::styled(
+                "Decodes without errors",
+                Style::default().fg(theme.good),
+            ))),
+            other => lines.push(Line::from(other.label())),
+        }
+    }
+
+    if let Some(blanks) = &file.blanks {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, "Black & silence"));
+        let segments = [("Black", &blanks.black), ("Silence", &blanks.silence)]
+            .into_iter()
+            .flat_map(|(kind, segments)| segments.iter().map(move |s| (kind, s)));
+        for (kind, segment) in segments {
+            let position = Position::of(segment, file.duration);
+            // Leading and trailing stretches are the ones that go to air
+            let color = if position == Position::Middle { theme.muted } else { theme.bad };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<12}", kind), Style::default().fg(theme.accent)),
+                Span::styled(
+                    format!(
+                        "{} - {} ({:.2}s, {})",
+                        blanks::format_timestamp(segment.start),
+                        blanks::format_timestamp(segment.end),
+                        segment.duration(),
+                        position.label()
+                    ),
+                    Style::default().fg(color),
+                ),
+            ]));
+        }
+        if blanks.black.is_empty() && blanks.silence.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No black frames or silence",
+                Style::default().fg(theme.good),
+            )));
+        }
+    }
+
+    if let Some(video) = first_stream(&probe, "video") {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, if still { "Image" } else { "Video" }));
+        lines.push(detail_line(theme, "Pixel fmt", file.pixel_format.clone()));
+        lines.push(detail_line(theme, "Bit depth", format_bit_depth(file.bit_depth)));
+        lines.push(detail_line(theme, "Chroma", chroma_subsampling(&file.pixel_format)));
+        if !still {
+            lines.push(detail_line(theme, "Profile", text(&video["profile"])));
+            lines.push(detail_line(theme, "Level", text(&video["level"])));
+        }
+        lines.push(detail_line(theme, "Color space", file.color_space.clone()));
+        lines.push(detail_line(theme, "Primaries", file.color_primaries.clone()));
+        lines.push(detail_line(theme, "Transfer", file.color_transfer.clone()));
+        lines.push(detail_line(theme, "HDR", file.hdr_format.clone()));
+        lines.push(detail_line(theme, "Alpha", yes_no(file.has_alpha)));
+    }
+
+    if let Some(mxf) = &file.mxf {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, "MXF"));
+        let known = |value: &Option<String>| value.clone().unwrap_or_else(|| "Unknown".to_string());
+        lines.push(detail_line(theme, "Pattern", known(&mxf.operational_pattern)));
+        lines.push(detail_line(theme, "Essence", mxf.essence.clone()));
+        lines.push(detail_line(theme, "Writer", known(&mxf.writer)));
+    }
+
+    if let Some(image) = &file.image {
+        let known = |value: &Option<String>| value.clone().unwrap_or_else(|| "None".to_string());
+        lines.push(detail_line(theme, "ICC profile", known(&image.color_profile)));
+        lines.push(detail_line(theme, "Camera", known(&image.camera)));
+        if let Some(lens) = &image.lens {
+            lines.push(detail_line(theme, "Lens", lens.clone()));
+        }
+        lines.push(detail_line(theme, "Captured", known(&image.captured)));
+        if let Some(exposure) = &image.exposure {
+            lines.push(detail_line(theme, "Exposure", exposure.clone()));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section_line(theme, "Media servers"));
+    for server in MediaServer::ALL {
+        let compat = compat::check(server, file);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<14}", server.name()), Style::default().fg(theme.accent)),
+            Span::styled(compat.support.label(), Style::default().fg(compat.support.color(theme))),
+        ]));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", compat.reason),
+            Style::default().fg(theme.muted),
+        )));
+    }
+
+    let audio_streams = probe["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|s| s["codec_type"] == "audio");
+    for (i, audio) in audio_streams.enumerate() {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, &format!("Audio #{}", i + 1)));
+        lines.push(detail_line(theme, "Codec", text(&audio["codec_name"])));
+        lines.push(detail_line(theme, "Channels", text(&audio["channels"])));
+        lines.push(detail_line(theme, "Sample rate", text(&audio["sample_rate"])));
+        let bits = audio_bit_depth(audio).map_or("N/A".to_string(), |bits| format!("{}-bit", bits));
+        lines.push(detail_line(theme, "Bit depth", bits));
+    }
+
+    if let Some(other) = cross_check {
+        lines.push(Line::from(""));
+        lines.push(section_line(theme, &format!("Cross-check ({})", other.analyzer.label())));
+        let fields: [(&str, Field); 11] = [
+            ("Container", |f| f.container.clone()),
+            ("Codec", |f| f.codec.clone()),
+            ("Resolution", |f| f.resolution.clone()),
+            ("FPS", |f| f.frame_rate.clone()),
+            ("Bitrate", format_bitrate),
+            ("Duration", |f| format_duration(f.duration)),
+            ("Pixel format", |f| f.pixel_format.clone()),
+            ("Bit depth", |f| format_bit_depth(f.bit_depth)),
+            ("HDR", |f| f.hdr_format.clone()),
+            ("Scan", |f| f.scan.clone()),
+            ("Audio", format_audio_summary),
+        ];
+        let disagreements: Vec<_> = fields.iter().filter(|(_, value)| value(file) != value(other)).collect();
+        if disagreements.is_empty() {
+            lines.push(Line::from(Span::styled("Both tools agree", Style::default().fg(theme.good))));
+        }
+        for (label, value) in disagreements {
+            lines.push(detail_line(theme, label, format!("{} here, {} there", value(file), value(other))));
+        }
+    }
+
+    lines
+}
+
+/// The selected file's frame, as an image when the terminal supports one.
+fn render_thumbnail(f: &mut Frame, app: &mut App, file_path: &str, duration: Option<f64>, area: Rect) {
+    let theme = app.theme;
+    let block = Block::default().borders(Borders::ALL).title("Preview");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    // A short or narrow terminal leaves no room inside the border
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    let thumbnail = match app.thumbnails.get(file_path) {
+        Some(Ok(thumbnail)) => thumbnail,
+        Some(Err(e)) => {
+            let message = Paragraph::new(format!("No preview: {}", e))
+                .style(Style::default().fg(theme.bad))
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, inner);
+            return;
+        }
+        None => {
+            if app.thumbnail_pending.insert(file_path.to_string()) {
+                app.thumbnailer.request(file_path.to_string(), duration);
+            }
+            f.render_widget(Paragraph::new("Loading preview...").style(Style::default().fg(theme.muted)), inner);
+            return;
+        }
+    };
+
+    // Half blocks make each cell two roughly square pixels
+    let cell = if app.graphics == Graphics::Blocks { (1, 2) } else { app.cell_size };
+    let (cols, rows) = thumbnail.fit(inner, cell);
+    let image_area = Rect {
+        x: inner.x + inner.width.saturating_sub(cols) / 2,
+        y: inner.y + inner.height.saturating_sub(rows) / 2,
+        width: cols,
+        height: rows,
+    };
+    if app.graphics == Graphics::Blocks {
+        f.render_widget(Paragraph::new(thumbnail::block_lines(thumbnail, cols, rows)), image_area);
+    } else {
+        app.thumbnail_overlay = Some((file_path.to_string(), image_area));
+    }
+}
+
+fn render_details(f: &mut Frame, app: &mut App, area: Rect) {
+    let selected = app.selected_file().map(|file| (file.path.clone(), file.duration));
+    let area = match &selected {
+        Some((path, duration)) if app.show_thumbnail => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(area.height / 2), Constraint::Min(0)])
+                .split(area);
+            render_thumbnail(f, app, path, *duration, chunks[0]);
+            chunks[1]
+        }
+        _ => area,
+    };
+    let raw_output = selected.map(|(path, _)| app.raw_store.get(&path)).unwrap_or_default();
+    let lines = match app.selected_file() {
+        Some(file) => detail_lines(
+            &app.theme,
+            file,
+            &raw_output,
+            app.profile(),
+            app.integrity.get(&file.path),
+            app.cross_checks.get(&file.path),
+        ),
+        None => vec![Line::from("No file selected")],
+    };
+    let details = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Details"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, area);
+}
+
+fn render_filters(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let presets = app.presets.iter().enumerate().map(|(i, preset)| {
+        let (marker, style) = if app.preset_active(preset) {
+            ("[x]", Style::default().fg(theme.good))
+        } else {
+            ("[ ]", Style::default().fg(theme.info))
+        };
+        let label = format!("{} Preset {}: {} ({})", marker, i + 1, preset.name, preset.query);
+        ListItem::new(label).style(style)
+    });
+    let options = app
+        .filter_options
+        .entries()
+        .into_iter()
+        .map(|(filter_type, value)| {
+            let state = app.option_state(&ActiveFilter::option(filter_type.clone(), value.clone()));
+            let (marker, style) = match state {
+                Some(false) => ("[x]", Style::default().fg(theme.good)),
+                Some(true) => ("[-]", Style::default().fg(theme.bad)),
+                None => ("[ ]", Style::default()),
+            };
+            ListItem::new(format!("{} {}: {}", marker, filter_type.label(), value)).style(style)
+        });
+    let items: Vec<ListItem> = presets.chain(options).collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Filters ({} active)",
+            app.active_filter_count()
+        )))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+    let summary = if app.active_filters.is_empty() {
+        format!("None - toggle options below or press '{}' to type a query", app.keymap.label(Action::Query))
+    } else {
+        query::to_query(&app.active_filters)
+    };
+    let summary = Paragraph::new(summary)
+        .style(Style::default().fg(theme.good))
+        .block(Block::default().borders(Borders::ALL).title("Active"));
+    f.render_widget(summary, chunks[0]);
+
+    app.page_size = chunks[1].height.saturating_sub(2) as usize;
+    f.render_stateful_widget(list, chunks[1], &mut app.filter_list_state);
+}
+
+fn render_fixes(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let name = app
+        .selected_file()
+        .map(|file| format!("{}.{}", file.name, file.container))
+        .unwrap_or_default();
+    let suggestions = app.fix_suggestions();
+    if suggestions.is_empty() {
+        let message = Paragraph::new("Nothing to fix - the file passes the QC profile and plays natively everywhere")
+            .style(Style::default().fg(theme.good))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title(format!("Fixes - {}", name)));
+        f.render_widget(message, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(suggestions.len() as u16 + 2), Constraint::Min(3)])
+        .split(area);
+    let items: Vec<ListItem> = suggestions.iter().map(|s| ListItem::new(s.title.clone())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Fixes - {}", name)))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[0], &mut app.fix_list_state);
+
+    let selected = app.fix_list_state.selected().unwrap_or(0);
+    let (command, output) = suggestions
+        .get(selected)
+        .map(|s| (s.command_line(&app.ffmpeg), s.output.clone()))
+        .unwrap_or_default();
+    let command = Paragraph::new(command)
+        .style(Style::default().fg(theme.accent))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(format!("Command - writes {}", output)));
+    f.render_widget(command, chunks[1]);
+}
+
+fn render_rename(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let name = |path: &str| Path::new(path).file_name().unwrap_or_default().to_string_lossy().to_string();
+    let items: Vec<ListItem> = app
+        .renames
+        .iter()
+        .map(|rename| match &rename.skipped {
+            None => ListItem::new(Line::from(vec![
+                Span::raw(name(&rename.from)),
+                Span::styled(" → ", Style::default().fg(theme.muted)),
+                Span::styled(name(&rename.to), Style::default().fg(theme.good)),
+            ])),
+            Some(reason) => ListItem::new(format!("{} - skipped, {}", name(&rename.from), reason))
+                .style(Style::default().fg(theme.muted)),
+        })
+        .collect();
+    let skipped = app.renames.iter().filter(|r| r.skipped.is_some()).count();
+    let title = format!(
+        "Rename - {} file(s) with {}{}",
+        app.renames.len() - skipped,
+        app.rename_pattern,
+        if skipped > 0 { format!(", {} skipped", skipped) } else { String::new() }
+    );
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.rename_list_state);
+}
+
+fn render_organize(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let name = |path: &str| Path::new(path).file_name().unwrap_or_default().to_string_lossy().to_string();
+    let items: Vec<ListItem> = app
+        .placements
+        .iter()
+        .map(|placement| match &placement.skipped {
+            None => ListItem::new(Line::from(vec![
+                Span::raw(name(&placement.from)),
+                Span::styled(" → ", Style::default().fg(theme.muted)),
+                Span::styled(placement.to.clone(), Style::default().fg(theme.good)),
+            ])),
+            Some(reason) => ListItem::new(format!("{} - skipped, {}", name(&placement.from), reason))
+                .style(Style::default().fg(theme.muted)),
+        })
+        .collect();
+    let skipped = app.placements.iter().filter(|p| p.skipped.is_some()).count();
+    let title = format!(
+        "Organize - dry run of {} file(s) into {}{}",
+        app.placements.len() - skipped,
+        app.organize_pattern,
+        if skipped > 0 { format!(", {} skipped", skipped) } else { String::new() }
+    );
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.organize_list_state);
+}
+
+fn render_confirm_delete(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let size: u64 = app
+        .media_files
+        .iter()
+        .filter(|file| app.deleting.contains(&file.path))
+        .filter_map(|file| file.file_size)
+        .sum();
+    let items: Vec<ListItem> = app.deleting.iter().map(|path| ListItem::new(path.as_str())).collect();
+    let title = format!("Delete {} file(s) from disk, {}?", app.deleting.len(), format_size(Some(size)));
+    let list = List::new(items)
+        .style(Style::default().fg(theme.bad))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, area);
+}
+
+fn render_duplicates(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let groups = app.duplicate_groups();
+    let mut items = Vec::new();
+    let mut reclaimable = 0;
+    let (mut identical, mut similar) = (0, 0);
+    let header_style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+    for (likeness, group) in &groups {
+        let best = match likeness {
+            Likeness::Identical => {
+                identical += 1;
+                let size = app.media_files[group[0]].file_size.unwrap_or(0);
+                let spare = size * (group.len() as u64 - 1);
+                reclaimable += spare;
+                items.push(ListItem::new(format!(
+                    "Group {} - {} copies of {}, {} in spares",
+                    identical,
+                    group.len(),
+                    format_size(Some(size)),
+                    format_size(Some(spare))
+                )).style(header_style));
+                None
+            }
+            Likeness::Similar => {
+                similar += 1;
+                items.push(ListItem::new(format!(
+                    "Similar ~{} - {} encodes of the same picture, ★ marks the best master",
+                    similar,
+                    group.len()
+                )).style(header_style));
+                phash::best(&app.media_files, group)
+            }
+        };
+        for &i in group {
+            let file = &app.media_files[i];
+            let marked = app.marked.contains(&file.path);
+            let mut text = format!("  {}{}", if marked { "● " } else { "  " }, file.path);
+            if *likeness == Likeness::Similar {
+                text.push_str(&format!(
+                    "  ({}, {}, {} Mbps){}",
+                    file.codec,
+                    file.resolution,
+                    format_bitrate(file),
+                    if best == Some(i) { " ★" } else { "" }
+                ));
+            }
+            let item = ListItem::new(text);
+            items.push(if marked { item.style(Style::default().fg(theme.marked)) } else { item });
+        }
+    }
+
+    let title = format!(
+        "Duplicates - {} identical group(s), {} reclaimable, {} similar group(s)",
+        identical,
+        format_size(Some(reclaimable)),
+        similar
+    );
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.duplicates_state);
+}
+
+/// The bitrate graph or GOP report, once the file's packets are in.
+fn render_packet_view(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let path = app.packet_file.clone().unwrap_or_default();
+    let is_bitrate = matches!(app.mode, AppMode::Bitrate);
+    let title = if is_bitrate { "Bitrate" } else { "GOP structure" };
+    let block = Block::default().borders(Borders::ALL).title(format!("{} - {}", title, path));
+    let (message, color) = match app.packets.get(&path) {
+        Some(Ok(packets)) if is_bitrate => {
+            render_bitrate_chart(f, &theme, packets, app.peak_bitrate, block, area);
+            return;
+        }
+        Some(Ok(packets)) => {
+            render_gop(f, &theme, packets, block, area);
+            return;
+        }
+        Some(Err(e)) => (format!("Could not read packets: {}", e), theme.bad),
+        None => ("Reading packets...".to_string(), theme.muted),
+    };
+    f.render_widget(Paragraph::new(message).style(Style::default().fg(color)).block(block), area);
+}
+
+/// Bitrate over consecutive windows of the timeline, with the windows above the
+/// peak threshold (twice the average unless configured) marked in red.
+fn render_bitrate_chart(f: &mut Frame, theme: &Theme, packets: &[Packet], peak_bitrate: Option<f64>, block: Block, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(4)])
+        .split(area);
+    let start = packets[0].time;
+    let span = packets[packets.len() - 1].time - start;
+    // One-second windows, widened on long files to about one per braille dot
+    let window = (span / (chunks[0].width.max(1) as f64 * 2.0)).max(1.0);
+    let series = packets::bitrate_series(packets, window);
+    let average = packets.iter().map(|p| p.size as f64 * 8.0).sum::<f64>()
+        / span.max(window)
+        / 1_000_000.0;
+    let threshold = peak_bitrate.unwrap_or(average * 2.0);
+    let peaks: Vec<(f64, f64)> = series.iter().copied().filter(|(_, mbps)| *mbps > threshold).collect();
+    let (max_time, max) = series
+        .iter()
+        .copied()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((start, 0.0));
+
+    let end = start + span.max(window);
+    let top = max.max(threshold) * 1.1;
+    let threshold_line = [(start, threshold), (end, threshold)];
+    let datasets = vec![
+        Dataset::default()
+            .name("Mbps")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.info))
+            .data(&series),
+        Dataset::default()
+            .name(format!("Peak > {:.1}", threshold))
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.warning))
+            .data(&threshold_line),
+        Dataset::default()
+            .marker(symbols::Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(theme.bad).add_modifier(Modifier::BOLD))
+            .data(&peaks),
+    ];
+    let label = |text: String| Span::styled(text, Style::default().fg(theme.muted));
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(Axis::default().bounds([start, end]).labels(vec![
+            label(format_duration(Some(start))),
+            label(format_duration(Some((start + end) / 2.0))),
+            label(format_duration(Some(end))),
+        ]))
+        .y_axis(Axis::default().bounds([0.0, top]).labels(vec![
+            label("0".to_string()),
+            label(format!("{:.0}", top / 2.0)),
+            label(format!("{:.0}", top)),
+        ]));
+    f.render_widget(chart, chunks[0]);
+
+    let keyframes = packets.iter().filter(|p| p.keyframe).count();
+    let mut lines = vec![Line::from(format!(
+        "Average {:.1} Mbps, max {:.1} Mbps at {} ({:.1}x), {} packets, {} keyframes, {:.1}s windows",
+        average,
+        max,
+        format_duration(Some(max_time)),
+        max / average.max(f64::EPSILON),
+        packets.len(),
+        keyframes,
+        window
+    ))];
+    lines.push(match peaks.first() {
+        Some((first, _)) => Line::from(Span::styled(
+            format!(
+                "{} window(s) above {:.1} Mbps, the first at {}",
+                peaks.len(),
+                threshold,
+                format_duration(Some(*first))
+            ),
+            Style::default().fg(theme.bad),
+        )),
+        None => Line::from(Span::styled(
+            format!("No windows above {:.1} Mbps", threshold),
+            Style::default().fg(theme.good),
+        )),
+    });
+    let stats = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Peaks"));
+    f.render_widget(stats, chunks[1]);
+}
+
+/// Keyframe spacing and frame reordering, with the GOP lengths across the file
+/// as a sparkline so drifting or scene-cut keyframes stand out.
+fn render_gop(f: &mut Frame, theme: &Theme, packets: &[Packet], block: Block, area: Rect) {
+    let stats = packets::gop_stats(packets);
+    let warnings = stats.warnings();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(7),
+            Constraint::Length(warnings.len().max(1) as u16 + 2),
+            Constraint::Min(3),
+        ])
+        .split(area);
+
+    let seconds = |value: Option<f64>| value.map_or("N/A".to_string(), |s| format!("{:.2}s", s));
+    let frames = |count: Option<&usize>| count.map_or("N/A".to_string(), |n| format!("{} frames", n));
+    let structure = if stats.intra_only() {
+        "Intra-only, every frame is a keyframe".to_string()
+    } else if stats.bframes > 0 {
+        "I, P and B-frames".to_string()
+    } else {
+        "I and P-frames, no B-frames".to_string()
+    };
+    let lines = vec![
+        Line::from(format!("Structure:          {}", structure)),
+        Line::from(format!("Frames:             {} ({} keyframes, {} B-frames)", stats.frames, stats.keyframes, stats.bframes)),
+        Line::from(format!("GOP length:         {} to {}", frames(stats.lengths.iter().min()), frames(stats.lengths.iter().max()))),
+        Line::from(format!("Keyframe interval:  {} average, {} longest", seconds(stats.mean_interval()), seconds(stats.max_interval()))),
+        Line::from(format!(
+            "Regularity:         {}",
+            stats.irregularity().map_or("N/A".to_string(), |spread| match spread {
+                s if s < 0.05 => "fixed".to_string(),
+                s => format!("varies by {:.0}%", s * 100.0),
+            })
+        )),
+    ];
+    f.render_widget(Paragraph::new(lines).block(block), chunks[0]);
+
+    let warning_lines: Vec<Line> = match warnings.is_empty() {
+        true => vec![Line::from(Span::styled(
+            "Short, regular GOP: fine for scrubbing and looping",
+            Style::default().fg(theme.good),
+        ))],
+        false => warnings
+            .into_iter()
+            .map(|w| Line::from(Span::styled(w, Style::default().fg(theme.warning))))
+            .collect(),
+    };
+    let warning_block = Block::default().borders(Borders::ALL).title("Warnings");
+    f.render_widget(Paragraph::new(warning_lines).block(warning_block).wrap(Wrap { trim: true }), chunks[1]);
+
+    let gops: Vec<u64> = stats.lengths.iter().map(|&n| n as u64).collect();
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title("Frames per GOP"))
+        .style(Style::default().fg(theme.info))
+        .data(&gops);
+    f.render_widget(sparkline, chunks[2]);
+}
+
+/// `[██████░░░░] 60%`, or a bare marker while the length is unknown.
+fn progress_bar(fraction: Option<f64>) -> String {
+    let Some(fraction) = fraction else {
+        return "[ running  ]".to_string();
+    };
+    let filled = (fraction * 10.0).round() as usize;
+    format!("[{}{}] {:>3.0}%", "█".repeat(filled), "░".repeat(10 - filled), fraction * 100.0)
+}
+
+fn render_chapters(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let Some(file) = app.selected_file() else {
+        return;
+    };
+    let title = format!("Chapters - {}.{} ({})", file.name, file.container, file.chapters.len());
+    let header = Row::new(["#", "Start", "End", "Length", "Title", "Notes"].map(|h| {
+        Cell::from(h).style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+    }));
+    let rows: Vec<Row> = file
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            // Marks that don't line up are what authoring tools and players choke on
+            let previous_end = i.checked_sub(1).map(|p| file.chapters[p].end);
+            let mut notes = Vec::new();
+            match previous_end {
+                Some(end) if chapter.start < end - 0.001 => notes.push("overlaps previous"),
+                Some(end) if chapter.start > end + 0.001 => notes.push("gap before"),
+                _ => {}
+            }
+            if chapter.end <= chapter.start {
+                notes.push("empty");
+            }
+            if file.duration.is_some_and(|d| chapter.end > d + 0.5) {
+                notes.push("past end of file");
+            }
+            let style = match notes.is_empty() {
+                true => Style::default(),
+                false => Style::default().fg(theme.bad),
+            };
+            Row::new(vec![
+                Cell::from((i + 1).to_string()),
+                Cell::from(blanks::format_timestamp(chapter.start)),
+                Cell::from(blanks::format_timestamp(chapter.end)),
+                Cell::from(blanks::format_timestamp(chapter.end - chapter.start)),
+                Cell::from(chapter.title.clone()),
+                Cell::from(notes.join(", ")),
+            ])
+            .style(style)
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(11),
+        Constraint::Length(11),
+        Constraint::Length(11),
+        Constraint::Percentage(40),
+        Constraint::Min(20),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&widths)
+        .column_spacing(1)
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(table, area, &mut app.chapter_state);
+}
+
+/// Fields down the side, a column per file; values unlike the first file's are
+/// highlighted, as are the labels of the fields the files disagree on.
+fn render_compare(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let Some(comparison) = app.comparison.as_mut() else {
+        return;
+    };
+    let files = comparison.files(&app.media_files);
+    let rows = comparison.rows(&app.media_files);
+    const LABEL_WIDTH: u16 = 20;
+    const MIN_COLUMN: u16 = 18;
+    // Room for the highlight symbol, the label and a column gap each
+    let room = area.width.saturating_sub(2 + 3 + LABEL_WIDTH);
+    let fit = ((room / (MIN_COLUMN + 1)) as usize).clamp(1, files.len().max(1));
+    comparison.offset = comparison.offset.min(files.len().saturating_sub(fit));
+    let shown = comparison.offset..(comparison.offset + fit).min(files.len());
+    let column_width = (room / fit as u16).saturating_sub(1) as usize;
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let accent = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+    let names = files[shown.clone()].iter().map(|file| {
+        Cell::from(truncate_middle(&format!("{}.{}", file.name, file.container), column_width)).style(accent)
+    });
+    let header = Row::new(std::iter::once(Cell::from("Field").style(accent)).chain(names));
+    let differing = rows.iter().filter(|row| row.differs).count();
+    let table_rows: Vec<Row> = rows
+        .iter()
+        .map(|row| {
+            let label = match row.differs {
+                true => Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+                false => Style::default().fg(theme.muted),
+            };
+            let label = Cell::from(row.label).style(label);
+            let values = row.values[shown.clone()].iter().map(|value| match value.as_str() {
+                "" => Cell::from("-").style(Style::default().fg(theme.faint)),
+                _ if row.differs && *value != row.values[0] => {
+                    Cell::from(value.clone()).style(Style::default().fg(theme.warning))
+                }
+                _ => Cell::from(value.clone()),
+            });
+            Row::new(std::iter::once(label).chain(values))
+        })
+        .collect();
 
-    if let Err(err) = res {
-        println!("{:?}", err);
+    let mut title = format!("Compare - {} files, {} fields differ", files.len(), differing);
+    if shown.len() < files.len() {
+        title.push_str(&format!(" (files {}-{} shown)", shown.start + 1, shown.end));
     }
-
-    Ok(())
+    if comparison.only_differences {
+        title.push_str(", only differences");
+    }
+    let mut widths = vec![Constraint::Length(LABEL_WIDTH)];
+    widths.extend(shown.map(|_| Constraint::Length(column_width as u16)));
+    let table = Table::new(table_rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&widths)
+        .column_spacing(1)
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(table, area, &mut comparison.table_state);
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+fn render_transcodes(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let file_name = |path: &str| {
+        Path::new(path)
+            .file_name()
+            .map_or(path.to_string(), |n| n.to_string_lossy().to_string())
+    };
+    let items: Vec<ListItem> = app
+        .transcodes
+        .jobs
+        .iter()
+        .map(|job| {
+            let (status, color) = match &job.status {
+                JobStatus::Queued => ("queued".to_string(), theme.muted),
+                JobStatus::Running(fraction) => (progress_bar(*fraction), theme.accent),
+                JobStatus::Done => ("done".to_string(), theme.good),
+                JobStatus::Failed(e) => (format!("failed: {}", e), theme.bad),
+            };
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(
+                        format!("{} → {}  ", file_name(&job.input), file_name(&job.output)),
+                        Style::default().fg(theme.info),
+                    ),
+                    Span::raw(job.title.clone()),
+                ]),
+                Line::from(Span::styled(format!("  {}", status), Style::default().fg(color))),
+            ])
+        })
+        .collect();
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match app.mode {
-                    AppMode::Normal => {
-                        match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('a') => app.mode = AppMode::AddFile,
-                            KeyCode::Char('r') => app.mode = AppMode::ShowRawOutput,
-                            KeyCode::Char('h') => app.mode = AppMode::Help,
-                            KeyCode::Char('c') => app.clear_all(),
-                            KeyCode::Down | KeyCode::Char('j') => app.next_file(),
-                            KeyCode::Up | KeyCode::Char('k') => app.previous_file(),
-                            KeyCode::Tab => {
-                                app.selected_tab = (app.selected_tab + 1) % 3;
-                            }
-                            _ => {}
-                        }
-                    }
-                    AppMode::AddFile => {
-                        match key.code {
-                            KeyCode::Enter => {
-                                let path = app.input.value().to_string();
-                                if !path.is_empty() {
-                                    app.add_file(&path)?;
-                                    app.input.reset();
-                                }
-                                app.mode = AppMode::Normal;
-                            }
-                            KeyCode::Esc => {
-                                app.input.reset();
-                                app.mode = AppMode::Normal;
-                            }
-                            _ => {
-                                app.input.handle_event(&Event::Key(key));
-                            }
-                        }
-                    }
-                    AppMode::ShowRawOutput => {
-                        match key.code {
-                            KeyCode::Esc => app.mode = AppMode::Normal,
-                            KeyCode::Up => {
-                                if app.raw_output_scroll > 0 {
-                                    app.raw_output_scroll -= 1;
-                                }
-                            }
-                            KeyCode::Down => app.raw_output_scroll += 1,
-                            _ => {}
-                        }
-                    }
-                    AppMode::Help => {
-                        match key.code {
-                            KeyCode::Esc => app.mode = AppMode::Normal,
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        }
+    if items.is_empty() {
+        let (fixes, queue) = (app.keymap.label(Action::Fixes), app.keymap.label(Action::QueueFixes));
+        let hint = format!(
+            "No transcodes queued - press '{}' in the fixes view ('{}') or '{}' on the Files tab",
+            QUEUE_FIX_KEY, fixes, queue
+        );
+        let message = Paragraph::new(hint)
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Transcode queue"));
+        f.render_widget(message, area);
+        return;
     }
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Transcode queue ({} active)",
+            app.transcodes.active()
+        )))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.transcodes.list_state);
 }
 
-fn ui(f: &mut Frame, app: &mut App) {
+fn render_tag_editor(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let Some(form) = app.tag_form.as_ref() else {
+        return;
+    };
+    let name = Path::new(&form.path).file_name().unwrap_or_default().to_string_lossy();
+    let block = Block::default().title(format!("Edit tags - {}", name)).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // Title
-            Constraint::Length(3),  // Tabs
-            Constraint::Min(10),    // Main content
-            Constraint::Length(3),  // Status/notification
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(1),
         ])
-        .split(f.size());
+        .split(inner);
+    for (i, (label, input)) in TagForm::LABELS.iter().zip(form.fields()).enumerate() {
+        let color = if i == form.focus { theme.accent } else { theme.muted };
+        let field = Paragraph::new(input.value())
+            .style(Style::default().fg(color))
+            .block(Block::default().borders(Borders::ALL).title(*label));
+        f.render_widget(field, chunks[i]);
+    }
+    let focused = chunks[form.focus];
+    f.set_cursor(focused.x + form.fields()[form.focus].visual_cursor() as u16 + 1, focused.y + 1);
 
-    // Title
-    let title = Paragraph::new("🎬 Video Analyzer TUI")
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(title, chunks[0]);
+    let help_text = vec![
+        Line::from("Enter writes the tags with a stream-copy remux that replaces the file, then re-analyzes it"),
+        Line::from("Tab/↑↓ switch fields, Esc cancels. An empty title or comment removes the tag"),
+        Line::from("Audio languages are ISO 639-2 codes for each audio stream in order, e.g. eng, fra"),
+    ];
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(theme.muted))
+        .wrap(Wrap { trim: true });
+    f.render_widget(help, chunks[3]);
+}
 
-    // Tabs
-    let tabs = Tabs::new(vec!["Files", "Filters", "Stats"])
-        .block(Block::default().borders(Borders::ALL))
-        .select(app.selected_tab)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
-    f.render_widget(tabs, chunks[1]);
+fn render_note_editor(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let Some(form) = app.note_form.as_ref() else {
+        return;
+    };
+    let title = match form.paths.as_slice() {
+        [path] => format!("Review - {}", Path::new(path).file_name().unwrap_or_default().to_string_lossy()),
+        paths => format!("Review - {} files", paths.len()),
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    // Main content based on mode
-    match app.mode {
-        AppMode::Normal => render_main_content(f, app, chunks[2]),
-        AppMode::AddFile => render_add_file_dialog(f, app, chunks[2]),
-        AppMode::ShowRawOutput => render_raw_output(f, app, chunks[2]),
-        AppMode::Help => render_help(f, chunks[2]),
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(1)])
+        .split(inner);
+    for (i, (label, input)) in NoteForm::LABELS.iter().zip(form.fields()).enumerate() {
+        let color = if i == form.focus { theme.accent } else { theme.muted };
+        let field = Paragraph::new(input.value())
+            .style(Style::default().fg(color))
+            .block(Block::default().borders(Borders::ALL).title(*label));
+        f.render_widget(field, chunks[i]);
     }
+    let focused = chunks[form.focus];
+    f.set_cursor(focused.x + form.fields()[form.focus].visual_cursor() as u16 + 1, focused.y + 1);
 
-    // Status bar
-    render_status_bar(f, app, chunks[3]);
+    let mut help_text = vec![
+        Line::from("Enter saves the tags and note in notes.json next to the config; the files are left untouched"),
+        Line::from("Tab/↑↓ switch fields, Esc cancels. Clearing both fields removes the note"),
+        Line::from("Filter on them with tag=approved or note~client"),
+    ];
+    if form.paths.len() > 1 {
+        help_text.push(Line::from(format!("Saving replaces what each of the {} files had", form.paths.len())));
+    }
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(theme.muted))
+        .wrap(Wrap { trim: true });
+    f.render_widget(help, chunks[2]);
 }
 
-fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
-    let filtered_files = app.get_filtered_files();
-    
-    if filtered_files.is_empty() {
-        let empty_msg = Paragraph::new("No files loaded. Press 'a' to add files, 'h' for help")
-            .style(Style::default().fg(Color::Gray))
+fn render_palette(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let Some(palette) = app.palette.as_mut() else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+    let input = Paragraph::new(format!("> {}", app.input.value()))
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title("Command"));
+    f.render_widget(input, chunks[0]);
+    f.set_cursor(chunks[0].x + app.input.visual_cursor() as u16 + 3, chunks[0].y + 1);
+
+    // Keys right-aligned in a column wide enough for the longest binding
+    let key_width = palette.matches().map(|entry| entry.keys.chars().count()).max().unwrap_or(0);
+    let name_width = (chunks[1].width as usize).saturating_sub(key_width + 6);
+    let items: Vec<ListItem> = palette
+        .matches()
+        .map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<width$} ", entry.name, width = name_width)),
+                Span::styled(format!("{:>width$}", entry.keys, width = key_width), Style::default().fg(theme.muted)),
+            ]))
+        })
+        .collect();
+    let title = format!("Commands ({})", items.len());
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[1], &mut palette.list_state);
+}
+
+fn render_tree(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let indices = app.filtered_indices();
+    let rows = app.dir_tree.rows(&app.media_files, &indices);
+    if rows.is_empty() {
+        let message = match app.media_files.is_empty() {
+            true => "No files loaded",
+            false => "No files match the active filters or search",
+        };
+        let empty = Paragraph::new(message)
+            .style(Style::default().fg(theme.muted))
             .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Files"));
-        f.render_widget(empty_msg, area);
+            .block(Block::default().borders(Borders::ALL).title("Tree"));
+        f.render_widget(empty, area);
         return;
     }
+    let selected = app.dir_tree.list_state.selected().unwrap_or(0).min(rows.len() - 1);
+    app.dir_tree.list_state.select(Some(selected));
 
-    let header_cells = ["Name", "Container", "Codec", "Resolution", "FPS", "Bitrate(Mbps)"]
+    let items: Vec<ListItem> = rows
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
-    let header = Row::new(header_cells).height(1);
+        .map(|row| {
+            let mut spans = vec![Span::raw("  ".repeat(row.depth))];
+            match &row.entry {
+                tree::Entry::Folder(summary) => {
+                    let marker = if app.dir_tree.is_collapsed(&row.key) { "▸ " } else { "▾ " };
+                    spans.push(Span::styled(marker, Style::default().fg(theme.accent)));
+                    let name_style = Style::default().fg(theme.info).add_modifier(Modifier::BOLD);
+                    spans.push(Span::styled(row.name.clone(), name_style));
+                    let totals = format!("  {} file(s), {}", summary.files.len(), format_size(Some(summary.size)));
+                    spans.push(Span::styled(totals, Style::default().fg(theme.muted)));
+                    if summary.mixed_codecs() {
+                        let codecs: Vec<&str> = summary.codecs.iter().map(String::as_str).collect();
+                        let warning = format!("  mixed codecs: {}", codecs.join(", "));
+                        spans.push(Span::styled(warning, Style::default().fg(theme.warning)));
+                    }
+                    ListItem::new(Line::from(spans))
+                }
+                tree::Entry::File(i) => {
+                    let file = &app.media_files[*i];
+                    let marked = app.marked.contains(&file.path);
+                    spans.push(Span::raw(if marked { "● " } else { "  " }));
+                    spans.push(Span::raw(row.name.clone()));
+                    let about = match file.audio_only {
+                        true => format!("  {}  {}", format_audio_summary(file), format_duration(file.duration)),
+                        false => format!(
+                            "  {}  {}  {}",
+                            file.codec,
+                            format_resolution(file),
+                            format_duration(file.duration)
+                        ),
+                    };
+                    spans.push(Span::styled(about, Style::default().fg(theme.muted)));
+                    let item = ListItem::new(Line::from(spans));
+                    match marked {
+                        true => item.style(Style::default().fg(theme.marked)),
+                        false => item,
+                    }
+                }
+            }
+        })
+        .collect();
+    let title = format!("Tree ({} files)", indices.len());
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.dir_tree.list_state);
+    let inner = area.inner(&Margin { vertical: 1, horizontal: 1 });
+    app.click_targets.tree_rows = Some((inner, app.dir_tree.list_state.offset()));
+    app.page_size = inner.height as usize;
+}
 
-    let rows = filtered_files.iter().map(|file| {
-        let cells = vec![
-            Cell::from(format!("{}.{}", file.name, file.container)),
-            Cell::from(file.container.clone()),
-            Cell::from(file.codec.clone()),
-            Cell::from(file.resolution.clone()),
-            Cell::from(file.frame_rate.clone()),
-            Cell::from(file.bitrate.clone()),
-        ];
-        Row::new(cells).height(1)
-    });
+fn render_stats(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let indices = app.filtered_indices();
+    if indices.is_empty() {
+        let message = match app.media_files.is_empty() {
+            true => "No files loaded",
+            false => "No files match the active filters or search",
+        };
+        let empty = Paragraph::new(message)
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Stats"));
+        f.render_widget(empty, area);
+        return;
+    }
+    let files: Vec<&MediaInfo> = indices.iter().map(|&i| &app.media_files[i]).collect();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    let mut codecs: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in files.iter().filter(|f| !f.audio_only) {
+        *codecs.entry(file.codec.as_str()).or_default() += 1;
+    }
+    let codecs: Vec<String> = codecs.iter().map(|(codec, n)| format!("{} ×{}", codec, n)).collect();
+    let targets = Target::all(app.estimate_mbps);
+    let estimates: Vec<Estimate> = targets.iter().map(|&target| estimate::estimate(target, &files)).collect();
+    let chosen = app.estimate_table.selected().unwrap_or(0).min(targets.len() - 1);
+    app.estimate_table.select(Some(chosen));
+    let projection = match estimates[chosen].ratio() {
+        Some(ratio) => format!(
+            "{} as {}, {:.1}x the {} they take now",
+            format_size(Some(estimates[chosen].bytes)),
+            targets[chosen].label(),
+            ratio,
+            format_size(Some(estimates[chosen].current)),
+        ),
+        None => format!("{} as {}", format_size(Some(estimates[chosen].bytes)), targets[chosen].label()),
+    };
+    let lines = vec![
+        detail_line(&theme, "Files", files.len().to_string()),
+        detail_line(&theme, "Size", format_size(Some(files.iter().filter_map(|f| f.file_size).sum()))),
+        detail_line(&theme, "Duration", format_duration(Some(files.iter().filter_map(|f| f.duration).sum()))),
+        detail_line(&theme, "Codecs", codecs.join(", ")),
+        detail_line(&theme, "Transcoded", projection),
+    ];
+    let title = format!("Stats ({} files)", files.len());
+    let totals = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(totals, chunks[0]);
 
+    let header = Row::new(["Target", "Size", "vs now"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = targets
+        .iter()
+        .zip(&estimates)
+        .map(|(target, estimate)| {
+            let ratio = estimate.ratio().map_or("-".to_string(), |ratio| format!("{:.1}x", ratio));
+            Row::new([target.label(), format_size(Some(estimate.bytes)), ratio])
+        })
+        .collect();
     let table = Table::new(rows)
         .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!("Files ({}/{})", 
-            filtered_files.len(), app.media_files.len())))
-        .widths(&[
-            Constraint::Percentage(25),
-            Constraint::Percentage(12),
-            Constraint::Percentage(15),
-            Constraint::Percentage(15),
-            Constraint::Percentage(8),
-            Constraint::Percentage(15),
-        ])
-        .column_spacing(1)
-        .highlight_style(Style::default().bg(Color::DarkGray))
+        .widths(&[Constraint::Length(20), Constraint::Length(12), Constraint::Length(10)])
+        .block(Block::default().borders(Borders::ALL).title("Transcode estimate"))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(table, chunks[1], &mut app.estimate_table);
+
+    let mut hint = "j/k choose a target, ←/→ change the H.264 and H.265 bitrate".to_string();
+    if estimates[chosen].skipped > 0 {
+        let skipped = estimates[chosen].skipped;
+        hint.push_str(&format!("; {} file(s) without a duration or frame size left out", skipped));
+    }
+    hint.push_str("; Hap is sized before Snappy compression");
+    f.render_widget(Paragraph::new(hint).style(Style::default().fg(theme.muted)), chunks[2]);
+}
+
+fn render_column_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let items: Vec<ListItem> = app
+        .column_picker
+        .iter()
+        .map(|&(spec, shown)| {
+            let column = spec.column();
+            let check = if shown { "[x]" } else { "[ ]" };
+            let width = spec.width().map_or("default width".to_string(), |width| format!("{} cells", width));
+            let mut spans = vec![
+                Span::raw(format!("{} {:<14}", check, column.header())),
+                Span::styled(format!("{:<16}", width), Style::default().fg(theme.muted)),
+            ];
+            if column.on_demand() {
+                spans.push(Span::styled("once a file has data", Style::default().fg(theme.faint)));
+            }
+            let item = ListItem::new(Line::from(spans));
+            match shown {
+                true => item,
+                false => item.style(Style::default().fg(theme.muted)),
+            }
+        })
+        .collect();
+    let shown = app.column_picker.iter().filter(|(_, shown)| *shown).count();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Columns ({} shown)", shown)))
+        .highlight_style(Style::default().bg(theme.selection))
         .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut app.column_list_state);
+}
+
+fn render_projects(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+    let input = Paragraph::new(app.input.value())
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title("New project name"));
+    f.render_widget(input, chunks[0]);
+    f.set_cursor(chunks[0].x + app.input.visual_cursor() as u16 + 1, chunks[0].y + 1);
 
-    f.render_stateful_widget(table, area, &mut app.table_state);
+    let items: Vec<ListItem> = match app.project_names.is_empty() {
+        true => vec![ListItem::new("No projects yet").style(Style::default().fg(theme.muted))],
+        false => app
+            .project_names
+            .iter()
+            .map(|name| match app.project.as_ref() == Some(name) {
+                true => ListItem::new(format!("{} (open)", name)).style(Style::default().fg(theme.info)),
+                false => ListItem::new(name.clone()),
+            })
+            .collect(),
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Projects"))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[1], &mut app.project_list_state);
 }
 
 fn render_add_file_dialog(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
     let block = Block::default()
         .title("Add File")
         .borders(Borders::ALL);
@@ -536,78 +6257,173 @@ fn render_add_file_dialog(f: &mut Frame, app: &mut App, area: Rect) {
         .split(inner);
 
     let input = Paragraph::new(app.input.value())
-        .style(Style::default().fg(Color::Yellow))
-        .block(Block::default().borders(Borders::ALL).title("File Path"));
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title("File Path or URL"));
     
     f.render_widget(input, chunks[0]);
 
+    // Set cursor position
+    f.set_cursor(
+        chunks[0].x + app.input.visual_cursor() as u16 + 1,
+        chunks[0].y + 1,
+    );
+
+    if let Some(completion) = app.completion.as_mut() {
+        let items: Vec<ListItem> = completion
+            .candidates
+            .iter()
+            .map(|candidate| {
+                if candidate.is_dir {
+                    ListItem::new(format!("{}/", candidate.name)).style(Style::default().fg(theme.info))
+                } else {
+                    ListItem::new(candidate.name.clone())
+                }
+            })
+            .collect();
+        let title = format!(
+            "{} matches - Tab/↑↓ select, Enter fills in or opens a folder, Esc closes",
+            completion.candidates.len()
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(theme.selection))
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(list, chunks[1], &mut completion.list_state);
+        return;
+    }
+
     let help_text = vec![
-        Line::from("Enter the full path to a video or image file"),
-        Line::from("Press Enter to analyze, Esc to cancel"),
+        Line::from("Enter the full path to a video or image file, a directory to scan, or an http(s) URL"),
+        Line::from("Press Tab to complete the path, ↑/↓ for recent paths, Enter to analyze, Esc to cancel"),
         Line::from(""),
         Line::from("Examples:"),
         Line::from("  /path/to/video.mp4"),
         Line::from("  /path/to/image.jpg"),
+        Line::from("  /path/to/footage/"),
+        Line::from("  https://cdn.example.com/mezzanine.mov"),
     ];
     
     let help = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(theme.muted))
         .wrap(Wrap { trim: true });
     
     f.render_widget(help, chunks[1]);
-
-    // Set cursor position
-    f.set_cursor(
-        chunks[0].x + app.input.visual_cursor() as u16 + 1,
-        chunks[0].y + 1,
-    );
 }
 
 fn render_raw_output(f: &mut Frame, app: &mut App, area: Rect) {
-    let selected_file = app.table_state.selected()
-        .and_then(|i| app.media_files.get(i));
-    
-    let content = if let Some(file) = selected_file {
-        file.raw_output.clone()
-    } else {
-        "No file selected".to_string()
+    let theme = app.theme;
+    let tree = &app.json_tree;
+    let query = tree.query();
+    let items: Vec<ListItem> = tree
+        .rows()
+        .iter()
+        .map(|row| ListItem::new(json_tree::row_line(row, tree.is_expanded(&row.path), query, &theme)))
+        .collect();
+
+    let mut title = "Raw FFprobe Output".to_string();
+    if !query.is_empty() {
+        title = format!("{} - /{} ({})", title, query, tree.match_status());
+    }
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection));
+
+    app.page_size = area.height.saturating_sub(2) as usize;
+    let Some(input) = &app.raw_search else {
+        f.render_stateful_widget(list, area, &mut app.json_tree.list_state);
+        return;
     };
 
-    let lines: Vec<Line> = content
-        .lines()
-        .skip(app.raw_output_scroll)
-        .map(|line| Line::from(line))
-        .collect();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(area);
+    let search = Paragraph::new(input.value())
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title("Search"));
+    f.render_widget(search, chunks[1]);
+    f.set_cursor(
+        chunks[1].x + input.visual_cursor() as u16 + 1,
+        chunks[1].y + 1,
+    );
+    f.render_stateful_widget(list, chunks[0], &mut app.json_tree.list_state);
+}
 
-    let paragraph = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Raw FFprobe Output"))
-        .style(Style::default().fg(Color::Green))
-        .wrap(Wrap { trim: false });
+fn render_log(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(5)])
+        .split(area);
+    let mut items: Vec<ListItem> = app
+        .log
+        .entries()
+        .iter()
+        .map(|entry| {
+            let color = match entry.level {
+                Level::Error => theme.bad,
+                Level::Warning => theme.warning,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", entry.time), Style::default().fg(theme.muted)),
+                Span::styled(format!("{:<8}", entry.level.label()), Style::default().fg(color)),
+                Span::raw(entry.message.clone()),
+            ]))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new("No errors or warnings so far").style(Style::default().fg(theme.muted)));
+    }
+    let title = format!(
+        "Log - {} error(s), {} warning(s), times in UTC",
+        app.log.count(Level::Error),
+        app.log.count(Level::Warning)
+    );
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(theme.selection))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[0], &mut app.log_state);
+    app.page_size = chunks[0].height.saturating_sub(2) as usize;
 
-    f.render_widget(paragraph, area);
+    // The selected entry in full, for lines too long for the list
+    let message = app.log_state.selected().and_then(|i| app.log.entries().get(i)).map(|e| e.message.clone());
+    let detail = Paragraph::new(message.unwrap_or_default())
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(detail, chunks[1]);
 }
 
-fn render_help(f: &mut Frame, area: Rect) {
-    let help_text = vec![
-        Line::from(Span::styled("Key Bindings:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+fn render_help(f: &mut Frame, theme: &Theme, keymap: &Keymap, area: Rect) {
+    let mut help_text = vec![
+        Line::from(Span::styled("Key Bindings:", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))),
         Line::from(""),
-        Line::from("  q - Quit application"),
-        Line::from("  a - Add file"),
-        Line::from("  r - Show raw FFprobe output"),
-        Line::from("  c - Clear all files"),
-        Line::from("  h - Show this help"),
-        Line::from("  ↑/k - Previous file"),
-        Line::from("  ↓/j - Next file"),
-        Line::from("  Tab - Switch tabs"),
+    ];
+    for action in Action::ALL {
+        let keys = keymap.label(action);
+        let keys = if keys.is_empty() { "(unbound)".to_string() } else { keys };
+        help_text.push(Line::from(format!("  {} - {}", keys, action.description())));
+    }
+    help_text.extend([
+        Line::from("  PgUp/PgDn - Move a page"),
+        Line::from("  Home/gg, End/G - Jump to first/last row"),
+        Line::from("  Enter - Toggle filter (Filters tab), fold a folder or show a file in the table (Tree tab)"),
+        Line::from("  ←/→ - Fold/unfold a folder (Tree tab)"),
+        Line::from("  1-9 - Apply preset"),
+        Line::from("  Alt+1-5 - Rate marked or selected files (again or Alt+0 clears, Rating column)"),
+        Line::from("  Mouse - Click a row or tab, click a header to sort (again to reverse), wheel to scroll"),
+    ]);
+    help_text.extend([
         Line::from(""),
-        Line::from(Span::styled("Features:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled("Features:", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from("• Drag-and-drop style file analysis"),
         Line::from("• Real-time filtering and highlighting"),
         Line::from("• Detailed metadata extraction"),
         Line::from("• Performance monitoring"),
         Line::from("• Cross-platform support"),
-    ];
+    ]);
 
     let help = Paragraph::new(help_text)
         .block(Block::default().borders(Borders::ALL).title("Help"))
@@ -616,14 +6432,135 @@ fn render_help(f: &mut Frame, area: Rect) {
     f.render_widget(help, area);
 }
 
+fn render_query_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let (title, style) = match &app.query_error {
+        Some(error) => (format!("Filter - {}", error), Style::default().fg(theme.bad)),
+        None => (
+            "Filter - e.g. codec=H.264 AND bitrate>20, Enter to apply, Esc to cancel".to_string(),
+            Style::default().fg(theme.accent),
+        ),
+    };
+    let bar = Paragraph::new(format!(":{}", app.query_input.value()))
+        .style(style)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(bar, area);
+    f.set_cursor(
+        area.x + app.query_input.visual_cursor() as u16 + 2,
+        area.y + 1,
+    );
+}
+
+fn render_preset_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let title = format!(
+        "Save preset - {}, Enter to save, Esc to cancel",
+        query::to_query(&app.active_filters)
+    );
+    let bar = Paragraph::new(app.input.value())
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(bar, area);
+    f.set_cursor(area.x + app.input.visual_cursor() as u16 + 1, area.y + 1);
+}
+
+fn render_rename_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let title = format!(
+        "Rename {} file(s) - {{name}}, {{codec}}, {{resolution}} and more, Enter to preview, Esc to cancel",
+        app.target_indices().len()
+    );
+    let bar = Paragraph::new(app.input.value())
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(bar, area);
+    f.set_cursor(area.x + app.input.visual_cursor() as u16 + 1, area.y + 1);
+}
+
+fn render_organize_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let title = format!(
+        "Organize {} file(s) into folders - {{codec}}, {{height}} and more, Enter to preview, Esc to cancel",
+        app.filtered_targets().len()
+    );
+    let bar = Paragraph::new(app.input.value())
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(bar, area);
+    f.set_cursor(area.x + app.input.visual_cursor() as u16 + 1, area.y + 1);
+}
+
 fn render_status_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
     let mut status_text = match app.mode {
-        AppMode::Normal => "Ready - Press 'h' for help".to_string(),
+        AppMode::Normal if app.pending_jobs > 0 => {
+            let cancel = app.keymap.label(Action::Cancel);
+            format!("Analyzing... {} file(s) remaining, '{}' to cancel", app.pending_jobs, cancel)
+        }
+        AppMode::Normal if !app.writing_tags.is_empty() => {
+            format!("Writing tags... {} file(s) remaining", app.writing_tags.len())
+        }
+        AppMode::Normal if !app.scanning_blanks.is_empty() => {
+            let cancel = app.keymap.label(Action::Cancel);
+            let remaining = app.scanning_blanks.len();
+            format!("Scanning for black and silence... {} file(s) remaining, '{}' to cancel", remaining, cancel)
+        }
+        AppMode::Normal if app.hashing > 0 => {
+            let cancel = app.keymap.label(Action::Cancel);
+            format!("Hashing frames... {} file(s) remaining, '{}' to cancel", app.hashing, cancel)
+        }
+        AppMode::Normal if app.organizing.is_some() => {
+            let (done, total) = app.organize_done;
+            format!("Organizing... {}/{} file(s), '{}' to cancel", done, total, app.keymap.label(Action::Cancel))
+        }
+        AppMode::Normal if app.transcodes.active() > 0 => {
+            let view = app.keymap.label(Action::Transcodes);
+            format!("Transcoding... {} job(s) remaining, '{}' to view", app.transcodes.active(), view)
+        }
+        AppMode::Normal => format!("Ready - Press '{}' for help", app.keymap.label(Action::Help)),
         AppMode::AddFile => "Enter file path...".to_string(),
-        AppMode::ShowRawOutput => "Viewing raw output - Press Esc to return".to_string(),
+        AppMode::ShowRawOutput => {
+            "Raw output - ←/→ collapse/expand, e/E all, / search, n/N next/prev, y copy, Esc return"
+                .to_string()
+        }
+        AppMode::Log => "Log - j/k scroll, y copy the entry, c clear, Esc return".to_string(),
         AppMode::Help => "Help - Press Esc to return".to_string(),
+        AppMode::Fixes => {
+            "Fixes - j/k select, Enter/y copy command, t add to transcode queue, Esc return".to_string()
+        }
+        AppMode::Rename => "Rename - j/k scroll, Enter/y rename, e edit the pattern, Esc cancel".to_string(),
+        AppMode::Organize => "Organize - j/k scroll, c copy, m move, e edit the pattern, Esc cancel".to_string(),
+        AppMode::ConfirmDelete => {
+            "Delete from disk - Enter/y move to the trash, ! delete for good, Esc cancel".to_string()
+        }
+        AppMode::Transcodes => "Transcode queue - j/k select, c clear finished, Esc return".to_string(),
+        AppMode::Duplicates => {
+            "Duplicates - j/k select, Space mark, Enter show in file list, p find similar clips, Esc return"
+                .to_string()
+        }
+        AppMode::Compare => {
+            "Compare - j/k select, ←/→ scroll files, d only differences, Esc return".to_string()
+        }
+        AppMode::Bitrate => "Bitrate over time - Esc return".to_string(),
+        AppMode::Gop => "GOP structure - Esc return".to_string(),
+        AppMode::Chapters => "Chapters - j/k select, Esc return".to_string(),
+        AppMode::EditTags => "Edit tags - Tab next field, Enter write, Esc cancel".to_string(),
+        AppMode::EditNote => "Review tags and note - Tab next field, Enter save, Esc cancel".to_string(),
+        AppMode::Projects => "Projects - ↑/↓ select, type a name to create one, Enter open, Esc cancel".to_string(),
+        AppMode::Palette => "Commands - type to search, ↑/↓ select, Enter run, Esc cancel".to_string(),
+        AppMode::Columns => {
+            "Columns - j/k select, Space show/hide, J/K move, +/- width, 0 default width, Enter save, Esc cancel"
+                .to_string()
+        }
+        AppMode::Query | AppMode::SavePreset | AppMode::RenamePattern | AppMode::OrganizePattern => String::new(),
     };
 
+    let mut style = Style::default().fg(theme.text);
+    if app.ffprobe_error.is_some() {
+        status_text = "ffprobe not found - files cannot be analyzed".to_string();
+        style = Style::default().fg(theme.bad);
+    }
+
     // Show notification if present
     if let Some((message, timestamp)) = &app.notification {
         if timestamp.elapsed() < Duration::from_secs(3) {
@@ -634,7 +6571,7 @@ fn render_status_bar(f: &mut Frame, app: &mut App, area: Rect) {
     }
 
     let status = Paragraph::new(status_text)
-        .style(Style::default().fg(Color::White))
+        .style(style)
         .alignment(Alignment::Left)
         .block(Block::default().borders(Borders::ALL));
 
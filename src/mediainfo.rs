@@ -0,0 +1,233 @@
+//! Translates `mediainfo --Output=JSON` into the ffprobe JSON that normalization
+//! reads. MediaInfo names formats its own way (`AVC`, `MPEG-4`, `PCM` ...), so the
+//! common ones are mapped to ffmpeg's codec and demuxer names.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+
+pub fn translate(output: &str) -> Result<Value> {
+    let report: Value = serde_json::from_str(output).map_err(|e| anyhow!("unreadable MediaInfo JSON: {}", e))?;
+    let tracks = report["media"]["track"].as_array().ok_or_else(|| anyhow!("MediaInfo found no tracks"))?;
+    let general = tracks.iter().find(|t| t["@type"] == "General").cloned().unwrap_or(Value::Null);
+    let text = |track: &Value, key: &str| track[key].as_str().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+
+    let mut streams = Vec::new();
+    for (index, track) in tracks.iter().filter(|t| t["@type"] != "General" && t["@type"] != "Other").enumerate() {
+        let field = |key: &str| text(track, key);
+        let mut stream = Map::new();
+        let mut tags = Map::new();
+        if let Some(language) = field("Language") {
+            tags.insert("language".to_string(), json!(language));
+        }
+        if let Some(title) = field("Title") {
+            tags.insert("title".to_string(), json!(title));
+        }
+        stream.insert("index".to_string(), json!(index));
+        let format = field("Format").unwrap_or_default();
+        match track["@type"].as_str().unwrap_or_default() {
+            "Video" | "Image" => {
+                let depth: u8 = field("BitDepth").and_then(|d| d.parse().ok()).unwrap_or(8);
+                stream.insert("codec_type".to_string(), json!("video"));
+                stream.insert("codec_name".to_string(), json!(video_codec(&format)));
+                set(&mut stream, "profile", field("Format_Profile").map(|p| profile(&p)));
+                set(&mut stream, "codec_tag_string", field("CodecID"));
+                set(&mut stream, "width", field("Width").and_then(|w| w.parse::<u64>().ok()).map(Value::from));
+                set(&mut stream, "height", field("Height").and_then(|h| h.parse::<u64>().ok()).map(Value::from));
+                stream.insert("pix_fmt".to_string(), json!(pixel_format(track, depth)));
+                stream.insert("bits_per_raw_sample".to_string(), json!(depth.to_string()));
+                set(&mut stream, "bit_rate", field("BitRate"));
+                set(&mut stream, "nb_frames", field("FrameCount"));
+                // ffprobe's rates are fractions; a maximum above the average is what VFR looks like
+                let rate = field("FrameRate").map(|fps| format!("{}/1", fps));
+                let peak = field("FrameRate_Maximum").map(|fps| format!("{}/1", fps)).or(rate.clone());
+                set(&mut stream, "avg_frame_rate", rate.clone());
+                set(&mut stream, "r_frame_rate", if field("FrameRate_Mode").as_deref() == Some("VFR") { peak } else { rate });
+                let par = field("PixelAspectRatio").and_then(|par| par.parse::<f64>().ok());
+                set(&mut stream, "sample_aspect_ratio", par.map(sample_aspect_ratio));
+                set(&mut stream, "field_order", field("ScanType").map(|scan| field_order(&scan, field("ScanOrder"))));
+                set(&mut stream, "color_transfer", field("transfer_characteristics").map(|t| color_name(&t)));
+                set(&mut stream, "color_primaries", field("colour_primaries").map(|p| color_name(&p)));
+                set(&mut stream, "color_space", field("matrix_coefficients").map(|m| color_name(&m)));
+                if let Some(rotation) = field("Rotation").and_then(|r| r.parse::<f64>().ok()) {
+                    // MediaInfo's rotation is clockwise, the display matrix's counter-clockwise
+                    stream.insert("side_data_list".to_string(), json!([{ "rotation": -rotation }]));
+                }
+                if field("HDR_Format").is_some_and(|hdr| hdr.contains("Dolby Vision")) {
+                    stream.insert("side_data_list".to_string(), json!([{ "side_data_type": "DOVI configuration record" }]));
+                }
+            }
+            "Audio" => {
+                stream.insert("codec_type".to_string(), json!("audio"));
+                stream.insert("codec_name".to_string(), json!(audio_codec(track, &format)));
+                set(&mut stream, "channels", field("Channels").and_then(|c| c.parse::<u64>().ok()).map(Value::from));
+                set(&mut stream, "sample_rate", field("SamplingRate").map(|r| r.split('.').next().unwrap_or_default().to_string()));
+                set(&mut stream, "bits_per_raw_sample", field("BitDepth"));
+                set(&mut stream, "bit_rate", field("BitRate"));
+            }
+            "Text" => {
+                stream.insert("codec_type".to_string(), json!("subtitle"));
+                stream.insert("codec_name".to_string(), json!(subtitle_codec(&format)));
+                if field("Forced").as_deref() == Some("Yes") {
+                    stream.insert("disposition".to_string(), json!({ "forced": 1 }));
+                }
+            }
+            _ => continue,
+        }
+        stream.insert("tags".to_string(), Value::Object(tags));
+        streams.push(Value::Object(stream));
+    }
+
+    let field = |key: &str| text(&general, key);
+    let mut tags = Map::new();
+    for (key, from) in [
+        ("title", "Title"),
+        ("artist", "Performer"),
+        ("encoder", "Encoded_Application"),
+        ("creation_time", "Encoded_Date"),
+    ] {
+        if let Some(value) = field(from) {
+            tags.insert(key.to_string(), json!(value.trim_end_matches(" UTC")));
+        }
+    }
+    let timecode = tracks.iter().find(|t| t["@type"] == "Other").and_then(|t| text(t, "TimeCode_FirstFrame"));
+    if let Some(timecode) = timecode {
+        tags.insert("timecode".to_string(), json!(timecode));
+    }
+    let mut format = Map::new();
+    format.insert("format_name".to_string(), json!(demuxer(&field("Format").unwrap_or_default())));
+    set(&mut format, "duration", field("Duration"));
+    set(&mut format, "size", field("FileSize"));
+    set(&mut format, "bit_rate", field("OverallBitRate"));
+    format.insert("tags".to_string(), Value::Object(tags));
+
+    Ok(json!({ "streams": streams, "format": format, "analyzer_output": report }))
+}
+
+fn set(object: &mut Map<String, Value>, key: &str, value: Option<impl Into<Value>>) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), value.into());
+    }
+}
+
+fn video_codec(format: &str) -> String {
+    match format {
+        "AVC" => "h264",
+        "HEVC" => "hevc",
+        "AV1" => "av1",
+        "VP8" => "vp8",
+        "VP9" => "vp9",
+        "MPEG Video" => "mpeg2video",
+        "MPEG-4 Visual" => "mpeg4",
+        "ProRes" => "prores",
+        "VC-3" | "DNxHD" | "DNxHR" => "dnxhd",
+        "HAP" | "Hap" => "hap",
+        "DV" => "dvvideo",
+        "JPEG" => "mjpeg",
+        "JPEG 2000" => "jpeg2000",
+        "PNG" => "png",
+        "TIFF" => "tiff",
+        "CineForm" => "cfhd",
+        "FFV1" => "ffv1",
+        other => return other.to_lowercase(),
+    }
+    .to_string()
+}
+
+/// MediaInfo writes `High@L4` and `422 HQ` where ffprobe writes `High` and `HQ`.
+fn profile(profile: &str) -> String {
+    let profile = profile.split('@').next().unwrap_or(profile);
+    profile.strip_prefix("422 ").unwrap_or(profile).to_string()
+}
+
+fn audio_codec(track: &Value, format: &str) -> String {
+    let profile = track["Format_Profile"].as_str().unwrap_or_default();
+    match format {
+        "AAC" => "aac".to_string(),
+        "MPEG Audio" if profile.contains("Layer 3") => "mp3".to_string(),
+        "AC-3" => "ac3".to_string(),
+        "E-AC-3" => "eac3".to_string(),
+        "Opus" => "opus".to_string(),
+        "Vorbis" => "vorbis".to_string(),
+        "FLAC" => "flac".to_string(),
+        "ALAC" => "alac".to_string(),
+        "PCM" => {
+            let depth = track["BitDepth"].as_str().unwrap_or("16");
+            let endian = if track["Format_Settings_Endianness"] == "Big" { "be" } else { "le" };
+            format!("pcm_s{}{}", depth, endian)
+        }
+        other => other.to_lowercase(),
+    }
+}
+
+fn subtitle_codec(format: &str) -> &str {
+    match format {
+        "UTF-8" | "SubRip" => "subrip",
+        "ASS" | "SSA" => "ass",
+        "WebVTT" => "webvtt",
+        "Timed Text" => "mov_text",
+        "PGS" => "hdmv_pgs_subtitle",
+        "DVB Subtitle" => "dvb_subtitle",
+        "Teletext" => "dvb_teletext",
+        "VobSub" => "dvd_subtitle",
+        "EIA-608" => "eia_608",
+        other => other,
+    }
+}
+
+/// A pixel format name built from the chroma subsampling, depth and alpha MediaInfo
+/// reports, e.g. `yuv422p10le`.
+fn pixel_format(track: &Value, depth: u8) -> String {
+    let alpha = track["ChromaSubsampling"].as_str().is_none() && track["ColorSpace"] == "RGBA";
+    let suffix = if depth > 8 { format!("{}le", depth) } else { String::new() };
+    match (track["ColorSpace"].as_str(), track["ChromaSubsampling"].as_str()) {
+        (Some("RGB"), _) if depth > 8 => format!("gbrp{}", suffix),
+        (Some("RGB"), _) => "rgb24".to_string(),
+        _ if alpha => "rgba".to_string(),
+        (_, Some("4:2:2")) => format!("yuv422p{}", suffix),
+        (_, Some("4:4:4")) => format!("yuv444p{}", suffix),
+        _ => format!("yuv420p{}", suffix),
+    }
+}
+
+/// MediaInfo gives the pixel shape as a decimal (`1.000`, `1.333`); ffprobe as a ratio.
+fn sample_aspect_ratio(par: f64) -> String {
+    if (par - 1.0).abs() < 0.001 {
+        return "1:1".to_string();
+    }
+    format!("{}:1000", (par * 1000.0).round())
+}
+
+fn field_order(scan: &str, order: Option<String>) -> String {
+    match (scan, order.as_deref()) {
+        ("Progressive", _) => "progressive".to_string(),
+        (_, Some("BFF")) => "bb".to_string(),
+        _ => "tt".to_string(),
+    }
+}
+
+/// `BT.709` -> `bt709`, `PQ` -> `smpte2084`, ...
+fn color_name(name: &str) -> String {
+    match name {
+        "PQ" => "smpte2084".to_string(),
+        "HLG" => "arib-std-b67".to_string(),
+        "BT.2020 non-constant" => "bt2020nc".to_string(),
+        other => other.to_lowercase().replace(['.', ' '], ""),
+    }
+}
+
+fn demuxer(format: &str) -> &str {
+    match format {
+        "MPEG-4" | "QuickTime" => "mov,mp4,m4a,3gp,3g2,mj2",
+        "Matroska" | "WebM" => "matroska,webm",
+        "MXF" => "mxf",
+        "MPEG-TS" => "mpegts",
+        "MPEG-PS" => "mpeg",
+        "AVI" => "avi",
+        "Wave" => "wav",
+        "AIFF" => "aiff",
+        "FLAC" => "flac",
+        "MPEG Audio" => "mp3",
+        "Ogg" => "ogg",
+        other => other,
+    }
+}
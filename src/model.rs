@@ -0,0 +1,411 @@
+//! What analysis produces: [`MediaInfo`], and the values the table, the details
+//! panel and the exports derive from it.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    analyzer::Backend, benchmark::Benchmark, blanks::Blanks, checksum, crop::Crop, hwdecode::HwDecode,
+    image::ImageInfo, loudness::Loudness, mxf::MxfInfo, notes::Note, quality::Quality, sequence::Sequence,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaInfo {
+    pub name: String,
+    pub container: String,
+    pub codec: String,
+    // As stored; players turn it by `rotation`
+    pub resolution: String,
+    // Clockwise degrees from the display matrix or rotate tag: 0, 90, 180 or 270
+    pub rotation: u16,
+    // Pixel shape as `num:den`, e.g. `4:3` for widescreen DV; `Unknown` reads as square
+    pub sample_aspect_ratio: String,
+    pub frame_rate: String,
+    pub variable_frame_rate: bool,
+    // Progressive, interlaced or telecined, see `interlace`
+    pub scan: String,
+    // Whether `scan` was measured with idet rather than read from the field_order tag
+    pub scan_detected: bool,
+    pub bitrate: String,
+    pub bitrate_estimated: bool,
+    pub duration: Option<f64>,
+    // From the container's tags
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    // Muxer or application that wrote the file, e.g. `Lavf60.3.100` or `HandBrake 1.7.2`
+    pub encoder: Option<String>,
+    // `2024-03-01 10:00:00`, as tagged (usually UTC)
+    pub creation_time: Option<String>,
+    // Start timecode, e.g. `01:00:00:00` (`;` before the frames when drop-frame)
+    pub timecode: Option<String>,
+    pub chapters: Vec<Chapter>,
+    pub file_size: Option<u64>,
+    pub pixel_format: String,
+    pub bit_depth: Option<u8>,
+    pub color_primaries: String,
+    pub color_transfer: String,
+    pub color_space: String,
+    pub hdr_format: String,
+    pub has_alpha: bool,
+    pub audio_codec: String,
+    pub audio_channels: Option<u32>,
+    // Of the first audio stream, in Hz
+    pub audio_sample_rate: Option<u32>,
+    // Stored bits per sample, `None` for lossy codecs
+    pub audio_bit_depth: Option<u8>,
+    // No picture at all, so the video fields read `N/A`; cover art doesn't count
+    pub audio_only: bool,
+    // Language tag of every audio stream in order, `und` where untagged
+    pub audio_languages: Vec<String>,
+    // Channel count of every audio stream in order; broadcast MXF carries 8 mono tracks
+    pub audio_track_channels: Vec<u32>,
+    // One entry per subtitle stream, e.g. `SRT (eng)` or `PGS (fra, forced)`
+    pub subtitles: Vec<String>,
+    // CEA-608/708 captions, embedded in the video or on their own track
+    pub closed_captions: bool,
+    // Whether the moov atom precedes the media data, `None` outside MP4/MOV
+    pub faststart: Option<bool>,
+    // Size and sampled-content hash shared by byte-identical copies
+    pub fingerprint: Option<String>,
+    // Filled in by the checksum pass, not by analysis
+    pub checksum: Option<Checksum>,
+    // Filled in by the loudness pass, not by analysis
+    pub loudness: Option<Loudness>,
+    // Filled in by the black and silence pass, not by analysis
+    pub blanks: Option<Blanks>,
+    // Active picture area, filled in by the cropdetect pass
+    pub crop: Option<Crop>,
+    // Scores against a reference, filled in by the quality pass
+    pub quality: Option<Quality>,
+    // Whether this machine decodes it in hardware, filled in once its decoders are known
+    pub hw_decode: Option<HwDecode>,
+    // Decode speed on this machine, filled in by the benchmark pass
+    pub benchmark: Option<Benchmark>,
+    // Review tags and note, filled in from the notes store, not by analysis
+    pub note: Option<Note>,
+    // ICC profile and EXIF data, for still images only
+    pub image: Option<ImageInfo>,
+    // Frame numbering, for image sequences only; `path` is then a `%04d` pattern
+    pub sequence: Option<Sequence>,
+    // Operational pattern, essence and writing application, for MXF only
+    pub mxf: Option<MxfInfo>,
+    // The tool that produced the row; `raw_output` is its report translated to ffprobe JSON
+    pub analyzer: Backend,
+    // What ffprobe warned about reading the file, a line each
+    pub warnings: Vec<String>,
+    pub path: String,
+    // Empty in the TUI's rows, which keep it on disk until the raw view asks
+    pub raw_output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: f64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checksum {
+    pub algorithm: checksum::Algorithm,
+    pub digest: String,
+}
+
+pub fn pixel_count(resolution: &str) -> Option<u64> {
+    let (w, h) = resolution.split_once('x')?;
+    Some(w.parse::<u64>().ok()? * h.parse::<u64>().ok()?)
+}
+
+pub fn format_duration(duration: Option<f64>) -> String {
+    match duration {
+        Some(secs) => {
+            let total = secs.round() as u64;
+            format!("{:02}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+        }
+        None => "Unknown".to_string(),
+    }
+}
+
+/// The time in UTC, `2026-03-01 14:05 UTC`.
+pub fn format_utc_now() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`
+    let z = seconds.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time = seconds.rem_euclid(86_400);
+    format!("{}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, time / 3600, time % 3600 / 60)
+}
+
+pub fn format_size(size: Option<u64>) -> String {
+    const KIB: f64 = 1024.0;
+    const MIB: f64 = KIB * 1024.0;
+    const GIB: f64 = MIB * 1024.0;
+    match size {
+        Some(bytes) => {
+            let bytes = bytes as f64;
+            if bytes >= GIB {
+                format!("{:.2} GiB", bytes / GIB)
+            } else if bytes >= MIB {
+                format!("{:.1} MiB", bytes / MIB)
+            } else if bytes >= KIB {
+                format!("{:.1} KiB", bytes / KIB)
+            } else {
+                format!("{} B", bytes)
+            }
+        }
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Derived bitrates get a `~` so they aren't mistaken for the file's own figure.
+pub fn format_bitrate(file: &MediaInfo) -> String {
+    if file.bitrate_estimated {
+        format!("~{}", file.bitrate)
+    } else {
+        file.bitrate.clone()
+    }
+}
+
+/// One loudness figure to a tenth, `Unknown` until the file has been measured.
+pub fn format_loudness(file: &MediaInfo, value: fn(&Loudness) -> f64) -> String {
+    file.loudness.as_ref().map_or("Unknown".to_string(), |l| format!("{:.1}", value(l)))
+}
+
+/// A quality score to `decimals` places, `Unknown` before the quality pass or without libvmaf.
+pub fn format_quality(file: &MediaInfo, value: fn(&Quality) -> Option<f64>, decimals: usize) -> String {
+    file.quality.as_ref().and_then(value).map_or("Unknown".to_string(), |v| format!("{:.*}", decimals, v))
+}
+
+/// Letterbox, Pillarbox, Windowbox or Full once cropdetect has run, `Unknown` before.
+pub fn matte(file: &MediaInfo) -> &'static str {
+    file.crop.map_or("Unknown", |crop| crop.matte(&file.resolution))
+}
+
+/// The resolution players show, turned by the rotation metadata.
+pub fn display_resolution(file: &MediaInfo) -> String {
+    match (file.rotation, file.resolution.split_once('x')) {
+        (90 | 270, Some((width, height))) => format!("{}x{}", height, width),
+        _ => file.resolution.clone(),
+    }
+}
+
+/// Landscape, Portrait or Square as displayed.
+pub fn orientation(file: &MediaInfo) -> &'static str {
+    let size = display_resolution(file);
+    let Some((width, height)) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u64>().ok()?, h.parse::<u64>().ok()?)))
+    else {
+        return "Unknown";
+    };
+    match width.cmp(&height) {
+        Ordering::Greater => "Landscape",
+        Ordering::Less => "Portrait",
+        Ordering::Equal => "Square",
+    }
+}
+
+/// `(num, den)` from `num:den`, `None` for ffprobe's `0:1` placeholder.
+pub fn parse_ratio(ratio: &str) -> Option<(f64, f64)> {
+    let (num, den) = ratio.split_once(':')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (num > 0.0 && den > 0.0).then_some((num, den))
+}
+
+/// Whether pixels are stored non-square, e.g. 720x576 DV shown at 16:9 or
+/// 1440x1080 HDV shown at 1920x1080.
+pub fn is_anamorphic(file: &MediaInfo) -> bool {
+    parse_ratio(&file.sample_aspect_ratio).is_some_and(|(num, den)| (num / den - 1.0).abs() > 0.01)
+}
+
+/// Width over height of the picture as shown: the stored frame stretched by the
+/// pixel shape, then turned by the rotation.
+pub fn display_aspect(file: &MediaInfo) -> Option<f64> {
+    let (width, height) = file.resolution.split_once('x')?;
+    let (width, height) = (width.parse::<f64>().ok()?, height.parse::<f64>().ok()?);
+    let (num, den) = parse_ratio(&file.sample_aspect_ratio).unwrap_or((1.0, 1.0));
+    let aspect = width * num / (height * den);
+    (aspect > 0.0 && aspect.is_finite()).then(|| if file.rotation % 180 == 90 { 1.0 / aspect } else { aspect })
+}
+
+/// The familiar name for a ratio (`16:9`, `2.39:1`), or two decimals when it has none.
+pub fn format_aspect(aspect: Option<f64>) -> String {
+    const NAMED: &[(&str, f64)] = &[
+        ("16:9", 16.0 / 9.0),
+        ("4:3", 4.0 / 3.0),
+        ("1:1", 1.0),
+        ("9:16", 9.0 / 16.0),
+        ("3:4", 3.0 / 4.0),
+        ("16:10", 1.6),
+        ("3:2", 1.5),
+        ("2:3", 2.0 / 3.0),
+        ("5:4", 1.25),
+        ("4:5", 0.8),
+        ("1.85:1", 1.85),
+        ("1.90:1", 256.0 / 135.0),
+        ("2.35:1", 2.35),
+        ("2.39:1", 2.39),
+    ];
+    let Some(aspect) = aspect else {
+        return "Unknown".to_string();
+    };
+    NAMED
+        .iter()
+        .find(|(_, ratio)| (aspect / ratio - 1.0).abs() < 0.005)
+        .map_or(format!("{:.2}:1", aspect), |(name, _)| name.to_string())
+}
+
+/// Rotated files show the resolution as displayed, marked so they don't pass for
+/// natively portrait (or upside-down) encodes.
+pub fn format_resolution(file: &MediaInfo) -> String {
+    match file.rotation {
+        0 => file.resolution.clone(),
+        _ => format!("{}↻", display_resolution(file)),
+    }
+}
+
+pub fn format_bit_depth(bit_depth: Option<u8>) -> String {
+    match bit_depth {
+        Some(bits) => format!("{}-bit", bits),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Bits per component implied by an ffmpeg pixel format name.
+pub fn pix_fmt_bit_depth(pix_fmt: &str) -> Option<u8> {
+    let base = pix_fmt.trim_end_matches("le").trim_end_matches("be");
+    match base {
+        "" => return None,
+        "rgb48" | "bgr48" | "rgba64" | "bgra64" | "p016" | "p216" | "p416" => return Some(16),
+        "p012" => return Some(12),
+        "p010" | "p210" | "p410" | "x2rgb10" | "x2bgr10" | "v210" => return Some(10),
+        _ => {}
+    }
+    // Gray formats carry the depth directly: gray, gray10, gray16
+    if let Some(digits) = base.strip_prefix("gray") {
+        return digits.parse().ok().or(Some(8));
+    }
+    // Planar formats put it after the trailing 'p': yuv422p10, gbrap12
+    if let Some(pos) = base.rfind('p') {
+        if let Ok(bits) = base[pos + 1..].parse() {
+            return Some(bits);
+        }
+    }
+    Some(8)
+}
+
+pub fn chroma_subsampling(pix_fmt: &str) -> &'static str {
+    let packed_422 = ["yuyv", "uyvy", "yvyu", "p210", "p216", "v210"];
+    if pix_fmt.is_empty() || pix_fmt == "Unknown" {
+        "Unknown"
+    } else if pix_fmt.contains("420") || pix_fmt.starts_with("nv12") || pix_fmt.starts_with("nv21")
+        || pix_fmt.starts_with("p01")
+    {
+        "4:2:0"
+    } else if pix_fmt.contains("422") || packed_422.iter().any(|p| pix_fmt.starts_with(p)) {
+        "4:2:2"
+    } else if pix_fmt.contains("411") {
+        "4:1:1"
+    } else if pix_fmt.contains("440") {
+        "4:4:0"
+    } else if pix_fmt.starts_with("gray") {
+        "4:0:0"
+    } else {
+        // 444, RGB and GBR variants all sample chroma at full resolution
+        "4:4:4"
+    }
+}
+
+pub fn faststart_label(faststart: Option<bool>) -> &'static str {
+    faststart.map_or("N/A", yes_no)
+}
+
+pub fn frame_rate_mode(variable: bool) -> &'static str {
+    if variable {
+        "VFR"
+    } else {
+        "CFR"
+    }
+}
+
+pub fn yes_no(value: bool) -> &'static str {
+    if value {
+        "Yes"
+    } else {
+        "No"
+    }
+}
+
+pub fn is_hdr(hdr_format: &str) -> bool {
+    !matches!(hdr_format, "SDR" | "Unknown")
+}
+
+/// `48 kHz`, `44.1 kHz`
+pub fn format_sample_rate(sample_rate: Option<u32>) -> String {
+    match sample_rate {
+        Some(rate) => format!("{} kHz", rate as f64 / 1000.0),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// `FLAC 48 kHz 24-bit 2ch`, leaving out whatever isn't known.
+pub fn format_audio_summary(file: &MediaInfo) -> String {
+    let mut parts = vec![file.audio_codec.clone()];
+    if file.audio_sample_rate.is_some() {
+        parts.push(format_sample_rate(file.audio_sample_rate));
+    }
+    if let Some(bits) = file.audio_bit_depth {
+        parts.push(format!("{}-bit", bits));
+    }
+    if let Some(channels) = file.audio_channels {
+        parts.push(format!("{}ch", channels));
+    }
+    parts.join(" ")
+}
+
+/// `stereo`, `5.1`, `8 × mono` or `stereo + 6 × mono`: runs of alike tracks counted.
+pub fn format_audio_layout(file: &MediaInfo) -> String {
+    let name = |channels: u32| match channels {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        6 => "5.1".to_string(),
+        8 => "7.1".to_string(),
+        0 => "?".to_string(),
+        n => format!("{}ch", n),
+    };
+    let mut runs: Vec<(u32, usize)> = Vec::new();
+    for &channels in &file.audio_track_channels {
+        match runs.last_mut() {
+            Some((last, count)) if *last == channels => *count += 1,
+            _ => runs.push((channels, 1)),
+        }
+    }
+    if runs.is_empty() {
+        return "None".to_string();
+    }
+    runs.into_iter()
+        .map(|(channels, count)| match count {
+            1 => name(channels),
+            count => format!("{} × {}", count, name(channels)),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Columns and filters read the languages as `eng, fra, deu`.
+pub fn format_languages(file: &MediaInfo) -> String {
+    match file.audio_languages.is_empty() {
+        true => "None".to_string(),
+        false => file.audio_languages.join(", "),
+    }
+}
@@ -0,0 +1,86 @@
+//! What broadcast deliverables are specified by: the MXF operational pattern, the
+//! essence flavour (XDCAM HD422, AVC-Intra 100, D-10 ...) and the application that
+//! wrote the file. Timecode and the mono audio tracks are read like any other file's.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::probe::{first_stream, format_tag};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MxfInfo {
+    /// `OP1a`, `OP-Atom` ...; `None` when the preface doesn't say
+    pub operational_pattern: Option<String>,
+    /// The video essence by the name deliverable specs use, e.g. `XDCAM HD422`
+    pub essence: String,
+    /// `Company Product Version` from the identification set
+    pub writer: Option<String>,
+}
+
+pub fn is_mxf(probe: &Value) -> bool {
+    probe["format"]["format_name"].as_str().is_some_and(|f| f.split(',').any(|f| f == "mxf"))
+}
+
+/// `codec` is the label analysis gave the video stream, `video_mbps` its bitrate.
+pub fn read(probe: &Value, codec: &str, video_mbps: Option<f64>) -> MxfInfo {
+    let writer = [
+        format_tag(probe, &["company_name"]),
+        format_tag(probe, &["product_name"]),
+        format_tag(probe, &["product_version"]),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    let pattern = format_tag(probe, &["operational_pattern_ul"]).and_then(|ul| operational_pattern(&ul));
+    MxfInfo {
+        operational_pattern: pattern,
+        essence: essence(probe, codec, video_mbps),
+        writer: (!writer.is_empty()).then(|| writer.join(" ")),
+    }
+}
+
+/// ffprobe prints the label as `060e2b34.04010101.0d010201.01010900`. Byte 13 is
+/// the item complexity (1-3, or 0x10 for OP-Atom) and byte 14 the package complexity
+/// (1-3 for a-c).
+fn operational_pattern(ul: &str) -> Option<String> {
+    let bytes: Vec<u8> = ul
+        .split('.')
+        .flat_map(|group| (0..group.len()).step_by(2).map(move |i| group.get(i..i + 2)))
+        .map(|pair| u8::from_str_radix(pair?, 16).ok())
+        .collect::<Option<_>>()?;
+    let (item, package) = (*bytes.get(12)?, *bytes.get(13)?);
+    match (item, package) {
+        (0x10, _) => Some("OP-Atom".to_string()),
+        (1..=3, 1..=3) => Some(format!("OP{}{}", item, (b'a' + package - 1) as char)),
+        _ => None,
+    }
+}
+
+fn essence(probe: &Value, codec: &str, video_mbps: Option<f64>) -> String {
+    let Some(video) = first_stream(probe, "video") else {
+        return "None".to_string();
+    };
+    let profile = video["profile"].as_str().unwrap_or_default();
+    let width = video["width"].as_u64().unwrap_or(0);
+    let height = video["height"].as_u64().unwrap_or(0);
+    let mbps = video_mbps.unwrap_or(0.0);
+    match video["codec_name"].as_str().unwrap_or_default() {
+        // IMX is SD 4:2:2 I-frame MPEG-2 with the VBI lines kept
+        "mpeg2video" if height == 608 || height == 512 => format!("D-10 (IMX {:.0})", mbps),
+        "mpeg2video" if height >= 720 && profile == "4:2:2" => "XDCAM HD422".to_string(),
+        "mpeg2video" if height >= 720 => "XDCAM HD".to_string(),
+        // AVC-Intra classes are named after their nominal bitrate
+        "h264" if profile.contains("Intra") => {
+            let class = [50, 100, 200]
+                .into_iter()
+                .min_by(|a, b| (*a as f64 - mbps).abs().total_cmp(&(*b as f64 - mbps).abs()))
+                .unwrap_or(100);
+            format!("AVC-Intra {}", class)
+        }
+        "h264" => "AVC Long GOP".to_string(),
+        "dvvideo" if width >= 960 => "DVCPRO HD".to_string(),
+        "dvvideo" => "DV".to_string(),
+        "jpeg2000" => "JPEG 2000".to_string(),
+        _ => codec.to_string(),
+    }
+}
@@ -0,0 +1,134 @@
+//! Review tags, notes, star ratings and accept/reject flags on files ("approved",
+//! "needs re-encode", "client hold"), kept by path in `notes.json` next to the
+//! config file so they stay with the library between sessions and across projects.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::{fs, path::PathBuf};
+
+use crate::{config, MediaInfo};
+
+/// A verdict from review, independent of the star rating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flag {
+    Accepted,
+    Rejected,
+}
+
+impl Flag {
+    pub fn label(self) -> &'static str {
+        match self {
+            Flag::Accepted => "Accepted",
+            Flag::Rejected => "Rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Note {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Free-form, empty when the file only has tags
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// 1 to 5 stars
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flag: Option<Flag>,
+}
+
+impl Note {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.text.is_empty() && self.rating.is_none() && self.flag.is_none()
+    }
+}
+
+/// `★★★☆☆` for a rating of 3.
+pub fn format_stars(rating: u8) -> String {
+    let rating = rating.min(5) as usize;
+    format!("{}{}", "★".repeat(rating), "☆".repeat(5 - rating))
+}
+
+pub struct Notes {
+    // By path
+    notes: BTreeMap<String, Note>,
+    file: Option<PathBuf>,
+}
+
+impl Notes {
+    /// Reads the notes file; a missing one starts empty. One that doesn't parse is an
+    /// error rather than a fresh start, which would overwrite it on the next edit.
+    pub fn load() -> Result<Self> {
+        let file = config::config_dir().map(|dir| dir.join("notes.json"));
+        let notes = match &file {
+            Some(path) if path.exists() => {
+                let text = fs::read_to_string(path).with_context(|| format!("reading notes {}", path.display()))?;
+                serde_json::from_str(&text).with_context(|| format!("parsing notes {}", path.display()))?
+            }
+            _ => BTreeMap::new(),
+        };
+        Ok(Self { notes, file })
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Note> {
+        self.notes.get(path)
+    }
+
+    /// Copies the stored note onto a freshly analyzed row.
+    pub fn apply(&self, file: &mut MediaInfo) {
+        file.note = self.notes.get(&file.path).cloned();
+    }
+
+    /// Edits the notes of `paths` and saves the file; a note left empty is removed.
+    pub fn update(&mut self, paths: &[String], edit: impl Fn(&mut Note)) -> Result<()> {
+        for path in paths {
+            let note = self.notes.entry(path.clone()).or_default();
+            edit(note);
+            if note.is_empty() {
+                self.notes.remove(path);
+            }
+        }
+        self.save()
+    }
+
+    /// Moves the notes of renamed files, `(from, to)`, to their new paths.
+    pub fn rename(&mut self, moved: &[(String, String)]) -> Result<()> {
+        let mut changed = false;
+        for (from, to) in moved {
+            if let Some(note) = self.notes.remove(from) {
+                self.notes.insert(to.clone(), note);
+                changed = true;
+            }
+        }
+        match changed {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(&self.notes)?;
+        fs::write(file, text).with_context(|| format!("writing notes {}", file.display()))
+    }
+}
+
+/// Tags typed as a comma-separated list, blanks and repeats (in any case) dropped.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
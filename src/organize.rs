@@ -0,0 +1,168 @@
+//! Sorting files into folders after their metadata, `Library/{codec}/{height}p`,
+//! to turn a scouted drop into an organized library. Each part of the folder
+//! pattern is filled in from the file's fields; the file keeps its name inside.
+//! Files are copied or moved on a thread of their own, one at a time, and nothing
+//! is ever written over an existing file.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::{cancel, paste::expand_home, rename, template::Template, MediaInfo};
+
+pub const DEFAULT_PATTERN: &str = "organized/{codec}/{height}p";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Copy,
+    Move,
+}
+
+impl Operation {
+    pub fn verb(self) -> &'static str {
+        match self {
+            Operation::Copy => "copy",
+            Operation::Move => "move",
+        }
+    }
+}
+
+/// A folder pattern, parsed a path component at a time so a field's value can't
+/// add folders of its own.
+pub struct Pattern {
+    root: PathBuf,
+    components: Vec<Template>,
+}
+
+impl Pattern {
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        // Everything up to the first field is taken as it's written, `~` and all
+        let fixed = text.find('{').map_or(text.len(), |i| text[..i].rfind('/').map_or(0, |slash| slash + 1));
+        let root = expand_home(&text[..fixed]);
+        let components = text[fixed..]
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(Template::parse_file_name)
+            .collect::<Result<_>>()?;
+        Ok(Pattern { root, components })
+    }
+
+    /// The folder `file` goes into.
+    pub fn folder(&self, file: &MediaInfo, index: usize) -> PathBuf {
+        let mut folder = self.root.clone();
+        for component in &self.components {
+            match rename::clean(component.render_file(file, index).trim()) {
+                // A field without a value would leave an empty folder name
+                name if name.is_empty() || name == "." || name == ".." => folder.push("Unknown"),
+                name => folder.push(name),
+            }
+        }
+        folder
+    }
+}
+
+/// Where one file would go, or why it stays.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub from: String,
+    pub to: String,
+    pub skipped: Option<String>,
+}
+
+/// Where `pattern` puts each file. Files already in place, names taken at the
+/// destination or by another file of the batch, and anything that isn't a single
+/// local file are skipped.
+pub fn plan(pattern: &Pattern, files: &[&MediaInfo]) -> Vec<Placement> {
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let from = Path::new(&file.path);
+            let to = pattern.folder(file, i).join(from.file_name().unwrap_or_default());
+            let skipped = if file.path.contains("://") {
+                Some("not a local file")
+            } else if file.sequence.is_some() {
+                Some("an image sequence")
+            } else if to == from || same_file(&to, from) {
+                Some("already in place")
+            } else if !taken.insert(to.clone()) {
+                Some("another file goes to the same place")
+            } else if to.exists() {
+                Some("a file of that name is there")
+            } else {
+                None
+            };
+            let to = to.to_string_lossy().to_string();
+            Placement { from: file.path.clone(), to, skipped: skipped.map(String::from) }
+        })
+        .collect()
+}
+
+/// Whether two paths, spelled differently, are the one file on disk.
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+/// One file done, with its place in the batch.
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+    pub from: String,
+    pub to: String,
+    /// What went wrong, without the file's path
+    pub result: Result<(), String>,
+}
+
+/// Copies or moves the placements `plan` didn't skip on a thread of its own,
+/// reporting each file as it's done. Moves are logged like renames. A cancel stops
+/// the batch between files, never halfway through one.
+pub fn start(placements: Vec<Placement>, operation: Operation) -> Receiver<Progress> {
+    let (tx, rx) = mpsc::channel();
+    let epoch = cancel::epoch();
+    thread::spawn(move || {
+        cancel::pin(epoch);
+        let placements: Vec<Placement> = placements.into_iter().filter(|p| p.skipped.is_none()).collect();
+        let total = placements.len();
+        for (i, placement) in placements.into_iter().enumerate() {
+            if cancel::cancelled() {
+                break;
+            }
+            let result = place(&placement.from, &placement.to, operation);
+            if operation == Operation::Move && result.is_ok() {
+                let _ = rename::log(&[(placement.from.clone(), placement.to.clone())]);
+            }
+            let progress = Progress { done: i + 1, total, from: placement.from, to: placement.to, result };
+            if tx.send(progress).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn place(from: &str, to: &str, operation: Operation) -> Result<(), String> {
+    let target = Path::new(to);
+    if target.exists() {
+        return Err(format!("{} exists", to));
+    }
+    if let Some(folder) = target.parent() {
+        fs::create_dir_all(folder).map_err(|e| format!("creating {}: {}", folder.display(), e))?;
+    }
+    if operation == Operation::Move && fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // A move to another file system is a copy, then removing the original
+    fs::copy(from, to).map_err(|e| e.to_string())?;
+    if operation == Operation::Move {
+        fs::remove_file(from).map_err(|e| format!("copied, but could not remove the original: {}", e))?;
+    }
+    Ok(())
+}
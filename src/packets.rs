@@ -0,0 +1,201 @@
+//! Packet-level probes of a file's video stream: the data behind the bitrate
+//! graph and the GOP report. Reading every packet header takes a few seconds on
+//! long files, so it runs in the background and only for files opened in a view.
+
+use std::{
+    io,
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::probe::ProbeOptions;
+
+pub struct Packet {
+    /// Presentation time in seconds
+    pub time: f64,
+    pub size: u64,
+    pub keyframe: bool,
+    /// Decoded after a frame that is shown later, which is what makes a B-frame
+    pub bframe: bool,
+}
+
+/// Keyframe intervals longer than this make scrubbing and looping stutter on
+/// media servers, which have to decode from the previous keyframe on every seek.
+pub const MAX_SCRUB_GOP: f64 = 1.0;
+
+/// Probes one file at a time on a background thread.
+pub struct PacketProber {
+    job_tx: Sender<String>,
+    results: Receiver<(String, Result<Vec<Packet>, String>)>,
+}
+
+impl PacketProber {
+    pub fn new(options: ProbeOptions) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<String>();
+        let (result_tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            for path in job_rx {
+                let result = probe(&options, &path);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, path: String) {
+        // The worker only stops when the prober is dropped
+        let _ = self.job_tx.send(path);
+    }
+
+    pub fn poll(&self) -> Vec<(String, Result<Vec<Packet>, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn probe(options: &ProbeOptions, path: &str) -> Result<Vec<Packet>, String> {
+    let input = options.input(path)?;
+    let mut command = Command::new(&options.ffprobe);
+    command
+        .args(&options.extra_args)
+        .args(options.sequence_args(path))
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "packet=pts_time,dts_time,size,flags"])
+        .args(options.stream_args(path))
+        .args(["-of", "csv=p=0", "-i", &input]);
+    let output = options.output(&mut command, path).map_err(|e| match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => e.to_string(),
+        _ => format!("could not start '{}': {}", options.ffprobe, e),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().last().unwrap_or("ffprobe failed").to_string());
+    }
+
+    // Lines look like `0.033367,0.000000,81234,K__` in decode order; timestamps can be N/A
+    let mut latest = f64::MIN;
+    let mut packets: Vec<Packet> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            let time = fields
+                .first()
+                .and_then(|pts| pts.parse().ok())
+                .or_else(|| fields.get(1)?.parse().ok())?;
+            let size = fields.get(2)?.parse().ok()?;
+            let bframe = time < latest;
+            latest = latest.max(time);
+            Some(Packet {
+                time,
+                size,
+                keyframe: fields.get(3).is_some_and(|flags| flags.starts_with('K')),
+                bframe,
+            })
+        })
+        .collect();
+    if packets.is_empty() {
+        return Err("no video packets found".to_string());
+    }
+    packets.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(packets)
+}
+
+/// Bitrate in Mbps over consecutive windows of `window` seconds, as
+/// `(window start, Mbps)` points.
+pub fn bitrate_series(packets: &[Packet], window: f64) -> Vec<(f64, f64)> {
+    let Some(start) = packets.first().map(|p| p.time) else {
+        return Vec::new();
+    };
+    let mut bits: Vec<f64> = Vec::new();
+    for packet in packets {
+        let bin = ((packet.time - start) / window) as usize;
+        if bits.len() <= bin {
+            bits.resize(bin + 1, 0.0);
+        }
+        bits[bin] += packet.size as f64 * 8.0;
+    }
+    bits.iter()
+        .enumerate()
+        .map(|(i, bits)| (start + i as f64 * window, bits / window / 1_000_000.0))
+        .collect()
+}
+
+pub struct GopStats {
+    /// Frames in each GOP, from one keyframe up to the next
+    pub lengths: Vec<usize>,
+    /// Seconds between consecutive keyframes
+    pub intervals: Vec<f64>,
+    pub frames: usize,
+    pub keyframes: usize,
+    pub bframes: usize,
+}
+
+impl GopStats {
+    pub fn intra_only(&self) -> bool {
+        self.keyframes == self.frames
+    }
+
+    pub fn mean_interval(&self) -> Option<f64> {
+        (!self.intervals.is_empty()).then(|| self.intervals.iter().sum::<f64>() / self.intervals.len() as f64)
+    }
+
+    pub fn max_interval(&self) -> Option<f64> {
+        self.intervals.iter().copied().max_by(f64::total_cmp)
+    }
+
+    /// Standard deviation of the keyframe interval over its mean: 0 for a fixed
+    /// GOP, higher when the encoder places keyframes on scene cuts.
+    pub fn irregularity(&self) -> Option<f64> {
+        let mean = self.mean_interval().filter(|mean| *mean > 0.0)?;
+        let variance = self.intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / self.intervals.len() as f64;
+        Some(variance.sqrt() / mean)
+    }
+
+    /// Problems worth flagging before the file goes to a media server.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.keyframes == 0 {
+            warnings.push("No keyframes found, seeking will fail or decode from the start".to_string());
+            return warnings;
+        }
+        let longest = self.max_interval().unwrap_or(0.0);
+        if self.keyframes == 1 && self.frames > 1 {
+            warnings.push("Only the first frame is a keyframe, every seek decodes from the start".to_string());
+        } else if longest > MAX_SCRUB_GOP {
+            warnings.push(format!(
+                "Long GOP: keyframes up to {:.2}s apart, scrubbing and loop points will stutter on media servers (aim for {:.0}s or an intra-only codec)",
+                longest, MAX_SCRUB_GOP
+            ));
+        }
+        if self.irregularity().is_some_and(|spread| spread > 0.25) {
+            warnings.push("Irregular keyframe interval, seek cost varies across the timeline".to_string());
+        }
+        if self.bframes > 0 {
+            warnings.push(format!(
+                "{} B-frame(s): frames decode out of order, which slows reverse playback and scratching",
+                self.bframes
+            ));
+        }
+        warnings
+    }
+}
+
+/// GOP structure of the packets, which must be in presentation order.
+pub fn gop_stats(packets: &[Packet]) -> GopStats {
+    let keyframe_times: Vec<f64> = packets.iter().filter(|p| p.keyframe).map(|p| p.time).collect();
+    let mut lengths = Vec::new();
+    for packet in packets.iter().skip_while(|p| !p.keyframe) {
+        match lengths.last_mut() {
+            Some(length) if !packet.keyframe => *length += 1,
+            _ => lengths.push(1),
+        }
+    }
+    GopStats {
+        lengths,
+        intervals: keyframe_times.windows(2).map(|pair| pair[1] - pair[0]).collect(),
+        frames: packets.len(),
+        keyframes: keyframe_times.len(),
+        bframes: packets.iter().filter(|p| p.bframe).count(),
+    }
+}
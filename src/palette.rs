@@ -0,0 +1,100 @@
+//! The command palette: every command by name, narrowed with a fuzzy search as
+//! the name is typed, so the ones without a key of their own are still a few
+//! letters away.
+
+use ratatui::widgets::ListState;
+
+pub struct Entry<C> {
+    pub command: C,
+    pub name: String,
+    /// The keys bound to the command, empty when there are none
+    pub keys: String,
+}
+
+pub struct CommandPalette<C> {
+    entries: Vec<Entry<C>>,
+    /// Indices of the entries matching the query, best first
+    matches: Vec<usize>,
+    pub list_state: ListState,
+}
+
+impl<C> CommandPalette<C> {
+    pub fn new(entries: Vec<Entry<C>>) -> Self {
+        let mut palette = Self { entries, matches: Vec::new(), list_state: ListState::default() };
+        palette.search("");
+        palette
+    }
+
+    /// Keeps the entries `query` matches, the closest matches first, and selects the best one.
+    pub fn search(&mut self, query: &str) {
+        let mut scored: Vec<(usize, i64)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_score(query, &entry.name).map(|score| (i, score)))
+            .collect();
+        // Stable, so equal scores keep the listing order
+        scored.sort_by_key(|&(_, score)| -score);
+        self.matches = scored.into_iter().map(|(i, _)| i).collect();
+        self.list_state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    pub fn matches(&self) -> impl Iterator<Item = &Entry<C>> {
+        self.matches.iter().map(|&i| &self.entries[i])
+    }
+
+    pub fn selected(&self) -> Option<&Entry<C>> {
+        let i = *self.matches.get(self.list_state.selected()?)?;
+        self.entries.get(i)
+    }
+
+    pub fn next(&mut self) {
+        let len = self.matches.len();
+        if len > 0 {
+            let current = self.list_state.selected().unwrap_or(0);
+            self.list_state.select(Some((current + 1) % len));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.matches.len();
+        if len > 0 {
+            let current = self.list_state.selected().unwrap_or(0);
+            self.list_state.select(Some((current + len - 1) % len));
+        }
+    }
+}
+
+/// How well `text` matches `query` when the query's characters appear in it in
+/// order, ignoring case and spaces; `None` when they don't. Matches at the start of
+/// words and runs of consecutive characters score higher, so `rsr` finds
+/// "Reverse sort order" ahead of "Show raw FFprobe output".
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let word_start = |i: usize| i == 0 || !text[i - 1].is_alphanumeric();
+    let mut score = 0;
+    let mut position = 0;
+    for (n, &wanted) in query.iter().enumerate() {
+        let first = position + text[position..].iter().position(|&c| c == wanted)?;
+        // A later word start beats the first occurrence, if the rest still fits after it
+        let fits = |i: usize| is_subsequence(&query[n + 1..], &text[i + 1..]);
+        let consecutive = (n > 0 && text.get(position) == Some(&wanted)).then_some(position);
+        let at_word = (first..text.len()).find(|&i| text[i] == wanted && word_start(i));
+        let found = consecutive.filter(|&i| fits(i)).or(at_word.filter(|&i| fits(i))).unwrap_or(first);
+        score += match (word_start(found), Some(found) == consecutive) {
+            (true, _) => 10,
+            (false, true) => 5,
+            (false, false) => 1,
+        };
+        // Skipped characters count against the match a little
+        score -= (found - position).min(12) as i64 / 3;
+        position = found + 1;
+    }
+    Some(score)
+}
+
+fn is_subsequence(needle: &[char], haystack: &[char]) -> bool {
+    let mut haystack = haystack.iter();
+    needle.iter().all(|c| haystack.any(|h| h == c))
+}
@@ -0,0 +1,159 @@
+//! Turns pasted or dragged-in text into paths. Terminals quote or backslash-escape
+//! a dropped file's path, and some hand over `file://` URIs instead.
+
+use std::{
+    env,
+    path::{self, PathBuf},
+};
+
+/// Every path in `text`, one or more per line.
+pub fn paths(text: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        // A plain path with spaces, e.g. from "Copy as Pathname"
+        let plain = expand_home(line);
+        if plain.exists() {
+            paths.push(plain.to_string_lossy().to_string());
+            continue;
+        }
+        let words = split_words(line);
+        paths.extend(words.iter().map(|word| expand_home(&from_uri(word)).to_string_lossy().to_string()));
+    }
+    paths
+}
+
+/// `~/Movies` -> `$HOME/Movies`, as a shell would have expanded it.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(path::is_separator) => {
+            PathBuf::from(home).join(rest.trim_start_matches(path::is_separator))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Splits on unquoted whitespace the way a shell would, undoing quotes and escapes.
+fn split_words(line: &str) -> Vec<String> {
+    // Backslash is the path separator on Windows, never an escape
+    let escapes = !cfg!(windows);
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') if escapes => {
+                if let Some(next) = chars.next() {
+                    if next != '"' && next != '\\' {
+                        word.push('\\');
+                    }
+                    word.push(next);
+                }
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') if escapes => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// `file:///Users/me/My%20Clip.mov` -> `/Users/me/My Clip.mov`; other text is returned as is.
+fn from_uri(word: &str) -> String {
+    let Some(rest) = word.strip_prefix("file://") else {
+        return word.to_string();
+    };
+    // Skip the host, which is empty or "localhost" for local files
+    let mut path = &rest[rest.find('/').unwrap_or(rest.len())..];
+    // file:///C:/clip.mov
+    if path.as_bytes().get(2) == Some(&b':') {
+        path = &path[1..];
+    }
+    percent_decode(path)
+}
+
+/// `My%20Clip.mov` -> `My Clip.mov`
+pub fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_split_on_unquoted_whitespace() {
+        assert_eq!(split_words("  a.mov\tb.mov  "), ["a.mov", "b.mov"]);
+        assert_eq!(split_words("'My Clip.mov' \"Other Clip.mov\""), ["My Clip.mov", "Other Clip.mov"]);
+        assert_eq!(split_words("Day' 1'/clip.mov"), ["Day 1/clip.mov"]);
+        // A quoted empty string is still a word
+        assert_eq!(split_words("'' a"), ["", "a"]);
+        assert!(split_words("   ").is_empty());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn backslashes_escape_outside_single_quotes() {
+        assert_eq!(split_words(r"My\ Clip.mov b.mov"), ["My Clip.mov", "b.mov"]);
+        assert_eq!(split_words(r#""say \"hi\".mov""#), ["say \"hi\".mov"]);
+        // Inside double quotes only `"` and `\` are escaped
+        assert_eq!(split_words(r#""a\b.mov""#), [r"a\b.mov"]);
+        assert_eq!(split_words(r"'a\ b.mov'"), [r"a\ b.mov"]);
+    }
+
+    #[test]
+    fn file_uris_become_paths() {
+        assert_eq!(from_uri("file:///Users/me/My%20Clip.mov"), "/Users/me/My Clip.mov");
+        assert_eq!(from_uri("file://localhost/srv/clip.mov"), "/srv/clip.mov");
+        assert_eq!(from_uri("file:///C:/Media/clip.mov"), "C:/Media/clip.mov");
+        assert_eq!(from_uri("/plain/100%25.mov"), "/plain/100%25.mov");
+    }
+
+    #[test]
+    fn bad_escapes_are_left_alone() {
+        assert_eq!(percent_decode("100%.mov"), "100%.mov");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9.mov"), "été.mov");
+    }
+}
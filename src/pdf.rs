@@ -0,0 +1,319 @@
+//! Just enough PDF for the reports: pages of text in the standard Helvetica faces,
+//! filled rectangles and JPEG images, which PDF takes as they are. The standard
+//! fonts need no embedding, so text is limited to what WinAnsi encodes.
+
+use std::fmt::Write;
+
+/// A4, in points
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+
+    /// Advance widths of ' ' through '~' in thousandths of the size, from the AFM files.
+    fn widths(self) -> &'static [u16; 95] {
+        match self {
+            Font::Regular => &HELVETICA,
+            Font::Bold => &HELVETICA_BOLD,
+        }
+    }
+}
+
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667, 611, 778,
+    722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278,
+    278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667, 611, 778,
+    722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333,
+    278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// The WinAnsi code for `c`, `?` for what it can't show.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '™' => 0x99,
+        _ => b'?',
+    }
+}
+
+/// Width of `text` set in `font` at `size` points.
+pub fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match win_ansi(c) {
+            code @ 32..=126 => font.widths()[code as usize - 32] as u32,
+            // Near enough for the accented letters and punctuation above ASCII
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// `text` broken into lines no wider than `width`, at spaces where it can be and
+/// mid-word where a word alone is too wide.
+pub fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if text_width(&candidate, font, size) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                if !line.is_empty() && text_width(&format!("{}{}", line, c), font, size) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// A baseline JPEG or a progressive one, grayscale or RGB.
+pub struct Jpeg {
+    data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    components: u8,
+}
+
+impl Jpeg {
+    /// Reads the frame header for the size; `None` for anything but a gray or RGB JPEG.
+    pub fn parse(data: Vec<u8>) -> Option<Self> {
+        if data.get(..2)? != [0xFF, 0xD8] {
+            return None;
+        }
+        let mut i = 2;
+        while i + 4 <= data.len() {
+            if data[i] != 0xFF {
+                return None;
+            }
+            let marker = data[i + 1];
+            let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            // SOF0 to SOF15, leaving out DHT, JPG and DAC, which share the range
+            if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                let frame = data.get(i + 4..i + 10)?;
+                let height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+                let width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+                let components = frame[5];
+                return matches!(components, 1 | 3).then_some(Jpeg { data, width, height, components });
+            }
+            i += 2 + length;
+        }
+        None
+    }
+}
+
+/// A document being drawn, page by page. Positions are in points from the top
+/// left corner, and text is placed by its baseline.
+#[derive(Default)]
+pub struct Pdf {
+    pages: Vec<String>,
+    current: usize,
+    images: Vec<Jpeg>,
+}
+
+impl Pdf {
+    /// Starts a page and draws on it from now on.
+    pub fn add_page(&mut self) {
+        self.pages.push(String::new());
+        self.current = self.pages.len() - 1;
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Draws on an earlier page, e.g. to number them once they're all laid out.
+    pub fn select_page(&mut self, index: usize) {
+        self.current = index.min(self.pages.len().saturating_sub(1));
+    }
+
+    fn content(&mut self) -> &mut String {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        &mut self.pages[self.current]
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, color: Rgb, text: &str) {
+        let mut literal = String::new();
+        for code in text.chars().map(win_ansi) {
+            match code {
+                b'(' | b')' | b'\\' => {
+                    literal.push('\\');
+                    literal.push(code as char);
+                }
+                32..=126 => literal.push(code as char),
+                // Everything else as octal, keeping the content stream ASCII
+                _ => {
+                    let _ = write!(literal, "\\{:03o}", code);
+                }
+            }
+        }
+        let (r, g, b) = fill(color);
+        let _ = writeln!(
+            self.content(),
+            "BT /{} {:.1} Tf {} {} {} rg {:.2} {:.2} Td ({}) Tj ET",
+            font.resource(),
+            size,
+            r,
+            g,
+            b,
+            x,
+            PAGE_HEIGHT - y,
+            literal
+        );
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        let (r, g, b) = fill(color);
+        let _ = writeln!(
+            self.content(),
+            "{} {} {} rg {:.2} {:.2} {:.2} {:.2} re f",
+            r,
+            g,
+            b,
+            x,
+            PAGE_HEIGHT - y - height,
+            width,
+            height
+        );
+    }
+
+    pub fn image(&mut self, jpeg: Jpeg, x: f32, y: f32, width: f32, height: f32) {
+        let name = format!("Im{}", self.images.len());
+        self.images.push(jpeg);
+        let _ = writeln!(
+            self.content(),
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /{} Do Q",
+            width,
+            height,
+            x,
+            PAGE_HEIGHT - y - height,
+            name
+        );
+    }
+
+    /// The finished file.
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        // 1 catalog, 2 page tree, 3 and 4 fonts, then the images, then each page
+        // and its content
+        let first_image = 5;
+        let first_page = first_image + self.images.len();
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| first_page + 2 * i).collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+                page_ids.len()
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+        ];
+        for image in &self.images {
+            let color_space = if image.components == 1 { "DeviceGray" } else { "DeviceRGB" };
+            let mut object = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8 \
+                 /Filter /DCTDecode /Length {} >>\nstream\n",
+                image.width,
+                image.height,
+                color_space,
+                image.data.len()
+            )
+            .into_bytes();
+            object.extend_from_slice(&image.data);
+            object.extend_from_slice(b"\nendstream");
+            objects.push(object);
+        }
+        let images: Vec<String> =
+            (0..self.images.len()).map(|i| format!("/Im{} {} 0 R", i, first_image + i)).collect();
+        let resources = format!("<< /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {} >> >>", images.join(" "));
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources {} /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    resources,
+                    page_ids[i] + 1
+                )
+                .into_bytes(),
+            );
+            let compressed = miniz_oxide::deflate::compress_to_vec_zlib(content.as_bytes(), 6);
+            let mut object =
+                format!("<< /Length {} /Filter /FlateDecode >>\nstream\n", compressed.len()).into_bytes();
+            object.extend_from_slice(&compressed);
+            object.extend_from_slice(b"\nendstream");
+            objects.push(object);
+        }
+
+        // The binary comment line tells tools the file isn't plain text
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(table, "{:010} 00000 n ", offset);
+        }
+        let size = objects.len() + 1;
+        let _ = write!(table, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", size, xref);
+        out.extend_from_slice(table.as_bytes());
+        out
+    }
+}
+
+/// A color as the operands of `rg`.
+fn fill((r, g, b): Rgb) -> (String, String, String) {
+    let channel = |value: u8| format!("{:.3}", value as f32 / 255.0);
+    (channel(r), channel(g), channel(b))
+}
@@ -0,0 +1,195 @@
+//! Perceptual hashes of sampled frames, for grouping clips that show the same
+//! picture but were encoded differently (another codec, bitrate or resolution).
+//!
+//! Each clip is sampled at the same relative positions, so versions with a few
+//! frames of difference in length still line up. Each frame is reduced to a 32x32
+//! grayscale image whose low DCT frequencies give a 64-bit hash.
+
+use std::{
+    collections::HashMap,
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, model::MediaInfo, sequence};
+
+/// Frames sampled per clip.
+const SAMPLES: usize = 8;
+/// Mean differing bits per frame hash, out of 64, under which two clips count as
+/// the same picture. Re-encodes land well under this, unrelated footage well over.
+const MAX_DISTANCE: f64 = 10.0;
+const SIDE: usize = 32;
+
+pub type Hashes = Vec<u64>;
+
+/// Hashes one clip at a time on a background thread; each needs a seek and a
+/// decode per sample.
+pub struct FrameHasher {
+    job_tx: Sender<(String, Option<f64>)>,
+    results: Receiver<(String, Result<Hashes, String>)>,
+}
+
+impl FrameHasher {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<(String, Option<f64>)>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for (path, duration) in job_rx {
+                let result = hash_clip(&ffmpeg, &path, duration);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, path: String, duration: Option<f64>) {
+        // The worker only stops when the hasher is dropped
+        let _ = self.job_tx.send((path, duration));
+    }
+
+    /// Clips finished since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Hashes, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn hash_clip(ffmpeg: &str, path: &str, duration: Option<f64>) -> Result<Hashes, String> {
+    // Stills and streams without a known length give a single sample
+    let times: Vec<f64> = match duration.filter(|d| *d > 0.0) {
+        Some(duration) => (0..SAMPLES)
+            .map(|i| duration * (i as f64 + 0.5) / SAMPLES as f64)
+            .collect(),
+        None => vec![0.0],
+    };
+    times.into_iter().map(|time| hash_frame(ffmpeg, path, time)).collect()
+}
+
+fn hash_frame(ffmpeg: &str, path: &str, time: f64) -> Result<u64, String> {
+    let scale = format!("scale={}:{}:flags=area,format=gray", SIDE, SIDE);
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-v", "error", "-ss", &format!("{:.3}", time)])
+        .args(sequence::input_args(path))
+        .args(["-i", path])
+        .args(["-map", "0:v:0", "-frames:v", "1", "-vf", &scale, "-f", "rawvideo", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    if output.stdout.len() < SIDE * SIDE {
+        let message = String::from_utf8_lossy(&output.stderr);
+        let message = message.lines().last().unwrap_or("no frame decoded");
+        return Err(format!("frame at {:.1}s: {}", time, message));
+    }
+    Ok(dct_hash(&output.stdout[..SIDE * SIDE]))
+}
+
+/// The classic pHash: each of the 8x8 lowest frequencies (bar the DC term, which is
+/// just brightness) becomes a bit set when it's above their median.
+fn dct_hash(pixels: &[u8]) -> u64 {
+    let cosines: Vec<Vec<f64>> = (0..8)
+        .map(|u| {
+            (0..SIDE)
+                .map(|x| ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / (2 * SIDE) as f64).cos())
+                .collect()
+        })
+        .collect();
+    let mut coefficients = Vec::with_capacity(64);
+    for cos_v in &cosines {
+        for cos_u in &cosines {
+            let mut sum = 0.0;
+            for y in 0..SIDE {
+                for x in 0..SIDE {
+                    sum += pixels[y * SIDE + x] as f64 * cos_u[x] * cos_v[y];
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c > median)
+        .fold(0, |hash, (i, _)| hash | 1 << i)
+}
+
+fn distance(a: &Hashes, b: &Hashes) -> Option<f64> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let bits: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+    Some(bits as f64 / a.len() as f64)
+}
+
+/// Indices of hashed files that look alike, in groups of two or more ordered by
+/// their first member. Groups made only of byte-identical copies are left to
+/// the exact duplicate check.
+pub fn groups(files: &[MediaInfo], hashes: &HashMap<String, Hashes>) -> Vec<Vec<usize>> {
+    let hashed: Vec<(usize, &Hashes)> = files
+        .iter()
+        .enumerate()
+        .filter_map(|(i, file)| Some((i, hashes.get(&file.path)?)))
+        .collect();
+
+    // Union-find over every close pair
+    let mut parent: Vec<usize> = (0..hashed.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for a in 0..hashed.len() {
+        for b in a + 1..hashed.len() {
+            if distance(hashed[a].1, hashed[b].1).is_some_and(|d| d <= MAX_DISTANCE) {
+                let (root_a, root_b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[root_b] = root_a;
+            }
+        }
+    }
+
+    let mut by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (member, (index, _)) in hashed.iter().enumerate() {
+        let group_root = root(&mut parent, member);
+        by_root.entry(group_root).or_default().push(*index);
+    }
+    let mut groups: Vec<Vec<usize>> = by_root
+        .into_values()
+        .filter(|group| {
+            let first = &files[group[0]].fingerprint;
+            let identical = first.is_some() && group.iter().all(|&i| files[i].fingerprint == *first);
+            group.len() > 1 && !identical
+        })
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort_by_key(|group| group[0]);
+    groups
+}
+
+/// The copy to keep from a group: the most pixels, then the highest bitrate, then the largest file.
+pub fn best(files: &[MediaInfo], group: &[usize]) -> Option<usize> {
+    let pixels = |file: &MediaInfo| {
+        file.resolution
+            .split_once('x')
+            .and_then(|(w, h)| Some(w.parse::<u64>().ok()? * h.parse::<u64>().ok()?))
+            .unwrap_or(0)
+    };
+    let bitrate = |file: &MediaInfo| file.bitrate.parse::<f64>().unwrap_or(0.0);
+    group.iter().copied().max_by(|&a, &b| {
+        let (a, b) = (&files[a], &files[b]);
+        pixels(a)
+            .cmp(&pixels(b))
+            .then(bitrate(a).total_cmp(&bitrate(b)))
+            .then(a.file_size.cmp(&b.file_size))
+    })
+}
@@ -0,0 +1,1189 @@
+//! Running ffprobe on a path and normalizing its JSON into a [`MediaInfo`], plus
+//! finding the media files a path stands for.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    time::Duration,
+};
+
+use crate::{
+    analyzer::Backend,
+    cancel,
+    config::Config,
+    dedupe,
+    ignore::Ignore,
+    image, interlace,
+    model::{parse_ratio, pix_fmt_bit_depth, Chapter, MediaInfo},
+    mxf, paste, query,
+    s3::{self, S3},
+    sequence,
+    ssh::{self, Ssh},
+};
+
+/// How ffprobe gets invoked, taken from the config.
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    pub(crate) ffprobe: String,
+    pub(crate) extra_args: Vec<String>,
+    pub(crate) sequence_fps: f64,
+    pub(crate) probe_timeout: Duration,
+    pub(crate) stream_timeout: Duration,
+    pub(crate) stream_sample: f64,
+    pub(crate) s3: S3,
+    pub(crate) ssh: Ssh,
+}
+
+/// The settings of a default config: ffprobe from PATH, no extra arguments.
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+impl ProbeOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ffprobe: config.ffprobe.clone(),
+            extra_args: config.ffprobe_args.clone(),
+            sequence_fps: config.sequence_fps,
+            probe_timeout: Duration::from_secs_f64(config.probe_timeout),
+            stream_timeout: Duration::from_secs_f64(config.stream_timeout),
+            stream_sample: config.stream_sample,
+            s3: S3::from_config(config),
+            ssh: Ssh::new(&config.hosts),
+        }
+    }
+
+    /// Input options for reading an image sequence at the configured frame rate;
+    /// empty for ordinary files.
+    pub(crate) fn sequence_args(&self, path: &str) -> Vec<String> {
+        let mut args = sequence::input_args(path);
+        if !args.is_empty() {
+            args.extend(["-framerate".to_string(), self.sequence_fps.to_string()]);
+        }
+        args
+    }
+
+    /// What ffprobe is pointed at for `path`: the path itself, or a presigned https
+    /// URL for an S3 object.
+    pub(crate) fn input(&self, path: &str) -> Result<String, String> {
+        match s3::is_s3(path) {
+            true => self.s3.presign(path),
+            false => Ok(path.to_string()),
+        }
+    }
+
+    /// Options that stop ffprobe's packet and frame listings after the sampled seconds
+    /// of a live stream, which would otherwise go on forever; empty for files.
+    pub(crate) fn stream_args(&self, path: &str) -> Vec<String> {
+        match is_stream(path) {
+            true => vec!["-read_intervals".to_string(), format!("%+{}", self.stream_sample)],
+            false => Vec::new(),
+        }
+    }
+
+    /// Runs an ffprobe `command` to completion, killing it once it has taken longer
+    /// than the configured timeout, the one for live streams or the one for anything
+    /// else, or once its work is cancelled. `ssh://` paths run it on their host.
+    pub(crate) fn output(&self, command: &mut Command, path: &str) -> io::Result<Output> {
+        let mut remote;
+        let command = match ssh::is_ssh(path) {
+            true => {
+                remote = self.ssh.wrap(command, path).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                &mut remote
+            }
+            false => command,
+        };
+        let timeout = if is_stream(path) { self.stream_timeout } else { self.probe_timeout };
+        cancel::output_within(command, Some(timeout)).map_err(|e| match e.kind() {
+            io::ErrorKind::TimedOut => {
+                let seconds = timeout.as_secs_f64();
+                let message = match is_stream(path) {
+                    true => format!("no answer from the stream within {}s", seconds),
+                    false => format!("timed out after {}s", seconds),
+                };
+                io::Error::new(io::ErrorKind::TimedOut, message)
+            }
+            _ => e,
+        })
+    }
+}
+
+pub const FFPROBE_INSTALL_HINT: &str = "Install FFmpeg (macOS: brew install ffmpeg, Debian/Ubuntu: \
+sudo apt install ffmpeg, Windows: winget install ffmpeg) or point media-scout at ffprobe with \
+--ffprobe, MEDIA_SCOUT_FFPROBE or the `ffprobe` config key";
+
+/// Runs `ffprobe -version` to make sure the configured binary actually works.
+/// Returns the version line on success.
+pub fn check_ffprobe(ffprobe: &str) -> Result<String> {
+    let output = Command::new(ffprobe)
+        .arg("-version")
+        .output()
+        .map_err(|e| anyhow::anyhow!("ffprobe could not be started ('{}': {})", ffprobe, e))?;
+    if !output.status.success() {
+        anyhow::bail!("'{}' -version exited with {}", ffprobe, output.status);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+/// Lines of ffprobe's stderr kept per file; a broken file can log one per packet.
+const MAX_WARNINGS: usize = 20;
+
+pub fn probe_file(path: &str, options: &ProbeOptions) -> Result<MediaInfo> {
+    let input = options.input(path).map_err(|e| anyhow::anyhow!("could not presign {}: {}", path, e))?;
+    let mut command = Command::new(&options.ffprobe);
+    // Extra flags go first: input options like -probesize only apply before -i. Only
+    // warnings and errors on stderr, whose stream listing would repeat the JSON; a -v
+    // among the extra flags comes later and wins.
+    command
+        .args(["-v", "warning"])
+        .args(&options.extra_args)
+        .args(options.sequence_args(path))
+        .args([
+            "-i", &input,
+            "-show_streams",
+            "-show_format",
+            "-show_chapters",
+            "-hide_banner",
+            "-of", "json"
+        ]);
+    let output = options.output(&mut command, path).map_err(|e| match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => anyhow::anyhow!("{}", e),
+        _ => anyhow::anyhow!("ffprobe could not be started ('{}': {})", options.ffprobe, e),
+    })?;
+
+    let raw_output = String::from_utf8_lossy(&output.stdout);
+    let mut info = normalize(path, &input, &raw_output, options);
+    info.warnings = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(MAX_WARNINGS)
+        .map(String::from)
+        .collect();
+    Ok(info)
+}
+
+/// Turns ffprobe's stream, format and chapter JSON for `path` into a [`MediaInfo`].
+/// `input` is what the tools were pointed at, e.g. a presigned URL for an S3 object.
+/// Other analyzers translate their output into the same shape and come through here.
+pub(crate) fn normalize(path: &str, input: &str, raw_output: &str, options: &ProbeOptions) -> MediaInfo {
+    let probe: Value = serde_json::from_str(raw_output).unwrap_or(Value::Null);
+    let sequence = sequence::scan(path);
+
+    // Parse basic info from path, or from a URL's path without the query
+    let local_path = match is_url(path) {
+        true => paste::percent_decode(path.split(['?', '#']).next().unwrap_or(path)),
+        false => path.to_string(),
+    };
+    let path_obj = Path::new(&local_path);
+    let mut name = path_obj.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    // `frame_%04d` reads better as `frame_[0001-0500]`
+    if let (Some((sequence, _)), Some(percent)) = (&sequence, name.rfind('%')) {
+        name = format!("{}[{}]", name[..percent].replace("%%", "%"), sequence.range());
+    }
+    // Streams rarely end in an extension; ffprobe's demuxer (`rtsp`, `hls` ...) says more
+    let mut container = path_obj.extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    if is_stream(path) && container.is_empty() {
+        let demuxer = probe["format"]["format_name"].as_str().unwrap_or_default();
+        container = demuxer.split(',').next().unwrap_or_default().to_string();
+    }
+
+    let codec = extract_codec(&probe);
+    let resolution = extract_resolution(&probe);
+    // Stills have no frame rate or bitrate to speak of, but do have a color profile and EXIF
+    let is_still = is_still_image(&probe) && sequence.is_none();
+    let image = is_still.then(|| image::read(path_obj));
+    let rotation = match extract_rotation(&probe) {
+        0 => image.as_ref().map_or(0, |image| image.rotation),
+        rotation => rotation,
+    };
+    let sample_aspect_ratio = first_stream(&probe, "video")
+        .and_then(|video| video["sample_aspect_ratio"].as_str())
+        .filter(|sar| parse_ratio(sar).is_some())
+        .unwrap_or("Unknown")
+        .to_string();
+    let (frame_rate, variable_frame_rate) = match (is_still, &sequence) {
+        (true, _) => ("N/A".to_string(), false),
+        (false, Some(_)) => (format_frame_rate(options.sequence_fps), false),
+        (false, None) => extract_frame_rate(&probe),
+    };
+    let scan = interlace::from_field_order(
+        first_stream(&probe, "video").and_then(|video| video["field_order"].as_str()),
+    )
+    .to_string();
+    // ffprobe stops at the first missing frame, so count them instead
+    let duration = match &sequence {
+        Some((sequence, _)) => Some(sequence.frames as f64 / options.sequence_fps),
+        None => extract_duration(&probe),
+    };
+    let title = format_tag(&probe, &["title"]);
+    let artist = format_tag(&probe, &["artist", "album_artist", "author"]);
+    let encoder = format_tag(&probe, &["encoder", "encoded_by", "com.apple.quicktime.software", "software"])
+        .or_else(|| first_stream(&probe, "video")?["tags"]["encoder"].as_str().map(str::to_string));
+    let creation_time = format_tag(&probe, &["creation_time", "date", "modification_date"])
+        .map(|time| format_creation_time(&time))
+        .or_else(|| image.as_ref()?.captured.clone());
+    let timecode = extract_timecode(&probe);
+    let chapters = extract_chapters(&probe);
+    let file_size = match &sequence {
+        Some((_, size)) => Some(*size),
+        // ffprobe knows a URL's size from the server's Content-Length
+        None => fs::metadata(path)
+            .ok()
+            .map(|m| m.len())
+            .or_else(|| probe["format"]["size"].as_str()?.parse().ok()),
+    };
+    let (bitrate, bitrate_estimated) = match (is_still, &sequence, duration, file_size) {
+        (true, ..) => ("N/A".to_string(), false),
+        // What a feed says it carries is often missing or nominal, so measure it
+        _ if is_stream(path) => match probe_packet_bitrate(path, options) {
+            Some(bits) => (format_mbps(bits), true),
+            None => ("Unknown".to_string(), false),
+        },
+        // The image2 demuxer's figure only covers the first frame
+        (false, Some(_), Some(duration), Some(size)) if duration > 0.0 => {
+            (format_mbps(size as f64 * 8.0 / duration), true)
+        }
+        _ => extract_bitrate(&probe, input, duration, file_size, options),
+    };
+    let pixel_format = extract_pixel_format(&probe);
+    let bit_depth = extract_bit_depth(&probe);
+    let video = first_stream(&probe, "video");
+    let color_field = |key: &str| {
+        video
+            .and_then(|v| v[key].as_str())
+            .unwrap_or("Unknown")
+            .to_string()
+    };
+    let color_primaries = color_field("color_primaries");
+    let color_transfer = color_field("color_transfer");
+    let color_space = color_field("color_space");
+    let hdr_format = extract_hdr_format(&probe, input, options);
+    let has_alpha = extract_has_alpha(&probe);
+    let (audio_codec, audio_channels) = extract_audio(&probe);
+    let (audio_sample_rate, audio_bit_depth) = extract_audio_format(&probe);
+    let audio_only = is_audio_only(&probe);
+    let audio_languages = extract_audio_languages(&probe);
+    let audio_track_channels = extract_audio_track_channels(&probe);
+    let mxf = mxf::is_mxf(&probe).then(|| mxf::read(&probe, &codec, bitrate.parse().ok()));
+    let encoder = encoder.or_else(|| mxf.as_ref()?.writer.clone());
+    let (subtitles, closed_captions) = extract_subtitles(&probe);
+    let is_mp4 = probe["format"]["format_name"]
+        .as_str()
+        .is_some_and(|f| f.contains("mp4") || f.contains("mov"));
+    let faststart = if is_mp4 { moov_before_mdat(Path::new(path)) } else { None };
+    let fingerprint = file_size.and_then(|size| dedupe::fingerprint(Path::new(path), size).ok());
+
+    let mut info = MediaInfo {
+        name,
+        container,
+        codec,
+        resolution,
+        rotation,
+        sample_aspect_ratio,
+        frame_rate,
+        variable_frame_rate,
+        scan,
+        scan_detected: false,
+        bitrate,
+        bitrate_estimated,
+        duration,
+        title,
+        artist,
+        encoder,
+        creation_time,
+        timecode,
+        chapters,
+        file_size,
+        pixel_format,
+        bit_depth,
+        color_primaries,
+        color_transfer,
+        color_space,
+        hdr_format,
+        has_alpha,
+        audio_codec,
+        audio_channels,
+        audio_sample_rate,
+        audio_bit_depth,
+        audio_only,
+        audio_languages,
+        audio_track_channels,
+        subtitles,
+        closed_captions,
+        faststart,
+        fingerprint,
+        checksum: None,
+        loudness: None,
+        blanks: None,
+        crop: None,
+        quality: None,
+        hw_decode: None,
+        benchmark: None,
+        note: None,
+        image,
+        sequence: sequence.map(|(sequence, _)| sequence),
+        mxf,
+        analyzer: Backend::Ffprobe,
+        warnings: Vec::new(),
+        path: path.to_string(),
+        raw_output: raw_output.to_string(),
+    };
+    // Sound files have no picture for the video fields to be unknown about
+    if audio_only {
+        info.codec = "None".to_string();
+        for field in [
+            &mut info.resolution,
+            &mut info.frame_rate,
+            &mut info.scan,
+            &mut info.pixel_format,
+            &mut info.color_primaries,
+            &mut info.color_transfer,
+            &mut info.color_space,
+            &mut info.hdr_format,
+        ] {
+            *field = "N/A".to_string();
+        }
+    }
+    info
+}
+
+/// Human-readable codec name for the first video stream, distinguishing the
+/// ProRes, DNx and Hap flavours media servers care about.
+fn extract_codec(probe: &Value) -> String {
+    let Some(video) = first_stream(probe, "video") else {
+        return "Unknown".to_string();
+    };
+    let codec_name = video["codec_name"].as_str().unwrap_or_default();
+    let tag = video["codec_tag_string"].as_str().unwrap_or_default();
+    let profile = video["profile"].as_str().unwrap_or_default();
+    let is_still = is_still_image(probe);
+
+    let label = match codec_name {
+        "h264" => "H.264",
+        "hevc" => "H.265",
+        "vp8" => "VP8",
+        "vp9" => "VP9",
+        "av1" => "AV1",
+        "mpeg2video" => "MPEG-2",
+        "mpeg4" => "MPEG-4",
+        "dvvideo" => "DV",
+        "prores" => prores_variant(tag, profile),
+        "dnxhd" => return dnx_variant(profile),
+        "hap" => hap_variant(tag),
+        "dxv" => "DXV3",
+        "notchlc" => "NotchLC",
+        "cfhd" => "CineForm",
+        "mjpeg" if is_still => "JPEG",
+        "mjpeg" => "MJPEG",
+        "png" => "PNG",
+        "tiff" => "TIFF",
+        "bmp" => "BMP",
+        "gif" => "GIF",
+        "webp" => "WebP",
+        "exr" => "EXR",
+        "dpx" => "DPX",
+        "jpeg2000" => "JPEG 2000",
+        "" => "Unknown",
+        // Better to show ffmpeg's own name than to hide it behind "Unknown"
+        other => return other.to_string(),
+    };
+    label.to_string()
+}
+
+/// ffprobe reads stills with the image2 demuxer or one of the `*_pipe` ones.
+fn is_still_image(probe: &Value) -> bool {
+    probe["format"]["format_name"]
+        .as_str()
+        .is_some_and(|f| f.contains("image2") || f.ends_with("_pipe"))
+}
+
+fn prores_variant(tag: &str, profile: &str) -> &'static str {
+    match (tag, profile) {
+        ("apco", _) | (_, "Proxy") => "ProRes 422 Proxy",
+        ("apcs", _) | (_, "LT") => "ProRes 422 LT",
+        ("apch", _) | (_, "HQ") => "ProRes 422 HQ",
+        ("ap4x", _) | (_, "4444 XQ") | (_, "XQ") => "ProRes 4444 XQ",
+        ("ap4h", _) | (_, "4444") => "ProRes 4444",
+        ("apcn", _) | (_, "Standard") => "ProRes 422",
+        _ => "ProRes",
+    }
+}
+
+/// ffprobe reports DNx profiles as "DNXHD" or "DNXHR HQX" and the like.
+fn dnx_variant(profile: &str) -> String {
+    match profile.strip_prefix("DNXHR") {
+        Some(rest) if !rest.trim().is_empty() => format!("DNxHR {}", rest.trim()),
+        Some(_) => "DNxHR".to_string(),
+        None => "DNxHD".to_string(),
+    }
+}
+
+fn hap_variant(tag: &str) -> &'static str {
+    match tag {
+        "Hap5" => "Hap Alpha",
+        "HapY" => "Hap Q",
+        "HapM" => "Hap Q Alpha",
+        "HapA" => "Hap Alpha Only",
+        _ => "Hap",
+    }
+}
+
+/// Clockwise display rotation. Phones store portrait video as landscape frames plus
+/// a display matrix (ffprobe shows it as counter-clockwise side data) or, in older
+/// files, a `rotate` tag.
+fn extract_rotation(probe: &Value) -> u16 {
+    let Some(video) = first_stream(probe, "video") else {
+        return 0;
+    };
+    let from_matrix = video["side_data_list"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|sd| sd["rotation"].as_f64().or_else(|| sd["rotation"].as_str()?.parse().ok()))
+        .map(|counter_clockwise| -counter_clockwise);
+    let from_tag = || video["tags"]["rotate"].as_str()?.parse::<f64>().ok();
+    let degrees = from_matrix.or_else(from_tag).unwrap_or(0.0);
+    // Snap to a quarter turn; anything else isn't something players honour
+    ((degrees / 90.0).round() as i64 * 90).rem_euclid(360) as u16
+}
+
+/// The first of `keys` found in the container's tags. Matroska writes them in
+/// upper case, so the lookup ignores case.
+pub fn format_tag(probe: &Value, keys: &[&str]) -> Option<String> {
+    let tags = probe["format"]["tags"].as_object()?;
+    keys.iter()
+        .find_map(|key| tags.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)))
+        .and_then(|(_, value)| value.as_str())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// `2024-03-01T10:00:00.000000Z` as `2024-03-01 10:00:00`, which still sorts by date.
+fn format_creation_time(time: &str) -> String {
+    let time = time.trim_end_matches('Z');
+    let time = time.split_once('.').map_or(time, |(seconds, _)| seconds);
+    time.replacen('T', " ", 1)
+}
+
+/// A `YYYY`, `YYYY-MM` or `YYYY-MM-DD` date as the number `YYYYMMDD`, so dates
+/// compare like numbers and a bare year means its first day.
+pub(crate) fn date_number(date: &str) -> Option<f64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year: u32 = parts.next().filter(|year| year.len() == 4)?.parse().ok()?;
+    let month: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let day: u32 = parts.next().map_or(Some(0), |d| d.parse().ok())?;
+    Some(f64::from(year * 10000 + month * 100 + day))
+}
+
+/// Start timecode, from the video stream's tags (MXF, some MOV), a tmcd track, or
+/// the container's tags, in that order.
+fn extract_timecode(probe: &Value) -> Option<String> {
+    let streams = probe["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let tmcd = streams.iter().find(|s| s["codec_tag_string"].as_str() == Some("tmcd"));
+    first_stream(probe, "video")
+        .into_iter()
+        .chain(tmcd)
+        .chain(streams)
+        .chain([&probe["format"]])
+        .find_map(|value| value["tags"]["timecode"].as_str())
+        .map(|timecode| timecode.trim().to_string())
+        .filter(|timecode| !timecode.is_empty())
+}
+
+/// Chapter marks in file order; untitled ones are numbered.
+fn extract_chapters(probe: &Value) -> Vec<Chapter> {
+    let time = |value: &Value| value.as_str()?.parse::<f64>().ok();
+    probe["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, chapter)| {
+            Some(Chapter {
+                start: time(&chapter["start_time"])?,
+                end: time(&chapter["end_time"])?,
+                title: chapter["tags"]["title"]
+                    .as_str()
+                    .map_or_else(|| format!("Chapter {}", i + 1), str::to_string),
+            })
+        })
+        .collect()
+}
+
+fn extract_resolution(probe: &Value) -> String {
+    let video = first_stream(probe, "video");
+    match video.map(|v| (v["width"].as_u64(), v["height"].as_u64())) {
+        Some((Some(width), Some(height))) => format!("{}x{}", width, height),
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// Parses ffprobe's "num/den" rates. "0/0" means unknown.
+fn parse_rational(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// 30000/1001 -> "29.97", 24000/1001 -> "23.976", 25/1 -> "25"
+fn format_frame_rate(fps: f64) -> String {
+    let formatted = format!("{:.3}", fps);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Returns the average frame rate (falling back to r_frame_rate) and whether the
+/// two disagree, which is how variable frame rate footage shows up.
+fn extract_frame_rate(probe: &Value) -> (String, bool) {
+    let Some(video) = first_stream(probe, "video") else {
+        return ("Unknown".to_string(), false);
+    };
+    let r_rate = video["r_frame_rate"].as_str().and_then(parse_rational);
+    let avg_rate = video["avg_frame_rate"].as_str().and_then(parse_rational);
+
+    let variable = match (r_rate, avg_rate) {
+        (Some(r), Some(avg)) => (r - avg).abs() / r > 0.001,
+        _ => false,
+    };
+    match avg_rate.or(r_rate) {
+        Some(fps) => (format_frame_rate(fps), variable),
+        None => ("Unknown".to_string(), false),
+    }
+}
+
+fn parse_bits(value: &Value) -> Option<f64> {
+    value.as_str().and_then(|b| b.parse::<f64>().ok()).filter(|b| *b > 0.0)
+}
+
+fn format_mbps(bits_per_second: f64) -> String {
+    format!("{:.1}", bits_per_second / 1_000_000.0)
+}
+
+/// Bitrate in Mbps and whether it had to be derived rather than read from the file.
+///
+/// Prefers the video stream's own figure (or the statistics tags mkvmerge writes),
+/// then the container total. Hap-in-MKV and similar files often report neither, so
+/// fall back to size ÷ duration, and finally to summing packet sizes.
+fn extract_bitrate(
+    probe: &Value,
+    path: &str,
+    duration: Option<f64>,
+    file_size: Option<u64>,
+    options: &ProbeOptions,
+) -> (String, bool) {
+    let video = first_stream(probe, "video");
+    let reported = video
+        .and_then(|v| {
+            parse_bits(&v["bit_rate"])
+                .or_else(|| parse_bits(&v["tags"]["BPS"]))
+                .or_else(|| parse_bits(&v["tags"]["BPS-eng"]))
+        })
+        .or_else(|| parse_bits(&probe["format"]["bit_rate"]));
+    if let Some(bits) = reported {
+        return (format_mbps(bits), false);
+    }
+
+    if let (Some(duration), Some(size)) = (duration, file_size) {
+        if duration > 0.0 {
+            return (format_mbps(size as f64 * 8.0 / duration), true);
+        }
+    }
+
+    match probe_packet_bitrate(path, options) {
+        Some(bits) => (format_mbps(bits), true),
+        None => ("Unknown".to_string(), false),
+    }
+}
+
+/// Sums packet sizes of the first video stream over the time its timestamps span.
+/// Live streams are sampled for the configured number of seconds.
+fn probe_packet_bitrate(path: &str, options: &ProbeOptions) -> Option<f64> {
+    let mut command = Command::new(&options.ffprobe);
+    command
+        .args(&options.extra_args)
+        .args([
+            "-v", "error",
+            "-select_streams", "v:0",
+            "-show_entries", "packet=size,pts_time",
+            "-of", "csv=p=0",
+        ])
+        .args(options.stream_args(path))
+        .args(["-i", path]);
+    let output = options.output(&mut command, path).ok()?;
+
+    let mut total_bytes = 0u64;
+    let mut first_pts = f64::MAX;
+    let mut last_pts = 0f64;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split(',');
+        let pts = fields.next().and_then(|p| p.parse::<f64>().ok());
+        let size = fields.next().and_then(|s| s.parse::<u64>().ok());
+        if let Some(size) = size {
+            total_bytes += size;
+        }
+        if let Some(pts) = pts {
+            first_pts = first_pts.min(pts);
+            last_pts = last_pts.max(pts);
+        }
+    }
+    // Files are timed from zero; a live stream's timestamps start wherever the feed
+    // was when ffprobe joined it
+    let start = if is_stream(path) { first_pts } else { 0.0 };
+    let span = last_pts - start;
+    (total_bytes > 0 && span > 0.0).then(|| total_bytes as f64 * 8.0 / span)
+}
+
+/// First stream of the given type ("video", "audio", ...) in the ffprobe output.
+/// Cover art embedded in MP3, FLAC and M4A shows up as a video stream and is skipped.
+pub fn first_stream<'a>(probe: &'a Value, codec_type: &str) -> Option<&'a Value> {
+    probe["streams"]
+        .as_array()?
+        .iter()
+        .find(|s| s["codec_type"] == codec_type && s["disposition"]["attached_pic"] != 1)
+}
+
+/// Sound files, and anything else with audio but no picture.
+fn is_audio_only(probe: &Value) -> bool {
+    first_stream(probe, "video").is_none() && first_stream(probe, "audio").is_some()
+}
+
+fn extract_pixel_format(probe: &Value) -> String {
+    first_stream(probe, "video")
+        .and_then(|video| video["pix_fmt"].as_str())
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+fn extract_bit_depth(probe: &Value) -> Option<u8> {
+    let video = first_stream(probe, "video")?;
+    // bits_per_raw_sample is the most direct answer when the decoder provides it
+    video["bits_per_raw_sample"]
+        .as_str()
+        .and_then(|bits| bits.parse().ok())
+        .or_else(|| video["pix_fmt"].as_str().and_then(pix_fmt_bit_depth))
+}
+
+fn has_side_data(stream: &Value, needle: &str) -> bool {
+    stream["side_data_list"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|sd| sd["side_data_type"].as_str().is_some_and(|t| t.contains(needle)))
+}
+
+/// Classifies the video stream as SDR or one of the HDR flavours.
+fn extract_hdr_format(probe: &Value, path: &str, options: &ProbeOptions) -> String {
+    let Some(video) = first_stream(probe, "video") else {
+        return "Unknown".to_string();
+    };
+    if has_side_data(video, "DOVI") {
+        return "Dolby Vision".to_string();
+    }
+    match video["color_transfer"].as_str() {
+        Some("smpte2084") if probe_hdr10_plus(path, options) => "HDR10+".to_string(),
+        Some("smpte2084") => "HDR10".to_string(),
+        Some("arib-std-b67") => "HLG".to_string(),
+        _ => "SDR".to_string(),
+    }
+}
+
+/// HDR10+ dynamic metadata is only attached to frames, so it takes a second,
+/// single-frame probe. Only worth running on PQ content.
+fn probe_hdr10_plus(path: &str, options: &ProbeOptions) -> bool {
+    let mut command = Command::new(&options.ffprobe);
+    command
+        .args(&options.extra_args)
+        .args([
+            "-v", "error",
+            "-select_streams", "v:0",
+            "-read_intervals", "%+#1",
+            "-show_entries", "frame=side_data_list",
+            "-of", "json",
+        ])
+        .args(options.sequence_args(path))
+        .args(["-i", path]);
+    let Ok(output) = options.output(&mut command, path) else {
+        return false;
+    };
+    let frames: Value = serde_json::from_slice(&output.stdout).unwrap_or(Value::Null);
+    frames["frames"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|frame| has_side_data(frame, "HDR10+"))
+}
+
+fn pix_fmt_has_alpha(pix_fmt: &str) -> bool {
+    ["yuva", "gbrap", "ya8", "ya16"].iter().any(|p| pix_fmt.starts_with(p))
+        || ["rgba", "bgra", "argb", "abgr"].iter().any(|p| pix_fmt.contains(p))
+}
+
+/// Covers alpha carried in the pixel format (ProRes 4444, PNG, TIFF, DXV) as well as
+/// codecs that only signal it in the tag (Hap Alpha, Hap Q Alpha) or stream tags (VP9/WebM).
+fn extract_has_alpha(probe: &Value) -> bool {
+    let Some(video) = first_stream(probe, "video") else {
+        return false;
+    };
+    let pix_fmt_alpha = video["pix_fmt"].as_str().is_some_and(pix_fmt_has_alpha);
+    let hap_alpha = matches!(video["codec_tag_string"].as_str(), Some("HapA" | "HapM" | "Hap5"));
+    let webm_alpha = video["tags"]["alpha_mode"].as_str() == Some("1")
+        || video["tags"]["ALPHA_MODE"].as_str() == Some("1");
+    pix_fmt_alpha || hap_alpha || webm_alpha
+}
+
+/// Codec and channel count of the first audio stream, "None" without audio.
+fn extract_audio(probe: &Value) -> (String, Option<u32>) {
+    let Some(audio) = first_stream(probe, "audio") else {
+        return ("None".to_string(), None);
+    };
+    let codec_name = audio["codec_name"].as_str().unwrap_or("Unknown");
+    let codec = match codec_name {
+        "aac" => "AAC",
+        "mp3" => "MP3",
+        "ac3" => "AC-3",
+        "eac3" => "E-AC-3",
+        "opus" => "Opus",
+        "vorbis" => "Vorbis",
+        "flac" => "FLAC",
+        "alac" => "ALAC",
+        name if name.starts_with("pcm_") => "PCM",
+        name => name,
+    };
+    let channels = audio["channels"].as_u64().map(|c| c as u32);
+    (codec.to_string(), channels)
+}
+
+/// Sample rate in Hz and stored bits per sample of the first audio stream. Lossy
+/// codecs have no bit depth of their own.
+fn extract_audio_format(probe: &Value) -> (Option<u32>, Option<u8>) {
+    let Some(audio) = first_stream(probe, "audio") else {
+        return (None, None);
+    };
+    let sample_rate = audio["sample_rate"].as_str().and_then(|rate| rate.parse().ok());
+    (sample_rate, audio_bit_depth(audio))
+}
+
+pub fn audio_bit_depth(audio: &Value) -> Option<u8> {
+    audio["bits_per_raw_sample"]
+        .as_str()
+        .and_then(|bits| bits.parse().ok())
+        .or_else(|| audio["bits_per_sample"].as_u64().and_then(|bits| u8::try_from(bits).ok()))
+        .filter(|bits| *bits > 0)
+}
+
+/// `48000`, `48k` or `44.1kHz` in Hz; bare numbers under 1000 are read as kHz.
+pub(crate) fn parse_sample_rate(value: &str) -> Option<f64> {
+    let value = value.to_lowercase();
+    let value = value.trim_end_matches("hz").trim();
+    let (number, scale) = match value.strip_suffix('k') {
+        Some(number) => (number.trim(), 1000.0),
+        None => (value, 1.0),
+    };
+    let rate: f64 = number.parse().ok()?;
+    Some(if scale == 1.0 && rate < 1000.0 { rate * 1000.0 } else { rate * scale })
+}
+
+fn extract_audio_languages(probe: &Value) -> Vec<String> {
+    probe["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|s| s["codec_type"] == "audio")
+        .map(|s| s["tags"]["language"].as_str().unwrap_or("und").to_lowercase())
+        .collect()
+}
+
+fn extract_audio_track_channels(probe: &Value) -> Vec<u32> {
+    probe["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|s| s["codec_type"] == "audio")
+        .map(|s| s["channels"].as_u64().unwrap_or(0) as u32)
+        .collect()
+}
+
+/// Subtitle streams with their language, and whether any CEA-608/708 captions are
+/// present. ffprobe only reports embedded captions it saw while probing.
+fn extract_subtitles(probe: &Value) -> (Vec<String>, bool) {
+    let streams = probe["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let is_caption_track = |stream: &Value| {
+        stream["codec_name"].as_str() == Some("eia_608")
+            || matches!(stream["codec_tag_string"].as_str(), Some("c608" | "c708"))
+    };
+    let subtitles = streams
+        .iter()
+        .filter(|s| s["codec_type"] == "subtitle" && !is_caption_track(s))
+        .map(|stream| {
+            let format = match stream["codec_name"].as_str().unwrap_or("Unknown") {
+                "subrip" | "srt" => "SRT",
+                "ass" | "ssa" => "ASS",
+                "webvtt" => "WebVTT",
+                "mov_text" => "TX3G",
+                "hdmv_pgs_subtitle" => "PGS",
+                "dvb_subtitle" => "DVB",
+                "dvb_teletext" => "Teletext",
+                "dvd_subtitle" => "VobSub",
+                name => name,
+            };
+            let mut notes: Vec<&str> = stream["tags"]["language"].as_str().into_iter().collect();
+            if stream["disposition"]["forced"].as_u64() == Some(1) {
+                notes.push("forced");
+            }
+            match notes.is_empty() {
+                true => format.to_string(),
+                false => format!("{} ({})", format, notes.join(", ")),
+            }
+        })
+        .collect();
+    let closed_captions = streams
+        .iter()
+        .any(|s| is_caption_track(s) || s["closed_captions"].as_u64() == Some(1));
+    (subtitles, closed_captions)
+}
+
+/// Walks the top-level MP4 boxes to see whether `moov` comes before `mdat`,
+/// which lets players start without seeking to the end of the file.
+fn moov_before_mdat(path: &Path) -> Option<bool> {
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut offset: u64 = 0;
+    while offset < len && len - offset >= 8 {
+        let mut header = [0u8; 16];
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut header[..8]).ok()?;
+        let size = u32::from_be_bytes(header[..4].try_into().ok()?) as u64;
+        let size = match size {
+            // 64-bit size follows the type
+            1 => {
+                file.read_exact(&mut header[8..]).ok()?;
+                u64::from_be_bytes(header[8..].try_into().ok()?)
+            }
+            // Box runs to the end of the file
+            0 => len - offset,
+            size => size,
+        };
+        match &header[4..8] {
+            b"moov" => return Some(true),
+            b"mdat" => return Some(false),
+            _ => {}
+        }
+        if size < 8 {
+            return None;
+        }
+        // A corrupt largesize can point past the end, or past what a u64 holds
+        offset = offset.checked_add(size)?;
+    }
+    None
+}
+
+fn extract_duration(probe: &Value) -> Option<f64> {
+    // ffprobe reports duration as a decimal string in the format section
+    probe["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse::<f64>().ok())
+}
+
+/// Extensions picked up when scanning a directory. Files named explicitly are
+/// always analyzed, whatever their extension.
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "mp4", "mov", "avi", "mkv", "mxf", "webm", "m4v", "mpg", "mpeg", "ts", "mts", "m2ts", "wmv",
+    "flv", "jpg", "jpeg", "png", "tif", "tiff", "bmp", "gif", "webp", "exr", "dpx", "wav", "aif",
+    "aiff", "flac", "mp3", "aac", "m4a", "ogg", "opus",
+];
+
+/// Protocols only live streams come over.
+const STREAM_SCHEMES: &[&str] = &["rtsp", "rtsps", "rtmp", "rtmps", "srt", "udp", "rtp"];
+
+/// The part of a URL before `://`, lowercased.
+fn url_scheme(path: &str) -> Option<String> {
+    let (scheme, _) = path.split_once("://")?;
+    scheme.chars().all(|c| c.is_ascii_alphanumeric()).then(|| scheme.to_ascii_lowercase())
+}
+
+fn is_web_url(path: &str) -> bool {
+    url_scheme(path).is_some_and(|scheme| scheme == "http" || scheme == "https")
+}
+
+/// `http(s)://` sources, live streams, S3 objects and files on SSH hosts: nothing on
+/// the local disk.
+pub fn is_url(path: &str) -> bool {
+    is_web_url(path) || is_stream(path) || s3::is_s3(path) || ssh::is_ssh(path)
+}
+
+/// Live inputs read for a while rather than to the end: RTSP, RTMP, SRT, UDP and RTP
+/// feeds, and HLS playlists.
+pub fn is_stream(path: &str) -> bool {
+    let hls = path.split(['?', '#']).next().unwrap_or(path).to_ascii_lowercase().ends_with(".m3u8");
+    url_scheme(path).is_some_and(|scheme| STREAM_SCHEMES.contains(&scheme.as_str())) || (hls && is_web_url(path))
+}
+
+pub fn is_media_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| MEDIA_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// What a directory scan does with the symlinks it meets. Paths named on the
+/// command line or added by hand are always followed.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Symlinks {
+    /// Scan what they point to, once however many links lead there
+    #[default]
+    Follow,
+    /// Leave them out, e.g. bins of links into footage that's scanned anyway
+    Skip,
+}
+
+/// How directories are scanned, taken from the config.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Numbered stills come back as one image sequence
+    pub sequences: bool,
+    pub symlinks: Symlinks,
+    pub ignore: Ignore,
+    /// Levels of folders gone down, 1 for the files of the scanned folder alone
+    pub max_depth: Option<usize>,
+    /// Lowercase, without the dot; every media extension when empty
+    pub extensions: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+}
+
+impl ScanOptions {
+    pub fn from_config(config: &Config) -> Self {
+        // Checked when the config was loaded
+        let size = |size: &Option<String>| size.as_deref().and_then(query::parse_size).map(|bytes| bytes as u64);
+        ScanOptions {
+            sequences: config.image_sequences,
+            symlinks: config.symlinks,
+            ignore: Ignore::new(&config.ignore),
+            max_depth: config.max_depth,
+            extensions: config.extensions.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect(),
+            min_size: size(&config.min_size),
+            max_size: size(&config.max_size),
+        }
+    }
+
+    /// Whether a file met in a directory is scanned: a media file, or one with an
+    /// extension of `extensions` when they're given, within the size limits.
+    fn wanted(&self, path: &Path) -> bool {
+        let kind = if self.extensions.is_empty() {
+            is_media_file(path)
+        } else {
+            let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+            extension.is_some_and(|extension| self.extensions.contains(&extension))
+        };
+        if !kind || (self.min_size.is_none() && self.max_size.is_none()) {
+            return kind;
+        }
+        // Image sequence patterns aren't files, and have no size to go by
+        let Ok(metadata) = fs::metadata(path) else {
+            return true;
+        };
+        let size = metadata.len();
+        self.min_size.is_none_or(|min| size >= min) && self.max_size.is_none_or(|max| size <= max)
+    }
+}
+
+/// The media files a path stands for, and what the scan left out.
+#[derive(Debug, Default)]
+pub struct Scan {
+    pub paths: Vec<String>,
+    /// Symlinks whose target is gone, `(link, target)`
+    pub broken_links: Vec<(String, String)>,
+    /// Symlinks left out by `Symlinks::Skip`
+    pub skipped_links: usize,
+    /// Files reached again through another link, and links back up the tree
+    pub duplicates: usize,
+}
+
+impl Scan {
+    fn listed(paths: Vec<String>) -> Self {
+        Scan { paths, ..Default::default() }
+    }
+
+    /// What was left out, `, 1 broken symlink(s), 2 duplicate(s) through symlinks`,
+    /// to follow a count of the files found.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        if let Some((link, target)) = self.broken_links.first() {
+            let count = self.broken_links.len();
+            summary.push_str(&format!(", {} broken symlink(s) like {} → {}", count, link, target));
+        }
+        if self.skipped_links > 0 {
+            summary.push_str(&format!(", {} symlink(s) skipped", self.skipped_links));
+        }
+        if self.duplicates > 0 {
+            summary.push_str(&format!(", {} duplicate(s) through symlinks left out", self.duplicates));
+        }
+        summary
+    }
+}
+
+/// Lists an S3 prefix or a directory on an SSH host, or expands a local path with
+/// `collect_media_paths`.
+pub fn expand_path(path: &str, options: &ScanOptions, s3: &S3, ssh: &Ssh) -> Result<Scan, String> {
+    if s3::is_s3(path) {
+        s3.list(path).map(Scan::listed)
+    } else if ssh::is_ssh(path) {
+        ssh.list(path).map(Scan::listed)
+    } else {
+        Ok(collect_media_paths(Path::new(path), options))
+    }
+}
+
+/// Expands a file or directory into the media files it contains, recursing into subdirectories
+/// and leaving out what the ignore rules match. With `sequences`, numbered stills in a directory
+/// come back as one pattern path. A file reached through several links comes back once, under a
+/// path without a link in it when there's one.
+pub fn collect_media_paths(path: &Path, options: &ScanOptions) -> Scan {
+    let mut walk = Walk { options: options.clone(), within: Vec::new(), found: Vec::new(), scan: Scan::default() };
+    walk.visit(path, false, 1);
+    let Walk { found, mut scan, .. } = walk;
+
+    let mut preferred: HashMap<&Path, usize> = HashMap::new();
+    for (i, (_, target, through_link)) in found.iter().enumerate() {
+        if let Some(target) = target {
+            let keep = preferred.get(target.as_path()).is_some_and(|&first| !found[first].2 || *through_link);
+            if !keep {
+                preferred.insert(target, i);
+            }
+        }
+    }
+    for (i, (path, target, _)) in found.iter().enumerate() {
+        if target.as_ref().is_some_and(|target| preferred[target.as_path()] != i) {
+            scan.duplicates += 1;
+        } else {
+            scan.paths.push(path.clone());
+        }
+    }
+    scan
+}
+
+struct Walk {
+    /// With the ignore rules of the directories being scanned
+    options: ScanOptions,
+    /// The directories being scanned, down from the top, by their real path
+    within: Vec<PathBuf>,
+    /// Files in scan order, with their real path and whether a link led to them
+    found: Vec<(String, Option<PathBuf>, bool)>,
+    scan: Scan,
+}
+
+impl Walk {
+    /// Scans `path`, `depth` levels down from the top when it's a directory.
+    fn visit(&mut self, path: &Path, through_link: bool, depth: usize) {
+        let through_link = through_link || is_symlink(path);
+        if is_symlink(path) && !path.exists() {
+            let target = fs::read_link(path).map_or(String::new(), |target| target.to_string_lossy().to_string());
+            self.scan.broken_links.push((path.to_string_lossy().to_string(), target));
+            return;
+        }
+        if !path.is_dir() {
+            // Image sequence patterns have no real path, and are never links
+            self.found.push((path.to_string_lossy().to_string(), fs::canonicalize(path).ok(), through_link));
+            return;
+        }
+        // A link back up the tree would be scanned forever. Another link to a
+        // directory is scanned again, and its files are sorted out afterwards.
+        let real = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if self.within.contains(&real) {
+            self.scan.duplicates += 1;
+            return;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        self.within.push(real);
+        self.options.ignore.enter(path);
+        let mut entries: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        entries.retain(|entry| !self.options.ignore.ignores(entry, entry.is_dir()));
+        entries.sort();
+        if self.options.sequences {
+            entries = sequence::group(entries);
+        }
+        for entry in entries {
+            let link = is_symlink(&entry);
+            if link && self.options.symlinks == Symlinks::Skip {
+                self.scan.skipped_links += 1;
+            } else if entry.is_dir() {
+                if self.options.max_depth.is_none_or(|max| depth < max) {
+                    self.visit(&entry, through_link, depth + 1);
+                }
+            } else if self.options.wanted(&entry) || (link && !entry.exists()) {
+                self.visit(&entry, through_link, depth);
+            }
+        }
+        self.options.ignore.leave();
+        self.within.pop();
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(kind: &[u8; 4], payload: usize) -> Vec<u8> {
+        let mut atom = ((payload + 8) as u32).to_be_bytes().to_vec();
+        atom.extend(kind);
+        atom.resize(payload + 8, 0);
+        atom
+    }
+
+    fn faststart(name: &str, bytes: &[u8]) -> Option<bool> {
+        let path = std::env::temp_dir().join(format!("media-scout-{}-{}.mp4", name, std::process::id()));
+        fs::write(&path, bytes).unwrap();
+        let found = moov_before_mdat(&path);
+        let _ = fs::remove_file(&path);
+        found
+    }
+
+    #[test]
+    fn moov_first_is_faststart() {
+        let file = [atom(b"ftyp", 16), atom(b"moov", 32), atom(b"mdat", 64)].concat();
+        assert_eq!(faststart("moov-first", &file), Some(true));
+        let file = [atom(b"ftyp", 16), atom(b"free", 4), atom(b"mdat", 64), atom(b"moov", 32)].concat();
+        assert_eq!(faststart("mdat-first", &file), Some(false));
+    }
+
+    #[test]
+    fn a_huge_largesize_is_not_followed() {
+        let mut file = atom(b"ftyp", 16);
+        // size 1: the real size is the 64-bit one after the type
+        file.extend(1u32.to_be_bytes());
+        file.extend(b"free");
+        file.extend((u64::MAX - 4).to_be_bytes());
+        file.extend(atom(b"moov", 8));
+        assert_eq!(faststart("largesize", &file), None);
+    }
+
+    #[test]
+    fn a_box_past_the_end_stops_the_walk() {
+        let mut file = atom(b"ftyp", 16);
+        file.extend(1u32.to_be_bytes());
+        file.extend(b"free");
+        file.extend(1_000_000u64.to_be_bytes());
+        assert_eq!(faststart("past-end", &file), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! Named projects, each its own file list and filters, kept as
+//! `projects/<name>.json` next to the config file so the content scouted for one
+//! production never mixes with another's.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::config;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    /// Every listed file, in the order they were added
+    pub paths: Vec<String>,
+    /// The active filters as a query, see `query::to_query`
+    pub query: String,
+}
+
+fn dir() -> Result<PathBuf> {
+    let dir = config::config_dir().context("no config directory found")?;
+    Ok(dir.join("projects"))
+}
+
+/// Where the project called `name` is saved. Names become file names, so anything
+/// that would leave the directory is refused.
+fn file(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(anyhow!("'{}' can't be a project name", name));
+    }
+    Ok(dir()?.join(format!("{}.json", name)))
+}
+
+/// Saved project names, sorted.
+pub fn names() -> Vec<String> {
+    let Some(entries) = dir().ok().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// The project called `name`; one that was never saved starts empty.
+pub fn load(name: &str) -> Result<Project> {
+    let file = file(name)?;
+    if !file.exists() {
+        return Ok(Project::default());
+    }
+    let text = fs::read_to_string(&file).with_context(|| format!("reading project {}", file.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("parsing project {}", file.display()))
+}
+
+pub fn save(name: &str, project: &Project) -> Result<()> {
+    let file = file(name)?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(project)?;
+    fs::write(&file, text).with_context(|| format!("writing project {}", file.display()))
+}
@@ -0,0 +1,140 @@
+//! Objective quality of an encode against its reference: PSNR and SSIM from
+//! ffmpeg's own filters, and VMAF when ffmpeg is built with libvmaf. Both files
+//! are decoded in full, so it runs on demand in the background.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quality {
+    /// Path of the file the encode was compared against
+    pub reference: String,
+    /// 0 to 100, `None` without libvmaf; around 93 is hard to tell from the source
+    pub vmaf: Option<f64>,
+    /// Average over the frames in dB, infinite for identical pictures
+    pub psnr: f64,
+    /// 0 to 1, across all planes
+    pub ssim: f64,
+}
+
+impl Quality {
+    /// The headline score: VMAF when measured, PSNR otherwise.
+    pub fn summary(&self) -> String {
+        match self.vmaf {
+            Some(vmaf) => format!("VMAF {:.1}", vmaf),
+            None => format!("PSNR {:.1} dB", self.psnr),
+        }
+    }
+}
+
+pub struct Job {
+    pub encode: String,
+    pub reference: String,
+    /// The reference's size, which the encode is scaled to before comparing
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Compares one pair at a time; decoding two files is CPU-bound enough that
+/// running several at once would starve the probes.
+pub struct QualityMeter {
+    job_tx: Sender<Job>,
+    results: Receiver<(String, Result<Quality, String>)>,
+}
+
+impl QualityMeter {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let (result_tx, results) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            // Asked once, on the first job
+            let mut vmaf = None;
+            for job in job_rx {
+                let vmaf = *vmaf.get_or_insert_with(|| has_libvmaf(&ffmpeg));
+                let result = measure(&ffmpeg, &job, vmaf);
+                if result_tx.send((job.encode, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, job: Job) {
+        // The worker only stops when the meter is dropped
+        let _ = self.job_tx.send(job);
+    }
+
+    /// Encodes measured since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<Quality, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn has_libvmaf(ffmpeg: &str) -> bool {
+    Command::new(ffmpeg)
+        .args(["-hide_banner", "-filters"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout).split_whitespace().any(|word| word == "libvmaf")
+        })
+}
+
+fn measure(ffmpeg: &str, job: &Job, vmaf: bool) -> Result<Quality, String> {
+    // Both timelines start at zero and the comparison stops with the shorter file
+    let copies = if vmaf { 3 } else { 2 };
+    let mut graph = format!(
+        "[0:v]scale={}:{}:flags=bicubic,setpts=PTS-STARTPTS,split={}[e0][e1]{};\
+         [1:v]setpts=PTS-STARTPTS,split={}[r0][r1]{};\
+         [e0][r0]psnr=shortest=1;[e1][r1]ssim=shortest=1",
+        job.width,
+        job.height,
+        copies,
+        if vmaf { "[e2]" } else { "" },
+        copies,
+        if vmaf { "[r2]" } else { "" },
+    );
+    if vmaf {
+        graph.push_str(";[e2][r2]libvmaf=shortest=1");
+    }
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin"])
+        .args(sequence::input_args(&job.encode))
+        .args(["-i", &job.encode])
+        .args(sequence::input_args(&job.reference))
+        .args(["-i", &job.reference])
+        .args(["-lavfi", &graph, "-f", "null", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+
+    // The filters log their averages once the inputs end
+    let score = |marker: &str, key: &str| -> Option<f64> {
+        let line = stderr.lines().rev().find(|line| line.contains(marker))?;
+        let rest = &line[line.find(key)? + key.len()..];
+        rest.split_whitespace().next()?.parse().ok()
+    };
+    let vmaf = match vmaf {
+        true => Some(score("VMAF score", "VMAF score:").ok_or("no VMAF score in ffmpeg output")?),
+        false => None,
+    };
+    Ok(Quality {
+        reference: job.reference.clone(),
+        vmaf,
+        psnr: score("PSNR", "average:").ok_or("no PSNR in ffmpeg output")?,
+        ssim: score("SSIM", "All:").ok_or("no SSIM in ffmpeg output")?,
+    })
+}
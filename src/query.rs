@@ -0,0 +1,367 @@
+//! The `:` filter bar language, e.g. `codec=H.265 OR AV1 AND bitrate>20 AND NOT hdr=SDR`.
+//!
+//! Each clause is `field op value` with `op` one of `=`, `!=`, `~` (contains), `>`, `>=`, `<`, `<=`.
+//! `NOT` negates a clause and `OR` binds tighter than `AND`. After `OR` a bare value
+//! repeats the previous field and operator.
+//! Values may be quoted when they contain spaces: `codec="Hap Q"`. Numeric fields
+//! also take inclusive ranges: `bitrate=10-50`, `duration=30s-10m`.
+
+use anyhow::{bail, Result};
+
+use crate::filter::{ActiveFilter, FilterOp, FilterType};
+
+fn field(name: &str) -> Option<FilterType> {
+    let field = match name.to_lowercase().as_str() {
+        "container" | "ext" => FilterType::Container,
+        "codec" => FilterType::Codec,
+        "resolution" | "res" => FilterType::Resolution,
+        "fps" | "framerate" | "frame_rate" => FilterType::FrameRate,
+        "bitrate" => FilterType::Bitrate,
+        "duration" => FilterType::Duration,
+        "timecode" | "tc" => FilterType::Timecode,
+        "chapters" => FilterType::Chapters,
+        "encoder" => FilterType::Encoder,
+        "created" | "date" => FilterType::Created,
+        "icc" | "color_profile" => FilterType::ColorProfile,
+        "camera" => FilterType::Camera,
+        "size" => FilterType::FileSize,
+        "depth" | "bitdepth" | "bit_depth" => FilterType::BitDepth,
+        "chroma" => FilterType::Chroma,
+        "hdr" => FilterType::Hdr,
+        "alpha" => FilterType::Alpha,
+        "vfr" | "fps_mode" => FilterType::FrameRateMode,
+        "audio" | "acodec" => FilterType::AudioCodec,
+        "channels" => FilterType::AudioChannels,
+        "sample_rate" | "samplerate" => FilterType::SampleRate,
+        "tracks" | "audio_tracks" => FilterType::AudioTracks,
+        "lang" | "language" => FilterType::AudioLanguage,
+        "subtitles" | "subs" => FilterType::Subtitles,
+        "captions" | "cc" => FilterType::Captions,
+        "faststart" => FilterType::Faststart,
+        "scan" | "interlace" => FilterType::Scan,
+        "matte" => FilterType::Matte,
+        "rotation" | "rotate" => FilterType::Rotation,
+        "orientation" => FilterType::Orientation,
+        "aspect" | "dar" => FilterType::Aspect,
+        "anamorphic" => FilterType::Anamorphic,
+        "op" | "pattern" => FilterType::OperationalPattern,
+        "essence" => FilterType::Essence,
+        "loudness" | "lufs" => FilterType::Loudness,
+        "truepeak" | "true_peak" | "tp" => FilterType::TruePeak,
+        "lra" => FilterType::LoudnessRange,
+        "tag" | "tags" => FilterType::Tag,
+        "note" | "notes" => FilterType::Note,
+        "rating" | "stars" => FilterType::Rating,
+        "flag" => FilterType::Flag,
+        "vmaf" => FilterType::Vmaf,
+        "psnr" => FilterType::Psnr,
+        "ssim" => FilterType::Ssim,
+        "hw" | "hwdecode" | "hw_decode" => FilterType::HwDecode,
+        _ => return None,
+    };
+    Some(field)
+}
+
+fn field_name(filter_type: &FilterType) -> &'static str {
+    match filter_type {
+        FilterType::Container => "container",
+        FilterType::Codec => "codec",
+        FilterType::Resolution => "resolution",
+        FilterType::FrameRate => "fps",
+        FilterType::Bitrate => "bitrate",
+        FilterType::Duration => "duration",
+        FilterType::Timecode => "timecode",
+        FilterType::Chapters => "chapters",
+        FilterType::Encoder => "encoder",
+        FilterType::Created => "created",
+        FilterType::ColorProfile => "icc",
+        FilterType::Camera => "camera",
+        FilterType::FileSize => "size",
+        FilterType::BitDepth => "depth",
+        FilterType::Chroma => "chroma",
+        FilterType::Hdr => "hdr",
+        FilterType::Alpha => "alpha",
+        FilterType::FrameRateMode => "fps_mode",
+        FilterType::AudioCodec => "audio",
+        FilterType::AudioChannels => "channels",
+        FilterType::SampleRate => "sample_rate",
+        FilterType::AudioTracks => "tracks",
+        FilterType::AudioLanguage => "language",
+        FilterType::Subtitles => "subtitles",
+        FilterType::Captions => "captions",
+        FilterType::Faststart => "faststart",
+        FilterType::Scan => "scan",
+        FilterType::Matte => "matte",
+        FilterType::Rotation => "rotation",
+        FilterType::Orientation => "orientation",
+        FilterType::Aspect => "aspect",
+        FilterType::Anamorphic => "anamorphic",
+        FilterType::OperationalPattern => "op",
+        FilterType::Essence => "essence",
+        FilterType::Loudness => "loudness",
+        FilterType::TruePeak => "truepeak",
+        FilterType::LoudnessRange => "lra",
+        FilterType::Tag => "tag",
+        FilterType::Note => "note",
+        FilterType::Rating => "rating",
+        FilterType::Flag => "flag",
+        FilterType::Vmaf => "vmaf",
+        FilterType::Psnr => "psnr",
+        FilterType::Ssim => "ssim",
+        FilterType::HwDecode => "hwdecode",
+    }
+}
+
+pub fn parse_op(text: &str) -> Option<(FilterOp, usize)> {
+    let op = match text.get(..2) {
+        Some(">=") => (FilterOp::GreaterOrEqual, 2),
+        Some("<=") => (FilterOp::LessOrEqual, 2),
+        _ => match text.chars().next()? {
+            '=' => (FilterOp::Equals, 1),
+            '~' => (FilterOp::Contains, 1),
+            '>' => (FilterOp::Greater, 1),
+            '<' => (FilterOp::Less, 1),
+            _ => return None,
+        },
+    };
+    Some(op)
+}
+
+fn parse_clause(clause: &str) -> Result<ActiveFilter> {
+    let Some(op_start) = clause.find(['=', '~', '<', '>']) else {
+        bail!("'{}' has no operator (use =, !=, ~, >, >=, <, <=)", clause);
+    };
+    let name = clause[..op_start].trim();
+    let (name, negated) = match name.strip_suffix('!') {
+        Some(name) => (name.trim(), true),
+        None => (name, false),
+    };
+    let Some(filter_type) = field(name) else {
+        bail!("unknown field '{}'", name);
+    };
+    let Some((op, op_len)) = parse_op(&clause[op_start..]) else {
+        bail!("bad operator in '{}'", clause);
+    };
+    let value = clause[op_start + op_len..].trim().trim_matches('"');
+    if value.is_empty() {
+        bail!("'{}' is missing a value", clause);
+    }
+    let mut filter = ActiveFilter::new(filter_type, op, value.to_string());
+    filter.negated = negated;
+    Ok(filter)
+}
+
+/// One side of an `OR`, with an optional leading `NOT`.
+fn parse_alternative(text: &str, previous: Option<&ActiveFilter>) -> Result<ActiveFilter> {
+    let (negated, clause) = match text.split_once(' ') {
+        Some((not, rest)) if not.eq_ignore_ascii_case("not") => (true, rest),
+        _ => (false, text),
+    };
+    let mut filter = match previous {
+        Some(previous) if !clause.contains(['=', '~', '<', '>']) => {
+            let op = match previous.op {
+                FilterOp::Between => FilterOp::Equals,
+                op => op,
+            };
+            let value = clause.trim().trim_matches('"').to_string();
+            ActiveFilter::new(previous.filter_type.clone(), op, value)
+        }
+        _ => parse_clause(clause)?,
+    };
+    filter.negated ^= negated;
+    Ok(filter)
+}
+
+/// Splits `words` on a whole-word, case-insensitive keyword.
+fn split_on<'a>(words: &[&'a str], keyword: &str) -> Result<Vec<Vec<&'a str>>> {
+    let parts: Vec<Vec<&str>> = words
+        .split(|word| word.eq_ignore_ascii_case(keyword))
+        .map(<[&str]>::to_vec)
+        .collect();
+    if parts.iter().any(Vec::is_empty) {
+        bail!("{} needs a clause on both sides", keyword.to_uppercase());
+    }
+    Ok(parts)
+}
+
+/// Compiles a query into groups that must all match, each matching when any of
+/// its filters does. An empty query clears the filters.
+pub fn parse(query: &str) -> Result<Vec<Vec<ActiveFilter>>> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut groups = Vec::new();
+    for conjunct in split_on(&words, "and")? {
+        let mut group: Vec<ActiveFilter> = Vec::new();
+        for alternative in split_on(&conjunct, "or")? {
+            let filter = parse_alternative(&alternative.join(" "), group.last())?;
+            group.push(filter);
+        }
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+fn clause_text(filter: &ActiveFilter) -> String {
+    let value = if filter.value.contains(' ') {
+        format!("\"{}\"", filter.value)
+    } else {
+        filter.value.clone()
+    };
+    let not = if filter.negated { "NOT " } else { "" };
+    format!("{}{}{}{}", not, field_name(&filter.filter_type), filter.op.symbol(), value)
+}
+
+/// Writes filters back out in query syntax, so the bar opens with the current state.
+pub fn to_query(groups: &[Vec<ActiveFilter>]) -> String {
+    groups
+        .iter()
+        .map(|group| group.iter().map(clause_text).collect::<Vec<_>>().join(" OR "))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Seconds from "90", "90s", "5m", "1.5h" or "00:01:30".
+pub fn parse_duration(value: &str) -> Option<f64> {
+    let value = value.trim().to_lowercase();
+    if value.contains(':') {
+        return value
+            .split(':')
+            .try_fold(0.0, |total, part| Some(total * 60.0 + part.parse::<f64>().ok()?));
+    }
+    let (number, scale) = match value.chars().last()? {
+        's' => (&value[..value.len() - 1], 1.0),
+        'm' => (&value[..value.len() - 1], 60.0),
+        'h' => (&value[..value.len() - 1], 3600.0),
+        _ => (value.as_str(), 1.0),
+    };
+    Some(number.trim().parse::<f64>().ok()? * scale)
+}
+
+/// Bytes from "500", "500MB", "1.5 GiB", "100M". Decimal and binary units are
+/// both treated as binary, matching how sizes are displayed.
+pub fn parse_size(value: &str) -> Option<f64> {
+    let value = value.trim().to_lowercase();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let scale = match value[split..].trim().trim_end_matches("ib").trim_end_matches('b') {
+        "" => 1.0,
+        "k" => 1024.0,
+        "m" => 1024.0 * 1024.0,
+        "g" => 1024.0 * 1024.0 * 1024.0,
+        "t" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn and_groups_hold_or_alternatives() {
+        let groups = parse("codec=H.265 OR AV1 AND bitrate>20 AND NOT hdr=SDR").unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].len(), 2);
+        // A bare value after OR repeats the field and operator
+        assert_eq!(groups[0][1], ActiveFilter::new(FilterType::Codec, FilterOp::Equals, "AV1".to_string()));
+        assert_eq!(groups[1][0].op, FilterOp::Greater);
+        assert_eq!(groups[1][0].value, "20");
+        assert!(groups[2][0].negated);
+        assert_eq!(groups[2][0].filter_type, FilterType::Hdr);
+    }
+
+    #[test]
+    fn keywords_are_whole_words_in_any_case() {
+        let groups = parse("encoder~Handbrake and codec=H.264 or hevc").unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0][0].op, FilterOp::Contains);
+        assert_eq!(groups[1][1].value, "hevc");
+    }
+
+    #[test]
+    fn bang_equals_negates() {
+        let filter = &parse("container!=mov").unwrap()[0][0];
+        assert_eq!(filter.op, FilterOp::Equals);
+        assert!(filter.negated);
+        assert!(!parse("NOT container!=mov").unwrap()[0][0].negated);
+    }
+
+    #[test]
+    fn quoted_values_keep_their_spaces() {
+        let filter = &parse("codec=\"Hap Q\"").unwrap()[0][0];
+        assert_eq!(filter.value, "Hap Q");
+    }
+
+    #[test]
+    fn numeric_equals_with_a_dash_is_a_range() {
+        let filter = &parse("duration=30s-10m").unwrap()[0][0];
+        assert_eq!(filter.op, FilterOp::Between);
+        // A bare value after a range is an exact match, not another range
+        let groups = parse("bitrate=10-50 OR 80").unwrap();
+        assert_eq!(groups[0][1].op, FilterOp::Equals);
+    }
+
+    #[test]
+    fn an_empty_query_clears() {
+        assert!(parse("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_queries_are_errors() {
+        assert!(parse("codec").unwrap_err().to_string().contains("no operator"));
+        assert!(parse("colour=red").unwrap_err().to_string().contains("unknown field 'colour'"));
+        assert!(parse("codec=").unwrap_err().to_string().contains("missing a value"));
+        assert!(parse("AND codec=H.264").unwrap_err().to_string().contains("AND needs a clause"));
+        assert!(parse("codec=H.264 OR").unwrap_err().to_string().contains("OR needs a clause"));
+    }
+
+    #[test]
+    fn to_query_round_trips() {
+        for query in [
+            "codec=H.265 OR codec=AV1 AND bitrate>20 AND NOT hdr=SDR",
+            "codec=\"Hap Q\"",
+            "duration=30s-10m AND size<=2GB",
+            "encoder~ffmpeg OR NOT container=mkv",
+        ] {
+            let groups = parse(query).unwrap();
+            assert_eq!(to_query(&groups), query);
+            assert_eq!(parse(&to_query(&groups)).unwrap(), groups);
+        }
+    }
+
+    #[test]
+    fn to_query_uses_the_canonical_field_names() {
+        assert_eq!(to_query(&parse("res=1920x1080 OR ext=mov").unwrap()), "resolution=1920x1080 OR container=mov");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90"), Some(90.0));
+        assert_eq!(parse_duration("90s"), Some(90.0));
+        assert_eq!(parse_duration("5m"), Some(300.0));
+        assert_eq!(parse_duration("1.5H"), Some(5400.0));
+        assert_eq!(parse_duration("00:01:30"), Some(90.0));
+        assert_eq!(parse_duration("1:02:03.5"), Some(3723.5));
+        assert_eq!(parse_duration("5 m"), Some(300.0));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("1:xx"), None);
+    }
+
+    #[test]
+    fn sizes_are_binary_either_way() {
+        assert_eq!(parse_size("500"), Some(500.0));
+        assert_eq!(parse_size("2k"), Some(2048.0));
+        assert_eq!(parse_size("500MB"), Some(500.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_size("100M"), Some(100.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_size("1.5 GiB"), Some(1.5 * 1024.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_size("1tb"), Some(1024f64.powi(4)));
+        assert_eq!(parse_size("12 parsecs"), None);
+        assert_eq!(parse_size("MB"), None);
+    }
+}
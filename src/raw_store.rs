@@ -0,0 +1,97 @@
+//! The raw ffprobe JSON of every listed file, kept in a scratch directory instead of
+//! in memory. The dump is often larger than everything else known about a file put
+//! together and is only read when the raw view, the details panel or a copy asks for
+//! the selected one, so thousands of rows shouldn't each hold theirs.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    process,
+};
+
+/// One file's output, taken out of the store and able to go back in.
+pub struct Entry(Stored);
+
+enum Stored {
+    File(PathBuf),
+    // Where the scratch directory couldn't be written
+    Memory(String),
+}
+
+pub struct RawStore {
+    dir: PathBuf,
+    entries: HashMap<String, Stored>,
+    // Names files in the directory, so a replaced entry never shares one
+    next: u64,
+    // The last output read, since the details panel asks for it every frame
+    recent: Option<(String, String)>,
+}
+
+impl RawStore {
+    pub fn new() -> Self {
+        let dir = env::temp_dir().join(format!("media-scout-{}", process::id()));
+        Self { dir, entries: HashMap::new(), next: 0, recent: None }
+    }
+
+    /// Keeps `raw` as the output for `path`, replacing any earlier one.
+    pub fn insert(&mut self, path: &str, raw: String) {
+        self.remove(path);
+        let file = self.dir.join(format!("{}.json", self.next));
+        self.next += 1;
+        let stored = match fs::create_dir_all(&self.dir).and_then(|()| fs::write(&file, &raw)) {
+            Ok(()) => Stored::File(file),
+            Err(_) => Stored::Memory(raw),
+        };
+        self.entries.insert(path.to_string(), stored);
+    }
+
+    /// The output for `path`; empty when there is none or it can't be read back.
+    pub fn get(&mut self, path: &str) -> String {
+        if let Some((recent, raw)) = &self.recent {
+            if recent == path {
+                return raw.clone();
+            }
+        }
+        let raw = match self.entries.get(path) {
+            Some(Stored::File(file)) => fs::read_to_string(file).unwrap_or_default(),
+            Some(Stored::Memory(raw)) => raw.clone(),
+            None => String::new(),
+        };
+        self.recent = Some((path.to_string(), raw.clone()));
+        raw
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        if let Some(Entry(Stored::File(file))) = self.take(path) {
+            let _ = fs::remove_file(file);
+        }
+    }
+
+    /// Takes the output for `path` out of the store, leaving any file on disk so the
+    /// entry can be put back with [`RawStore::restore`].
+    pub fn take(&mut self, path: &str) -> Option<Entry> {
+        if self.recent.as_ref().is_some_and(|(recent, _)| recent == path) {
+            self.recent = None;
+        }
+        self.entries.remove(path).map(Entry)
+    }
+
+    pub fn restore(&mut self, path: &str, entry: Entry) {
+        self.remove(path);
+        self.entries.insert(path.to_string(), entry.0);
+    }
+
+    /// Deletes what a taken entry left on disk, once it won't be restored.
+    pub fn discard(entry: Entry) {
+        if let Stored::File(file) = entry.0 {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+impl Drop for RawStore {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,250 @@
+//! ffmpeg commands that fix files failing a QC profile or a media-server check.
+
+use std::path::Path;
+
+use crate::{
+    compat::{self, MediaServer, Support},
+    filter::{FilterOp, FilterType},
+    model::MediaInfo,
+    probe::is_url,
+    query,
+    validate::{ValidationProfile, Verdict},
+};
+
+/// One suggested re-encode. `args` excludes the ffmpeg binary itself.
+pub struct Suggestion {
+    pub title: String,
+    pub args: Vec<String>,
+    pub output: String,
+}
+
+impl Suggestion {
+    /// The command as it would be typed into a shell.
+    pub fn command_line(&self, ffmpeg: &str) -> String {
+        std::iter::once(ffmpeg)
+            .chain(self.args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-+=/.,:@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// `clip.mov` -> `clip_hapq.mov` next to the original, or `frame_[0001-0500]` ->
+/// `frame_hapq.mov` for an image sequence.
+fn output_path(file: &MediaInfo, suffix: &str, extension: &str) -> String {
+    let path = Path::new(&file.path);
+    let stem = match file.sequence {
+        Some(_) => file.name.split('[').next().unwrap_or_default().trim_end_matches(['_', '-', '.', ' ']),
+        None => &file.name,
+    };
+    let stem = if stem.is_empty() { "sequence" } else { stem };
+    let name = format!("{}_{}.{}", stem, suffix, extension);
+    // A URL's output lands in the working directory
+    if is_url(&file.path) {
+        return name;
+    }
+    path.with_file_name(name).to_string_lossy().to_string()
+}
+
+fn suggestion(title: String, file: &MediaInfo, args: &[&str], output: String) -> Suggestion {
+    let mut all = Vec::new();
+    // Read at the frame rate it was analyzed at, from its first frame on
+    if let Some(sequence) = &file.sequence {
+        all.extend(["-framerate".to_string(), file.frame_rate.clone()]);
+        all.extend(["-start_number".to_string(), sequence.first.to_string()]);
+    }
+    all.extend(["-i".to_string(), file.path.clone()]);
+    all.extend(args.iter().map(|a| a.to_string()));
+    all.push(output.clone());
+    Suggestion { title, args: all, output }
+}
+
+fn hap(file: &MediaInfo, reason: &str) -> Suggestion {
+    let (format, suffix) = if file.has_alpha {
+        ("hap_alpha", "hapalpha")
+    } else {
+        ("hap_q", "hapq")
+    };
+    let output = output_path(file, suffix, "mov");
+    let title = format!("Re-encode to {} ({})", if file.has_alpha { "Hap Alpha" } else { "Hap Q" }, reason);
+    // Hap stores 4x4 blocks, so round the frame up to a multiple of 4
+    let args = [
+        "-vf", "pad=ceil(iw/4)*4:ceil(ih/4)*4", "-c:v", "hap", "-format", format, "-c:a", "pcm_s16le",
+    ];
+    suggestion(title, file, &args, output)
+}
+
+fn prores(file: &MediaInfo, reason: &str) -> Suggestion {
+    let (profile, pix_fmt, label) = if file.has_alpha {
+        ("4", "yuva444p10le", "ProRes 4444")
+    } else {
+        ("3", "yuv422p10le", "ProRes 422 HQ")
+    };
+    let output = output_path(file, "prores", "mov");
+    let args = ["-c:v", "prores_ks", "-profile:v", profile, "-pix_fmt", pix_fmt, "-c:a", "pcm_s16le"];
+    suggestion(format!("Re-encode to {} ({})", label, reason), file, &args, output)
+}
+
+/// The values a profile's `require` checks pin down, read from clauses like
+/// `codec=H.264`, `fps=25` or `bitrate=10-20`. Checks using OR or NOT are skipped.
+#[derive(Default)]
+struct Target {
+    codec: Option<String>,
+    container: Option<String>,
+    resolution: Option<(u32, u32)>,
+    frame_rate: Option<String>,
+    bitrate: Option<(Option<f64>, Option<f64>)>,
+    audio_codec: Option<String>,
+    channels: Option<String>,
+}
+
+fn profile_target(profile: &ValidationProfile) -> Target {
+    let mut target = Target::default();
+    let filters = profile
+        .require
+        .iter()
+        .filter_map(|check| query::parse(check).ok())
+        .flatten()
+        .filter(|group| group.len() == 1 && !group[0].negated)
+        .map(|mut group| group.remove(0));
+    for filter in filters {
+        let value = filter.value.trim().to_string();
+        match (&filter.filter_type, filter.op) {
+            (FilterType::Codec, FilterOp::Equals | FilterOp::Contains) => target.codec = Some(value),
+            (FilterType::Container, FilterOp::Equals | FilterOp::Contains) => {
+                target.container = Some(value)
+            }
+            (FilterType::Resolution, FilterOp::Equals) => {
+                target.resolution = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+            }
+            (FilterType::FrameRate, FilterOp::Equals) => target.frame_rate = Some(value),
+            (FilterType::Bitrate, FilterOp::Between) => {
+                let (low, high) = value.split_once('-').unwrap_or_default();
+                target.bitrate = Some((low.trim().parse().ok(), high.trim().parse().ok()));
+            }
+            (FilterType::Bitrate, FilterOp::Equals) => {
+                let rate = value.parse().ok();
+                target.bitrate = Some((rate, rate));
+            }
+            (FilterType::Bitrate, FilterOp::Less | FilterOp::LessOrEqual) => {
+                target.bitrate = Some((None, value.parse().ok()));
+            }
+            (FilterType::AudioCodec, FilterOp::Equals | FilterOp::Contains) => {
+                target.audio_codec = Some(value)
+            }
+            (FilterType::AudioChannels, FilterOp::Equals) => target.channels = Some(value),
+            _ => {}
+        }
+    }
+    target
+}
+
+/// A re-encode to the profile's spec, falling back to the file's own values
+/// for anything the profile doesn't pin down.
+fn to_profile(file: &MediaInfo, profile: &ValidationProfile) -> Suggestion {
+    let target = profile_target(profile);
+    let codec = target.codec.unwrap_or_else(|| "H.264".to_string()).to_lowercase();
+    let mut args: Vec<String> = Vec::new();
+    let push = |args: &mut Vec<String>, items: &[&str]| args.extend(items.iter().map(|s| s.to_string()));
+
+    let default_extension = if codec.starts_with("h.26") {
+        push(&mut args, &["-c:v", if codec == "h.265" { "libx265" } else { "libx264" }]);
+        push(&mut args, &["-pix_fmt", "yuv420p"]);
+        "mp4"
+    } else if codec.starts_with("prores") {
+        push(&mut args, &["-c:v", "prores_ks", "-profile:v", "3"]);
+        "mov"
+    } else if codec.starts_with("hap") {
+        push(&mut args, &["-c:v", "hap", "-format", "hap_q"]);
+        "mov"
+    } else {
+        push(&mut args, &["-c:v", "libx264", "-pix_fmt", "yuv420p"]);
+        "mp4"
+    };
+    let extension = target.container.unwrap_or_else(|| default_extension.to_string());
+
+    if let Some((width, height)) = target.resolution {
+        let scale = format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+            w = width,
+            h = height
+        );
+        push(&mut args, &["-vf", &scale]);
+    }
+    if let Some(rate) = &target.frame_rate {
+        push(&mut args, &["-r", rate]);
+    }
+    match target.bitrate {
+        Some((Some(low), Some(high))) => {
+            let average = format!("{}M", (low + high) / 2.0);
+            let max = format!("{}M", high);
+            let buffer = format!("{}M", high * 2.0);
+            push(&mut args, &["-b:v", &average, "-maxrate", &max, "-bufsize", &buffer]);
+        }
+        Some((_, Some(high))) => {
+            let max = format!("{}M", high);
+            let buffer = format!("{}M", high * 2.0);
+            push(&mut args, &["-maxrate", &max, "-bufsize", &buffer]);
+        }
+        _ => {}
+    }
+    match target.audio_codec.as_deref().map(str::to_lowercase).as_deref() {
+        Some("aac") => push(&mut args, &["-c:a", "aac", "-b:a", "320k"]),
+        Some("pcm") => push(&mut args, &["-c:a", "pcm_s16le"]),
+        Some("none") => push(&mut args, &["-an"]),
+        _ => push(&mut args, &["-c:a", "copy"]),
+    }
+    if let Some(channels) = &target.channels {
+        push(&mut args, &["-ac", channels]);
+    }
+    if extension == "mp4" || extension == "mov" {
+        push(&mut args, &["-movflags", "+faststart"]);
+    }
+
+    let output = output_path(file, &profile.name.to_lowercase().replace(' ', "_"), &extension);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    suggestion(format!("Re-encode to the {} profile", profile.name), file, &args, output)
+}
+
+/// Fixes for `file`: one per failed check, so a clip that fails nothing gets none.
+pub fn suggestions(file: &MediaInfo, profile: Option<&ValidationProfile>) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    if let Some(profile) = profile.filter(|p| p.check(file).verdict != Verdict::Pass) {
+        suggestions.push(to_profile(file, profile));
+    }
+
+    if file.faststart == Some(false) {
+        // A remux, so it's fast and lossless
+        let output = output_path(file, "faststart", &file.container);
+        let args = ["-map", "0", "-c", "copy", "-movflags", "+faststart"];
+        let title = "Move the moov atom to the front (faststart)".to_string();
+        suggestions.push(suggestion(title, file, &args, output));
+    }
+
+    let mut needs_hap = Vec::new();
+    for server in MediaServer::ALL {
+        let support = compat::check(server, file).support;
+        if support == Support::Native {
+            continue;
+        }
+        match server {
+            MediaServer::Millumin => suggestions.push(prores(file, "Millumin")),
+            _ => needs_hap.push(server.name()),
+        }
+    }
+    if !needs_hap.is_empty() {
+        suggestions.push(hap(file, &needs_hap.join(", ")));
+    }
+    suggestions
+}
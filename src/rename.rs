@@ -0,0 +1,115 @@
+//! Renaming files on disk after their metadata with a pattern such as
+//! `{name}_{resolution}_{codec}_{fps}.{container}`, to bring the names of a content
+//! drop into line. Nothing is renamed over an existing file, and every rename is
+//! appended to `renames.log` in the config directory, so a batch can be traced and
+//! put back by hand after media-scout has closed.
+
+use anyhow::{Context, Result};
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{config, model::format_utc_now, template::Template, MediaInfo};
+
+pub const DEFAULT_PATTERN: &str = "{name}_{resolution}_{codec}_{fps}.{container}";
+
+/// One file's new name, or why it keeps its old one.
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+    pub skipped: Option<String>,
+}
+
+/// `name` with the characters the common file systems refuse, and path separators,
+/// replaced by `_`.
+pub fn clean(name: &str) -> String {
+    let reserved = |c: char| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control();
+    name.chars().map(|c| if reserved(c) { '_' } else { c }).collect()
+}
+
+/// What `pattern` makes of each file's name in its own directory. Names two files
+/// would share and names taken on disk, even by another file of the batch, are
+/// skipped, as are files that aren't a single local file.
+pub fn plan(pattern: &Template, files: &[&MediaInfo]) -> Vec<Rename> {
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let name = clean(pattern.render_file(file, i).trim());
+            let from = Path::new(&file.path);
+            let to = from.with_file_name(&name);
+            // URLs, streams, S3 objects and files on other hosts
+            let skipped = if file.path.contains("://") {
+                Some("not a local file")
+            } else if file.sequence.is_some() {
+                Some("an image sequence")
+            } else if name.is_empty() || name.starts_with('.') {
+                Some("the pattern leaves no name")
+            } else if to == from {
+                Some("already named so")
+            } else if !taken.insert(to.clone()) {
+                Some("another file gets the same name")
+            } else if to.exists() {
+                Some("a file of that name exists")
+            } else {
+                None
+            };
+            let to = to.to_string_lossy().to_string();
+            Rename { from: file.path.clone(), to, skipped: skipped.map(String::from) }
+        })
+        .collect()
+}
+
+/// Renames the files `plan` didn't skip, in order, and logs each one. Returns the
+/// `(from, to)` pairs that were renamed and the errors of those that weren't.
+pub fn apply(renames: &[Rename]) -> (Vec<(String, String)>, Vec<String>) {
+    move_files(renames.iter().filter(|r| r.skipped.is_none()).map(|r| (r.from.as_str(), r.to.as_str())))
+}
+
+/// Puts renamed files back under their old names; the same results as `apply`.
+pub fn undo(renamed: &[(String, String)]) -> (Vec<(String, String)>, Vec<String>) {
+    move_files(renamed.iter().rev().map(|(from, to)| (to.as_str(), from.as_str())))
+}
+
+fn move_files<'a>(moves: impl Iterator<Item = (&'a str, &'a str)>) -> (Vec<(String, String)>, Vec<String>) {
+    let (mut moved, mut errors) = (Vec::new(), Vec::new());
+    for (from, to) in moves {
+        // Checked again, something may have taken the name since the preview
+        if Path::new(to).exists() {
+            errors.push(format!("{} exists", to));
+            continue;
+        }
+        match fs::rename(from, to) {
+            Ok(()) => moved.push((from.to_string(), to.to_string())),
+            Err(e) => errors.push(format!("{}: {}", from, e)),
+        }
+    }
+    if let Err(e) = log(&moved) {
+        errors.push(format!("{:#}", e));
+    }
+    (moved, errors)
+}
+
+/// Appends `time<TAB>from<TAB>to` lines to `renames.log`.
+pub fn log(moved: &[(String, String)]) -> Result<()> {
+    let Some(dir) = config::config_dir().filter(|_| !moved.is_empty()) else {
+        return Ok(());
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    let path = dir.join("renames.log");
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    let time = format_utc_now();
+    for (from, to) in moved {
+        writeln!(file, "{}\t{}\t{}", time, from, to).with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,544 @@
+//! A shareable report of a set of files: totals, the QC results under a profile,
+//! an index of the files and every exported field of each, as one self-contained
+//! HTML page, Markdown document or printable PDF for people who will never open
+//! the TUI. The PDF has a title page with the letterhead from `[brand]` and a
+//! spec sheet per file, the way broadcasters want a QC report with a delivery.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use crate::{
+    export,
+    model::{format_duration, format_resolution, format_size, format_utc_now, MediaInfo},
+    pdf::{self, Font, Jpeg, Pdf, Rgb, PAGE_HEIGHT, PAGE_WIDTH},
+    validate::{ValidationProfile, Verdict},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Markdown,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// The letterhead of PDF reports, e.g. `[brand] name = "Northlight Post"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Brand {
+    /// On the title page and in every page's footer
+    pub name: Option<String>,
+    /// A JPEG for the title page
+    pub logo: Option<PathBuf>,
+    /// `#rrggbb` of the title page's rule and the headings
+    pub color: Option<String>,
+}
+
+impl Brand {
+    /// The color, a dark blue when unset.
+    pub fn accent(&self) -> Option<Rgb> {
+        match &self.color {
+            Some(color) => parse_hex(color),
+            None => Some((31, 78, 140)),
+        }
+    }
+}
+
+/// `#1f4e8c` as red, green and blue.
+pub fn parse_hex(color: &str) -> Option<Rgb> {
+    let hex = color.trim().strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+/// The heading of a report that isn't of a project.
+pub const REPORT_TITLE: &str = "Media report";
+
+/// What goes into a report besides the files.
+pub struct ReportOptions<'a> {
+    pub title: &'a str,
+    /// The query the files were picked with, shown under the title
+    pub filter: Option<&'a str>,
+    pub profile: Option<&'a ValidationProfile>,
+    pub brand: Option<&'a Brand>,
+}
+
+/// A section of the report, kept apart from the markup so both formats say the same.
+enum Block {
+    Heading(u8, String),
+    Text(String),
+    /// Header cells, then rows; a cell may carry a verdict to color it by
+    Table(Vec<String>, Vec<Vec<(String, Option<Verdict>)>>),
+    Bullets(Vec<(String, Option<Verdict>)>),
+}
+
+/// The document's bytes: UTF-8 text for HTML and Markdown.
+pub fn render(files: &[&MediaInfo], options: &ReportOptions, format: ReportFormat) -> Vec<u8> {
+    let blocks = blocks(files, options);
+    match format {
+        ReportFormat::Html => render_html(options.title, &blocks).into_bytes(),
+        ReportFormat::Markdown => render_markdown(&blocks).into_bytes(),
+        ReportFormat::Pdf => render_pdf(options, &blocks),
+    }
+}
+
+fn blocks(files: &[&MediaInfo], options: &ReportOptions) -> Vec<Block> {
+    let plain = |cells: Vec<String>| -> Vec<(String, Option<Verdict>)> {
+        cells.into_iter().map(|cell| (cell, None)).collect()
+    };
+    let mut blocks = vec![Block::Heading(1, options.title.to_string())];
+    let mut about = format!("{} file(s)", files.len());
+    if let Some(filter) = options.filter.filter(|f| !f.is_empty()) {
+        about.push_str(&format!(" matching {}", filter));
+    }
+    blocks.push(Block::Text(about));
+    blocks.push(Block::Text(format!("Generated {}", format_utc_now())));
+
+    blocks.push(Block::Heading(2, "Summary".to_string()));
+    let videos: Vec<&MediaInfo> = files.iter().copied().filter(|f| !f.audio_only).collect();
+    let mut summary = vec![
+        ("Files", files.len().to_string()),
+        ("Total size", format_size(Some(files.iter().filter_map(|f| f.file_size).sum()))),
+        ("Total duration", format_duration(Some(files.iter().filter_map(|f| f.duration).sum()))),
+        ("Containers", tally(files.iter().map(|f| f.container.as_str()))),
+    ];
+    if !videos.is_empty() {
+        summary.push(("Video codecs", tally(videos.iter().map(|f| f.codec.as_str()))));
+        summary.push(("Resolutions", tally(videos.iter().map(|f| f.resolution.as_str()))));
+    }
+    let rows = summary.into_iter().map(|(label, value)| plain(vec![label.to_string(), value])).collect();
+    blocks.push(Block::Table(vec!["Field".to_string(), "Value".to_string()], rows));
+
+    let reports: Vec<_> = match options.profile {
+        Some(profile) => files.iter().map(|file| Some(profile.check(file))).collect(),
+        None => files.iter().map(|_| None).collect(),
+    };
+    if let Some(profile) = options.profile {
+        blocks.push(Block::Heading(2, format!("QC: {}", profile.name)));
+        let count = |verdict: Verdict| reports.iter().flatten().filter(|r| r.verdict == verdict).count();
+        blocks.push(Block::Text(format!(
+            "{} passed, {} with warnings, {} failed.",
+            count(Verdict::Pass),
+            count(Verdict::Warn),
+            count(Verdict::Fail)
+        )));
+        let failing: Vec<Vec<(String, Option<Verdict>)>> = files
+            .iter()
+            .zip(reports.iter().flatten())
+            .filter(|(_, report)| report.verdict != Verdict::Pass)
+            .map(|(file, report)| {
+                let reasons: Vec<&str> = report.reasons.iter().map(|(_, reason)| reason.as_str()).collect();
+                vec![
+                    (format!("{}.{}", file.name, file.container), None),
+                    (report.verdict.label().to_string(), Some(report.verdict)),
+                    (reasons.join("; "), None),
+                ]
+            })
+            .collect();
+        if !failing.is_empty() {
+            let header = ["File", "Verdict", "Issues"].map(String::from).to_vec();
+            blocks.push(Block::Table(header, failing));
+        }
+    }
+
+    blocks.push(Block::Heading(2, "Files".to_string()));
+    let mut header = ["Name", "Codec", "Resolution", "FPS", "Duration", "Size"].map(String::from).to_vec();
+    if options.profile.is_some() {
+        header.push("QC".to_string());
+    }
+    let rows = files
+        .iter()
+        .zip(&reports)
+        .map(|(file, report)| {
+            let mut row = plain(vec![
+                format!("{}.{}", file.name, file.container),
+                file.codec.clone(),
+                format_resolution(file),
+                file.frame_rate.clone(),
+                format_duration(file.duration),
+                format_size(file.file_size),
+            ]);
+            if let Some(report) = report {
+                row.push((report.verdict.label().to_string(), Some(report.verdict)));
+            }
+            row
+        })
+        .collect();
+    blocks.push(Block::Table(header, rows));
+
+    blocks.push(Block::Heading(2, "Details".to_string()));
+    for (file, report) in files.iter().zip(&reports) {
+        blocks.push(Block::Heading(3, format!("{}.{}", file.name, file.container)));
+        // Fields with nothing to say would make every file's table twice as long
+        let mut rows: Vec<_> = export::fields(file)
+            .into_iter()
+            .filter(|(label, value)| *label != "Name" && !value.is_empty())
+            .map(|(label, value)| plain(vec![label.to_string(), value]))
+            .collect();
+        if let Some(report) = report {
+            let verdict = (report.verdict.label().to_string(), Some(report.verdict));
+            rows.insert(0, vec![("QC".to_string(), None), verdict]);
+        }
+        blocks.push(Block::Table(vec!["Field".to_string(), "Value".to_string()], rows));
+        if let Some(report) = report.as_ref().filter(|r| !r.reasons.is_empty()) {
+            let issues = report
+                .reasons
+                .iter()
+                .map(|(verdict, reason)| (format!("{}: {}", verdict.label(), reason), Some(*verdict)))
+                .collect();
+            blocks.push(Block::Bullets(issues));
+        }
+    }
+    blocks
+}
+
+/// `H.264 (3), MPEG-2 (1)`
+fn tally<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts.iter().map(|(value, n)| format!("{} ({})", value, n)).collect::<Vec<_>>().join(", ")
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("{} {}\n\n", "#".repeat(*level as usize), text))
+            }
+            Block::Text(text) => out.push_str(&format!("{}\n\n", text)),
+            Block::Table(header, rows) => {
+                let mut cells = vec![header.clone()];
+                cells.extend(rows.iter().map(|row| row.iter().map(|(cell, _)| cell.clone()).collect()));
+                out.push_str(&export::markdown_table(&cells));
+                out.push('\n');
+            }
+            Block::Bullets(items) => {
+                for (item, _) in items {
+                    out.push_str(&format!("- {}\n", item));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn verdict_class(verdict: Option<Verdict>) -> &'static str {
+    match verdict {
+        Some(Verdict::Pass) => " class=\"pass\"",
+        Some(Verdict::Warn) => " class=\"warn\"",
+        Some(Verdict::Fail) => " class=\"fail\"",
+        None => "",
+    }
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em auto;max-width:72em;padding:0 1em;color:#222}\
+table{border-collapse:collapse;margin:0 0 1.5em}th,td{border:1px solid #ddd;padding:.3em .6em;text-align:left;\
+vertical-align:top}th{background:#f4f4f4}td{word-break:break-word}h3{margin-top:2em}\
+.pass{color:#1a7f37}.warn{color:#9a6700}.fail{color:#cf222e;font-weight:bold}";
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n",
+        escape(title),
+        STYLE
+    );
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => out.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text))),
+            Block::Text(text) => out.push_str(&format!("<p>{}</p>\n", escape(text))),
+            Block::Table(header, rows) => {
+                out.push_str("<table>\n<tr>");
+                for cell in header {
+                    out.push_str(&format!("<th>{}</th>", escape(cell)));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for (cell, verdict) in row {
+                        out.push_str(&format!("<td{}>{}</td>", verdict_class(*verdict), escape(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+            Block::Bullets(items) => {
+                out.push_str("<ul>\n");
+                for (item, verdict) in items {
+                    out.push_str(&format!("<li{}>{}</li>\n", verdict_class(*verdict), escape(item)));
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const MARGIN: f32 = 50.0;
+/// Where the body stops, above the footer
+const BODY_BOTTOM: f32 = PAGE_HEIGHT - 60.0;
+const TEXT: Rgb = (34, 34, 34);
+const MUTED: Rgb = (110, 110, 110);
+const RULE: Rgb = (221, 221, 221);
+const HEADER_FILL: Rgb = (244, 244, 244);
+const CELL_SIZE: f32 = 8.5;
+const CELL_LEADING: f32 = 11.0;
+const CELL_PADDING: f32 = 4.0;
+
+/// Verdicts stand out in bold.
+fn cell_font(verdict: Option<Verdict>) -> Font {
+    if verdict.is_some() {
+        Font::Bold
+    } else {
+        Font::Regular
+    }
+}
+
+fn verdict_color(verdict: Verdict) -> Rgb {
+    match verdict {
+        Verdict::Pass => (26, 127, 55),
+        Verdict::Warn => (154, 103, 0),
+        Verdict::Fail => (207, 34, 46),
+    }
+}
+
+/// Lays the blocks out down A4 pages: the title and the lines under it on a title
+/// page, each file's details on a page of its own, and tables broken across pages
+/// with their header repeated.
+fn render_pdf(options: &ReportOptions, blocks: &[Block]) -> Vec<u8> {
+    let brand = options.brand.cloned().unwrap_or_default();
+    let accent = brand.accent().unwrap_or((31, 78, 140));
+    let mut pdf = Pdf::default();
+    let width = PAGE_WIDTH - 2.0 * MARGIN;
+    let mut y = 0.0;
+    let mut title_page = false;
+    // Until something is drawn under the last heading
+    let mut under_heading = false;
+    for (i, block) in blocks.iter().enumerate() {
+        let after_heading = std::mem::replace(&mut under_heading, matches!(block, Block::Heading(..)));
+        match block {
+            Block::Heading(1, title) => {
+                pdf.add_page();
+                y = title_page_header(&mut pdf, &brand, accent);
+                y += 150.0;
+                for line in pdf::wrap(title, Font::Bold, 28.0, width) {
+                    pdf.text(MARGIN, y, Font::Bold, 28.0, TEXT, &line);
+                    y += 34.0;
+                }
+                y += 10.0;
+                title_page = true;
+            }
+            Block::Text(text) if title_page => {
+                for line in pdf::wrap(text, Font::Regular, 13.0, width) {
+                    pdf.text(MARGIN, y, Font::Regular, 13.0, MUTED, &line);
+                    y += 19.0;
+                }
+            }
+            Block::Heading(level, text) => {
+                let size = if *level == 2 { 15.0 } else { 14.0 };
+                // A file's spec sheet starts a page, and so does the first section and one
+                // that only heads the spec sheets
+                let next_is_sheet = matches!(blocks.get(i + 1), Some(Block::Heading(3, _)));
+                let new_page = match level {
+                    2 => title_page || next_is_sheet || y + 80.0 > BODY_BOTTOM,
+                    _ => !after_heading,
+                };
+                if new_page {
+                    pdf.add_page();
+                    y = MARGIN;
+                    title_page = false;
+                } else {
+                    y += 14.0;
+                }
+                y += size;
+                for line in pdf::wrap(text, Font::Bold, size, width) {
+                    pdf.text(MARGIN, y, Font::Bold, size, accent, &line);
+                    y += size + 4.0;
+                }
+                pdf.rect(MARGIN, y - 2.0, width, 1.0, accent);
+                y += 10.0;
+            }
+            Block::Text(text) => {
+                for line in pdf::wrap(text, Font::Regular, 10.0, width) {
+                    y = page_break(&mut pdf, y, 14.0);
+                    pdf.text(MARGIN, y + 10.0, Font::Regular, 10.0, TEXT, &line);
+                    y += 14.0;
+                }
+                y += 6.0;
+            }
+            Block::Table(header, rows) => y = pdf_table(&mut pdf, y, header, rows) + 10.0,
+            Block::Bullets(items) => {
+                for (item, verdict) in items {
+                    let color = verdict.map_or(TEXT, verdict_color);
+                    for (i, line) in pdf::wrap(item, Font::Regular, 10.0, width - 12.0).iter().enumerate() {
+                        y = page_break(&mut pdf, y, 14.0);
+                        if i == 0 {
+                            pdf.text(MARGIN, y + 10.0, Font::Regular, 10.0, color, "•");
+                        }
+                        pdf.text(MARGIN + 12.0, y + 10.0, Font::Regular, 10.0, color, line);
+                        y += 14.0;
+                    }
+                }
+                y += 6.0;
+            }
+        }
+    }
+
+    // Every page but the title page is numbered once the count is known
+    let pages = pdf.page_count();
+    let footer = match &brand.name {
+        Some(name) => format!("{} · {}", name, options.title),
+        None => options.title.to_string(),
+    };
+    for page in 1..pages {
+        pdf.select_page(page);
+        pdf.rect(MARGIN, PAGE_HEIGHT - 45.0, width, 0.5, RULE);
+        pdf.text(MARGIN, PAGE_HEIGHT - 32.0, Font::Regular, 8.0, MUTED, &footer);
+        let number = format!("Page {} of {}", page + 1, pages);
+        let x = PAGE_WIDTH - MARGIN - pdf::text_width(&number, Font::Regular, 8.0);
+        pdf.text(x, PAGE_HEIGHT - 32.0, Font::Regular, 8.0, MUTED, &number);
+    }
+    pdf.finish()
+}
+
+/// The logo and the brand's name across the top of the title page, over a rule in
+/// the accent color; returns where the rule is. A logo that can't be read is left
+/// out rather than failing the report.
+fn title_page_header(pdf: &mut Pdf, brand: &Brand, accent: Rgb) -> f32 {
+    let mut x = MARGIN;
+    let logo = brand.logo.as_ref().and_then(|path| fs::read(path).ok()).and_then(Jpeg::parse);
+    if let Some(logo) = logo {
+        // At most 60 points high and a third of the page wide
+        let scale = (60.0 / logo.height as f32).min(PAGE_WIDTH / 3.0 / logo.width as f32);
+        let (width, height) = (logo.width as f32 * scale, logo.height as f32 * scale);
+        pdf.image(logo, MARGIN, MARGIN + (60.0 - height) / 2.0, width, height);
+        x += width + 16.0;
+    }
+    if let Some(name) = &brand.name {
+        pdf.text(x, MARGIN + 36.0, Font::Bold, 18.0, TEXT, name);
+    }
+    let rule = MARGIN + 76.0;
+    pdf.rect(MARGIN, rule, PAGE_WIDTH - 2.0 * MARGIN, 3.0, accent);
+    rule
+}
+
+/// Starts a page when `height` more doesn't fit below `y`; returns where to draw.
+fn page_break(pdf: &mut Pdf, y: f32, height: f32) -> f32 {
+    match y + height > BODY_BOTTOM {
+        true => {
+            pdf.add_page();
+            MARGIN
+        }
+        false => y,
+    }
+}
+
+/// Columns as wide as their widest cell, the widest squeezed down to share what's
+/// left when they don't all fit, with cells wrapped to their column.
+fn column_widths(header: &[String], rows: &[Vec<(String, Option<Verdict>)>], width: f32) -> Vec<f32> {
+    let natural: Vec<f32> = (0..header.len())
+        .map(|i| {
+            let widest = rows
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|(cell, verdict)| pdf::text_width(cell, cell_font(*verdict), CELL_SIZE))
+                .fold(pdf::text_width(&header[i], Font::Bold, CELL_SIZE), f32::max);
+            // A point over, so rounding doesn't wrap the widest cell
+            widest + 2.0 * CELL_PADDING + 1.0
+        })
+        .collect();
+    let mut widths = natural.clone();
+    let mut fixed = vec![false; natural.len()];
+    loop {
+        let used: f32 = widths.iter().zip(&fixed).filter(|(_, fixed)| **fixed).map(|(w, _)| w).sum();
+        let open = fixed.iter().filter(|fixed| !**fixed).count();
+        if open == 0 {
+            break;
+        }
+        let share = (width - used) / open as f32;
+        let narrow: Vec<usize> = (0..natural.len()).filter(|&i| !fixed[i] && natural[i] <= share).collect();
+        if narrow.is_empty() {
+            for i in (0..natural.len()).filter(|&i| !fixed[i]) {
+                widths[i] = share;
+            }
+            break;
+        }
+        for i in narrow {
+            widths[i] = natural[i];
+            fixed[i] = true;
+        }
+    }
+    widths
+}
+
+fn pdf_table(pdf: &mut Pdf, mut y: f32, header: &[String], rows: &[Vec<(String, Option<Verdict>)>]) -> f32 {
+    let widths = column_widths(header, rows, PAGE_WIDTH - 2.0 * MARGIN);
+    let table_width: f32 = widths.iter().sum();
+    let draw_row = |pdf: &mut Pdf, y: f32, cells: &[(Vec<String>, Rgb, Font)], fill: Option<Rgb>| -> f32 {
+        let lines = cells.iter().map(|(lines, _, _)| lines.len()).max().unwrap_or(1);
+        let height = lines as f32 * CELL_LEADING + 2.0 * CELL_PADDING;
+        if let Some(fill) = fill {
+            pdf.rect(MARGIN, y, table_width, height, fill);
+        }
+        let mut x = MARGIN;
+        for ((lines, color, font), width) in cells.iter().zip(&widths) {
+            for (n, line) in lines.iter().enumerate() {
+                let baseline = y + CELL_PADDING + CELL_SIZE + n as f32 * CELL_LEADING;
+                pdf.text(x + CELL_PADDING, baseline, *font, CELL_SIZE, *color, line);
+            }
+            x += width;
+        }
+        pdf.rect(MARGIN, y + height, table_width, 0.5, RULE);
+        height
+    };
+    let wrap = |text: &str, font: Font, width: f32| pdf::wrap(text, font, CELL_SIZE, width - 2.0 * CELL_PADDING);
+    let header_cells: Vec<(Vec<String>, Rgb, Font)> = header
+        .iter()
+        .zip(&widths)
+        .map(|(cell, &width)| (wrap(cell, Font::Bold, width), TEXT, Font::Bold))
+        .collect();
+    let header_height = |cells: &[(Vec<String>, Rgb, Font)]| {
+        cells.iter().map(|(lines, _, _)| lines.len()).max().unwrap_or(1) as f32 * CELL_LEADING + 2.0 * CELL_PADDING
+    };
+    y = page_break(pdf, y, header_height(&header_cells) + CELL_LEADING + 2.0 * CELL_PADDING);
+    y += draw_row(pdf, y, &header_cells, Some(HEADER_FILL));
+    for row in rows {
+        let cells: Vec<(Vec<String>, Rgb, Font)> = row
+            .iter()
+            .zip(&widths)
+            .map(|((cell, verdict), &width)| {
+                let font = cell_font(*verdict);
+                (wrap(cell, font, width), verdict.map_or(TEXT, verdict_color), font)
+            })
+            .collect();
+        let height = header_height(&cells);
+        if y + height > BODY_BOTTOM {
+            pdf.add_page();
+            y = MARGIN;
+            y += draw_row(pdf, y, &header_cells, Some(HEADER_FILL));
+        }
+        y += draw_row(pdf, y, &cells, None);
+    }
+    y
+}
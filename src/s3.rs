@@ -0,0 +1,94 @@
+//! `s3://bucket/key` sources, on AWS or any S3-compatible store. Listing and
+//! presigning go through the AWS CLI, so the usual credentials (profiles, SSO,
+//! instance roles) just work; ffprobe then range-reads the presigned https URL
+//! instead of the whole object being downloaded.
+
+use serde_json::Value;
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{config::Config, probe::is_media_file};
+
+/// Long enough for a slow probe, short enough not to leave usable links lying around.
+const PRESIGN_SECONDS: u32 = 3600;
+
+pub fn is_s3(path: &str) -> bool {
+    path.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("s3://"))
+}
+
+/// `s3://bucket/dir/clip.mov` -> (`bucket`, `dir/clip.mov`)
+fn split(url: &str) -> Option<(&str, &str)> {
+    let rest = url.get(5..)?;
+    let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+    (!bucket.is_empty()).then_some((bucket, key))
+}
+
+#[derive(Debug, Clone)]
+pub struct S3 {
+    aws: String,
+    endpoint: Option<String>,
+}
+
+impl S3 {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            aws: config.aws.clone(),
+            endpoint: config.s3_endpoint.clone(),
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String, String> {
+        let mut command = Command::new(&self.aws);
+        if let Some(endpoint) = &self.endpoint {
+            command.args(["--endpoint-url", endpoint]);
+        }
+        let output = command
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("could not start '{}': {}", self.aws, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.trim().lines().last().unwrap_or("aws failed").to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// The media objects under a prefix such as `s3://bucket/renders/`, or the object
+    /// itself when `url` names a media file.
+    pub fn list(&self, url: &str) -> Result<Vec<String>, String> {
+        if is_media_file(Path::new(url)) {
+            return Ok(vec![url.to_string()]);
+        }
+        let (bucket, prefix) = split(url).ok_or("no bucket in the URL")?;
+        // `renders` means the folder, not every key starting with those letters
+        let prefix = match prefix.is_empty() || prefix.ends_with('/') {
+            true => prefix.to_string(),
+            false => format!("{}/", prefix),
+        };
+        // The CLI follows the continuation tokens itself and merges the pages
+        let listing = self.run(&[
+            "s3api", "list-objects-v2",
+            "--bucket", bucket,
+            "--prefix", &prefix,
+            "--output", "json",
+        ])?;
+        let listing: Value = serde_json::from_str(&listing).unwrap_or(Value::Null);
+        let mut keys: Vec<&str> = listing["Contents"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|object| object["Key"].as_str())
+            .filter(|key| is_media_file(Path::new(key)))
+            .collect();
+        keys.sort();
+        Ok(keys.into_iter().map(|key| format!("s3://{}/{}", bucket, key)).collect())
+    }
+
+    /// A time-limited https URL ffprobe can read the object from.
+    pub fn presign(&self, url: &str) -> Result<String, String> {
+        self.run(&["s3", "presign", url, "--expires-in", &PRESIGN_SECONDS.to_string()])
+    }
+}
@@ -0,0 +1,172 @@
+//! Numbered image sequences, e.g. `frame_0001.png` … `frame_0500.png`, collapsed into
+//! one entry when a directory is scanned. The entry's path is the printf-style pattern
+//! ffmpeg reads sequences by, `frame_%04d.png`, so ffprobe and the ffmpeg passes see a
+//! single video stream.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Still formats rendered out as sequences. GIF is left out: it animates on its own.
+const EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "exr", "dpx", "bmp", "webp"];
+/// Fewer numbered stills than this stay separate files.
+const MIN_FRAMES: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sequence {
+    pub first: u64,
+    pub last: u64,
+    /// Frames on disk, fewer than `first..=last` spans when some are missing
+    pub frames: usize,
+    /// Digits frame numbers are zero-padded to, 0 when unpadded
+    pub padding: usize,
+}
+
+impl Sequence {
+    /// `0001-0500`
+    pub fn range(&self) -> String {
+        format!("{:0width$}-{:0width$}", self.first, self.last, width = self.padding)
+    }
+
+    /// Frame numbers between the first and last with no file. ffmpeg stops reading
+    /// at the first of them.
+    pub fn missing(&self) -> u64 {
+        self.last - self.first + 1 - self.frames as u64
+    }
+}
+
+/// The parts of a sequence's file names: `frame_` and `png` around the frame number.
+struct Pattern {
+    prefix: String,
+    padding: usize,
+    extension: String,
+}
+
+impl Pattern {
+    /// Reads `frame_%04d.png` (or `frame_%d.png` when unpadded).
+    fn parse(file_name: &str) -> Option<Self> {
+        let (stem, extension) = file_name.rsplit_once('.')?;
+        let stem = stem.strip_suffix('d')?;
+        let percent = stem.rfind('%')?;
+        let spec = &stem[percent + 1..];
+        let padding = match spec {
+            "" => 0,
+            _ if spec.starts_with('0') => spec.parse().ok()?,
+            _ => return None,
+        };
+        Some(Self {
+            prefix: stem[..percent].replace("%%", "%"),
+            padding,
+            extension: extension.to_string(),
+        })
+    }
+
+    fn file_name(&self) -> String {
+        let number = match self.padding {
+            0 => "%d".to_string(),
+            padding => format!("%0{}d", padding),
+        };
+        format!("{}{}.{}", self.prefix.replace('%', "%%"), number, self.extension)
+    }
+
+    /// The frame number when `file_name` belongs to the sequence.
+    fn frame(&self, file_name: &str) -> Option<u64> {
+        let (prefix, digits, extension) = split(file_name)?;
+        let number = digits.parse().ok()?;
+        let belongs = prefix == self.prefix
+            && extension == self.extension
+            && format!("{:0width$}", number, width = self.padding) == digits;
+        belongs.then_some(number)
+    }
+}
+
+/// `frame_0001.png` -> (`frame_`, `0001`, `png`), for still formats only.
+fn split(file_name: &str) -> Option<(&str, &str, &str)> {
+    let (stem, extension) = file_name.rsplit_once('.')?;
+    if !EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+        return None;
+    }
+    let prefix = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let digits = &stem[prefix.len()..];
+    (!digits.is_empty()).then_some((prefix, digits, extension))
+}
+
+/// Replaces the numbered stills among one directory's `files` with a pattern path per
+/// sequence, placed where its first frame was. Everything else passes through.
+pub fn group(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut candidates: HashMap<(String, String), Vec<(usize, String)>> = HashMap::new();
+    let name = |file: &PathBuf| file.file_name().and_then(|n| n.to_str()).map(str::to_string);
+    for (i, file) in files.iter().enumerate() {
+        let Some(file_name) = name(file) else {
+            continue;
+        };
+        let Some((prefix, digits, extension)) = split(&file_name) else {
+            continue;
+        };
+        let key = (prefix.to_string(), extension.to_string());
+        candidates.entry(key).or_default().push((i, digits.to_string()));
+    }
+
+    // Index of each grouped file, and the pattern that replaces the first of them
+    let mut grouped: HashMap<usize, Option<PathBuf>> = HashMap::new();
+    for ((prefix, extension), members) in candidates {
+        if members.len() < MIN_FRAMES {
+            continue;
+        }
+        let padded = members.iter().any(|(_, digits)| digits.len() > 1 && digits.starts_with('0'));
+        let padding = match padded {
+            true => members.iter().map(|(_, digits)| digits.len()).min().unwrap_or(0),
+            false => 0,
+        };
+        let pattern = Pattern { prefix, padding, extension };
+        let file_name = pattern.file_name();
+        // Mixed padding, like `frame_001.png` next to `frame_0001.png`, isn't one sequence
+        if !members.iter().all(|(i, _)| name(&files[*i]).and_then(|n| pattern.frame(&n)).is_some()) {
+            continue;
+        }
+        for (n, (i, _)) in members.iter().enumerate() {
+            let replacement = (n == 0).then(|| files[*i].with_file_name(&file_name));
+            grouped.insert(*i, replacement);
+        }
+    }
+
+    files
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, file)| match grouped.remove(&i) {
+            Some(pattern) => pattern,
+            None => Some(file),
+        })
+        .collect()
+}
+
+/// Lists the frames a pattern path covers, with their total size in bytes.
+pub fn scan(path: &str) -> Option<(Sequence, u64)> {
+    let path = Path::new(path);
+    let pattern = Pattern::parse(path.file_name()?.to_str()?)?;
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let (mut first, mut last, mut frames, mut size) = (u64::MAX, 0, 0, 0);
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let Some(number) = entry.file_name().to_str().and_then(|name| pattern.frame(name)) else {
+            continue;
+        };
+        first = first.min(number);
+        last = last.max(number);
+        frames += 1;
+        size += entry.metadata().map_or(0, |m| m.len());
+    }
+    let sequence = Sequence { first, last, frames, padding: pattern.padding };
+    (frames > 0).then_some((sequence, size))
+}
+
+/// Input options ffmpeg needs before `-i` to read a sequence that doesn't start at
+/// frame 0-4; empty for ordinary files.
+pub fn input_args(path: &str) -> Vec<String> {
+    match scan(path) {
+        Some((sequence, _)) => vec!["-start_number".to_string(), sequence.first.to_string()],
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,135 @@
+//! `ssh://host/path` sources: ffprobe runs on the host and only its JSON comes
+//! back, so a playout server's content drive can be scouted without mounting it.
+//! `host` is a name from the `[[hosts]]` config tables, or anything `ssh` itself
+//! accepts (`user@server`, an alias from `~/.ssh/config`), optionally with a port.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::probe::is_media_file;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteHost {
+    /// What `ssh://<name>/...` paths call the host
+    pub name: String,
+    /// Destination handed to ssh, e.g. `ops@playout01.example.com`; the name when unset
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// ffprobe on the host, looked up on its PATH when unset
+    #[serde(default)]
+    pub ffprobe: Option<String>,
+}
+
+pub fn is_ssh(path: &str) -> bool {
+    path.get(..6).is_some_and(|scheme| scheme.eq_ignore_ascii_case("ssh://"))
+}
+
+/// Single-quotes `arg` for the remote shell ssh hands the command line to.
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Where one `ssh://` path points.
+struct Target {
+    destination: String,
+    port: Option<u16>,
+    ffprobe: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ssh {
+    hosts: Vec<RemoteHost>,
+}
+
+impl Ssh {
+    pub fn new(hosts: &[RemoteHost]) -> Self {
+        Self { hosts: hosts.to_vec() }
+    }
+
+    fn target(&self, url: &str) -> Option<Target> {
+        if !is_ssh(url) {
+            return None;
+        }
+        let (authority, path) = url.get(6..)?.split_once('/')?;
+        let path = format!("/{}", path);
+        if let Some(host) = self.hosts.iter().find(|host| host.name == authority) {
+            return Some(Target {
+                destination: host.address.clone().unwrap_or_else(|| host.name.clone()),
+                port: host.port,
+                ffprobe: host.ffprobe.clone().unwrap_or_else(|| "ffprobe".to_string()),
+                path,
+            });
+        }
+        let (destination, port) = match authority.rsplit_once(':') {
+            Some((destination, port)) => (destination, Some(port.parse().ok()?)),
+            None => (authority, None),
+        };
+        (!destination.is_empty()).then(|| Target {
+            destination: destination.to_string(),
+            port,
+            ffprobe: "ffprobe".to_string(),
+            path,
+        })
+    }
+
+    /// `ssh` set up to run a command on the target's host. Batch mode makes a host
+    /// that wants a password fail instead of prompting behind the TUI.
+    fn ssh(target: &Target) -> Command {
+        let mut command = Command::new("ssh");
+        command.args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"]);
+        if let Some(port) = target.port {
+            command.args(["-p", &port.to_string()]);
+        }
+        // A destination like `-oProxyCommand=...` would otherwise be read as an option
+        command.arg("--").arg(&target.destination).stdin(Stdio::null());
+        command
+    }
+
+    /// Turns a local ffprobe `command` into the same run on the host of `url`. Every
+    /// argument naming an `ssh://` path becomes the path on that host.
+    pub fn wrap(&self, command: &Command, url: &str) -> Result<Command, String> {
+        let target = self.target(url).ok_or("not an ssh://host/path URL")?;
+        let mut line = vec![quote(&target.ffprobe)];
+        for arg in command.get_args() {
+            let arg = arg.to_string_lossy();
+            match self.target(&arg) {
+                Some(other) => line.push(quote(&other.path)),
+                None => line.push(quote(&arg)),
+            }
+        }
+        let mut remote = Self::ssh(&target);
+        remote.arg(line.join(" "));
+        Ok(remote)
+    }
+
+    /// The media files under a directory on the host, or the file itself when `url`
+    /// names one, as `ssh://` paths.
+    pub fn list(&self, url: &str) -> Result<Vec<String>, String> {
+        if is_media_file(Path::new(url)) {
+            return Ok(vec![url.to_string()]);
+        }
+        let target = self.target(url).ok_or("not an ssh://host/path URL")?;
+        let output = Self::ssh(&target)
+            .arg(format!("find {} -type f", quote(&target.path)))
+            .output()
+            .map_err(|e| format!("could not start 'ssh': {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.trim().lines().last().unwrap_or("ssh failed").to_string());
+        }
+        let authority = url[6..].split('/').next().unwrap_or_default();
+        let mut paths: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|path| is_media_file(Path::new(path)))
+            .map(|path| format!("ssh://{}{}", authority, path))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+}
@@ -0,0 +1,107 @@
+//! Rewrites a file's title, comment and audio languages with an ffmpeg stream-copy
+//! remux. The remux goes to a hidden file next to the original, which replaces it
+//! only once ffmpeg has succeeded.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+/// The tags to write. Empty values remove the tag.
+pub struct TagEdit {
+    pub title: String,
+    pub comment: String,
+    /// One ISO 639-2 code per audio stream in order; streams past the end keep theirs
+    pub languages: Vec<String>,
+    /// Keeps the moov atom up front for MP4/MOV that already had it there
+    pub faststart: bool,
+}
+
+/// Checks a comma-separated language list, e.g. `eng, fra`.
+pub fn parse_languages(text: &str, streams: usize) -> Result<Vec<String>, String> {
+    let languages: Vec<String> = text
+        .split(',')
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+        .collect();
+    let valid = |code: &String| code.len() == 3 && code.chars().all(|c| c.is_ascii_lowercase());
+    if let Some(bad) = languages.iter().find(|code| !valid(code)) {
+        return Err(format!("'{}' is not a three-letter ISO 639-2 code", bad));
+    }
+    if languages.len() > streams {
+        return Err(format!("{} languages given for {} audio stream(s)", languages.len(), streams));
+    }
+    Ok(languages)
+}
+
+/// Writes one file at a time on a background thread.
+pub struct TagWriter {
+    job_tx: Sender<(String, TagEdit)>,
+    results: Receiver<(String, Result<(), String>)>,
+}
+
+impl TagWriter {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<(String, TagEdit)>();
+        let (result_tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            for (path, edit) in job_rx {
+                let result = write(&ffmpeg, &path, &edit);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn queue(&self, path: String, edit: TagEdit) {
+        // The worker only stops when the writer is dropped
+        let _ = self.job_tx.send((path, edit));
+    }
+
+    /// Files written since the last call.
+    pub fn poll(&self) -> Vec<(String, Result<(), String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+fn write(ffmpeg: &str, path: &str, edit: &TagEdit) -> Result<(), String> {
+    let original = Path::new(path);
+    let stem = original.file_stem().ok_or("not a file")?.to_string_lossy();
+    let extension = original.extension().ok_or("no extension to pick a muxer by")?.to_string_lossy();
+    // Same extension, so ffmpeg picks the same muxer
+    let temp = original.with_file_name(format!(".{}.tags.{}", stem, extension));
+
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-y", "-i", path])
+        .args(["-map", "0", "-c", "copy", "-map_metadata", "0"])
+        .arg("-metadata")
+        .arg(format!("title={}", edit.title))
+        .arg("-metadata")
+        .arg(format!("comment={}", edit.comment));
+    for (i, language) in edit.languages.iter().enumerate() {
+        command.arg(format!("-metadata:s:a:{}", i)).arg(format!("language={}", language));
+    }
+    if edit.faststart {
+        command.args(["-movflags", "+faststart"]);
+    }
+    let output = command
+        .arg(&temp)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&temp);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().last().unwrap_or("ffmpeg failed").to_string());
+    }
+    fs::rename(&temp, original).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("could not replace {}: {}", path, e)
+    })
+}
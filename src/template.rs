@@ -0,0 +1,273 @@
+//! User templates for printing files, `{{name}} {{codec}} {{bitrate}} Mbps`, to
+//! make playlists, EDLs or wiki tables straight from a scan. A field is any export
+//! column, named in lowercase with underscores and without its unit (`bitrate`,
+//! `display_resolution`, `true_peak`), or one of `index`, `stem`, `seconds`, `width` and `height`.
+//!
+//! The whole template is repeated for each file unless part of it is wrapped in
+//! `{{#files}}` … `{{/files}}`; then only that part is, and what's around it is
+//! printed once as a header and footer, where `{{count}}` is the number of files.
+
+use anyhow::{bail, Result};
+use std::fmt::Write;
+
+use crate::{export, MediaInfo};
+
+const SECTION_START: &str = "#files";
+const SECTION_END: &str = "/files";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    /// An export column, by its position in `export::COLUMNS`
+    Column(usize),
+    /// The file's place in the output, from 1
+    Index,
+    /// The file name without its extension
+    Stem,
+    /// The duration in seconds
+    Seconds,
+    /// The frame size, in pixels
+    Width,
+    Height,
+    /// How many files there are, for headers
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    header: Vec<Part>,
+    body: Vec<Part>,
+    footer: Vec<Part>,
+    /// Whether each file gets a line of its own, when the body doesn't end its lines
+    line_per_file: bool,
+}
+
+/// `Bitrate(Mbps)` as `bitrate`, `HW Decode` as `hw_decode`.
+fn key(column: &str) -> String {
+    let name = column.split('(').next().unwrap_or(column);
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// The names a template can use, for error messages and the README.
+pub fn field_names() -> Vec<String> {
+    let mut names: Vec<String> = export::COLUMNS.iter().map(|column| key(column)).collect();
+    names.extend(["index", "stem", "seconds", "width", "height", "count"].map(String::from));
+    names
+}
+
+fn field(name: &str) -> Option<Field> {
+    match name {
+        "index" => Some(Field::Index),
+        "stem" => Some(Field::Stem),
+        "seconds" => Some(Field::Seconds),
+        "width" => Some(Field::Width),
+        "height" => Some(Field::Height),
+        "count" => Some(Field::Count),
+        _ => export::COLUMNS.iter().position(|column| key(column) == name).map(Field::Column),
+    }
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::parse_with(text, ("{{", "}}"), field)
+    }
+
+    /// A file name pattern of one file's fields, `{name}_{codec}.{container}`, in
+    /// single braces, where `name` is the file's name without its extension.
+    pub fn parse_file_name(text: &str) -> Result<Self> {
+        let field = |name: &str| if name == "name" { Some(Field::Stem) } else { field(name) };
+        let template = Self::parse_with(text, ("{", "}"), field)?;
+        if !template.header.is_empty() || !template.footer.is_empty() {
+            bail!("a file name pattern can only use the fields of its file");
+        }
+        Ok(template)
+    }
+
+    fn parse_with(text: &str, (open, close): (&str, &str), field: impl Fn(&str) -> Option<Field>) -> Result<Self> {
+        // The parts before the section and in it, once they're done, then the current ones
+        let mut sections: Vec<Vec<Part>> = Vec::new();
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(open) {
+            let inner = start + open.len();
+            let Some(length) = rest[inner..].find(close) else {
+                bail!("'{}' without a closing '{}' in template", open, close);
+            };
+            let name = rest[inner..inner + length].trim();
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            rest = &rest[inner + length + close.len()..];
+            match name {
+                SECTION_START if sections.is_empty() => sections.push(std::mem::take(&mut parts)),
+                SECTION_END if sections.len() == 1 => sections.push(std::mem::take(&mut parts)),
+                SECTION_START | SECTION_END => bail!("template has a misplaced {}{}{}", open, name, close),
+                _ => match field(name) {
+                    Some(field) => parts.push(Part::Field(field)),
+                    None => {
+                        let fields = field_names().join(", ");
+                        bail!("unknown field {}{}{} in template; fields are {}", open, name, close, fields)
+                    }
+                },
+            }
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        sections.push(parts);
+
+        let delimited = |name: &str| format!("{}{}{}", open, name, close);
+        let (start, end) = (delimited(SECTION_START), delimited(SECTION_END));
+        let template = match sections.len() {
+            1 => {
+                let body = sections.remove(0);
+                let line_per_file = !matches!(body.last(), Some(Part::Text(text)) if text.ends_with('\n'));
+                Template { header: Vec::new(), body, footer: Vec::new(), line_per_file }
+            }
+            3 => {
+                let footer = sections.pop().unwrap_or_default();
+                let body = sections.pop().unwrap_or_default();
+                Template { header: sections.pop().unwrap_or_default(), body, footer, line_per_file: false }
+            }
+            _ => bail!("template has {} without {}", start, end),
+        };
+        let per_file =
+            |parts: &[Part]| parts.iter().any(|part| matches!(part, Part::Field(field) if *field != Field::Count));
+        if per_file(&template.header) || per_file(&template.footer) {
+            bail!("only {} can be used outside {} … {}", delimited("count"), start, end);
+        }
+        Ok(template)
+    }
+
+    pub fn render(&self, files: &[&MediaInfo]) -> String {
+        let mut out = String::new();
+        Self::render_parts(&mut out, &self.header, None, files.len());
+        for (i, file) in files.iter().enumerate() {
+            Self::render_parts(&mut out, &self.body, Some((i, file)), files.len());
+            if self.line_per_file {
+                out.push('\n');
+            }
+        }
+        Self::render_parts(&mut out, &self.footer, None, files.len());
+        out
+    }
+
+    /// What the template makes of one file alone, the `index`th of the output.
+    pub fn render_file(&self, file: &MediaInfo, index: usize) -> String {
+        let mut out = String::new();
+        Self::render_parts(&mut out, &self.body, Some((index, file)), 1);
+        out
+    }
+
+    fn render_parts(out: &mut String, parts: &[Part], file: Option<(usize, &MediaInfo)>, count: usize) {
+        let fields = file.map(|(_, file)| export::fields(file));
+        for part in parts {
+            match (part, file) {
+                (Part::Text(text), _) => out.push_str(text),
+                (Part::Field(Field::Count), _) => {
+                    let _ = write!(out, "{}", count);
+                }
+                (Part::Field(Field::Column(column)), Some(_)) => {
+                    if let Some((_, value)) = fields.as_ref().and_then(|fields| fields.get(*column)) {
+                        out.push_str(value);
+                    }
+                }
+                (Part::Field(Field::Index), Some((index, _))) => {
+                    let _ = write!(out, "{}", index + 1);
+                }
+                (Part::Field(Field::Stem), Some((_, file))) => out.push_str(&file.name),
+                (Part::Field(Field::Seconds), Some((_, file))) => {
+                    if let Some(duration) = file.duration {
+                        let _ = write!(out, "{:.3}", duration);
+                    }
+                }
+                (Part::Field(size @ (Field::Width | Field::Height)), Some((_, file))) => {
+                    if let Some((width, height)) = file.resolution.split_once('x') {
+                        out.push_str(if *size == Field::Width { width } else { height });
+                    }
+                }
+                // Refused by `parse`
+                (Part::Field(_), None) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Template::parse(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn fields_and_text_alternate() {
+        let template = Template::parse("{{index}}. {{name}} ({{bitrate}} Mbps)").unwrap();
+        assert_eq!(template.body.len(), 6);
+        assert_eq!(template.body[0], Part::Field(Field::Index));
+        assert_eq!(template.body[1], Part::Text(". ".to_string()));
+        assert!(template.line_per_file);
+        assert!(!Template::parse("{{name}}\n").unwrap().line_per_file);
+    }
+
+    #[test]
+    fn a_section_splits_header_body_and_footer() {
+        let template = Template::parse("{{count}} files\n{{#files}}{{ name }}\n{{/files}}end\n").unwrap();
+        assert_eq!(template.header, [Part::Field(Field::Count), Part::Text(" files\n".to_string())]);
+        assert_eq!(template.body.len(), 2);
+        assert_eq!(template.footer, [Part::Text("end\n".to_string())]);
+        assert!(!template.line_per_file);
+    }
+
+    #[test]
+    fn an_unclosed_field_is_an_error() {
+        assert_eq!(error("{{name"), "'{{' without a closing '}}' in template");
+    }
+
+    #[test]
+    fn an_unknown_field_lists_the_known_ones() {
+        let message = error("{{colour}}");
+        assert!(message.starts_with("unknown field {{colour}} in template; fields are "));
+        assert!(message.contains("display_resolution") && message.contains("stem"));
+    }
+
+    #[test]
+    fn sections_must_open_once_and_close() {
+        assert_eq!(error("{{#files}}{{name}}"), "template has {{#files}} without {{/files}}");
+        assert_eq!(error("{{/files}}"), "template has a misplaced {{/files}}");
+        assert_eq!(error("{{#files}}{{#files}}"), "template has a misplaced {{#files}}");
+        assert_eq!(error("{{#files}}{{/files}}{{#files}}"), "template has a misplaced {{#files}}");
+    }
+
+    #[test]
+    fn only_count_goes_outside_the_section() {
+        let message = "only {{count}} can be used outside {{#files}} … {{/files}}";
+        assert_eq!(error("{{name}}{{#files}}{{/files}}"), message);
+        assert_eq!(error("{{#files}}{{/files}}{{index}}"), message);
+    }
+
+    #[test]
+    fn file_name_patterns_use_single_braces() {
+        let template = Template::parse_file_name("{name}_{codec}.{container}").unwrap();
+        assert_eq!(template.body[0], Part::Field(Field::Stem));
+        assert_eq!(
+            Template::parse_file_name("{name").unwrap_err().to_string(),
+            "'{' without a closing '}' in template"
+        );
+        assert!(Template::parse_file_name("{shot}").unwrap_err().to_string().starts_with("unknown field {shot}"));
+        assert_eq!(
+            Template::parse_file_name("{count} {#files}{name}{/files}").unwrap_err().to_string(),
+            "a file name pattern can only use the fields of its file"
+        );
+    }
+}
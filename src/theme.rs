@@ -0,0 +1,166 @@
+//! Colors of the interface by role, from a built-in theme (`dark`, `light`,
+//! `high-contrast`) or a palette in the `[themes]` table of the config that
+//! overrides some roles of one of them.
+
+use anyhow::{anyhow, Result};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::highlight::parse_color;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// The title bar
+    pub title: Color,
+    /// Ordinary text: tabs, the status bar
+    pub text: Color,
+    /// Table headers, field labels, text being typed
+    pub accent: Color,
+    /// Secondary highlights: JSON keys, directories, the open project
+    pub info: Color,
+    /// Hints, placeholders and work in progress
+    pub muted: Color,
+    /// Empty cells
+    pub faint: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    /// Passed checks, finished jobs
+    pub good: Color,
+    /// Warnings
+    pub warning: Color,
+    /// Failures and errors
+    pub bad: Color,
+    /// Marked rows
+    pub marked: Color,
+}
+
+/// A palette from the config: every role it names replaces the base theme's color.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    /// Built-in theme the palette starts from, `dark` when unset
+    pub base: Option<String>,
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub accent: Option<String>,
+    pub info: Option<String>,
+    pub muted: Option<String>,
+    pub faint: Option<String>,
+    pub selection: Option<String>,
+    pub good: Option<String>,
+    pub warning: Option<String>,
+    pub bad: Option<String>,
+    pub marked: Option<String>,
+}
+
+pub const BUILT_IN: [&str; 3] = ["dark", "light", "high-contrast"];
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        title: Color::Cyan,
+        text: Color::White,
+        accent: Color::Yellow,
+        info: Color::Cyan,
+        muted: Color::Gray,
+        faint: Color::DarkGray,
+        selection: Color::DarkGray,
+        good: Color::Green,
+        warning: Color::Yellow,
+        bad: Color::Red,
+        marked: Color::Magenta,
+    };
+
+    /// For terminals with a light background, where yellow and white text
+    /// barely show and gray is the faint end rather than the muted one.
+    pub const LIGHT: Theme = Theme {
+        title: Color::Blue,
+        text: Color::Black,
+        accent: Color::Rgb(0, 0, 175),
+        info: Color::Rgb(0, 110, 140),
+        muted: Color::DarkGray,
+        faint: Color::Gray,
+        selection: Color::Rgb(210, 210, 210),
+        good: Color::Rgb(0, 120, 0),
+        warning: Color::Rgb(175, 95, 0),
+        bad: Color::Rgb(190, 0, 0),
+        marked: Color::Magenta,
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        title: Color::White,
+        text: Color::White,
+        accent: Color::LightYellow,
+        info: Color::LightCyan,
+        muted: Color::White,
+        faint: Color::Gray,
+        selection: Color::Blue,
+        good: Color::LightGreen,
+        warning: Color::LightYellow,
+        bad: Color::LightRed,
+        marked: Color::LightMagenta,
+    };
+
+    pub fn built_in(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::DARK),
+            "light" => Some(Theme::LIGHT),
+            "high-contrast" => Some(Theme::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+
+    /// The theme called `name`: a palette of `palettes`, which may shadow a
+    /// built-in, or a built-in. A typo in either is reported at startup.
+    pub fn new(name: &str, palettes: &BTreeMap<String, Palette>) -> Result<Theme> {
+        let built_in = BUILT_IN.join(", ");
+        let Some(palette) = palettes.get(name) else {
+            return Theme::built_in(name).ok_or_else(|| {
+                anyhow!("unknown theme '{}', expected one of {} or a [themes.{}] table", name, built_in, name)
+            });
+        };
+        let base = palette.base.as_deref().unwrap_or("dark");
+        let mut theme = Theme::built_in(base)
+            .ok_or_else(|| anyhow!("theme '{}': unknown base '{}', expected one of {}", name, base, built_in))?;
+        let roles = [
+            ("title", &palette.title, &mut theme.title),
+            ("text", &palette.text, &mut theme.text),
+            ("accent", &palette.accent, &mut theme.accent),
+            ("info", &palette.info, &mut theme.info),
+            ("muted", &palette.muted, &mut theme.muted),
+            ("faint", &palette.faint, &mut theme.faint),
+            ("selection", &palette.selection, &mut theme.selection),
+            ("good", &palette.good, &mut theme.good),
+            ("warning", &palette.warning, &mut theme.warning),
+            ("bad", &palette.bad, &mut theme.bad),
+            ("marked", &palette.marked, &mut theme.marked),
+        ];
+        for (role, value, color) in roles {
+            if let Some(value) = value {
+                *color = parse_color(value)
+                    .ok_or_else(|| anyhow!("theme '{}': unknown color '{}' for {}", name, value, role))?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// The color of a role named in a highlight rule (`color = "bad"`), so rules
+    /// can follow the theme instead of fixing a color.
+    pub fn role(&self, name: &str) -> Option<Color> {
+        let color = match name {
+            "title" => self.title,
+            "text" => self.text,
+            "accent" => self.accent,
+            "info" => self.info,
+            "muted" => self.muted,
+            "faint" => self.faint,
+            "selection" => self.selection,
+            "good" => self.good,
+            "warning" => self.warning,
+            "bad" => self.bad,
+            "marked" => self.marked,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
@@ -0,0 +1,299 @@
+//! Frame previews for the details panel. Terminals that speak an image protocol
+//! (kitty, iTerm2, sixel) get the picture itself; everything else gets it drawn
+//! with half-block characters, two pixels per cell.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fmt::Write,
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+/// Width frames are scaled to before display; plenty for a side panel.
+const FRAME_WIDTH: u32 = 480;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Graphics {
+    /// Picked from the terminal's environment variables
+    #[default]
+    Auto,
+    Kitty,
+    Iterm,
+    Sixel,
+    Blocks,
+}
+
+impl Graphics {
+    /// Resolves `Auto`. Sixel support can't be told from the environment, so it's
+    /// only used when configured.
+    pub fn detect(self) -> Self {
+        if self != Graphics::Auto {
+            return self;
+        }
+        let var = |name: &str| env::var(name).unwrap_or_default();
+        if env::var_os("KITTY_WINDOW_ID").is_some() || var("TERM") == "xterm-kitty" || var("TERM_PROGRAM") == "ghostty" {
+            Graphics::Kitty
+        } else if matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm") {
+            Graphics::Iterm
+        } else {
+            Graphics::Blocks
+        }
+    }
+}
+
+/// A decoded RGB frame.
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    rgb: Vec<u8>,
+}
+
+impl Thumbnail {
+    fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        let i = ((y.min(self.height - 1) * self.width + x.min(self.width - 1)) * 3) as usize;
+        (self.rgb[i], self.rgb[i + 1], self.rgb[i + 2])
+    }
+
+    /// Nearest-neighbour resize, enough for a preview.
+    fn resized(&self, width: u32, height: u32) -> Thumbnail {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = self.pixel(x * self.width / width, y * self.height / height);
+                rgb.extend([r, g, b]);
+            }
+        }
+        Thumbnail { width, height, rgb }
+    }
+
+    /// The largest `(columns, rows)` within `area` that keeps the frame's shape,
+    /// given the pixel size of a cell.
+    pub fn fit(&self, area: Rect, cell: (u16, u16)) -> (u16, u16) {
+        let (cell_w, cell_h) = (cell.0.max(1) as f64, cell.1.max(1) as f64);
+        let aspect = self.width as f64 / self.height.max(1) as f64;
+        let mut cols = area.width as f64;
+        let mut rows = cols * cell_w / aspect / cell_h;
+        if rows > area.height as f64 {
+            rows = area.height as f64;
+            cols = rows * cell_h * aspect / cell_w;
+        }
+        ((cols.floor() as u16).max(1), (rows.floor() as u16).max(1))
+    }
+}
+
+/// Extracts one frame per request on a background thread.
+pub struct Thumbnailer {
+    job_tx: Sender<(String, Option<f64>)>,
+    results: Receiver<(String, Result<Thumbnail, String>)>,
+}
+
+impl Thumbnailer {
+    pub fn new(ffmpeg: String) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<(String, Option<f64>)>();
+        let (result_tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            for (path, duration) in job_rx {
+                let result = extract(&ffmpeg, &path, duration);
+                if result_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { job_tx, results }
+    }
+
+    pub fn request(&self, path: String, duration: Option<f64>) {
+        // The worker only stops when the thumbnailer is dropped
+        let _ = self.job_tx.send((path, duration));
+    }
+
+    pub fn poll(&self) -> Vec<(String, Result<Thumbnail, String>)> {
+        self.results.try_iter().collect()
+    }
+}
+
+/// A frame a third of the way in, past most fade-ins and slates.
+fn extract(ffmpeg: &str, path: &str, duration: Option<f64>) -> Result<Thumbnail, String> {
+    let time = duration.map_or(0.0, |d| d / 3.0);
+    let scale = format!("scale={}:-2", FRAME_WIDTH);
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-v", "error", "-ss", &format!("{:.3}", time)])
+        .args(sequence::input_args(path))
+        .args(["-i", path])
+        .args(["-map", "0:v:0", "-frames:v", "1", "-vf", &scale, "-f", "image2pipe", "-c:v", "ppm", "-"]);
+    let output = cancel::output(&mut command).map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+    parse_ppm(&output.stdout).ok_or_else(|| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        stderr.lines().last().unwrap_or("no frame decoded").to_string()
+    })
+}
+
+/// Binary PPM (`P6`) as written by ffmpeg's ppm encoder.
+fn parse_ppm(data: &[u8]) -> Option<Thumbnail> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while fields.len() < 4 {
+        while data.get(pos)?.is_ascii_whitespace() {
+            pos += 1;
+        }
+        let start = pos;
+        while !data.get(pos)?.is_ascii_whitespace() {
+            pos += 1;
+        }
+        fields.push(std::str::from_utf8(&data[start..pos]).ok()?);
+    }
+    let (width, height): (u32, u32) = (fields[1].parse().ok()?, fields[2].parse().ok()?);
+    let rgb = data.get(pos + 1..pos + 1 + (width * height * 3) as usize)?.to_vec();
+    (fields[0] == "P6" && fields[3] == "255" && width > 0 && height > 0).then_some(Thumbnail { width, height, rgb })
+}
+
+/// The frame as `rows` lines of `▀`, each cell's foreground the upper pixel and
+/// background the lower one.
+pub fn block_lines(thumbnail: &Thumbnail, cols: u16, rows: u16) -> Vec<Line<'static>> {
+    let image = thumbnail.resized(cols as u32, rows as u32 * 2);
+    (0..rows as u32)
+        .map(|row| {
+            let spans: Vec<Span> = (0..cols as u32)
+                .map(|x| {
+                    let (r, g, b) = image.pixel(x, row * 2);
+                    let (r2, g2, b2) = image.pixel(x, row * 2 + 1);
+                    Span::styled("▀", Style::default().fg(Color::Rgb(r, g, b)).bg(Color::Rgb(r2, g2, b2)))
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// The escape sequence that draws the frame at the cursor, `cols` by `rows` cells.
+/// `cell` is the pixel size of a cell, which sixel needs to size the image.
+pub fn escape(graphics: Graphics, thumbnail: &Thumbnail, cols: u16, rows: u16, cell: (u16, u16)) -> String {
+    match graphics {
+        Graphics::Kitty => kitty(thumbnail, cols, rows),
+        Graphics::Iterm => format!(
+            "\x1b]1337;File=inline=1;width={};height={};preserveAspectRatio=0:{}\x07",
+            cols,
+            rows,
+            STANDARD.encode(bmp(thumbnail))
+        ),
+        Graphics::Sixel => {
+            let image = thumbnail.resized(cols as u32 * cell.0 as u32, rows as u32 * cell.1 as u32);
+            sixel(&image)
+        }
+        Graphics::Auto | Graphics::Blocks => String::new(),
+    }
+}
+
+/// Removes images drawn with `escape`. Only kitty keeps images apart from the
+/// text; the others are simply drawn over.
+pub fn clear(graphics: Graphics) -> &'static str {
+    match graphics {
+        Graphics::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+        _ => "",
+    }
+}
+
+/// Raw RGB sent in the 4096-byte chunks the kitty protocol requires.
+fn kitty(thumbnail: &Thumbnail, cols: u16, rows: u16) -> String {
+    let payload = STANDARD.encode(&thumbnail.rgb);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            let _ = write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},C=1,q=2,m={};{}\x1b\\",
+                thumbnail.width, thumbnail.height, cols, rows, more, chunk
+            );
+        } else {
+            let _ = write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+    out
+}
+
+/// A 24-bit BMP, the simplest format iTerm2 decodes.
+fn bmp(thumbnail: &Thumbnail) -> Vec<u8> {
+    let (width, height) = (thumbnail.width, thumbnail.height);
+    let stride = (width * 3).div_ceil(4) * 4;
+    let size = 54 + stride * height;
+    let mut out = Vec::with_capacity(size as usize);
+    out.extend(b"BM");
+    out.extend(size.to_le_bytes());
+    out.extend([0u8; 4]);
+    out.extend(54u32.to_le_bytes());
+    out.extend(40u32.to_le_bytes());
+    out.extend((width as i32).to_le_bytes());
+    out.extend((height as i32).to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(24u16.to_le_bytes());
+    out.extend([0u8; 24]);
+    // Rows run bottom-up in BGR order
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let (r, g, b) = thumbnail.pixel(x, y);
+            out.extend([b, g, r]);
+        }
+        out.resize(out.len() + (stride - width * 3) as usize, 0);
+    }
+    out
+}
+
+/// Sixel with a fixed 6x6x6 color cube, which avoids a quantization pass.
+fn sixel(image: &Thumbnail) -> String {
+    let level = |c: u8| (c as u32 * 5 + 127) / 255;
+    let index = |(r, g, b): (u8, u8, u8)| (level(r) * 36 + level(g) * 6 + level(b)) as usize;
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", image.width, image.height);
+    for i in 0..216 {
+        let (r, g, b) = (i / 36, i / 6 % 6, i % 6);
+        let _ = write!(out, "#{};2;{};{};{}", i, r * 20, g * 20, b * 20);
+    }
+    for band in (0..image.height).step_by(6) {
+        let band_rows = (image.height - band).min(6);
+        // Which of the six rows each color covers, column by column
+        let mut masks = vec![vec![0u8; image.width as usize]; 216];
+        let mut used = [false; 216];
+        for dy in 0..band_rows {
+            for x in 0..image.width {
+                let color = index(image.pixel(x, band + dy));
+                masks[color][x as usize] |= 1 << dy;
+                used[color] = true;
+            }
+        }
+        for color in (0..216).filter(|&c| used[c]) {
+            let _ = write!(out, "#{}", color);
+            let mask = &masks[color];
+            let mut x = 0;
+            while x < mask.len() {
+                let run = mask[x..].iter().take_while(|&&m| m == mask[x]).count();
+                let symbol = (63 + mask[x]) as char;
+                if run > 3 {
+                    let _ = write!(out, "!{}{}", run, symbol);
+                } else {
+                    out.extend(std::iter::repeat_n(symbol, run));
+                }
+                x += run;
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
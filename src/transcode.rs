@@ -0,0 +1,203 @@
+//! Runs suggested ffmpeg re-encodes one at a time in the background.
+
+use ratatui::widgets::ListState;
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{query, remedy::Suggestion};
+
+pub enum JobStatus {
+    Queued,
+    /// Fraction done, when the input's duration is known
+    Running(Option<f64>),
+    Done,
+    Failed(String),
+}
+
+pub struct TranscodeJob {
+    id: usize,
+    pub title: String,
+    pub input: String,
+    pub output: String,
+    duration: Option<f64>,
+    pub status: JobStatus,
+}
+
+struct WorkItem {
+    id: usize,
+    args: Vec<String>,
+}
+
+enum Update {
+    Started(usize),
+    /// Seconds of output written so far
+    Progress(usize, f64),
+    Finished(usize, Result<(), String>),
+}
+
+pub struct TranscodeQueue {
+    pub jobs: Vec<TranscodeJob>,
+    pub list_state: ListState,
+    next_id: usize,
+    work_tx: Sender<WorkItem>,
+    updates: Receiver<Update>,
+}
+
+impl TranscodeQueue {
+    pub fn new(ffmpeg: String) -> Self {
+        let (work_tx, work_rx) = mpsc::channel::<WorkItem>();
+        let (update_tx, updates) = mpsc::channel();
+        thread::spawn(move || {
+            for item in work_rx {
+                if update_tx.send(Update::Started(item.id)).is_err() {
+                    break;
+                }
+                let result = run_ffmpeg(&ffmpeg, &item.args, |seconds| {
+                    let _ = update_tx.send(Update::Progress(item.id, seconds));
+                });
+                if update_tx.send(Update::Finished(item.id, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            jobs: Vec::new(),
+            list_state: ListState::default(),
+            next_id: 0,
+            work_tx,
+            updates,
+        }
+    }
+
+    /// Queues a suggestion for `input`; `duration` lets the queue show a percentage.
+    pub fn push(&mut self, input: &str, duration: Option<f64>, suggestion: Suggestion) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(TranscodeJob {
+            id,
+            title: suggestion.title,
+            input: input.to_string(),
+            output: suggestion.output,
+            duration,
+            status: JobStatus::Queued,
+        });
+        if self.list_state.selected().is_none() {
+            self.list_state.select(Some(0));
+        }
+        // The worker only stops when the queue is dropped
+        let _ = self.work_tx.send(WorkItem { id, args: suggestion.args });
+    }
+
+    /// Applies progress from the worker. Returns each job that finished since the
+    /// last call as its output path and result.
+    pub fn poll(&mut self) -> Vec<(String, Result<(), String>)> {
+        let mut finished = Vec::new();
+        while let Ok(update) = self.updates.try_recv() {
+            let id = match &update {
+                Update::Started(id) | Update::Progress(id, _) | Update::Finished(id, _) => *id,
+            };
+            let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) else {
+                continue;
+            };
+            match update {
+                Update::Started(_) => job.status = JobStatus::Running(None),
+                Update::Progress(_, seconds) => {
+                    let fraction = job.duration.filter(|d| *d > 0.0).map(|d| (seconds / d).min(1.0));
+                    job.status = JobStatus::Running(fraction);
+                }
+                Update::Finished(_, result) => {
+                    job.status = match &result {
+                        Ok(()) => JobStatus::Done,
+                        Err(e) => JobStatus::Failed(e.clone()),
+                    };
+                    finished.push((job.output.clone(), result));
+                }
+            }
+        }
+        finished
+    }
+
+    /// Jobs queued or running.
+    pub fn active(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| matches!(job.status, JobStatus::Queued | JobStatus::Running(_)))
+            .count()
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs
+            .retain(|job| matches!(job.status, JobStatus::Queued | JobStatus::Running(_)));
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(selected.min(self.jobs.len().saturating_sub(1))));
+    }
+
+    pub fn next(&mut self) {
+        let i = self.list_state.selected().map_or(0, |i| {
+            (i + 1).min(self.jobs.len().saturating_sub(1))
+        });
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = self.list_state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.list_state.select(Some(i));
+    }
+}
+
+/// Runs ffmpeg, reporting the `time=` of each stats line. Existing outputs are
+/// never overwritten. On failure the error is ffmpeg's last line of output.
+fn run_ffmpeg(ffmpeg: &str, args: &[String], mut progress: impl FnMut(f64)) -> Result<(), String> {
+    let mut child = Command::new(ffmpeg)
+        .args(["-hide_banner", "-nostdin", "-n"])
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start '{}': {}", ffmpeg, e))?;
+
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let mut pending = Vec::new();
+    let mut last_line = String::new();
+    let mut buffer = [0u8; 4096];
+    // Stats lines end in '\r' while other messages end in '\n'
+    while let Ok(read) = stderr.read(&mut buffer) {
+        if read == 0 {
+            break;
+        }
+        for &byte in &buffer[..read] {
+            if byte != b'\r' && byte != b'\n' {
+                pending.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&pending).trim().to_string();
+            pending.clear();
+            if let Some(seconds) = stats_time(&line) {
+                progress(seconds);
+            } else if !line.is_empty() {
+                last_line = line;
+            }
+        }
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else if last_line.is_empty() {
+        Err(format!("ffmpeg exited with {}", status))
+    } else {
+        Err(last_line)
+    }
+}
+
+/// The `time=00:01:02.50` field of an ffmpeg stats line, in seconds.
+fn stats_time(line: &str) -> Option<f64> {
+    let start = line.find("time=")? + "time=".len();
+    let value = line[start..].split_whitespace().next()?;
+    query::parse_duration(value)
+}
@@ -0,0 +1,106 @@
+//! Deleting files from disk, to the system trash where they can still be
+//! restored, or for good. Finder and the Recycle Bin are asked to do it on macOS
+//! and Windows; elsewhere files go to the freedesktop.org home trash that the
+//! Linux desktops share, with the `.trashinfo` their file managers restore from.
+
+use anyhow::{bail, Context, Result};
+use std::{
+    env,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{self, Path, PathBuf},
+    process::Command,
+};
+
+use crate::model::format_utc_now;
+
+/// Moves `path` to the trash.
+pub fn trash(path: &str) -> Result<()> {
+    if cfg!(target_os = "macos") {
+        let script = format!("tell application \"Finder\" to delete POSIX file \"{}\"", escape(path));
+        run(Command::new("osascript").args(["-e", &script]))
+    } else if cfg!(windows) {
+        let script = format!(
+            "Add-Type -AssemblyName Microsoft.VisualBasic; [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteFile(\
+             '{}', 'OnlyErrorDialogs', 'SendToRecycleBin')",
+            path.replace('\'', "''")
+        );
+        run(Command::new("powershell").args(["-NoProfile", "-Command", &script]))
+    } else {
+        freedesktop_trash(Path::new(path))
+    }
+}
+
+/// Deletes `path` for good.
+pub fn delete(path: &str) -> Result<()> {
+    fs::remove_file(path).with_context(|| format!("deleting {}", path))
+}
+
+/// `text` with its quotes and backslashes escaped, for an AppleScript string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command.output().with_context(|| format!("could not start '{}'", program))?;
+    if !output.status.success() {
+        bail!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+fn freedesktop_trash(path: &Path) -> Result<()> {
+    let data = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .context("no home directory to keep the trash in")?;
+    let (files, info) = (data.join("Trash/files"), data.join("Trash/info"));
+    for dir in [&files, &info] {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    // The link itself for a symlink, not what it points to
+    let original = path::absolute(path).with_context(|| path.display().to_string())?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let date = format!("{}:00", format_utc_now().trim_end_matches(" UTC").replacen(' ', "T", 1));
+
+    // A name no other trashed file has, claimed by creating its info file
+    for n in 1.. {
+        let name = if n == 1 { format!("{}{}", stem, extension) } else { format!("{}.{}{}", stem, n, extension) };
+        let info_path = info.join(format!("{}.trashinfo", name));
+        let mut info_file = match OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).with_context(|| format!("creating {}", info_path.display())),
+        };
+        let written = write!(info_file, "[Trash Info]\nPath={}\nDeletionDate={}\n", encode(&original), date);
+        let moved = written.map_err(anyhow::Error::from).and_then(|()| match fs::rename(path, files.join(&name)) {
+            Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+                bail!("the trash is on another drive than {}; delete it for good instead", path.display())
+            }
+            result => result.with_context(|| format!("moving {} to the trash", path.display())),
+        });
+        if moved.is_err() {
+            let _ = fs::remove_file(&info_path);
+        }
+        return moved;
+    }
+    unreachable!()
+}
+
+/// A path as the trash spec writes it, percent-encoded like a URL's.
+fn encode(path: &Path) -> String {
+    let mut out = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,153 @@
+//! The Tree tab: the analyzed files under their folders, each folder with the
+//! count, size and codecs of everything below it.
+
+use ratatui::widgets::ListState;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::is_separator;
+
+use crate::MediaInfo;
+
+/// What a folder holds, its subfolders included.
+pub struct Summary {
+    /// Indices into the file list
+    pub files: Vec<usize>,
+    pub size: u64,
+    pub codecs: BTreeSet<String>,
+}
+
+impl Summary {
+    /// Whether the folder's video isn't all one codec, usually a sign something
+    /// got into a delivery that shouldn't have.
+    pub fn mixed_codecs(&self) -> bool {
+        self.codecs.len() > 1
+    }
+}
+
+pub enum Entry {
+    Folder(Summary),
+    File(usize),
+}
+
+/// One visible line of the tree. `key` is a folder's path, or a file's.
+pub struct TreeRow {
+    pub key: String,
+    pub name: String,
+    pub depth: usize,
+    pub entry: Entry,
+}
+
+#[derive(Default)]
+struct Folder {
+    files: Vec<usize>,
+    // By path
+    folders: BTreeMap<String, Folder>,
+}
+
+impl Folder {
+    fn summary(&self, media_files: &[MediaInfo]) -> Summary {
+        let mut summary = Summary { files: self.files.clone(), size: 0, codecs: BTreeSet::new() };
+        for &i in &self.files {
+            let file = &media_files[i];
+            summary.size += file.file_size.unwrap_or(0);
+            if !file.audio_only && !file.codec.is_empty() {
+                summary.codecs.insert(file.codec.clone());
+            }
+        }
+        for folder in self.folders.values() {
+            let below = folder.summary(media_files);
+            summary.files.extend(below.files);
+            summary.size += below.size;
+            summary.codecs.extend(below.codecs);
+        }
+        summary
+    }
+}
+
+/// Folders the user folded away, and the cursor.
+#[derive(Default)]
+pub struct DirTree {
+    collapsed: HashSet<String>,
+    pub list_state: ListState,
+}
+
+impl DirTree {
+    /// The tree of the files at `indices`, which keep their order within a folder.
+    /// A folder holding nothing but one subfolder shares its row, so the part of
+    /// the paths every file has in common is a single line at the top.
+    pub fn rows(&self, media_files: &[MediaInfo], indices: &[usize]) -> Vec<TreeRow> {
+        let mut root = Folder::default();
+        for &i in indices {
+            let mut folder = &mut root;
+            for ancestor in ancestors(&media_files[i].path) {
+                folder = folder.folders.entry(ancestor.to_string()).or_default();
+            }
+            folder.files.push(i);
+        }
+        let mut rows = Vec::new();
+        self.push_folder(&root, "", 0, media_files, &mut rows);
+        rows
+    }
+
+    fn push_folder(
+        &self,
+        folder: &Folder,
+        path: &str,
+        depth: usize,
+        media_files: &[MediaInfo],
+        rows: &mut Vec<TreeRow>,
+    ) {
+        for (key, child) in &folder.folders {
+            let (mut key, mut child) = (key, child);
+            while child.files.is_empty() && child.folders.len() == 1 {
+                let Some((only_key, only)) = child.folders.iter().next() else {
+                    break;
+                };
+                (key, child) = (only_key, only);
+            }
+            // The top folders show their whole path
+            let name = match path.is_empty() {
+                true => key.clone(),
+                false => key[path.len()..].trim_start_matches(is_separator).to_string(),
+            };
+            let entry = Entry::Folder(child.summary(media_files));
+            rows.push(TreeRow { key: key.clone(), name, depth, entry });
+            if !self.collapsed.contains(key) {
+                self.push_folder(child, key, depth + 1, media_files, rows);
+            }
+        }
+        for &i in &folder.files {
+            let path = &media_files[i].path;
+            let name = path.rsplit(is_separator).next().unwrap_or(path).to_string();
+            rows.push(TreeRow { key: path.clone(), name, depth, entry: Entry::File(i) });
+        }
+    }
+
+    pub fn is_collapsed(&self, key: &str) -> bool {
+        self.collapsed.contains(key)
+    }
+
+    pub fn toggle(&mut self, key: &str) {
+        if !self.collapsed.remove(key) {
+            self.collapsed.insert(key.to_string());
+        }
+    }
+
+    pub fn expand(&mut self, key: &str) {
+        self.collapsed.remove(key);
+    }
+
+    /// Folds the folder; `false` when it already was.
+    pub fn collapse(&mut self, key: &str) -> bool {
+        self.collapsed.insert(key.to_string())
+    }
+}
+
+/// The folders above `path`, outermost first, each as the start of `path`:
+/// `/renders`, `/renders/shots`. A URL's scheme counts as a folder.
+fn ancestors(path: &str) -> Vec<&str> {
+    path.char_indices()
+        .filter(|&(_, c)| is_separator(c))
+        .map(|(i, _)| &path[..i])
+        .filter(|prefix| !prefix.is_empty() && !prefix.ends_with(is_separator))
+        .collect()
+}
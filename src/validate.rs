@@ -0,0 +1,89 @@
+//! QC profiles: delivery specs from the config that every file is checked against.
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::slice;
+
+use crate::{model::MediaInfo, query, theme::Theme};
+
+/// A delivery spec such as "H.264, 1920x1080, 25 fps, 10-20 Mbps, stereo AAC".
+/// Each check is a filter query; failing a `require` check fails the file,
+/// failing a `warn` check only flags it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationProfile {
+    pub name: String,
+    #[serde(default)]
+    pub require: Vec<String>,
+    #[serde(default)]
+    pub warn: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Verdict {
+    pub fn label(self) -> &'static str {
+        match self {
+            Verdict::Pass => "PASS",
+            Verdict::Warn => "WARN",
+            Verdict::Fail => "FAIL",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            Verdict::Pass => theme.good,
+            Verdict::Warn => theme.warning,
+            Verdict::Fail => theme.bad,
+        }
+    }
+}
+
+pub struct Report {
+    pub verdict: Verdict,
+    /// Every check that didn't hold, with how much it counts against the file
+    pub reasons: Vec<(Verdict, String)>,
+}
+
+impl ValidationProfile {
+    pub fn check(&self, file: &MediaInfo) -> Report {
+        let mut reasons = Vec::new();
+        for (checks, severity) in [(&self.require, Verdict::Fail), (&self.warn, Verdict::Warn)] {
+            for check in checks {
+                if let Some(reason) = failed_check(check, file) {
+                    reasons.push((severity, reason));
+                }
+            }
+        }
+        let verdict = reasons.iter().map(|(v, _)| *v).max().unwrap_or(Verdict::Pass);
+        Report { verdict, reasons }
+    }
+}
+
+/// Why `check` doesn't hold for `file`, e.g. "bitrate=10-20 (is 35.0)", or
+/// `None` when it does.
+fn failed_check(check: &str, file: &MediaInfo) -> Option<String> {
+    let groups = match query::parse(check) {
+        Ok(groups) => groups,
+        Err(e) => return Some(format!("invalid check '{}': {}", check, e)),
+    };
+    let failed: Vec<String> = groups
+        .iter()
+        .filter(|group| !group.iter().any(|filter| filter.matches(file)))
+        .map(|group| {
+            let expected = query::to_query(slice::from_ref(group));
+            let actual = group[0].filter_type.text_value(file);
+            format!("{} (is {})", expected, actual)
+        })
+        .collect();
+    if failed.is_empty() {
+        None
+    } else {
+        Some(failed.join(", "))
+    }
+}
@@ -0,0 +1,99 @@
+//! Full decode passes that catch corrupt or truncated media a header probe misses.
+
+use std::{
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{cancel, sequence};
+
+/// How many decoder messages are kept per file; a badly broken file can log thousands.
+const MAX_ERRORS: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Integrity {
+    Queued,
+    Checking,
+    Clean,
+    /// Decoder errors, in the order ffmpeg reported them
+    Corrupt(Vec<String>),
+}
+
+impl Integrity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Integrity::Queued => "queued",
+            Integrity::Checking => "...",
+            Integrity::Clean => "OK",
+            Integrity::Corrupt(_) => "ERR",
+        }
+    }
+}
+
+/// Decodes one file at a time on a background thread; decoding is CPU-bound
+/// enough that running several at once would starve the probes.
+pub struct Verifier {
+    path_tx: Sender<String>,
+    updates: Receiver<(String, Integrity)>,
+}
+
+impl Verifier {
+    pub fn new(ffmpeg: String) -> Self {
+        let (path_tx, path_rx) = mpsc::channel::<String>();
+        let (update_tx, updates) = mpsc::channel();
+        let epoch = cancel::epoch();
+        thread::spawn(move || {
+            cancel::pin(epoch);
+            for path in path_rx {
+                if update_tx.send((path.clone(), Integrity::Checking)).is_err() {
+                    break;
+                }
+                let result = decode(&ffmpeg, &path);
+                if update_tx.send((path, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { path_tx, updates }
+    }
+
+    pub fn queue(&self, path: String) {
+        // The worker only stops when the verifier is dropped
+        let _ = self.path_tx.send(path);
+    }
+
+    /// Status changes since the last call.
+    pub fn poll(&self) -> Vec<(String, Integrity)> {
+        self.updates.try_iter().collect()
+    }
+}
+
+fn decode(ffmpeg: &str, path: &str) -> Integrity {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-hide_banner", "-nostdin", "-v", "error"])
+        .args(sequence::input_args(path))
+        .args(["-i", path, "-f", "null", "-"]);
+    let output = cancel::output(&mut command);
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => return Integrity::Corrupt(vec![format!("could not start '{}': {}", ffmpeg, e)]),
+    };
+
+    let mut errors: Vec<String> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(MAX_ERRORS)
+        .map(String::from)
+        .collect();
+    if errors.is_empty() && !output.status.success() {
+        errors.push(format!("ffmpeg exited with {}", output.status));
+    }
+    if errors.is_empty() {
+        Integrity::Clean
+    } else {
+        Integrity::Corrupt(errors)
+    }
+}
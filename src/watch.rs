@@ -0,0 +1,76 @@
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+use crate::{ignore::Ignore, probe::is_media_file};
+
+/// How long a file must stay untouched before it's analyzed. Renders and copies
+/// fire a stream of modify events while they're still being written.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+pub struct FolderWatcher {
+    // Dropping the watcher stops the notifications, so it has to be kept alive
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    pending: HashMap<PathBuf, Instant>,
+    /// The folders watched, as given and as their real path, for the ignore rules to
+    /// apply below them whichever way the events spell it
+    roots: Vec<PathBuf>,
+    ignore: Ignore,
+}
+
+impl FolderWatcher {
+    pub fn new(dirs: &[String], ignore: Ignore) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        let mut roots = Vec::new();
+        for dir in dirs {
+            watcher.watch(Path::new(dir), RecursiveMode::Recursive)?;
+            roots.push(PathBuf::from(dir));
+            roots.extend(fs::canonicalize(dir));
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            pending: HashMap::new(),
+            roots,
+            ignore,
+        })
+    }
+
+    /// Returns files that were created or modified and have since settled.
+    pub fn ready_paths(&mut self) -> Vec<String> {
+        while let Ok(event) = self.events.try_recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if is_media_file(&path) && !self.roots.iter().any(|root| self.ignore.hides(root, &path)) {
+                    self.pending.insert(path, Instant::now());
+                }
+            }
+        }
+
+        let mut ready = Vec::new();
+        self.pending.retain(|path, last_event| {
+            if last_event.elapsed() < SETTLE_TIME {
+                return true;
+            }
+            if path.is_file() {
+                ready.push(path.to_string_lossy().to_string());
+            }
+            false
+        });
+        ready.sort();
+        ready
+    }
+}